@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::world::World;
+
+fn scene(count: usize) -> World {
+    let mut world = World::new();
+    for i in 0..count {
+        let particle = match i % 3 {
+            0 => Particle::Electron,
+            1 => Particle::Proton,
+            _ => Particle::Neutron,
+        };
+        let t = i as f32;
+        world.spawn_particle(particle, Some(vec3(t.cos() * t, t.sin() * t, t * 0.5)));
+    }
+    world
+}
+
+fn forces(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply_forces");
+    for count in [10, 100, 1000] {
+        let mut world = scene(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| systems::apply_forces(black_box(&mut world)))
+        });
+    }
+    group.finish();
+}
+
+fn integrators(c: &mut Criterion) {
+    let mut group = c.benchmark_group("integrate");
+    for count in [100, 10000] {
+        let mut world = scene(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| systems::integrate(black_box(&mut world)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, forces, integrators);
+criterion_main!(benches);
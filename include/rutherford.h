@@ -0,0 +1,40 @@
+/* C interface to the Rutherford atomic model simulation core.
+ * Build the library with `cargo build --release --features ffi`.
+ * State arrays are x y z float triples, one per particle in slot order.
+ * A panic inside the library never unwinds into the caller: the function
+ * returns NULL, -1, UINT64_MAX or SIZE_MAX instead, and a handle that
+ * panicked while changing can only be freed from then on. */
+#ifndef RUTHERFORD_H
+#define RUTHERFORD_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Simulation RutherfordSim;
+
+RutherfordSim *rutherford_sim_new(void);
+/* Returns NULL when the scene text does not parse */
+RutherfordSim *rutherford_sim_from_scenario(const char *text);
+void rutherford_sim_free(RutherfordSim *sim);
+
+/* Returns the new particle's slot, or -1 for an unknown species */
+int64_t rutherford_sim_spawn(RutherfordSim *sim, const char *species, float x, float y, float z);
+/* Returns how many ticks ran; fewer than asked if the state went bad */
+uint64_t rutherford_sim_step(RutherfordSim *sim, uint64_t ticks);
+size_t rutherford_sim_clear(RutherfordSim *sim);
+
+size_t rutherford_sim_particle_count(const RutherfordSim *sim);
+/* `out` has room for `capacity` particles (3 * capacity floats); returns how
+ * many particles were written */
+size_t rutherford_sim_get_positions(const RutherfordSim *sim, float *out, size_t capacity);
+size_t rutherford_sim_get_velocities(const RutherfordSim *sim, float *out, size_t capacity);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
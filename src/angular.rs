@@ -0,0 +1,88 @@
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::detector::{self, Detector};
+
+use crate::dock;
+use crate::gui;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// The residual strip spans this many standard deviations either side of zero
+const RESIDUAL_RANGE: f64 = 3.;
+
+// Detector counts per scattering angle as bars, with the fitted Rutherford
+// curve, times each bin's acceptance, drawn over them as a line and the
+// residuals in a strip below.
+pub fn draw_distribution(ctx: &egui::Context, detector: &Detector, theme: &Theme) {
+    let counts = detector.angular_counts();
+    let (width, height) = (theme.scaled(420.), theme.scaled(300.));
+    let x = (screen_width() - width) / 2.;
+    let y = screen_height() - height - theme.scaled(10.);
+    // Bins in degrees, each bar at its bin's centre
+    let bin = detector.bin_width().to_degrees() as f64;
+    let centre = |i: usize| (i as f64 + 0.5) * bin;
+    // Expected counts, leaving out the forward bin where the formula diverges
+    let fit = detector::fit(detector);
+    let total: u32 = counts.iter().sum();
+    // The forward bin's count can dwarf the rest, so it is cut off at the
+    // tallest of the others
+    let peak = counts
+        .iter()
+        .skip(1)
+        .map(|c| *c as f32)
+        .chain(fit.expected.iter().skip(1).map(|e| e * fit.scale))
+        .fold(1., f32::max) as f64;
+
+    dock::egui_window("angular.title", Rect::new(x, y, width, height)).show(ctx, |ui| {
+        egui::plot::Plot::new("angular_counts")
+            .height(160.)
+            .include_x(0.)
+            .include_x(180.)
+            .include_y(0.)
+            .x_axis_formatter(|angle, _| format!("{:.0}°", angle))
+            .show(ui, |plot| {
+                let bars = counts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, count)| (centre(i), (*count as f64).min(peak)));
+                plot.bar_chart(gui::bars(tr("angular.hits"), bars, bin, theme.selection));
+                let curve = fit
+                    .expected
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(i, e)| [centre(i), (e * fit.scale) as f64])
+                    .collect();
+                plot.line(gui::line(tr("angular.expected"), curve, theme.warning).width(2.));
+            });
+        egui::plot::Plot::new("angular_residuals")
+            .height(60.)
+            .include_x(0.)
+            .include_x(180.)
+            .include_y(-RESIDUAL_RANGE)
+            .include_y(RESIDUAL_RANGE)
+            .show_x(false)
+            .x_axis_formatter(|angle, _| format!("{:.0}°", angle))
+            .show(ui, |plot| {
+                let residuals = fit.residuals.iter().enumerate().filter_map(|(i, r)| {
+                    r.map(|r| (centre(i), (r as f64).clamp(-RESIDUAL_RANGE, RESIDUAL_RANGE)))
+                });
+                plot.bar_chart(gui::bars(
+                    tr("angular.residuals"),
+                    residuals,
+                    bin,
+                    theme.warning,
+                ));
+            });
+        ui.label(trf(
+            "angular.fit",
+            &[
+                &format!("{:.3e}", fit.scale),
+                &format!("{:.1}", fit.chi_square),
+                &fit.degrees_of_freedom,
+            ],
+        ));
+        ui.label(trf("angular.footer", &[&total, &format!("{:.0}", bin)]));
+    });
+}
@@ -0,0 +1,79 @@
+use macroquad::prelude::*;
+
+use crate::events::Event;
+use crate::units::SPEED_OF_LIGHT;
+use crate::world::World;
+
+// A particle and its antiparticle closer than this annihilate
+pub const CAPTURE_RADIUS: f32 = 5.;
+// Ticks the flash and its photons stay visible
+pub const FLASH_TICKS: u32 = 90;
+
+// Where a pair annihilated and the axis its two photons fly off along.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Flash {
+    pub pos: Vec3,
+    pub direction: Vec3,
+    pub age: u32,
+}
+
+impl Flash {
+    // Positions of the two back-to-back photons.
+    pub fn photons(&self) -> [Vec3; 2] {
+        let offset = self.direction * SPEED_OF_LIGHT * self.age as f32;
+        [self.pos + offset, self.pos - offset]
+    }
+}
+
+// Removes every particle-antiparticle pair within `CAPTURE_RADIUS`, each
+// particle taking part in at most one annihilation per tick.
+pub fn annihilate(world: &mut World) {
+    let particles = &world.particles;
+    let mut used = vec![false; particles.len()];
+    let mut pairs = Vec::new();
+    for i in 0..particles.len() {
+        let Some(anti) = particles.particle[i].antiparticle() else {
+            continue;
+        };
+        for j in (i + 1)..particles.len() {
+            if used[i] {
+                break;
+            }
+            if used[j] || particles.particle[j] != anti {
+                continue;
+            }
+            if particles.pos[i].distance(particles.pos[j]) < CAPTURE_RADIUS {
+                used[i] = true;
+                used[j] = true;
+                let entities = particles.entities();
+                let pos = (particles.pos[i] + particles.pos[j]) / 2.;
+                pairs.push((entities[i], entities[j], pos));
+            }
+        }
+    }
+
+    for (a, b, pos) in pairs {
+        world.despawn(a);
+        world.despawn(b);
+        let direction = world.random_vector(-1., 1.).normalize_or_zero();
+        world.flashes.push(Flash {
+            pos,
+            direction: if direction == Vec3::ZERO {
+                Vec3::X
+            } else {
+                direction
+            },
+            age: 0,
+        });
+        world.annihilations += 1;
+        world.events.push(Event::Annihilated(a, b));
+        log::debug!("{:?} and {:?} annihilated at {}", a, b, pos);
+    }
+}
+
+pub fn age_flashes(world: &mut World) {
+    for flash in &mut world.flashes {
+        flash.age += 1;
+    }
+    world.flashes.retain(|flash| flash.age < FLASH_TICKS);
+}
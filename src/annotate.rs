@@ -0,0 +1,299 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use crate::dock;
+use crate::i18n::tr;
+use crate::selection;
+use crate::theme::Theme;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tool {
+    Pen,
+    Arrow,
+    Circle,
+    Label,
+}
+
+const TOOLS: [(Tool, &str); 4] = [
+    (Tool::Pen, "annotate.pen"),
+    (Tool::Arrow, "annotate.arrow"),
+    (Tool::Circle, "annotate.circle"),
+    (Tool::Label, "annotate.label"),
+];
+
+impl Tool {
+    fn code(&self) -> &'static str {
+        match self {
+            Tool::Pen => "stroke",
+            Tool::Arrow => "arrow",
+            Tool::Circle => "circle",
+            Tool::Label => "label",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Tool> {
+        match code {
+            "stroke" => Some(Tool::Pen),
+            "arrow" => Some(Tool::Arrow),
+            "circle" => Some(Tool::Circle),
+            "label" => Some(Tool::Label),
+            _ => None,
+        }
+    }
+}
+
+// Screen points are stored as fractions of the window so they survive resizes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Point {
+    Screen(Vec2),
+    World(Vec3),
+}
+
+impl Point {
+    fn to_screen(self, camera: &Camera3D) -> Option<Vec2> {
+        match self {
+            Point::Screen(p) => Some(p * vec2(screen_width(), screen_height())),
+            Point::World(p) => selection::world_to_screen(camera, p),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Annotation {
+    tool: Tool,
+    points: Vec<Point>,
+    text: String,
+}
+
+impl Annotation {
+    // `annotation = <tool> <screen|world> <coordinates...> [| label text]`
+    fn to_line(&self) -> String {
+        let world = matches!(self.points.first(), Some(Point::World(_)));
+        let mut line = format!(
+            "annotation = {} {}",
+            self.tool.code(),
+            if world { "world" } else { "screen" }
+        );
+        for point in &self.points {
+            match point {
+                Point::Screen(p) => line.push_str(&format!(" {} {}", p.x, p.y)),
+                Point::World(p) => line.push_str(&format!(" {} {} {}", p.x, p.y, p.z)),
+            }
+        }
+        if self.tool == Tool::Label {
+            line.push_str(&format!(" | {}", self.text));
+        }
+        line
+    }
+
+    fn from_value(value: &str) -> Option<Annotation> {
+        let (coords, text) = value.split_once('|').unwrap_or((value, ""));
+        let mut fields = coords.split_whitespace();
+        let tool = Tool::from_code(fields.next()?)?;
+        let world = match fields.next()? {
+            "world" => true,
+            "screen" => false,
+            _ => return None,
+        };
+        let numbers: Vec<f32> = fields.map(|n| n.parse().ok()).collect::<Option<_>>()?;
+        let points = if world {
+            numbers
+                .chunks_exact(3)
+                .map(|c| Point::World(vec3(c[0], c[1], c[2])))
+                .collect()
+        } else {
+            numbers
+                .chunks_exact(2)
+                .map(|c| Point::Screen(vec2(c[0], c[1])))
+                .collect()
+        };
+        Some(Annotation {
+            tool,
+            points,
+            text: text.trim().to_string(),
+        })
+    }
+}
+
+// Teaching overlay of freehand strokes, arrows, circles and labels, drawn with
+// the mouse while the panel is open. Anchored annotations stick to the scene
+// when the camera moves; the rest stay put on screen.
+pub struct Annotations {
+    pub visible: bool,
+    tool: Tool,
+    anchored: bool,
+    label_text: String,
+    items: Vec<Annotation>,
+    drawing: Option<Annotation>,
+}
+
+impl Annotations {
+    pub fn new() -> Annotations {
+        Annotations {
+            visible: false,
+            tool: Tool::Pen,
+            anchored: false,
+            label_text: String::new(),
+            items: Vec::new(),
+            drawing: None,
+        }
+    }
+
+    pub fn undo(&mut self) {
+        self.items.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        self.items.iter().map(Annotation::to_line).collect()
+    }
+
+    pub fn load_lines<'a>(&mut self, values: impl Iterator<Item = &'a str>) {
+        self.items = values
+            .filter_map(|value| {
+                let annotation = Annotation::from_value(value);
+                if annotation.is_none() {
+                    log::warn!("ignoring malformed annotation '{}'", value);
+                }
+                annotation
+            })
+            .collect();
+    }
+
+    // Mouse position as an annotation point. Anchored points land on the plane
+    // through `focus` facing the camera.
+    fn point(&self, camera: &Camera3D, focus: Vec3) -> Point {
+        let (mx, my) = mouse_position();
+        if !self.anchored {
+            return Point::Screen(vec2(mx / screen_width(), my / screen_height()));
+        }
+        let (origin, dir) = selection::mouse_ray(camera);
+        let normal = (camera.target - camera.position).normalize_or_zero();
+        let denom = dir.dot(normal);
+        let t = if denom.abs() > 1e-6 {
+            (focus - origin).dot(normal) / denom
+        } else {
+            0.
+        };
+        Point::World(origin + dir * t.max(0.))
+    }
+
+    // Handles drawing input; `enabled` is false while the cursor is grabbed
+    // or over another window.
+    pub fn update(&mut self, camera: &Camera3D, focus: Vec3, enabled: bool) {
+        if !self.visible {
+            self.drawing = None;
+            return;
+        }
+        if enabled && is_mouse_button_pressed(MouseButton::Left) {
+            let start = self.point(camera, focus);
+            if self.tool == Tool::Label {
+                if !self.label_text.trim().is_empty() {
+                    self.items.push(Annotation {
+                        tool: Tool::Label,
+                        points: vec![start],
+                        text: self.label_text.trim().to_string(),
+                    });
+                }
+                return;
+            }
+            self.drawing = Some(Annotation {
+                tool: self.tool,
+                points: vec![start, start],
+                text: String::new(),
+            });
+        }
+        let point = self.point(camera, focus);
+        if let Some(drawing) = &mut self.drawing {
+            match drawing.tool {
+                Tool::Pen => {
+                    if drawing.points.last() != Some(&point) {
+                        drawing.points.push(point);
+                    }
+                }
+                _ => drawing.points[1] = point,
+            }
+            if !is_mouse_button_down(MouseButton::Left) {
+                self.items.extend(self.drawing.take());
+            }
+        }
+    }
+
+    pub fn draw(&self, camera: &Camera3D, theme: &Theme) {
+        let thickness = theme.scaled(3.);
+        let color = theme.warning;
+        for item in self.items.iter().chain(&self.drawing) {
+            let points: Option<Vec<Vec2>> =
+                item.points.iter().map(|p| p.to_screen(camera)).collect();
+            let Some(points) = points else {
+                continue;
+            };
+            match (item.tool, points.as_slice()) {
+                (Tool::Pen, _) => {
+                    for pair in points.windows(2) {
+                        draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, thickness, color);
+                    }
+                }
+                (Tool::Arrow, [a, b]) => {
+                    draw_line(a.x, a.y, b.x, b.y, thickness, color);
+                    let back = (*a - *b).normalize_or_zero() * theme.scaled(16.);
+                    let side = vec2(-back.y, back.x) * 0.5;
+                    draw_triangle(*b, *b + back + side, *b + back - side, color);
+                }
+                (Tool::Circle, [center, edge]) => {
+                    let radius = center.distance(*edge);
+                    draw_circle_lines(center.x, center.y, radius, thickness, color);
+                }
+                (Tool::Label, [at]) => {
+                    draw_text(&item.text, at.x, at.y, theme.text_size(26.), color);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Returns true when the scene should be saved or loaded, as (save, load).
+    pub fn draw_panel(&mut self, theme: &Theme) -> (bool, bool) {
+        let mut requested = (false, false);
+        if !self.visible {
+            return requested;
+        }
+        dock::window(
+            "annotate.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(520.)),
+            vec2(theme.scaled(320.), theme.scaled(270.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            for (tool, key) in TOOLS {
+                let label = if tool == self.tool {
+                    format!("> {}", tr(key))
+                } else {
+                    tr(key).to_string()
+                };
+                if ui.button(None, label.as_str()) {
+                    self.tool = tool;
+                }
+                ui.same_line(0.);
+            }
+            ui.separator();
+            ui.input_text(hash!(), tr("annotate.text"), &mut self.label_text);
+            ui.checkbox(hash!(), tr("annotate.anchored"), &mut self.anchored);
+            if ui.button(None, tr("annotate.undo")) {
+                self.undo();
+            }
+            ui.same_line(0.);
+            if ui.button(None, tr("annotate.clear")) {
+                self.clear();
+            }
+            ui.separator();
+            requested.0 = ui.button(None, tr("scene.save"));
+            ui.same_line(0.);
+            requested.1 = ui.button(None, tr("scene.load"));
+        });
+        requested
+    }
+}
@@ -0,0 +1,220 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::statistics::Statistics;
+use rutherford_atomic_model::world::World;
+
+use crate::json::{self, scientific, string, vector};
+
+// How long a connection waits for the render loop to answer
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+// Scenario uploads larger than this are refused
+const MAX_BODY: usize = 1 << 20;
+// Longest request line or header line, and most header lines, read before
+// the request is refused
+const MAX_LINE: usize = 8 << 10;
+const MAX_HEADERS: usize = 64;
+// How long a client has to send its whole request. Connections are served
+// one at a time, so this is also the longest a slow client holds up the rest.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const ENDPOINTS: &str = r#"["GET /particles","GET /stats","POST /scenario","POST /preset/<name>[?seed=<n>]","POST /pause","POST /resume"]"#;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub body: String,
+    reply: Sender<(u16, String)>,
+}
+
+impl Request {
+    // Sends a JSON body back with the given status.
+    pub fn respond(self, status: u16, json: String) {
+        self.reply.send((status, json)).ok();
+    }
+
+    pub fn query_value(&self, key: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+}
+
+// Optional HTTP control server for notebooks and other outside tools. The
+// listener thread only parses requests; they are answered from the render
+// loop, which owns the world, so every reply sees a whole tick.
+pub struct Api {
+    requests: Receiver<Request>,
+}
+
+impl Api {
+    pub fn start(port: u16) -> Option<Api> {
+        let listener = match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::warn!("could not start the HTTP API on port {}: {}", port, err);
+                return None;
+            }
+        };
+        let (sender, requests) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(err) = serve(stream, &sender) {
+                    log::debug!("HTTP API connection failed: {}", err);
+                }
+            }
+        });
+        log::info!("HTTP API listening on http://127.0.0.1:{}", port);
+        Some(Api { requests })
+    }
+
+    // Requests that arrived since the last call.
+    pub fn poll(&self) -> Vec<Request> {
+        self.requests.try_iter().collect()
+    }
+}
+
+// Makes reads from `stream` give up at `deadline`.
+fn read_until(stream: &TcpStream, deadline: Instant) -> std::io::Result<()> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return Err(std::io::ErrorKind::TimedOut.into());
+    }
+    stream.set_read_timeout(Some(remaining))
+}
+
+// Reads one line of at most `MAX_LINE` bytes into `line`, giving up once
+// `deadline` has passed. False if the line was cut off at the limit.
+fn read_line(
+    reader: &mut BufReader<TcpStream>,
+    line: &mut String,
+    deadline: Instant,
+) -> std::io::Result<bool> {
+    read_until(reader.get_ref(), deadline)?;
+    line.clear();
+    let read = reader.by_ref().take(MAX_LINE as u64).read_line(line)?;
+    Ok(read < MAX_LINE || line.ends_with('\n'))
+}
+
+fn serve(stream: TcpStream, requests: &Sender<Request>) -> std::io::Result<()> {
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    if !read_line(&mut reader, &mut line, deadline)? {
+        return write_response(stream, 414, &error_json("request line too long"));
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (path, query) = (path.to_string(), query.to_string());
+
+    let mut length = 0;
+    let mut json_body = false;
+    let mut headers = 0;
+    loop {
+        if !read_line(&mut reader, &mut line, deadline)? || headers == MAX_HEADERS {
+            return write_response(stream, 431, &error_json("request headers too large"));
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if let Some((name, value)) = line.split_once(':') {
+            let (name, value) = (name.trim(), value.trim());
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("content-type") {
+                json_body = value
+                    .split(';')
+                    .next()
+                    .is_some_and(|kind| kind.trim().eq_ignore_ascii_case("application/json"));
+            }
+        }
+    }
+    // A web page can only send a cross-origin POST without asking first if
+    // it is form data or plain text, so insisting on JSON keeps pages the
+    // user visits from driving the simulation.
+    let (status, json) = if method == "POST" && !json_body {
+        (
+            415,
+            error_json("POST requests need Content-Type: application/json"),
+        )
+    } else if length > MAX_BODY {
+        (413, error_json("request body too large"))
+    } else {
+        let mut body = vec![0; length];
+        read_until(reader.get_ref(), deadline)?;
+        reader.read_exact(&mut body)?;
+        let (reply, response) = channel();
+        let request = Request {
+            method,
+            path,
+            query,
+            body: String::from_utf8_lossy(&body).into_owned(),
+            reply,
+        };
+        requests.send(request).ok();
+        response
+            .recv_timeout(RESPONSE_TIMEOUT)
+            .unwrap_or_else(|_| (503, error_json("the simulation did not answer")))
+    };
+    write_response(stream, status, &json)
+}
+
+fn write_response(mut stream: TcpStream, status: u16, json: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        415 => "Unsupported Media Type",
+        431 => "Request Header Fields Too Large",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        json.len(),
+        json
+    )
+}
+
+pub fn error_json(message: &str) -> String {
+    format!("{{\"error\":{}}}", string(message))
+}
+
+pub fn particles_json(world: &World) -> String {
+    let items: Vec<String> = (0..world.particle_count())
+        .map(|slot| json::particle(world, slot))
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+pub fn stats_json(world: &World, paused: bool) -> String {
+    let statistics = Statistics::gather(world);
+    format!(
+        "{{\"particles\":{},\"paused\":{},\"kinetic_energy\":{},\"potential_energy\":{},\"total_energy\":{},\"angular_momentum\":{},\"annihilations\":{},\"detector_hits\":{},\"fps\":{}}}",
+        statistics.particles,
+        paused,
+        scientific(statistics.kinetic),
+        scientific(statistics.potential),
+        scientific(statistics.total_energy()),
+        vector(statistics.angular_momentum, scientific),
+        world.annihilations,
+        world.detector.as_ref().map_or(0, |detector| detector.hits.len()),
+        get_fps()
+    )
+}
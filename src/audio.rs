@@ -0,0 +1,239 @@
+use std::f32::consts::TAU;
+
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+use macroquad::audio::stop_sound;
+
+use rutherford_atomic_model::events::Event;
+
+const SAMPLE_RATE: u32 = 44100;
+// Sonification pitches span two octaves upward from this frequency.
+const BASE_PITCH: f32 = 220.;
+const PITCH_STEPS: usize = 25;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cue {
+    Spawn,
+    Collision,
+    Ionization,
+    Hit,
+    Annihilation,
+}
+
+const CUES: [Cue; 5] = [
+    Cue::Spawn,
+    Cue::Collision,
+    Cue::Ionization,
+    Cue::Hit,
+    Cue::Annihilation,
+];
+
+impl Cue {
+    // Frequency in Hz and duration in seconds of the generated tone.
+    fn tone(&self) -> (f32, f32) {
+        match self {
+            Cue::Spawn => (880., 0.08),
+            Cue::Collision => (220., 0.15),
+            Cue::Ionization => (1320., 0.3),
+            // Short and high, like a scintillation click
+            Cue::Hit => (2640., 0.03),
+            Cue::Annihilation => (440., 0.4),
+        }
+    }
+
+    fn for_event(event: &Event) -> Option<Cue> {
+        match event {
+            Event::Spawned(_) => Some(Cue::Spawn),
+            Event::Collision(_, _) => Some(Cue::Collision),
+            Event::Ionized(_) => Some(Cue::Ionization),
+            Event::DetectorHit(_) => Some(Cue::Hit),
+            Event::Annihilated(_, _) => Some(Cue::Annihilation),
+        }
+    }
+}
+
+pub struct Audio {
+    pub volume: f32,
+    pub muted: bool,
+    pub enabled: [bool; CUES.len()],
+    sounds: Vec<Sound>,
+}
+
+impl Audio {
+    pub async fn new() -> Audio {
+        let mut sounds = Vec::new();
+        for cue in CUES {
+            let (frequency, duration) = cue.tone();
+            match load_sound_from_bytes(&tone_wav(frequency, duration, true)).await {
+                Ok(sound) => sounds.push(sound),
+                Err(err) => {
+                    log::warn!("audio unavailable: {}", err);
+                    sounds.clear();
+                    break;
+                }
+            }
+        }
+
+        Audio {
+            volume: 0.5,
+            muted: false,
+            enabled: [true; CUES.len()],
+            sounds,
+        }
+    }
+
+    pub fn play(&self, cue: Cue) {
+        if self.muted || !self.enabled[cue as usize] {
+            return;
+        }
+        if let Some(sound) = self.sounds.get(cue as usize) {
+            play_sound(
+                *sound,
+                PlaySoundParams {
+                    looped: false,
+                    volume: self.volume,
+                },
+            );
+        }
+    }
+
+    pub fn handle(&self, events: &[Event]) {
+        // Play each cue at most once per frame so bursts don't clip
+        let mut played = [false; CUES.len()];
+        for cue in events.iter().filter_map(Cue::for_event) {
+            if !played[cue as usize] {
+                played[cue as usize] = true;
+                self.play(cue);
+            }
+        }
+    }
+
+    pub fn change_volume(&mut self, amount: f32) {
+        self.volume = (self.volume + amount).clamp(0., 1.);
+        log::info!("volume {:.0}%", self.volume * 100.);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SonifyMode {
+    OrbitalFrequency,
+    KineticEnergy,
+}
+
+// Maps a scalar of the selected particle to a looping tone. The value range
+// is learned while running, so both slow decays and fast orbits are audible.
+pub struct Sonifier {
+    pub enabled: bool,
+    pub mode: SonifyMode,
+    bank: Vec<Sound>,
+    playing: Option<usize>,
+    range: Option<(f32, f32)>,
+}
+
+impl Sonifier {
+    pub async fn new() -> Sonifier {
+        let mut bank = Vec::new();
+        for step in 0..PITCH_STEPS {
+            let frequency = BASE_PITCH * 2f32.powf(step as f32 / 12.);
+            // Whole number of cycles so the loop point doesn't click
+            let duration = (frequency * 0.5).round() / frequency;
+            match load_sound_from_bytes(&tone_wav(frequency, duration, false)).await {
+                Ok(sound) => bank.push(sound),
+                Err(_) => {
+                    bank.clear();
+                    break;
+                }
+            }
+        }
+
+        Sonifier {
+            enabled: false,
+            mode: SonifyMode::OrbitalFrequency,
+            bank,
+            playing: None,
+            range: None,
+        }
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            SonifyMode::OrbitalFrequency => SonifyMode::KineticEnergy,
+            SonifyMode::KineticEnergy => SonifyMode::OrbitalFrequency,
+        };
+        self.range = None;
+    }
+
+    pub fn update(&mut self, value: Option<f32>, audio: &Audio) {
+        let step = match value {
+            Some(value) if self.enabled && !audio.muted && value > 0. && value.is_finite() => {
+                Some(self.step_for(value.ln()))
+            }
+            _ => {
+                self.range = None;
+                None
+            }
+        };
+        if step == self.playing {
+            return;
+        }
+
+        if let Some(sound) = self.playing.and_then(|i| self.bank.get(i)) {
+            stop_sound(*sound);
+        }
+        if let Some(sound) = step.and_then(|i| self.bank.get(i)) {
+            play_sound(
+                *sound,
+                PlaySoundParams {
+                    looped: true,
+                    volume: audio.volume * 0.5,
+                },
+            );
+        }
+        self.playing = step;
+    }
+
+    fn step_for(&mut self, value: f32) -> usize {
+        let (min, max) = match self.range {
+            Some((min, max)) => (min.min(value), max.max(value)),
+            None => (value, value),
+        };
+        self.range = Some((min, max));
+        if max - min <= f32::EPSILON {
+            return PITCH_STEPS / 2;
+        }
+        let t = (value - min) / (max - min);
+        (t * (PITCH_STEPS - 1) as f32).round() as usize
+    }
+}
+
+// 16-bit mono PCM sine wrapped in a WAV header, optionally fading out.
+fn tone_wav(frequency: f32, duration: f32, fade: bool) -> Vec<u8> {
+    let samples = (SAMPLE_RATE as f32 * duration) as u32;
+    let data_len = samples * 2;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    for n in 0..samples {
+        let t = n as f32 / SAMPLE_RATE as f32;
+        let envelope = if fade {
+            1. - n as f32 / samples as f32
+        } else {
+            1.
+        };
+        let value = (t * frequency * TAU).sin() * envelope * i16::MAX as f32 * 0.8;
+        wav.extend_from_slice(&(value as i16).to_le_bytes());
+    }
+    wav
+}
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::events::BOUND_RADIUS;
+use rutherford_atomic_model::particle::{Particle, PARTICLE_RADIUS};
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Ticks between the snapshots kept for jumping back, and how many are kept
+const SNAPSHOT_INTERVAL: u64 = 60;
+const BUFFER: usize = 60;
+// A jump lands at least this many ticks before the event, so it plays out
+const LEAD: u64 = 30;
+// Oldest bookmarks are dropped beyond this many
+const MAX_BOOKMARKS: usize = 200;
+// Two particles closer than this have nearly collided
+const NEAR_DISTANCE: f32 = 4. * PARTICLE_RADIUS;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Kind {
+    // An alpha turned back on itself, more than 90° from where it was heading
+    BackScatter,
+    // An electron became bound to a proton
+    Capture,
+    NearCollision,
+}
+
+impl Kind {
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            Kind::BackScatter => "bookmarks.back_scatter",
+            Kind::Capture => "bookmarks.capture",
+            Kind::NearCollision => "bookmarks.near_collision",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    pub kind: Kind,
+    pub tick: u64,
+    pub entities: (Entity, Entity),
+    pub detail: String,
+}
+
+// Spots notable events as the simulation runs and bookmarks them on a
+// timeline. Snapshots taken every `SNAPSHOT_INTERVAL` ticks make up the
+// replay buffer a bookmark jumps back into, as long as it is still recent
+// enough to be covered.
+pub struct Bookmarks {
+    pub visible: bool,
+    tick: u64,
+    snapshots: VecDeque<(u64, World)>,
+    bookmarks: VecDeque<Bookmark>,
+    // Particles seen before, so that a scene starting out bound or close
+    // together isn't reported
+    known: HashSet<Entity>,
+    // Where each alpha was first seen heading
+    headings: HashMap<Entity, Vec3>,
+    back_scattered: HashSet<Entity>,
+    captured: HashSet<Entity>,
+    near: HashSet<(Entity, Entity)>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Bookmarks {
+        Bookmarks {
+            visible: false,
+            tick: 0,
+            snapshots: VecDeque::with_capacity(BUFFER),
+            bookmarks: VecDeque::new(),
+            known: HashSet::new(),
+            headings: HashMap::new(),
+            back_scattered: HashSet::new(),
+            captured: HashSet::new(),
+            near: HashSet::new(),
+        }
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World) {
+        if self.tick.is_multiple_of(SNAPSHOT_INTERVAL) {
+            if self.snapshots.len() == BUFFER {
+                self.snapshots.pop_front();
+            }
+            self.snapshots.push_back((self.tick, world.clone()));
+        }
+        self.detect(world);
+        self.tick += 1;
+    }
+
+    fn detect(&mut self, world: &World) {
+        let particles = &world.particles;
+        let entities = particles.entities();
+        let mut found = Vec::new();
+        let fresh: HashSet<Entity> = entities
+            .iter()
+            .copied()
+            .filter(|entity| self.known.insert(*entity))
+            .collect();
+
+        for (i, entity) in entities.iter().enumerate() {
+            match particles.particle[i] {
+                Particle::Alpha => {
+                    let vel = particles.vel[i];
+                    let Some(heading) = self.headings.get(entity) else {
+                        if vel != Vec3::ZERO {
+                            self.headings.insert(*entity, vel);
+                        }
+                        continue;
+                    };
+                    let angle = heading.angle_between(vel).to_degrees();
+                    if angle <= 90. {
+                        self.back_scattered.remove(entity);
+                    } else if self.back_scattered.insert(*entity) {
+                        found.push((
+                            Kind::BackScatter,
+                            (*entity, *entity),
+                            trf("bookmarks.angle", &[&format!("{:.0}", angle)]),
+                        ));
+                    }
+                }
+                Particle::Electron => {
+                    let Some(j) = particles.nearest(i, Particle::Proton) else {
+                        self.captured.remove(entity);
+                        continue;
+                    };
+                    let distance = particles.pos[i].distance(particles.pos[j]);
+                    let relative = particles.vel[i] - particles.vel[j];
+                    let energy = 0.5 * particles.mass[i] * relative.length_squared()
+                        + diagnostics::pair_potential(
+                            &world.force_model,
+                            particles.charge[i],
+                            particles.charge[j],
+                            distance,
+                        );
+                    let bound = distance < BOUND_RADIUS && energy < 0.;
+                    if !bound {
+                        self.captured.remove(entity);
+                    } else if self.captured.insert(*entity) && !fresh.contains(entity) {
+                        found.push((
+                            Kind::Capture,
+                            (*entity, entities[j]),
+                            trf("bookmarks.energy", &[&format!("{:.3e}", energy)]),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut near = HashSet::new();
+        for i in 0..particles.len() {
+            for j in (i + 1)..particles.len() {
+                let distance = particles.pos[i].distance(particles.pos[j]);
+                if distance >= NEAR_DISTANCE {
+                    continue;
+                }
+                let pair = (entities[i], entities[j]);
+                if !self.near.contains(&pair)
+                    && !fresh.contains(&pair.0)
+                    && !fresh.contains(&pair.1)
+                {
+                    found.push((
+                        Kind::NearCollision,
+                        pair,
+                        trf("bookmarks.distance", &[&format!("{:.2}", distance)]),
+                    ));
+                }
+                near.insert(pair);
+            }
+        }
+        self.near = near;
+
+        self.known
+            .retain(|entity| particles.slot(*entity).is_some());
+        self.headings
+            .retain(|entity, _| particles.slot(*entity).is_some());
+        self.back_scattered
+            .retain(|entity| particles.slot(*entity).is_some());
+        self.captured
+            .retain(|entity| particles.slot(*entity).is_some());
+        for (kind, entities, detail) in found {
+            self.add(world, kind, entities, detail);
+        }
+    }
+
+    fn add(&mut self, world: &World, kind: Kind, entities: (Entity, Entity), detail: String) {
+        let species = |entity| {
+            let particles = &world.particles;
+            particles
+                .slot(entity)
+                .map_or_else(String::new, |slot| particles.particle[slot].name())
+        };
+        log::info!(
+            "bookmarked {:?} of {} at tick {}",
+            kind,
+            species(entities.0),
+            self.tick
+        );
+        // Replaying past an event finds it again; move the bookmark rather
+        // than adding a second one
+        self.bookmarks
+            .retain(|bookmark| bookmark.kind != kind || bookmark.entities != entities);
+        if self.bookmarks.len() == MAX_BOOKMARKS {
+            self.bookmarks.pop_front();
+        }
+        self.bookmarks.push_back(Bookmark {
+            kind,
+            tick: self.tick,
+            entities,
+            detail,
+        });
+    }
+
+    // Latest snapshot at least `LEAD` ticks before `tick`, or the oldest one
+    // if the event is that close to it
+    fn snapshot_for(&self, tick: u64) -> Option<&(u64, World)> {
+        let (oldest, _) = self.snapshots.front()?;
+        if tick < *oldest {
+            return None;
+        }
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|(at, _)| *at + LEAD <= tick)
+            .or(self.snapshots.front())
+    }
+
+    // Rewinds to just before a bookmark and drops the snapshots after it, as
+    // the run diverges from there.
+    fn jump(&mut self, tick: u64) -> Option<World> {
+        let (at, world) = self.snapshot_for(tick)?.clone();
+        self.snapshots.retain(|(kept, _)| *kept <= at);
+        self.snapshots.pop_back();
+        self.tick = at;
+        log::info!("jumped back to tick {}", at);
+        Some(world)
+    }
+
+    // Returns the world to continue from when a bookmark is picked.
+    pub fn draw(&mut self, theme: &Theme) -> Option<World> {
+        if !self.visible {
+            return None;
+        }
+        let mut jump = None;
+        dock::window(
+            "bookmarks.title",
+            hash!(),
+            vec2(theme.scaled(380.), theme.scaled(160.)),
+            vec2(theme.scaled(380.), theme.scaled(400.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            ui.label(
+                None,
+                &trf("bookmarks.tick", &[&self.tick, &self.bookmarks.len()]),
+            );
+            if ui.button(None, tr("bookmarks.clear")) {
+                self.bookmarks.clear();
+            }
+            ui.separator();
+            if self.bookmarks.is_empty() {
+                ui.label(None, tr("bookmarks.none"));
+            }
+            for bookmark in self.bookmarks.iter().rev() {
+                let text = trf(
+                    "bookmarks.entry",
+                    &[
+                        &bookmark.tick,
+                        &tr(bookmark.kind.name_key()),
+                        &bookmark.detail,
+                    ],
+                );
+                if self.snapshot_for(bookmark.tick).is_none() {
+                    ui.label(None, &trf("bookmarks.expired", &[&text]));
+                } else if ui.button(None, text.as_str()) {
+                    jump = Some(bookmark.tick);
+                }
+            }
+        });
+        jump.and_then(|tick| self.jump(tick))
+    }
+}
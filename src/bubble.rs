@@ -0,0 +1,70 @@
+use macroquad::prelude::*;
+
+use crate::world::World;
+
+// A sphere in which time runs slower for the particles inside, or stands
+// still. Particles outside still feel the ones inside, which push and pull
+// like any other charge, so a busy nucleus can be held while a scene is set
+// up around it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimeBubble {
+    pub center: Vec3,
+    pub radius: f32,
+    // Fraction of each tick that passes inside, 0 when frozen
+    pub rate: f32,
+}
+
+impl TimeBubble {
+    pub fn contains(&self, pos: Vec3) -> bool {
+        pos.distance_squared(self.center) <= self.radius * self.radius
+    }
+}
+
+// How fast time passes at `pos`; the slowest bubble wins where they overlap.
+pub fn rate_at(bubbles: &[TimeBubble], pos: Vec3) -> f32 {
+    bubbles
+        .iter()
+        .filter(|bubble| bubble.contains(pos))
+        .map(|bubble| bubble.rate)
+        .fold(1., f32::min)
+}
+
+// A particle that starts a tick inside a bubble: its slot, the bubble's
+// rate, and its position and velocity before the tick.
+pub struct Held {
+    slot: usize,
+    rate: f32,
+    pos: Vec3,
+    vel: Vec3,
+}
+
+// Every particle inside a bubble; call before the tick is integrated.
+pub fn held(world: &World) -> Vec<Held> {
+    if world.bubbles.is_empty() {
+        return Vec::new();
+    }
+    let particles = &world.particles;
+    (0..particles.len())
+        .filter_map(|slot| {
+            let rate = rate_at(&world.bubbles, particles.pos[slot]);
+            (rate < 1.).then(|| Held {
+                slot,
+                rate,
+                pos: particles.pos[slot],
+                vel: particles.vel[slot],
+            })
+        })
+        .collect()
+}
+
+// Takes back part of the tick just integrated for the particles `held`
+// found inside a bubble, so each only moves on by its bubble's share of the
+// tick.
+pub fn dilate(world: &mut World, held: &[Held]) {
+    let particles = &mut world.particles;
+    for held in held {
+        let i = held.slot;
+        particles.pos[i] = held.pos.lerp(particles.pos[i], held.rate);
+        particles.vel[i] = held.vel.lerp(particles.vel[i], held.rate);
+    }
+}
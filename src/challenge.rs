@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::events::Event;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Ticks an orbit has to stay within tolerance to count as stable
+const STABLE_TICKS: u32 = 300;
+const ORBIT_TOLERANCE: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Goal {
+    Ionize,
+    Collide,
+    StableOrbit(f32),
+    Backscatter,
+}
+
+struct Challenge {
+    key: &'static str,
+    goal: Goal,
+    setup: &'static str,
+    points: u32,
+}
+
+const CHALLENGES: [Challenge; 4] = [
+    Challenge {
+        key: "challenge.collide",
+        goal: Goal::Collide,
+        setup: "particle = proton 0 0 0\nparticle = electron 80 0 0",
+        points: 100,
+    },
+    Challenge {
+        key: "challenge.orbit",
+        goal: Goal::StableOrbit(50.),
+        setup: "particle = proton 0 0 0\nparticle = electron 50 0 0",
+        points: 300,
+    },
+    Challenge {
+        key: "challenge.backscatter",
+        goal: Goal::Backscatter,
+        setup: "particle = proton 0 0 0\nparticle = proton -200 5 0 0.5 0 0",
+        points: 200,
+    },
+    Challenge {
+        key: "challenge.ionize",
+        goal: Goal::Ionize,
+        setup: "particle = proton 100 0 0\nparticle = electron 200 0 0",
+        points: 200,
+    },
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Phase {
+    Playing,
+    Succeeded,
+    Finished,
+}
+
+// Gamified mode: a fixed sequence of tasks, each starting from its own scene
+// and checked automatically from the world state and simulation events.
+pub struct Quiz {
+    current: Option<usize>,
+    phase: Phase,
+    elapsed: f32,
+    stable_ticks: u32,
+    // Starting velocity of every particle, for measuring deflection
+    initial: HashMap<Entity, Vec3>,
+    scores: Vec<Option<u32>>,
+}
+
+impl Quiz {
+    pub fn new() -> Quiz {
+        Quiz {
+            current: None,
+            phase: Phase::Playing,
+            elapsed: 0.,
+            stable_ticks: 0,
+            initial: HashMap::new(),
+            scores: Vec::new(),
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn stop(&mut self) {
+        self.current = None;
+    }
+
+    // Starts the first challenge and returns its scene.
+    pub fn start(&mut self) -> World {
+        self.scores.clear();
+        self.begin(0)
+    }
+
+    fn begin(&mut self, index: usize) -> World {
+        let world = Scenario::parse(CHALLENGES[index].setup)
+            .expect("challenge scenes are valid")
+            .build();
+        self.current = Some(index);
+        self.phase = Phase::Playing;
+        self.elapsed = 0.;
+        self.stable_ticks = 0;
+        self.initial = world
+            .particles
+            .entities()
+            .iter()
+            .copied()
+            .zip(world.particles.vel.iter().copied())
+            .collect();
+        world
+    }
+
+    // Enter moves on: to the next challenge after a success, skipping the
+    // current one (for no points) otherwise. Returns the next scene, if any.
+    pub fn next(&mut self) -> Option<World> {
+        let index = self.current?;
+        if self.phase == Phase::Finished {
+            self.current = None;
+            return None;
+        }
+        if self.phase == Phase::Playing {
+            self.scores.push(None);
+        }
+        if index + 1 < CHALLENGES.len() {
+            Some(self.begin(index + 1))
+        } else {
+            self.phase = Phase::Finished;
+            None
+        }
+    }
+
+    pub fn update(&mut self, world: &World, events: &[Event], ticked: bool, delta: f32) {
+        let Some(index) = self.current else {
+            return;
+        };
+        if self.phase != Phase::Playing {
+            return;
+        }
+        self.elapsed += delta;
+        let challenge = &CHALLENGES[index];
+        let particles = &world.particles;
+
+        let done = match challenge.goal {
+            Goal::Ionize => events.iter().any(|e| matches!(e, Event::Ionized(_))),
+            Goal::Collide => events.iter().any(|e| match e {
+                Event::Collision(a, b) => {
+                    let species = |entity: &Entity| {
+                        particles.slot(*entity).map(|slot| particles.particle[slot])
+                    };
+                    let pair = (species(a), species(b));
+                    pair == (Some(Particle::Electron), Some(Particle::Proton))
+                        || pair == (Some(Particle::Proton), Some(Particle::Electron))
+                }
+                _ => false,
+            }),
+            Goal::StableOrbit(radius) => {
+                let in_orbit = (0..particles.len()).any(|i| {
+                    particles.particle[i] == Particle::Electron
+                        && particles.nearest(i, Particle::Proton).is_some_and(|j| {
+                            let r = particles.pos[i].distance(particles.pos[j]);
+                            (r - radius).abs() <= radius * ORBIT_TOLERANCE
+                        })
+                });
+                if ticked {
+                    self.stable_ticks = if in_orbit { self.stable_ticks + 1 } else { 0 };
+                }
+                self.stable_ticks >= STABLE_TICKS
+            }
+            Goal::Backscatter => self.initial.iter().any(|(entity, start)| {
+                *start != Vec3::ZERO
+                    && particles.slot(*entity).is_some_and(|slot| {
+                        let vel = particles.vel[slot];
+                        vel != Vec3::ZERO && start.angle_between(vel) > std::f32::consts::FRAC_PI_2
+                    })
+            }),
+        };
+
+        if done {
+            // Faster solutions score higher, down to a tenth of the points
+            let bonus = (1. - self.elapsed / 120.).max(0.1);
+            let score = (challenge.points as f32 * bonus).round() as u32;
+            self.scores.push(Some(score));
+            self.phase = Phase::Succeeded;
+            log::info!("{}", trf("challenge.succeeded", &[&score]));
+        }
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        let Some(index) = self.current else {
+            return;
+        };
+        let width = theme.scaled(560.);
+        let x = (screen_width() - width) / 2.;
+        let y = theme.scaled(10.);
+        let line = theme.scaled(26.);
+        let size = theme.text_size(22.);
+        let left = x + theme.scaled(14.);
+
+        if self.phase == Phase::Finished {
+            let height = line * (CHALLENGES.len() as f32 + 3.);
+            draw_rectangle(x, y, width, height, theme.panel);
+            draw_text(
+                tr("challenge.results"),
+                left,
+                y + line,
+                theme.text_size(30.),
+                theme.text,
+            );
+            for (i, (challenge, score)) in CHALLENGES.iter().zip(&self.scores).enumerate() {
+                let result = match score {
+                    Some(points) => trf("challenge.points", &[points]),
+                    None => tr("challenge.skipped").to_string(),
+                };
+                draw_text(
+                    &format!("{}  {}", tr(challenge.key), result),
+                    left,
+                    y + line * (i as f32 + 2.),
+                    size,
+                    theme.text,
+                );
+            }
+            let total: u32 = self.scores.iter().flatten().sum();
+            draw_text(
+                &trf("challenge.total", &[&total]),
+                left,
+                y + line * (CHALLENGES.len() as f32 + 2.5),
+                size,
+                theme.warning,
+            );
+            return;
+        }
+
+        let height = line * 3.;
+        draw_rectangle(x, y, width, height, theme.panel);
+        let header = trf("challenge.header", &[&(index + 1), &CHALLENGES.len()]);
+        draw_text(&header, left, y + line, size, theme.warning);
+        draw_text(
+            tr(CHALLENGES[index].key),
+            left,
+            y + line * 2.,
+            size,
+            theme.text,
+        );
+        let status = match self.phase {
+            Phase::Succeeded => tr("challenge.success").to_string(),
+            _ => trf("challenge.elapsed", &[&format!("{:.1}", self.elapsed)]),
+        };
+        draw_text(
+            &status,
+            left,
+            y + line * 2.8,
+            theme.text_size(18.),
+            theme.text,
+        );
+    }
+}
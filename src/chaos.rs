@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::units::SECONDS_PER_TICK;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Distance in units the shadow starts off from the main world, and is brought
+// back to after every `RENORMALIZE` ticks
+const SEPARATION: f32 = 1e-3;
+const RENORMALIZE: u32 = 10;
+// A jump this far within one period can only mean the main world was
+// replaced, by an undo or a load, and the shadow is no longer its twin
+const DESYNC: f32 = 10.;
+// Renormalizations kept for the divergence plot
+const HISTORY: usize = 1000;
+
+// Largest Lyapunov exponent of the scene, estimated by the method of
+// Benettin et al.: a shadow copy of the world starts a tiny distance away
+// and is stepped in lockstep with it. Every few ticks the growth of the
+// distance between them is logged and the shadow is pulled back in along
+// the same direction, so the separation never saturates at the size of the
+// atom. The logged growth, summed, climbs in a straight line for a chaotic
+// scene such as a classical helium atom, and levels off for a regular one
+// such as hydrogen.
+pub struct Chaos {
+    shadow: Option<World>,
+    // Ticks since the shadow started, and since the last renormalization
+    ticks: u64,
+    since: u32,
+    // Log of the total growth, after each renormalization
+    growth: VecDeque<(u64, f32)>,
+    total: f32,
+}
+
+impl Chaos {
+    pub fn new() -> Chaos {
+        Chaos {
+            shadow: None,
+            ticks: 0,
+            since: 0,
+            growth: VecDeque::with_capacity(HISTORY),
+            total: 0.,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.shadow.is_some()
+    }
+
+    // Starts a shadow with `target`, or the first electron, nudged off.
+    // Returns false when there is nothing to nudge.
+    pub fn start(&mut self, world: &World, target: Option<Entity>) -> bool {
+        let particles = &world.particles;
+        let slot = target
+            .and_then(|entity| particles.slot(entity))
+            .or_else(|| {
+                particles
+                    .particle
+                    .iter()
+                    .position(|p| *p == Particle::Electron)
+            });
+        let Some(slot) = slot else {
+            return false;
+        };
+        let mut shadow = world.clone();
+        shadow.events.clear();
+        shadow.particles.pos[slot] += world.constrain(Vec3::ONE).normalize() * SEPARATION;
+        *self = Chaos::new();
+        self.shadow = Some(shadow);
+        true
+    }
+
+    pub fn stop(&mut self) {
+        *self = Chaos::new();
+    }
+
+    // Steps the shadow along with the main world; call once per tick, after
+    // the main world has stepped.
+    pub fn step(&mut self, world: &World) {
+        let Some(shadow) = &mut self.shadow else {
+            return;
+        };
+        if shadow.particles.entities() != world.particles.entities() {
+            self.stop();
+            return;
+        }
+        systems::step(shadow);
+        // Only the main world drives sounds and other event consumers
+        shadow.events.clear();
+        self.ticks += 1;
+        self.since += 1;
+        if self.since < RENORMALIZE {
+            return;
+        }
+        self.since = 0;
+        let distance = separation(world, shadow);
+        if distance > DESYNC {
+            self.stop();
+            return;
+        }
+        if distance == 0. {
+            return;
+        }
+        // Back to the starting distance, along the direction it grew in
+        let scale = SEPARATION / distance;
+        let (ours, theirs) = (&world.particles, &mut shadow.particles);
+        for i in 0..ours.len() {
+            theirs.pos[i] = ours.pos[i] + (theirs.pos[i] - ours.pos[i]) * scale;
+            theirs.vel[i] = ours.vel[i] + (theirs.vel[i] - ours.vel[i]) * scale;
+        }
+        self.total += (distance / SEPARATION).ln();
+        if self.growth.len() == HISTORY {
+            self.growth.pop_front();
+        }
+        self.growth.push_back((self.ticks, self.total));
+    }
+
+    // Exponent in e-foldings per tick, once there is something to go on.
+    fn exponent(&self) -> Option<f32> {
+        (!self.growth.is_empty()).then(|| self.total / self.ticks as f32)
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if self.shadow.is_none() {
+            return;
+        }
+        let (width, height) = (theme.scaled(340.), theme.scaled(240.));
+        let x = screen_width() - width - theme.scaled(10.);
+        let y = screen_height() - height - theme.scaled(10.);
+        let Rect {
+            x,
+            y,
+            w: width,
+            h: height,
+        } = dock::place("chaos.title", Rect::new(x, y, width, height));
+        draw_rectangle(x, y, width, height, theme.panel);
+        let size = theme.text_size(18.);
+        let left = x + 6.;
+        draw_text(tr("chaos.title"), left, y + size, size, theme.text);
+        let Some(exponent) = self.exponent() else {
+            draw_text(tr("chaos.waiting"), left, y + size * 2.2, size, theme.text);
+            return;
+        };
+        let per_second = exponent / SECONDS_PER_TICK;
+        let lines = [
+            trf(
+                "chaos.exponent",
+                &[&format!("{:.2e}", exponent), &format!("{:.2e}", per_second)],
+            ),
+            if exponent > 0. {
+                trf("chaos.time", &[&format!("{:.0}", 1. / exponent)])
+            } else {
+                tr("chaos.regular").to_string()
+            },
+        ];
+        for (k, line) in lines.iter().enumerate() {
+            draw_text(line, left, y + size * (k as f32 + 2.2), size, theme.text);
+        }
+
+        // Total growth against time
+        let Some(&(last_tick, _)) = self.growth.back() else {
+            return;
+        };
+        let first_tick = self.growth.front().map_or(0, |(tick, _)| *tick);
+        let (low, high) = self
+            .growth
+            .iter()
+            .fold((0f32, f32::MIN_POSITIVE), |(low, high), (_, total)| {
+                (low.min(*total), high.max(*total))
+            });
+        let plot = Rect::new(
+            left,
+            y + size * 4.4,
+            width - 12.,
+            height - size * 5.6 - theme.scaled(8.),
+        );
+        let span = (last_tick - first_tick).max(1) as f32;
+        let to_screen = |(tick, total): (u64, f32)| {
+            vec2(
+                plot.x + (tick - first_tick) as f32 / span * plot.w,
+                plot.y + plot.h - (total - low) / (high - low) * plot.h,
+            )
+        };
+        for k in 1..self.growth.len() {
+            let a = to_screen(self.growth[k - 1]);
+            let b = to_screen(self.growth[k]);
+            draw_line(a.x, a.y, b.x, b.y, theme.scaled(1.5), theme.selection);
+        }
+        let axes = trf("chaos.axes", &[&format!("{:.1}", high)]);
+        draw_text(&axes, left, y + height - 6., size, theme.text);
+    }
+}
+
+// Distance between two copies of the same world, over every position.
+fn separation(a: &World, b: &World) -> f32 {
+    a.particles
+        .pos
+        .iter()
+        .zip(&b.particles.pos)
+        .map(|(p, q)| p.distance_squared(*q))
+        .sum::<f32>()
+        .sqrt()
+}
@@ -0,0 +1,149 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::lz;
+use rutherford_atomic_model::snapshot;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::settings::Settings;
+use crate::theme::Theme;
+use crate::toast;
+
+// Seconds of running simulation between automatic checkpoints
+const INTERVAL: f32 = 60.;
+// Older checkpoints are deleted once there are more than this many
+const KEEP: usize = 10;
+const EXTENSION: &str = "rck";
+
+pub fn dir() -> PathBuf {
+    Settings::path().with_file_name("checkpoints")
+}
+
+pub fn load(path: &Path) -> Option<World> {
+    let result = fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|data| lz::decompress(&data).ok_or_else(|| "corrupt checkpoint".to_string()))
+        .and_then(|text| snapshot::from_text(&String::from_utf8_lossy(&text)));
+    match result {
+        Ok(world) => {
+            log::info!("restored checkpoint {}", path.display());
+            Some(world)
+        }
+        Err(err) => {
+            log::warn!("could not restore checkpoint {}: {}", path.display(), err);
+            toast::warning(tr("checkpoints.not_restored").to_string());
+            None
+        }
+    }
+}
+
+// Writes a checkpoint every `INTERVAL` seconds the simulation runs, and lists
+// the saved ones, newest first, for restoring.
+pub struct Checkpoints {
+    pub visible: bool,
+    running: f32,
+    // Newest first, with when each was written
+    files: Vec<(PathBuf, SystemTime)>,
+}
+
+impl Checkpoints {
+    pub fn new() -> Checkpoints {
+        let mut checkpoints = Checkpoints {
+            visible: false,
+            running: 0.,
+            files: Vec::new(),
+        };
+        checkpoints.scan();
+        checkpoints
+    }
+
+    fn scan(&mut self) {
+        self.files = fs::read_dir(dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|path| {
+                let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                Some((path, modified))
+            })
+            .collect();
+        self.files.sort_by_key(|(_, modified)| Reverse(*modified));
+    }
+
+    // Call on frames the simulation advanced.
+    pub fn update(&mut self, world: &World, delta: f32) {
+        self.running += delta;
+        if self.running >= INTERVAL {
+            self.save(world);
+        }
+    }
+
+    pub fn save(&mut self, world: &World) {
+        self.running = 0.;
+        let dir = dir();
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis());
+        let path = dir.join(format!("checkpoint-{}.{}", stamp, EXTENSION));
+        let data = lz::compress(snapshot::to_text(world).as_bytes());
+        match fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &data)) {
+            Ok(()) => log::info!("wrote checkpoint {} ({} bytes)", path.display(), data.len()),
+            Err(err) => {
+                log::warn!("could not write checkpoint {}: {}", path.display(), err);
+                return;
+            }
+        }
+        self.scan();
+        for (old, _) in self.files.drain(KEEP.min(self.files.len())..) {
+            if let Err(err) = fs::remove_file(&old) {
+                log::warn!("could not delete old checkpoint {}: {}", old.display(), err);
+            }
+        }
+    }
+
+    // Returns the restored world when a checkpoint is picked.
+    pub fn draw(&mut self, world: &World, theme: &Theme) -> Option<World> {
+        if !self.visible {
+            return None;
+        }
+        let mut restored = None;
+        let now = SystemTime::now();
+        dock::window(
+            "checkpoints.title",
+            hash!(),
+            vec2(theme.scaled(380.), theme.scaled(160.)),
+            vec2(theme.scaled(320.), theme.scaled(100. + 28. * KEEP as f32)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            if ui.button(None, tr("checkpoints.save")) {
+                self.save(world);
+            }
+            ui.separator();
+            if self.files.is_empty() {
+                ui.label(None, tr("checkpoints.none"));
+            }
+            let mut chosen = None;
+            for (path, modified) in &self.files {
+                let minutes = now
+                    .duration_since(*modified)
+                    .map_or(0, |age| age.as_secs() / 60);
+                if ui.button(None, trf("checkpoints.entry", &[&minutes]).as_str()) {
+                    chosen = Some(path.clone());
+                }
+            }
+            if let Some(path) = chosen {
+                restored = load(&path);
+            }
+        });
+        restored
+    }
+}
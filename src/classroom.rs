@@ -0,0 +1,299 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use rutherford_atomic_model::foil;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::snapshot;
+use rutherford_atomic_model::world::World;
+
+// Frames between snapshots. Students keep simulating in between, so this only
+// has to catch the drift between machines and the teacher's edits.
+const SYNC_FRAMES: u32 = 30;
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+// Time a new connection has to give the join code
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+// Join codes are this many characters, left out the ones easily mistaken
+// for each other on a board
+const CODE_LENGTH: usize = 6;
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+// Snapshots larger than this are taken to be a corrupt stream
+const MAX_SNAPSHOT: usize = 16 << 20;
+
+// What a student mirrors: the teacher's whole world, run settings
+// included, and whether it is paused.
+pub struct Snapshot {
+    pub world: World,
+    pub paused: bool,
+}
+
+// A student opens with `join <code>`, and a teacher given the wrong code
+// answers `refused` and hangs up. After that each message is a header line,
+// `sync <paused> <length>`, followed by that many bytes of the world as
+// `snapshot::to_text` writes it.
+fn encode(world: &World, paused: bool) -> Vec<u8> {
+    let text = snapshot::to_text(world);
+    let mut message = format!("sync {} {}\n", paused as u8, text.len()).into_bytes();
+    message.extend_from_slice(text.as_bytes());
+    message
+}
+
+// Random code for students to join with, from the standard library's
+// per-process hash keys, which the system seeds
+fn join_code() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(time) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(time.as_nanos());
+    }
+    let mut bits = hasher.finish();
+    (0..CODE_LENGTH)
+        .map(|_| {
+            let symbol = CODE_ALPHABET[(bits % CODE_ALPHABET.len() as u64) as usize];
+            bits /= CODE_ALPHABET.len() as u64;
+            symbol as char
+        })
+        .collect()
+}
+
+// Reads the join line of a new connection and answers a wrong one.
+fn admit(stream: &TcpStream, code: &str) -> bool {
+    stream.set_read_timeout(Some(JOIN_TIMEOUT)).ok();
+    let mut line = String::new();
+    let read = BufReader::new(stream.take(64)).read_line(&mut line);
+    let admitted = read.is_ok()
+        && line
+            .strip_prefix("join ")
+            .is_some_and(|given| given.trim().eq_ignore_ascii_case(code));
+    if !admitted {
+        (&*stream).write_all(b"refused\n").ok();
+    }
+    admitted
+}
+
+// Broadcasts the scene to every connected student that gives the join code.
+pub struct Teacher {
+    frames: u32,
+    students: Arc<AtomicUsize>,
+    messages: SyncSender<Vec<u8>>,
+    code: String,
+}
+
+impl Teacher {
+    pub fn start(port: u16) -> Option<Teacher> {
+        let listener = match TcpListener::bind(("0.0.0.0", port)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::warn!("could not start classroom mode on port {}: {}", port, err);
+                return None;
+            }
+        };
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let students = Arc::new(AtomicUsize::new(0));
+        let code = join_code();
+
+        let (accepted, accept_count) = (Arc::clone(&clients), Arc::clone(&students));
+        let expected = code.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // On a thread of its own, so one slow connection does not
+                // hold up the rest
+                let (accepted, accept_count) = (Arc::clone(&accepted), Arc::clone(&accept_count));
+                let expected = expected.clone();
+                thread::spawn(move || {
+                    let peer = stream.peer_addr().ok();
+                    stream.set_write_timeout(Some(WRITE_TIMEOUT)).ok();
+                    if !admit(&stream, &expected) {
+                        log::warn!("refused a student from {:?}: wrong join code", peer);
+                        return;
+                    }
+                    log::info!("student connected from {:?}", peer);
+                    let mut clients = accepted.lock().unwrap_or_else(PoisonError::into_inner);
+                    clients.push(stream);
+                    accept_count.store(clients.len(), Ordering::Relaxed);
+                });
+            }
+        });
+
+        // Only the newest snapshot matters, so a short queue is enough
+        let (messages, receiver) = sync_channel::<Vec<u8>>(2);
+        let (sending, send_count) = (Arc::clone(&clients), Arc::clone(&students));
+        thread::spawn(move || {
+            for message in receiver {
+                let mut clients = sending.lock().unwrap_or_else(PoisonError::into_inner);
+                clients.retain_mut(|client| client.write_all(&message).is_ok());
+                send_count.store(clients.len(), Ordering::Relaxed);
+            }
+        });
+
+        log::info!("teaching on port {}, join code {}", port, code);
+        Some(Teacher {
+            frames: 0,
+            students,
+            messages,
+            code,
+        })
+    }
+
+    pub fn students(&self) -> usize {
+        self.students.load(Ordering::Relaxed)
+    }
+
+    // What students pass as `--code` to join.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    // Call once per frame; sends a snapshot every `SYNC_FRAMES` frames, or at
+    // once when `edited`.
+    pub fn update(&mut self, world: &World, paused: bool, edited: bool) {
+        self.frames += 1;
+        if (edited || self.frames >= SYNC_FRAMES) && self.students() > 0 {
+            self.frames = 0;
+            self.messages.try_send(encode(world, paused)).ok();
+        }
+    }
+}
+
+// Brings `world` in line with the teacher's. Bodies are moved in place when
+// both hold the same scene, so trails and the selection survive, and the
+// run settings are copied over; otherwise the teacher's world replaces it
+// outright. Returns whether it was replaced.
+pub fn mirror(world: &mut World, teacher: World) -> bool {
+    if !Scenario::from_world(&teacher).apply_to(world) {
+        *world = teacher;
+        return true;
+    }
+    let bodies = |world: &World| -> Vec<usize> {
+        let entities = world.particles.entities();
+        (0..entities.len())
+            .filter(|i| foil::foil_of(world, entities[*i]).is_none())
+            .collect()
+    };
+    for (mine, theirs) in bodies(world).into_iter().zip(bodies(&teacher)) {
+        world.particles.mass[mine] = teacher.particles.mass[theirs];
+        world.particles.charge[mine] = teacher.particles.charge[theirs];
+    }
+    world.rng = teacher.rng;
+    world.integrator = teacher.integrator;
+    world.substepping = teacher.substepping;
+    world.pair_forces = teacher.pair_forces;
+    world.force_model = teacher.force_model;
+    world.bounds = teacher.bounds;
+    world.bubbles = teacher.bubbles;
+    world.sources = teacher.sources;
+    world.magnets = teacher.magnets;
+    world.medium = teacher.medium;
+    false
+}
+
+// Follows a teacher, reconnecting whenever the connection drops.
+pub struct Student {
+    latest: Arc<Mutex<Option<Snapshot>>>,
+    connected: Arc<AtomicBool>,
+    refused: Arc<AtomicBool>,
+}
+
+impl Student {
+    pub fn connect(address: &str, code: &str) -> Student {
+        let latest = Arc::new(Mutex::new(None));
+        let connected = Arc::new(AtomicBool::new(false));
+        let refused = Arc::new(AtomicBool::new(false));
+        let (slot, flag, rejected, address, code) = (
+            Arc::clone(&latest),
+            Arc::clone(&connected),
+            Arc::clone(&refused),
+            address.to_string(),
+            code.to_string(),
+        );
+        thread::spawn(move || loop {
+            match TcpStream::connect(&address) {
+                Ok(stream) => {
+                    log::info!("following the teacher at {}", address);
+                    flag.store(true, Ordering::Relaxed);
+                    let result = follow(stream, &code, &slot);
+                    flag.store(false, Ordering::Relaxed);
+                    match result {
+                        // The code will not get any better by asking again
+                        Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                            log::error!("the teacher at {} refused join code {}", address, code);
+                            rejected.store(true, Ordering::Relaxed);
+                            return;
+                        }
+                        Err(err) => log::warn!("lost the teacher at {}: {}", address, err),
+                        Ok(()) => {}
+                    }
+                }
+                Err(err) => log::debug!("could not reach the teacher at {}: {}", address, err),
+            }
+            thread::sleep(RECONNECT_DELAY);
+        });
+        Student {
+            latest,
+            connected,
+            refused,
+        }
+    }
+
+    pub fn connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    // Whether the teacher turned the join code down, which ends following.
+    pub fn refused(&self) -> bool {
+        self.refused.load(Ordering::Relaxed)
+    }
+
+    // Newest snapshot, if one arrived since the last call.
+    pub fn take(&self) -> Option<Snapshot> {
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
+
+fn follow(
+    mut stream: TcpStream,
+    code: &str,
+    latest: &Mutex<Option<Snapshot>>,
+) -> std::io::Result<()> {
+    stream.write_all(format!("join {}\n", code).as_bytes())?;
+    let mut reader = BufReader::new(stream);
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header)? == 0 {
+            return Err(std::io::Error::other("connection closed"));
+        }
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let (paused, length) = match fields.as_slice() {
+            ["sync", paused, length] => (*paused == "1", length.parse::<usize>().unwrap_or(0)),
+            ["refused"] => {
+                return Err(std::io::Error::new(
+                    ErrorKind::PermissionDenied,
+                    "the join code was refused",
+                ))
+            }
+            _ => return Err(std::io::Error::other("unexpected message")),
+        };
+        if length > MAX_SNAPSHOT {
+            return Err(std::io::Error::other("snapshot too large"));
+        }
+        let mut text = vec![0; length];
+        reader.read_exact(&mut text)?;
+        match snapshot::from_text(&String::from_utf8_lossy(&text)) {
+            Ok(world) => {
+                *latest.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some(Snapshot { world, paused });
+            }
+            Err(err) => log::warn!("ignoring a bad snapshot from the teacher: {}", err),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+#[derive(Clone, Default, Debug)]
+pub struct Args {
+    pub verbose: bool,
+    pub lang: Option<String>,
+    pub lesson: Option<String>,
+    // Scene file to start from; it is reloaded whenever it changes
+    pub scene: Option<String>,
+    // Port for the HTTP control API, which is off unless given
+    pub http: Option<u16>,
+    // Port for the WebSocket telemetry stream, also off unless given
+    pub telemetry: Option<u16>,
+    // Port to broadcast the scene on to students, for classroom mode
+    pub teach: Option<u16>,
+    // Teacher address (host:port) whose scene this instance mirrors
+    pub follow: Option<String>,
+    // Join code the teacher shows, needed to follow it
+    pub code: Option<String>,
+    // Start without the default electron and proton
+    pub empty: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = Args::default();
+        let mut argv = std::env::args().skip(1);
+        while let Some(arg) = argv.next() {
+            match arg.as_str() {
+                "-v" | "--verbose" => args.verbose = true,
+                "--lang" => args.lang = argv.next(),
+                "--lesson" => args.lesson = argv.next(),
+                "--empty" => args.empty = true,
+                "--scene" => args.scene = argv.next(),
+                "--http" => args.http = argv.next().and_then(|port| port.parse().ok()),
+                "--telemetry" => args.telemetry = argv.next().and_then(|port| port.parse().ok()),
+                "--teach" => args.teach = argv.next().and_then(|port| port.parse().ok()),
+                "--follow" => args.follow = argv.next(),
+                "--code" => args.code = argv.next(),
+                _ => eprintln!("Ignoring unknown argument: {}", arg),
+            }
+        }
+        if args.follow.is_some() && args.code.is_none() {
+            eprintln!("--follow needs the join code the teacher shows, given with --code");
+            std::process::exit(2);
+        }
+        args
+    }
+}
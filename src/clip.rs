@@ -0,0 +1,206 @@
+use macroquad::models::{Mesh, Vertex};
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::tr;
+use crate::theme::Theme;
+use crate::translucent::Translucent;
+
+// Cells per side of the cut face when colored by potential
+const SECTION_CELLS: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClipAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl ClipAxis {
+    fn next(&self) -> ClipAxis {
+        match self {
+            ClipAxis::X => ClipAxis::Y,
+            ClipAxis::Y => ClipAxis::Z,
+            ClipAxis::Z => ClipAxis::X,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ClipAxis::X => "X",
+            ClipAxis::Y => "Y",
+            ClipAxis::Z => "Z",
+        }
+    }
+
+    // Normal followed by two vectors spanning the plane.
+    fn basis(&self) -> (Vec3, Vec3, Vec3) {
+        match self {
+            ClipAxis::X => (Vec3::X, Vec3::Y, Vec3::Z),
+            ClipAxis::Y => (Vec3::Y, Vec3::X, Vec3::Z),
+            ClipAxis::Z => (Vec3::Z, Vec3::X, Vec3::Y),
+        }
+    }
+}
+
+// Axis-aligned plane that hides everything on its positive side (or negative
+// side when flipped) so the inside of a cluster can be inspected.
+pub struct ClipPlane {
+    pub visible: bool,
+    pub enabled: bool,
+    pub axis: ClipAxis,
+    pub offset: f32,
+    pub flip: bool,
+    // Colors the cut face by electric potential instead of hatching it
+    pub potential: bool,
+}
+
+impl ClipPlane {
+    pub fn new() -> ClipPlane {
+        ClipPlane {
+            visible: false,
+            enabled: false,
+            axis: ClipAxis::Z,
+            offset: 0.,
+            flip: false,
+            potential: true,
+        }
+    }
+
+    fn side(&self, pos: Vec3) -> f32 {
+        let distance = pos.dot(self.axis.basis().0) - self.offset;
+        if self.flip {
+            -distance
+        } else {
+            distance
+        }
+    }
+
+    pub fn keeps(&self, pos: Vec3) -> bool {
+        !self.enabled || self.side(pos) <= 0.
+    }
+
+    pub fn draw_section(&self, world: &World, theme: &Theme, queue: &mut Translucent) {
+        if !self.enabled {
+            return;
+        }
+        let (normal, u, v) = self.axis.basis();
+        let (center, radius) = world.bounding_sphere().unwrap_or((Vec3::ZERO, 0.));
+        let center = center - normal * (center.dot(normal) - self.offset);
+        let half = radius + 50.;
+        let corner = center - (u + v) * half;
+        let cell = half * 2. / SECTION_CELLS as f32;
+
+        if !self.potential {
+            let color = Color {
+                a: 0.6,
+                ..theme.grid_axes
+            };
+            for i in 0..=SECTION_CELLS {
+                let t = i as f32 * cell;
+                queue.line(corner + u * t, corner + v * t, color);
+                let far = corner + (u + v) * half * 2.;
+                queue.line(far - u * t, far - v * t, color);
+            }
+            return;
+        }
+
+        let potentials: Vec<f32> = (0..=SECTION_CELLS)
+            .flat_map(|j| (0..=SECTION_CELLS).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                potential(
+                    world,
+                    corner + u * (i as f32 * cell) + v * (j as f32 * cell),
+                )
+            })
+            .collect();
+        let scale = potentials
+            .iter()
+            .map(|p| p.abs())
+            .fold(0., f32::max)
+            .max(f32::MIN_POSITIVE);
+
+        let side = SECTION_CELLS + 1;
+        let vertices = potentials
+            .iter()
+            .enumerate()
+            .map(|(n, phi)| {
+                let (i, j) = (n % side, n / side);
+                // Square root keeps the far field visible next to the 1/r peaks
+                let strength = (phi.abs() / scale).sqrt();
+                let base = if *phi >= 0. {
+                    theme.proton
+                } else {
+                    theme.electron
+                };
+                Vertex {
+                    position: corner + u * (i as f32 * cell) + v * (j as f32 * cell),
+                    uv: vec2(i as f32, j as f32) / SECTION_CELLS as f32,
+                    color: Color {
+                        a: 0.15 + 0.65 * strength,
+                        ..base
+                    },
+                }
+            })
+            .collect();
+        let mut indices = Vec::with_capacity(SECTION_CELLS * SECTION_CELLS * 6);
+        for j in 0..SECTION_CELLS {
+            for i in 0..SECTION_CELLS {
+                let a = (j * side + i) as u16;
+                let b = a + side as u16;
+                indices.extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+            }
+        }
+        queue.mesh(
+            Mesh {
+                vertices,
+                indices,
+                texture: None,
+            },
+            center,
+        );
+    }
+
+    pub fn draw_panel(&mut self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        dock::window(
+            "clip.title",
+            hash!(),
+            vec2(screen_width() - theme.scaled(330.), theme.scaled(280.)),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            ui.checkbox(hash!(), tr("clip.enabled"), &mut self.enabled);
+            if ui.button(
+                None,
+                format!("{} {}", tr("clip.axis"), self.axis.name()).as_str(),
+            ) {
+                self.axis = self.axis.next();
+            }
+            ui.slider(
+                hash!(),
+                tr("clip.offset"),
+                -500f32..500f32,
+                &mut self.offset,
+            );
+            ui.checkbox(hash!(), tr("clip.flip"), &mut self.flip);
+            ui.checkbox(hash!(), tr("clip.potential"), &mut self.potential);
+        });
+    }
+}
+
+// Coulomb potential up to the constant factor, which the color scale absorbs.
+fn potential(world: &World, at: Vec3) -> f32 {
+    let particles = &world.particles;
+    particles
+        .pos
+        .iter()
+        .zip(&particles.charge)
+        .map(|(pos, charge)| charge / pos.distance(at).max(1.))
+        .sum()
+}
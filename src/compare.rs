@@ -0,0 +1,78 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::systems::{self, Integrator};
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::tr;
+use crate::theme::Theme;
+
+pub fn integrator_key(integrator: Integrator) -> &'static str {
+    match integrator {
+        Integrator::ExplicitEuler => "integrator.explicit_euler",
+        Integrator::SemiImplicitEuler => "integrator.semi_implicit_euler",
+        Integrator::Verlet => "integrator.verlet",
+        Integrator::RungeKutta4 => "integrator.runge_kutta",
+    }
+}
+
+// One line on what the integrator costs and how it treats energy
+pub fn integrator_note_key(integrator: Integrator) -> &'static str {
+    match integrator {
+        Integrator::ExplicitEuler => "integrator.explicit_euler_note",
+        Integrator::SemiImplicitEuler => "integrator.semi_implicit_euler_note",
+        Integrator::Verlet => "integrator.verlet_note",
+        Integrator::RungeKutta4 => "integrator.runge_kutta_note",
+    }
+}
+
+// A copy of the main world stepped in lockstep with it but using a different
+// integrator, shown in the right half of the window.
+pub struct Comparison {
+    pub world: World,
+}
+
+impl Comparison {
+    pub fn start(world: &World) -> Comparison {
+        let mut copy = world.clone();
+        copy.events.clear();
+        copy.integrator = match world.integrator {
+            Integrator::Verlet => Integrator::SemiImplicitEuler,
+            _ => Integrator::Verlet,
+        };
+        Comparison { world: copy }
+    }
+
+    pub fn step(&mut self) {
+        systems::step(&mut self.world);
+        // Only the main world drives sounds and other event consumers
+        self.world.events.clear();
+    }
+
+    pub fn draw_labels(&self, primary: &World, theme: &Theme) {
+        let size = theme.text_size(24.);
+        let y = screen_height() - theme.scaled(20.);
+        let half = screen_width() / 2.;
+        draw_text(
+            tr(integrator_key(primary.integrator)),
+            theme.scaled(10.),
+            y,
+            size,
+            theme.text,
+        );
+        draw_text(
+            tr(integrator_key(self.world.integrator)),
+            half + theme.scaled(10.),
+            y,
+            size,
+            theme.text,
+        );
+        draw_line(
+            half,
+            0.,
+            half,
+            screen_height(),
+            theme.scaled(2.),
+            theme.text,
+        );
+    }
+}
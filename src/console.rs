@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::presets::{self, PRESETS};
+use crate::reload;
+use crate::theme::Theme;
+
+const OUTPUT_LINES: usize = 8;
+// Largest jump a single command may ask for
+pub const MAX_FAST_FORWARD: u64 = 1_000_000;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    // Advance the simulation this many ticks without rendering
+    FastForward(u64),
+    // Turn sub-stepping on or off, optionally with a new threshold and limit;
+    // with no arguments just report the current state
+    Substep(Option<bool>, Option<f32>, Option<u32>),
+    // Replace the scene with a built-in preset, randomized by the seed if
+    // one is given
+    Preset(String, Option<u64>),
+    // Remove every particle
+    Clear,
+    // Copy the selected particle, at a new offset if one is given
+    Duplicate(Option<Vec3>),
+    // Set how the watched scene file is reloaded; with no argument just
+    // report the current state
+    Reload(Option<reload::Mode>),
+    // Seed the simulation's own generator for deterministic mode, or turn it
+    // off with None; with no argument just report the current state
+    Seed(Option<Option<u64>>),
+}
+
+// Drop-down command line. While it is open it owns the keyboard, so the
+// app's shortcuts are held back.
+pub struct Console {
+    pub visible: bool,
+    line: String,
+    output: VecDeque<String>,
+    ticks: f32,
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            visible: false,
+            line: String::new(),
+            output: VecDeque::new(),
+            ticks: 1000.,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        // The key that opened the console should not end up in the line
+        while get_char_pressed().is_some() {}
+    }
+
+    pub fn print(&mut self, text: String) {
+        if self.output.len() == OUTPUT_LINES {
+            self.output.pop_front();
+        }
+        self.output.push_back(text);
+    }
+
+    // Edits the command line and returns a command once Enter is pressed.
+    pub fn update(&mut self) -> Option<Command> {
+        if !self.visible {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.visible = false;
+            return None;
+        }
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && c != '`' {
+                self.line.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.line.pop();
+        }
+        if !is_key_pressed(KeyCode::Enter) {
+            return None;
+        }
+
+        let line = std::mem::take(&mut self.line);
+        self.print(format!("> {}", line));
+        if line.trim() == "help" {
+            self.print(tr("console.help").to_string());
+            return None;
+        }
+        match parse(&line) {
+            Ok(command) => command,
+            Err(message) => {
+                self.print(message);
+                None
+            }
+        }
+    }
+
+    // Returns a command when the fast-forward button is clicked.
+    pub fn draw(&mut self, theme: &Theme) -> Option<Command> {
+        if !self.visible {
+            return None;
+        }
+        let mut command = None;
+        dock::window(
+            "console.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(40.)),
+            vec2(theme.scaled(520.), theme.scaled(280.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            for line in &self.output {
+                ui.label(None, line);
+            }
+            ui.label(None, &format!("> {}_", self.line));
+            ui.separator();
+            ui.slider(
+                hash!(),
+                tr("console.ticks"),
+                1.0..100_000.0,
+                &mut self.ticks,
+            );
+            let ticks = self.ticks.round().max(1.) as u64;
+            if ui.button(None, trf("console.fast_forward", &[&ticks]).as_str()) {
+                command = Some(Command::FastForward(ticks));
+            }
+        });
+        command
+    }
+}
+
+fn parse(line: &str) -> Result<Option<Command>, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Ok(None);
+    };
+    match name {
+        "ff" | "step" => {
+            let ticks = match words.next() {
+                Some(word) => word
+                    .parse::<u64>()
+                    .map_err(|_| trf("console.not_a_number", &[&word]))?,
+                None => 1,
+            };
+            Ok(Some(Command::FastForward(ticks.clamp(1, MAX_FAST_FORWARD))))
+        }
+        "substep" => {
+            let words: Vec<&str> = words.collect();
+            let number = |word: &str| {
+                word.parse::<f32>()
+                    .ok()
+                    .filter(|n| *n > 0.)
+                    .ok_or_else(|| trf("console.not_a_number", &[&word]))
+            };
+            match words.as_slice() {
+                [] => Ok(Some(Command::Substep(None, None, None))),
+                ["on"] => Ok(Some(Command::Substep(Some(true), None, None))),
+                ["off"] => Ok(Some(Command::Substep(Some(false), None, None))),
+                [threshold] => Ok(Some(Command::Substep(
+                    Some(true),
+                    Some(number(threshold)?),
+                    None,
+                ))),
+                [threshold, max] => Ok(Some(Command::Substep(
+                    Some(true),
+                    Some(number(threshold)?),
+                    Some(number(max)? as u32),
+                ))),
+                _ => Err(tr("console.substep_usage").to_string()),
+            }
+        }
+        "preset" => match words.next() {
+            Some(code) if presets::find(code).is_some() => {
+                let seed = match words.next() {
+                    Some(word) => Some(
+                        word.parse::<u64>()
+                            .map_err(|_| trf("console.not_a_number", &[&word]))?,
+                    ),
+                    None => None,
+                };
+                Ok(Some(Command::Preset(code.to_string(), seed)))
+            }
+            _ => Err(trf("console.presets", &[&preset_list()])),
+        },
+        "clear" => Ok(Some(Command::Clear)),
+        "duplicate" | "dup" => {
+            let numbers = words
+                .map(|word| word.parse::<f32>().ok())
+                .collect::<Option<Vec<f32>>>();
+            match numbers.as_deref() {
+                Some([]) => Ok(Some(Command::Duplicate(None))),
+                Some([x, y, z]) => Ok(Some(Command::Duplicate(Some(vec3(*x, *y, *z))))),
+                _ => Err(tr("console.duplicate_usage").to_string()),
+            }
+        }
+        "reload" => match words.next() {
+            None => Ok(Some(Command::Reload(None))),
+            Some(word) => reload::Mode::from_code(word)
+                .map(|mode| Some(Command::Reload(Some(mode))))
+                .ok_or_else(|| tr("reload.usage").to_string()),
+        },
+        "seed" => match words.next() {
+            None => Ok(Some(Command::Seed(None))),
+            Some("off") => Ok(Some(Command::Seed(Some(None)))),
+            Some(word) => word
+                .parse::<u64>()
+                .map(|seed| Some(Command::Seed(Some(Some(seed)))))
+                .map_err(|_| tr("console.seed_usage").to_string()),
+        },
+        "presets" => Err(trf("console.presets", &[&preset_list()])),
+        other => Err(trf("console.unknown", &[&other])),
+    }
+}
+
+fn preset_list() -> String {
+    PRESETS
+        .iter()
+        .map(|preset| preset.code)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,91 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, PoisonError};
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::world::World;
+
+use crate::annotate::Annotations;
+use crate::i18n::tr;
+use crate::scene;
+use crate::theme::Theme;
+
+// Wrap the message rather than letting it run off the screen
+const LINE_CHARS: usize = 90;
+
+// First panic message, with where it was raised. Later ones are usually
+// knock-on effects and would only hide the cause.
+static MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+// Records panic messages, from any thread, for the crash screen; the default
+// hook still prints them to stderr.
+pub fn install_hook() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let text = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let message = match info.location() {
+            Some(location) => format!("{} ({}:{})", text, location.file(), location.line()),
+            None => text,
+        };
+        MESSAGE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(message);
+        default(info);
+    }));
+}
+
+fn message() -> String {
+    MESSAGE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| tr("crash.unknown").to_string())
+}
+
+// Takes over the window after a panic and returns once the user has chosen to
+// quit, saving the scene first if they asked to.
+pub async fn show(world: &World, annotations: &Annotations, theme: &Theme) {
+    set_cursor_grab(false);
+    show_mouse(true);
+    let message = message();
+    let lines: Vec<String> = message
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(LINE_CHARS)
+        .map(|chunk| chunk.iter().collect())
+        .collect();
+    loop {
+        set_default_camera();
+        clear_background(theme.background);
+        let font_size = theme.text_size(24.);
+        let x = theme.scaled(40.);
+        let mut y = theme.scaled(80.);
+        draw_text(tr("crash.title"), x, y, theme.text_size(40.), theme.error);
+        y += font_size * 2.;
+        for line in &lines {
+            draw_text(line, x, y, font_size, theme.text);
+            y += font_size;
+        }
+        y += font_size;
+        draw_text(tr("crash.prompt"), x, y, font_size, theme.warning);
+
+        if is_key_pressed(KeyCode::S) {
+            // The world may be half-way through an update, so saving it could
+            // fail in turn
+            if panic::catch_unwind(AssertUnwindSafe(|| scene::save(world, annotations))).is_err() {
+                log::error!("could not save the scene after the crash");
+            }
+            return;
+        }
+        if is_key_pressed(KeyCode::Q) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+        next_frame().await
+    }
+}
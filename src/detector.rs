@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::ecs::Entity;
+use crate::events::Event;
+use crate::world::World;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DetectorShape {
+    // Square screen facing the beam at `x = distance`
+    Plate,
+    // Open cylinder around the vertical axis through the origin
+    Cylinder,
+    // Sphere around the origin, catching every direction
+    Sphere,
+}
+
+impl DetectorShape {
+    pub fn code(&self) -> &'static str {
+        match self {
+            DetectorShape::Plate => "plate",
+            DetectorShape::Cylinder => "cylinder",
+            DetectorShape::Sphere => "sphere",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<DetectorShape> {
+        match code {
+            "plate" => Some(DetectorShape::Plate),
+            "cylinder" => Some(DetectorShape::Cylinder),
+            "sphere" => Some(DetectorShape::Sphere),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hit {
+    // None when restored from a checkpoint that no longer has the particle
+    pub entity: Option<Entity>,
+    pub pos: Vec3,
+    pub tick: u64,
+}
+
+// Scintillation screen: records where positively charged projectiles cross
+// its surface. Electrons are ignored, as a ZnS screen would barely see them.
+#[derive(Clone, Debug)]
+pub struct Detector {
+    pub shape: DetectorShape,
+    // Plate distance along x, or cylinder radius
+    pub distance: f32,
+    // Half the plate's side, or half the cylinder's height
+    pub extent: f32,
+    // Number of scattering-angle bins between 0 and 180 degrees
+    pub bins: usize,
+    pub hits: Vec<Hit>,
+    pub tick: u64,
+    last: HashMap<Entity, Vec3>,
+}
+
+impl Detector {
+    pub fn new(shape: DetectorShape) -> Detector {
+        Detector {
+            shape,
+            distance: 300.,
+            extent: 150.,
+            bins: 18,
+            hits: Vec::new(),
+            tick: 0,
+            last: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.hits.clear();
+    }
+
+    // Point where the step from `from` to `to` crosses the surface, if it does.
+    fn crossing(&self, from: Vec3, to: Vec3) -> Option<Vec3> {
+        let (a, b) = match self.shape {
+            DetectorShape::Plate => (from.x - self.distance, to.x - self.distance),
+            DetectorShape::Cylinder => (
+                from.xz().length() - self.distance,
+                to.xz().length() - self.distance,
+            ),
+            DetectorShape::Sphere => (from.length() - self.distance, to.length() - self.distance),
+        };
+        if (a < 0.) == (b < 0.) || a == b {
+            return None;
+        }
+        let hit = from.lerp(to, a / (a - b));
+        let inside = match self.shape {
+            DetectorShape::Plate => hit.y.abs() <= self.extent && hit.z.abs() <= self.extent,
+            DetectorShape::Cylinder => hit.y.abs() <= self.extent,
+            DetectorShape::Sphere => true,
+        };
+        inside.then_some(hit)
+    }
+
+    // Angle between the beam direction (+x) and the line from the target at
+    // the origin to `pos`, in radians. Cylinders measure it in the xz plane.
+    pub fn scattering_angle(&self, pos: Vec3) -> f32 {
+        let direction = match self.shape {
+            DetectorShape::Cylinder => vec3(pos.x, 0., pos.z),
+            _ => pos,
+        };
+        if direction == Vec3::ZERO {
+            return 0.;
+        }
+        Vec3::X.angle_between(direction)
+    }
+
+    pub fn bin_width(&self) -> f32 {
+        std::f32::consts::PI / self.bins.max(1) as f32
+    }
+
+    // Share of the directions around the target that fall in `bin`, which
+    // the counts there are in proportion to for an isotropic scatterer. A
+    // bin of a sphere or plate is a cone shell of solid angle
+    // 2π(cos θ₁ - cos θ₂); a cylinder measures its angle in the xz plane,
+    // where every bin is just as wide.
+    pub fn acceptance(&self, bin: usize) -> f32 {
+        let width = self.bin_width();
+        let (from, to) = (bin as f32 * width, (bin + 1) as f32 * width);
+        match self.shape {
+            DetectorShape::Cylinder => width,
+            DetectorShape::Plate | DetectorShape::Sphere => {
+                std::f32::consts::TAU * (from.cos() - to.cos())
+            }
+        }
+    }
+
+    // Hit counts per scattering-angle bin.
+    pub fn angular_counts(&self) -> Vec<u32> {
+        let bins = self.bins.max(1);
+        let mut counts = vec![0; bins];
+        for hit in &self.hits {
+            let bin = (self.scattering_angle(hit.pos) / self.bin_width()) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+        counts
+    }
+}
+
+pub fn detect_hits(world: &mut World) {
+    let Some(detector) = &mut world.detector else {
+        return;
+    };
+    detector.tick += 1;
+    let particles = &world.particles;
+    let mut last = HashMap::with_capacity(particles.len());
+    for (i, entity) in particles.entities().iter().enumerate() {
+        let pos = particles.pos[i];
+        last.insert(*entity, pos);
+        if particles.charge[i] <= 0. {
+            continue;
+        }
+        let Some(from) = detector.last.get(entity) else {
+            continue;
+        };
+        if let Some(hit) = detector.crossing(*from, pos) {
+            detector.hits.push(Hit {
+                entity: Some(*entity),
+                pos: hit,
+                tick: detector.tick,
+            });
+            world.events.push(Event::DetectorHit(*entity));
+        }
+    }
+    detector.last = last;
+}
+
+// Rutherford's cross-section up to a constant: 1 / sin^4(θ/2).
+pub fn rutherford(angle: f32) -> f32 {
+    1. / (angle / 2.).sin().powi(4)
+}
+
+// `rutherford` integrated over the acceptance of `bin`, in closed form: with
+// u = sin²(θ/2), 2π sin θ dθ / u² = 4π du / u² over a cone shell, and the
+// integral of 1 / sin^4(θ/2) is -2 cot(θ/2) - 2/3 cot³(θ/2) in a plane.
+pub fn rutherford_in_bin(detector: &Detector, bin: usize) -> f32 {
+    let width = detector.bin_width();
+    let (from, to) = (bin as f32 * width, (bin + 1) as f32 * width);
+    match detector.shape {
+        DetectorShape::Cylinder => {
+            let integral = |angle: f32| {
+                let cot = 1. / (angle / 2.).tan();
+                -2. * cot - 2. / 3. * cot * cot * cot
+            };
+            integral(to) - integral(from)
+        }
+        DetectorShape::Plate | DetectorShape::Sphere => {
+            let inverse = |angle: f32| 1. / (angle / 2.).sin().powi(2);
+            2. * std::f32::consts::TAU * (inverse(from) - inverse(to))
+        }
+    }
+}
+
+// How well the detector counts follow Rutherford's formula. The forward bin,
+// where the formula diverges, is left out throughout.
+#[derive(Clone, Debug)]
+pub struct Fit {
+    // Unscaled prediction per bin: the cross-section integrated over the
+    // bin's acceptance; infinite for the forward bin
+    pub expected: Vec<f32>,
+    // Least-squares `A` of `A ∫ dΩ / sin^4(θ/2)`, each bin weighted by the
+    // inverse of its Poisson variance (its count, or 1 for an empty bin)
+    pub scale: f32,
+    // Per bin, how many standard deviations the count lies above the fitted
+    // curve; None for the forward bin
+    pub residuals: Vec<Option<f32>>,
+    // `n` of a least-squares fit of the log of the counts per acceptance to
+    // `A / sin^n(θ/2)`, which should come out near 4; needs at least two
+    // bins with hits
+    pub exponent: Option<f32>,
+    // Sum of the squared residuals
+    pub chi_square: f32,
+    pub degrees_of_freedom: usize,
+}
+
+pub fn fit(detector: &Detector) -> Fit {
+    let counts = detector.angular_counts();
+    let width = detector.bin_width();
+    let centers: Vec<f32> = (0..counts.len())
+        .map(|bin| (bin as f32 + 0.5) * width)
+        .collect();
+    let acceptance: Vec<f32> = (0..counts.len())
+        .map(|bin| detector.acceptance(bin))
+        .collect();
+    let expected: Vec<f32> = (0..counts.len())
+        .map(|bin| rutherford_in_bin(detector, bin))
+        .collect();
+    let variance = |count: u32| (count as f32).max(1.);
+    let (numerator, denominator) = counts.iter().zip(&expected).skip(1).fold(
+        (0., 0.),
+        |(numerator, denominator), (count, e)| {
+            (
+                numerator + *count as f32 * e / variance(*count),
+                denominator + e * e / variance(*count),
+            )
+        },
+    );
+    let scale = if denominator > 0. {
+        numerator / denominator
+    } else {
+        0.
+    };
+    let residuals: Vec<Option<f32>> = counts
+        .iter()
+        .zip(&expected)
+        .enumerate()
+        .map(|(bin, (count, e))| {
+            (bin > 0).then(|| (*count as f32 - scale * e) / variance(*count).sqrt())
+        })
+        .collect();
+    let chi_square = residuals.iter().flatten().map(|r| r * r).sum();
+
+    // ln(c / Ω) = ln A - n ln sin(θ/2), over the bins that saw anything
+    let points: Vec<(f32, f32)> = counts
+        .iter()
+        .zip(centers.iter().zip(&acceptance))
+        .skip(1)
+        .filter(|(count, _)| **count > 0)
+        .map(|(count, (angle, acceptance))| {
+            ((angle / 2.).sin().ln(), (*count as f32 / acceptance).ln())
+        })
+        .collect();
+    let n = points.len() as f32;
+    let (mean_x, mean_y) = points
+        .iter()
+        .fold((0., 0.), |(x, y), p| (x + p.0 / n, y + p.1 / n));
+    let spread: f32 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let exponent = (points.len() >= 2 && spread > 0.).then(|| {
+        let slope = points
+            .iter()
+            .map(|p| (p.0 - mean_x) * (p.1 - mean_y))
+            .sum::<f32>()
+            / spread;
+        -slope
+    });
+
+    Fit {
+        expected,
+        scale,
+        residuals,
+        exponent,
+        chi_square,
+        degrees_of_freedom: counts.len().saturating_sub(2),
+    }
+}
@@ -0,0 +1,186 @@
+use macroquad::prelude::*;
+
+use crate::ecs::Entity;
+use crate::group::kinetic_energy;
+use crate::particle::Particle;
+use crate::statistics::Statistics;
+use crate::systems::{ForceModel, COULOMB_K};
+use crate::world::World;
+
+// Angular velocity about the nearest proton divided by 2π, in orbits per tick.
+pub fn orbital_frequency(world: &World, entity: Entity) -> Option<f32> {
+    let particles = &world.particles;
+    let i = particles.slot(entity)?;
+    let nucleus = particles.nearest(i, Particle::Proton)?;
+
+    let r = particles.pos[i] - particles.pos[nucleus];
+    let v = particles.vel[i] - particles.vel[nucleus];
+    if r.length_squared() == 0. {
+        return None;
+    }
+    Some(r.cross(v).length() / r.length_squared() / std::f32::consts::TAU)
+}
+
+pub fn particle_kinetic_energy(world: &World, entity: Entity) -> Option<f32> {
+    let particles = &world.particles;
+    let i = particles.slot(entity)?;
+    Some(kinetic_energy(particles.mass[i], particles.vel[i]))
+}
+
+// Distance from the nearest proton and the momentum component along that
+// direction; negative momentum means the particle is falling inwards.
+pub fn radial_phase(world: &World, entity: Entity) -> Option<(f32, f32)> {
+    let particles = &world.particles;
+    let i = particles.slot(entity)?;
+    let nucleus = particles.nearest(i, Particle::Proton)?;
+
+    let r = particles.pos[i] - particles.pos[nucleus];
+    let v = particles.vel[i] - particles.vel[nucleus];
+    let distance = r.length();
+    if distance == 0. {
+        return None;
+    }
+    Some((distance, particles.mass[i] * v.dot(r) / distance))
+}
+
+// Mean position of all protons and neutrons, if there are any.
+pub fn nucleus_center(world: &World) -> Option<Vec3> {
+    let particles = &world.particles;
+    let nucleons: Vec<Vec3> = particles
+        .particle
+        .iter()
+        .zip(&particles.pos)
+        .filter(|(particle, _)| **particle != Particle::Electron)
+        .map(|(_, pos)| *pos)
+        .collect();
+    if nucleons.is_empty() {
+        return None;
+    }
+    Some(nucleons.iter().sum::<Vec3>() / nucleons.len() as f32)
+}
+
+pub fn pair_potential(model: &ForceModel, charge_a: f32, charge_b: f32, distance: f32) -> f32 {
+    if distance == 0. {
+        return 0.;
+    }
+    COULOMB_K * charge_a * charge_b * model.potential_factor(distance) / distance
+}
+
+// Coulomb force on `entity` from every other particle it interacts with.
+pub fn force_contributions(world: &World, entity: Entity) -> Vec<(Entity, Vec3)> {
+    let particles = &world.particles;
+    let Some(i) = particles.slot(entity) else {
+        return Vec::new();
+    };
+    particles
+        .entities()
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| {
+            *j != i
+                && world
+                    .pair_forces
+                    .allows(particles.particle[i], particles.particle[*j])
+        })
+        .filter_map(|(j, other)| {
+            let r = particles.pos[i] - particles.pos[j];
+            let distance = r.length();
+            if distance == 0. {
+                return None;
+            }
+            let magnitude = COULOMB_K
+                * particles.charge[i]
+                * particles.charge[j]
+                * world.force_model.force_factor(distance)
+                / distance.powi(3);
+            Some((*other, r * magnitude))
+        })
+        .collect()
+}
+
+// Potential energy between `entity` and every other particle.
+pub fn potential_contributions(world: &World, entity: Entity) -> Vec<(Entity, f32)> {
+    let particles = &world.particles;
+    let Some(i) = particles.slot(entity) else {
+        return Vec::new();
+    };
+    particles
+        .entities()
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| {
+            *j != i
+                && world
+                    .pair_forces
+                    .allows(particles.particle[i], particles.particle[*j])
+        })
+        .map(|(j, other)| {
+            let distance = particles.pos[i].distance(particles.pos[j]);
+            (
+                *other,
+                pair_potential(
+                    &world.force_model,
+                    particles.charge[i],
+                    particles.charge[j],
+                    distance,
+                ),
+            )
+        })
+        .collect()
+}
+
+// Position and velocity of the center of mass.
+pub fn center_of_mass(world: &World) -> Option<(Vec3, Vec3)> {
+    let particles = &world.particles;
+    let total: f32 = particles.mass.iter().sum();
+    if total <= 0. {
+        return None;
+    }
+    let weighted = |column: &[Vec3]| {
+        column
+            .iter()
+            .zip(&particles.mass)
+            .fold(Vec3::ZERO, |sum, (v, m)| sum + *v * *m)
+            / total
+    };
+    Some((weighted(&particles.pos), weighted(&particles.vel)))
+}
+
+// Total angular momentum about the center of mass, in its rest frame.
+pub fn total_angular_momentum(world: &World) -> Vec3 {
+    let Some((center, drift)) = center_of_mass(world) else {
+        return Vec3::ZERO;
+    };
+    let particles = &world.particles;
+    (0..particles.len())
+        .map(|i| (particles.pos[i] - center).cross(particles.vel[i] - drift) * particles.mass[i])
+        .fold(Vec3::ZERO, |sum, l| sum + l)
+}
+
+// Angular momentum of `entity` about its nearest proton.
+pub fn angular_momentum_about_nucleus(world: &World, entity: Entity) -> Option<Vec3> {
+    let particles = &world.particles;
+    let i = particles.slot(entity)?;
+    let nucleus = particles.nearest(i, Particle::Proton)?;
+
+    let r = particles.pos[i] - particles.pos[nucleus];
+    let v = particles.vel[i] - particles.vel[nucleus];
+    Some(r.cross(v) * particles.mass[i])
+}
+
+pub fn total_kinetic_energy(world: &World) -> f32 {
+    let particles = &world.particles;
+    particles
+        .mass
+        .iter()
+        .zip(&particles.vel)
+        .map(|(m, v)| kinetic_energy(*m, *v))
+        .sum()
+}
+
+// Sum of the potential energy of every pair, each pair counted once, and of
+// every particle in the field of the fixed sources and in gravity. The pair
+// loop lives in `Statistics::gather`, which needs it anyway.
+pub fn total_potential_energy(world: &World) -> f32 {
+    Statistics::gather(world).potential
+}
@@ -0,0 +1,138 @@
+use std::fs;
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::group::kinetic_energy;
+use rutherford_atomic_model::histogram::Histogram;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::gui;
+use crate::i18n::{tr, trf};
+use crate::settings::Settings;
+use crate::theme::Theme;
+use crate::toast;
+
+const BINS: usize = 20;
+// Rebuilding every frame is wasted work for a chart nobody reads that fast
+const UPDATE_INTERVAL: u32 = 10;
+
+// Title key, file name and histogram for each distribution.
+pub type Named = (&'static str, &'static str, Histogram);
+
+// Distance-from-nucleus, speed and kinetic energy histograms of all particles.
+pub fn histograms(world: &World) -> [Named; 3] {
+    let particles = &world.particles;
+    let center = diagnostics::nucleus_center(world).unwrap_or(Vec3::ZERO);
+    [
+        (
+            "histogram.radial",
+            "radial",
+            Histogram::new(particles.pos.iter().map(|p| p.distance(center)), BINS),
+        ),
+        (
+            "histogram.speed",
+            "speed",
+            Histogram::new(particles.vel.iter().map(|v| v.length()), BINS),
+        ),
+        (
+            "histogram.energy",
+            "energy",
+            Histogram::new(
+                particles
+                    .mass
+                    .iter()
+                    .zip(&particles.vel)
+                    .map(|(m, v)| kinetic_energy(*m, *v)),
+                BINS,
+            ),
+        ),
+    ]
+}
+
+// The histograms above as small charts, refreshed while shown.
+pub struct Distributions {
+    pub visible: bool,
+    frame: u32,
+    histograms: [Named; 3],
+}
+
+impl Distributions {
+    pub fn new() -> Distributions {
+        Distributions {
+            visible: false,
+            frame: 0,
+            histograms: histograms(&World::new()),
+        }
+    }
+
+    pub fn update(&mut self, world: &World) {
+        if !self.visible {
+            return;
+        }
+        self.frame += 1;
+        if self.frame % UPDATE_INTERVAL != 1 {
+            return;
+        }
+
+        self.histograms = histograms(world);
+    }
+
+    // Writes one CSV per histogram next to the settings file.
+    pub fn export(&self) {
+        let dir = Settings::path().with_file_name("histograms");
+        let result = fs::create_dir_all(&dir).and_then(|_| {
+            self.histograms.iter().try_for_each(|(_, name, histogram)| {
+                fs::write(dir.join(format!("{}.csv", name)), histogram.to_csv())
+            })
+        });
+        match result {
+            Ok(()) => {
+                let message = trf("histogram.exported", &[&dir.display()]);
+                log::info!("{}", message);
+                toast::info(message);
+            }
+            Err(err) => log::warn!("could not export histograms to {}: {}", dir.display(), err),
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(300.), theme.scaled(400.));
+        let x = screen_width() - width - theme.scaled(10.);
+        dock::egui_window(
+            "histogram.title",
+            Rect::new(x, theme.scaled(280.), width, height),
+        )
+        .open(&mut self.visible)
+        .show(ctx, |ui| {
+            for (key, _, histogram) in &self.histograms {
+                ui.label(tr(key));
+                // Bars by bin number; the range they cover is written below
+                egui::plot::Plot::new(key)
+                    .height(70.)
+                    .include_y(0.)
+                    .show_x(false)
+                    .show_axes([false, true])
+                    .show(ui, |plot| {
+                        let bars = histogram
+                            .counts
+                            .iter()
+                            .enumerate()
+                            .map(|(bin, count)| (bin as f64 + 0.5, *count as f64));
+                        plot.bar_chart(gui::bars(tr(key), bars, 1., theme.selection));
+                    });
+                ui.label(format!(
+                    "{:.3e} .. {:.3e}   n={}",
+                    histogram.min,
+                    histogram.max,
+                    histogram.total()
+                ));
+            }
+        });
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets, Id};
+
+use crate::i18n::{tr, trf};
+use crate::settings::Settings;
+use crate::theme::Theme;
+use crate::toast;
+
+// Layout saved whenever the workspace changes and restored at startup
+const LAST_LAYOUT: &str = "last";
+const MIN_COLUMN: f32 = 200.;
+const MIN_PANEL: f32 = 80.;
+
+// Where a panel sits: wherever it was dragged to, or stacked in a column
+// down one side of the window or a row along the bottom.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Slot {
+    Floating,
+    Left,
+    Right,
+    Bottom,
+}
+
+const SLOTS: [Slot; 4] = [Slot::Floating, Slot::Left, Slot::Right, Slot::Bottom];
+
+impl Slot {
+    fn code(&self) -> &'static str {
+        match self {
+            Slot::Floating => "floating",
+            Slot::Left => "left",
+            Slot::Right => "right",
+            Slot::Bottom => "bottom",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Slot> {
+        SLOTS.into_iter().find(|slot| slot.code() == code)
+    }
+
+    fn name_key(&self) -> &'static str {
+        match self {
+            Slot::Floating => "dock.floating",
+            Slot::Left => "dock.left",
+            Slot::Right => "dock.right",
+            Slot::Bottom => "dock.bottom",
+        }
+    }
+
+    fn next(&self) -> Slot {
+        match self {
+            Slot::Floating => Slot::Left,
+            Slot::Left => Slot::Right,
+            Slot::Right => Slot::Bottom,
+            Slot::Bottom => Slot::Floating,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Placement {
+    slot: Slot,
+    // Height in a side column or width in the bottom row, in unscaled
+    // pixels; zero keeps the panel's own
+    size: f32,
+}
+
+// The panels' arrangement, shared like the toasts so every panel can ask for
+// its place without a handle being passed down to it.
+struct Dock {
+    placements: BTreeMap<String, Placement>,
+    // Width of the side columns and height of the bottom row
+    column: f32,
+    row: f32,
+    // Every panel drawn so far this session, by its title key, with the
+    // size it asks for
+    known: BTreeMap<&'static str, Vec2>,
+    // Panels laid out in a column last frame, so one that goes back to
+    // floating can be put back where it started
+    docked: BTreeSet<&'static str>,
+    // How far down each column, and along the row, is taken this frame
+    taken: [f32; 3],
+    scale: f32,
+}
+
+static DOCK: Mutex<Dock> = Mutex::new(Dock {
+    placements: BTreeMap::new(),
+    column: 340.,
+    row: 260.,
+    known: BTreeMap::new(),
+    docked: BTreeSet::new(),
+    taken: [0.; 3],
+    scale: 1.,
+});
+
+fn layouts_dir() -> PathBuf {
+    Settings::path().with_file_name("layouts")
+}
+
+impl Dock {
+    fn placement(&self, name: &str) -> Placement {
+        self.placements.get(name).copied().unwrap_or(Placement {
+            slot: Slot::Floating,
+            size: 0.,
+        })
+    }
+
+    fn place(&mut self, name: &'static str, default: Rect) -> Rect {
+        self.known
+            .insert(name, vec2(default.w, default.h) / self.scale);
+        let placement = self.placement(name);
+        let size = placement.size * self.scale;
+        let (column, row) = (self.column * self.scale, self.row * self.scale);
+        // The side columns stop short of the bottom row when it is in use
+        let bottom = screen_height()
+            - if self.placements.values().any(|p| p.slot == Slot::Bottom) {
+                row
+            } else {
+                0.
+            };
+        match placement.slot {
+            Slot::Floating => default,
+            Slot::Left | Slot::Right => {
+                let k = (placement.slot == Slot::Right) as usize;
+                let height = if size > 0. { size } else { default.h };
+                let y = self.taken[k];
+                self.taken[k] += height;
+                let x = if k == 0 { 0. } else { screen_width() - column };
+                Rect::new(x, y, column, height.min(bottom - y).max(MIN_PANEL))
+            }
+            Slot::Bottom => {
+                let width = if size > 0. { size } else { default.w };
+                let x = self.taken[2];
+                self.taken[2] += width;
+                Rect::new(x, screen_height() - row, width, row)
+            }
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!("column = {}\nrow = {}\n", self.column, self.row);
+        for (name, placement) in &self.placements {
+            if placement.slot != Slot::Floating {
+                text.push_str(&format!(
+                    "{} = {} {}\n",
+                    name,
+                    placement.slot.code(),
+                    placement.size
+                ));
+            }
+        }
+        text
+    }
+
+    fn parse(&mut self, source: &str) {
+        self.placements.clear();
+        for line in source.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "column" => {
+                    if let Ok(width) = value.parse::<f32>() {
+                        self.column = width.max(MIN_COLUMN);
+                    }
+                }
+                "row" => {
+                    if let Ok(height) = value.parse::<f32>() {
+                        self.row = height.max(MIN_PANEL);
+                    }
+                }
+                name => {
+                    let mut fields = value.split_whitespace();
+                    let slot = fields.next().and_then(Slot::from_code);
+                    let size = fields.next().and_then(|size| size.parse::<f32>().ok());
+                    match (slot, size) {
+                        (Some(slot), Some(size)) => {
+                            self.placements
+                                .insert(name.to_string(), Placement { slot, size });
+                        }
+                        _ => log::warn!("bad layout line '{}'", line),
+                    }
+                }
+            }
+        }
+    }
+
+    fn save(&self, name: &str) -> bool {
+        let path = layouts_dir().join(format!("{}.txt", name));
+        let result = fs::create_dir_all(layouts_dir()).and_then(|_| fs::write(&path, self.text()));
+        if let Err(err) = &result {
+            log::warn!("could not save layout to {}: {}", path.display(), err);
+        }
+        result.is_ok()
+    }
+
+    fn load(&mut self, name: &str) -> bool {
+        match fs::read_to_string(layouts_dir().join(format!("{}.txt", name))) {
+            Ok(source) => {
+                self.parse(&source);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+// Starts a frame's layout; call before any panel is drawn.
+pub fn begin_frame(theme: &Theme) {
+    if let Ok(mut dock) = DOCK.lock() {
+        dock.taken = [0.; 3];
+        dock.scale = theme.scaled(1.);
+    }
+}
+
+// Restores the layout from the end of the last session.
+pub fn restore() {
+    if let Ok(mut dock) = DOCK.lock() {
+        dock.load(LAST_LAYOUT);
+    }
+}
+
+// Where a hand-drawn panel titled `name` goes this frame, given where it
+// would go on its own.
+pub fn place(name: &'static str, default: Rect) -> Rect {
+    match DOCK.lock() {
+        Ok(mut dock) => dock.place(name, default),
+        Err(_) => default,
+    }
+}
+
+// A window for the panel titled `name`, at `position` and `size` unless it
+// is docked. Docked windows are pinned to their place in the column;
+// floating ones move freely, starting from `position`.
+pub fn window(name: &'static str, id: Id, position: Vec2, size: Vec2) -> widgets::Window {
+    let Ok(mut dock) = DOCK.lock() else {
+        return widgets::Window::new(id, position, size);
+    };
+    let rect = dock.place(name, Rect::new(position.x, position.y, size.x, size.y));
+    let docked = dock.placement(name).slot != Slot::Floating;
+    let was_docked = if docked {
+        !dock.docked.insert(name)
+    } else {
+        dock.docked.remove(name)
+    };
+    if docked || was_docked {
+        root_ui().move_window(id, rect.point());
+    }
+    widgets::Window::new(id, rect.point(), rect.size())
+        .label(tr(name))
+        .movable(!docked)
+}
+
+// The egui window for the panel titled `name`, the counterpart of `window`
+// for panels built with egui. `default` is in pixels like everywhere else in
+// the dock; egui measures in points, the theme scale apart.
+pub fn egui_window<'open>(name: &'static str, default: Rect) -> egui::Window<'open> {
+    let window = egui::Window::new(tr(name)).id(egui::Id::new(name));
+    let Ok(mut dock) = DOCK.lock() else {
+        return window
+            .default_pos([default.x, default.y])
+            .default_width(default.w);
+    };
+    let scale = dock.scale;
+    let rect = dock.place(name, default);
+    let (position, size) = (rect.point() / scale, rect.size() / scale);
+    let docked = dock.placement(name).slot != Slot::Floating;
+    let was_docked = if docked {
+        !dock.docked.insert(name)
+    } else {
+        dock.docked.remove(name)
+    };
+    if docked {
+        window
+            .fixed_pos([position.x, position.y])
+            .fixed_size([size.x, size.y])
+    } else if was_docked {
+        window
+            .current_pos([position.x, position.y])
+            .default_width(size.x)
+    } else {
+        window
+            .default_pos([position.x, position.y])
+            .default_width(size.x)
+    }
+}
+
+// Lists every panel opened so far with where it goes, and saves and loads
+// named layouts.
+pub struct WorkspacePanel {
+    pub visible: bool,
+    name: String,
+}
+
+impl WorkspacePanel {
+    pub fn new() -> WorkspacePanel {
+        WorkspacePanel {
+            visible: false,
+            name: String::new(),
+        }
+    }
+
+    pub fn draw(&mut self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let Ok(mut dock) = DOCK.lock() else {
+            return;
+        };
+        let before = dock.text();
+        let saved: Vec<String> = fs::read_dir(layouts_dir())
+            .map(|entries| {
+                let mut names: Vec<String> = entries
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        let name = path.file_stem()?.to_str()?.to_string();
+                        (name != LAST_LAYOUT).then_some(name)
+                    })
+                    .collect();
+                names.sort();
+                names
+            })
+            .unwrap_or_default();
+        widgets::Window::new(
+            hash!(),
+            vec2(theme.scaled(380.), theme.scaled(120.)),
+            vec2(theme.scaled(360.), theme.scaled(520.)),
+        )
+        .label(tr("dock.title"))
+        .ui(&mut root_ui(), |ui| {
+            ui.slider(
+                hash!(),
+                tr("dock.column"),
+                MIN_COLUMN..800.0,
+                &mut dock.column,
+            );
+            ui.slider(hash!(), tr("dock.row"), MIN_PANEL..600.0, &mut dock.row);
+            ui.separator();
+            if dock.known.is_empty() {
+                ui.label(None, tr("dock.empty"));
+            }
+            let known: Vec<(&'static str, Vec2)> = dock
+                .known
+                .iter()
+                .map(|(name, size)| (*name, *size))
+                .collect();
+            for (i, (name, own)) in known.into_iter().enumerate() {
+                let mut placement = dock.placement(name);
+                let label = trf("dock.panel", &[&tr(name), &tr(placement.slot.name_key())]);
+                if ui.button(None, label.as_str()) {
+                    placement.slot = placement.slot.next();
+                }
+                if placement.slot != Slot::Floating {
+                    let (own, limit) = match placement.slot {
+                        Slot::Bottom => (own.x, 1200.),
+                        _ => (own.y, 1000.),
+                    };
+                    if placement.size <= 0. {
+                        placement.size = own;
+                    }
+                    ui.slider(
+                        hash!(("dock_size", i)),
+                        tr("dock.size"),
+                        MIN_PANEL..limit,
+                        &mut placement.size,
+                    );
+                }
+                dock.placements.insert(name.to_string(), placement);
+            }
+            ui.separator();
+            ui.input_text(hash!(), tr("dock.name"), &mut self.name);
+            let name = self.name.trim();
+            let usable = !name.is_empty()
+                && name != LAST_LAYOUT
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+            if usable && ui.button(None, tr("dock.save")) && dock.save(name) {
+                toast::info(trf("dock.saved", &[&name]));
+            }
+            for (i, layout) in saved.iter().enumerate() {
+                let label = trf("dock.load", &[layout]);
+                if ui.button(None, label.as_str()) && dock.load(layout) {
+                    toast::info(trf("dock.loaded", &[layout]));
+                }
+                if i % 2 == 0 {
+                    ui.same_line(0.);
+                }
+            }
+        });
+        if dock.text() != before {
+            dock.save(LAST_LAYOUT);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Entities {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+}
+
+impl Entities {
+    pub fn spawn(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity {
+                    index,
+                    generation: self.generations[index as usize],
+                }
+            }
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity {
+                    index: self.generations.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        let index = entity.index();
+        self.alive[index] = false;
+        self.generations[index] += 1;
+        self.free.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index();
+        index < self.alive.len()
+            && self.alive[index]
+            && self.generations[index] == entity.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.alive.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Maps entities to dense slots. Removal swaps the last slot into the hole, so
+// storages built on top must `swap_remove` their columns in the same way.
+#[derive(Clone, Default, Debug)]
+pub struct SparseIndex {
+    sparse: Vec<Option<usize>>,
+    entities: Vec<Entity>,
+}
+
+impl SparseIndex {
+    // Returns the slot for `entity` and whether it was newly allocated.
+    pub fn insert(&mut self, entity: Entity) -> (usize, bool) {
+        let index = entity.index();
+        if index >= self.sparse.len() {
+            self.sparse.resize(index + 1, None);
+        }
+        match self.sparse[index] {
+            Some(slot) => {
+                self.entities[slot] = entity;
+                (slot, false)
+            }
+            None => {
+                let slot = self.entities.len();
+                self.sparse[index] = Some(slot);
+                self.entities.push(entity);
+                (slot, true)
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<usize> {
+        let slot = self.slot(entity)?;
+        self.sparse[entity.index()] = None;
+        self.entities.swap_remove(slot);
+        if let Some(moved) = self.entities.get(slot) {
+            self.sparse[moved.index()] = Some(slot);
+        }
+        Some(slot)
+    }
+
+    pub fn slot(&self, entity: Entity) -> Option<usize> {
+        let slot = (*self.sparse.get(entity.index())?)?;
+        if self.entities[slot] == entity {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+// Sparse set: components are kept densely packed for fast iteration, with a
+// sparse index from entity to dense slot for lookups.
+#[derive(Clone, Debug)]
+pub struct Components<T> {
+    index: SparseIndex,
+    data: Vec<T>,
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Self {
+        Components {
+            index: SparseIndex::default(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<T> Components<T> {
+    pub fn insert(&mut self, entity: Entity, component: T) {
+        match self.index.insert(entity) {
+            (slot, false) => self.data[slot] = component,
+            (_, true) => self.data.push(component),
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.index.remove(entity)?;
+        Some(self.data.swap_remove(slot))
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.index.slot(entity).map(|slot| &self.data[slot])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.index
+            .slot(entity)
+            .map(move |slot| &mut self.data[slot])
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.index.slot(entity).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.index.entities().iter().copied().zip(self.data.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.index
+            .entities()
+            .iter()
+            .copied()
+            .zip(self.data.iter_mut())
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::gui;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+const HISTORY: usize = 600;
+// Only the strongest interactions are listed individually
+const LISTED: usize = 5;
+
+struct Sample {
+    kinetic: f32,
+    potential: f32,
+}
+
+impl Sample {
+    // Kinetic, potential and total, in the order the sparklines are drawn
+    fn values(&self) -> [f32; 3] {
+        [self.kinetic, self.potential, self.kinetic + self.potential]
+    }
+}
+
+// Kinetic, potential and total mechanical energy of the selected particle,
+// with the potential split into its largest contributions and the recent
+// history drawn as sparklines.
+pub struct EnergyPanel {
+    pub visible: bool,
+    entity: Option<Entity>,
+    history: VecDeque<Sample>,
+    contributions: Vec<(Entity, f32)>,
+}
+
+impl EnergyPanel {
+    pub fn new() -> EnergyPanel {
+        EnergyPanel {
+            visible: false,
+            entity: None,
+            history: VecDeque::with_capacity(HISTORY),
+            contributions: Vec::new(),
+        }
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World, selected: Option<Entity>) {
+        if selected != self.entity {
+            self.entity = selected;
+            self.history.clear();
+        }
+        let Some(entity) = selected else {
+            self.contributions.clear();
+            return;
+        };
+        let Some(kinetic) = diagnostics::particle_kinetic_energy(world, entity) else {
+            return;
+        };
+        let mut contributions = diagnostics::potential_contributions(world, entity);
+        let potential = contributions.iter().map(|(_, u)| u).sum();
+        contributions.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+        contributions.truncate(LISTED);
+        self.contributions = contributions;
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(Sample { kinetic, potential });
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, world: &World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(340.), theme.scaled(330.));
+        let x = theme.scaled(340.);
+        let y = screen_height() - height - theme.scaled(10.);
+        let mut open = true;
+        dock::egui_window("energy.title", Rect::new(x, y, width, height))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some(last) = self.history.back() else {
+                    ui.label(tr("energy.empty"));
+                    return;
+                };
+                let total = last.kinetic + last.potential;
+                let rows = [
+                    (
+                        trf("energy.kinetic", &[&format!("{:.3e}", last.kinetic)]),
+                        theme.selection,
+                    ),
+                    (
+                        trf("energy.potential", &[&format!("{:.3e}", last.potential)]),
+                        theme.warning,
+                    ),
+                    (
+                        trf("energy.total", &[&format!("{:.3e}", total)]),
+                        theme.text,
+                    ),
+                ];
+                for (text, color) in rows {
+                    ui.colored_label(gui::color(color), text);
+                }
+                ui.separator();
+                for (other, u) in &self.contributions {
+                    let species = world
+                        .particles
+                        .slot(*other)
+                        .map(|slot| world.particles.particle[slot].name())
+                        .unwrap_or_else(|| "?".to_string());
+                    ui.label(trf(
+                        "energy.contribution",
+                        &[&species, &other.index(), &format!("{:.3e}", u)],
+                    ));
+                }
+
+                // All three series share one vertical scale so exchange is visible
+                let names = [
+                    "energy.series_kinetic",
+                    "energy.series_potential",
+                    "energy.series_total",
+                ];
+                let colors = [theme.selection, theme.warning, theme.text];
+                egui::plot::Plot::new("energy_history")
+                    .height(160.)
+                    .legend(egui::plot::Legend::default())
+                    .show(ui, |plot| {
+                        for (series, (name, color)) in names.into_iter().zip(colors).enumerate() {
+                            let points = self
+                                .history
+                                .iter()
+                                .enumerate()
+                                .map(|(i, s)| [i as f64, s.values()[series] as f64])
+                                .collect();
+                            plot.line(gui::line(tr(name), points, color));
+                        }
+                    });
+            });
+        self.visible = open;
+    }
+}
@@ -0,0 +1,160 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::particle::Trail;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+use crate::translucent::Translucent;
+
+const MAX_COPIES: f32 = 32.;
+
+// Copies of the scene started from slightly different initial conditions and
+// stepped in lockstep with it, drawn faintly over the main view. Regular
+// motion keeps the bundle of trajectories tight; sensitivity to initial
+// conditions fans it out. Each copy is nudged as by `Scenario::perturbed`,
+// from its own seed, so the same count, spread and seed give the same bundle.
+pub struct Ensemble {
+    pub visible: bool,
+    copies: Vec<World>,
+    count: f32,
+    // Multiple of the jitter `Scenario::randomized` applies
+    spread: f32,
+    seed: u64,
+}
+
+impl Ensemble {
+    pub fn new() -> Ensemble {
+        Ensemble {
+            visible: false,
+            copies: Vec::new(),
+            count: 12.,
+            spread: 0.2,
+            seed: 1,
+        }
+    }
+
+    fn start(&mut self, world: &World) {
+        let scenario = Scenario::from_world(world);
+        let count = self.count.round().max(1.) as u64;
+        self.copies = (0..count)
+            .map(|i| {
+                let mut copy = world.clone();
+                copy.events.clear();
+                scenario
+                    .perturbed(self.seed + i, self.spread)
+                    .apply_to(&mut copy);
+                // Trails start over so the bundle fans out from here
+                for (entity, trail) in copy.trails.iter_mut() {
+                    if let Some(slot) = copy.particles.slot(entity) {
+                        *trail = Trail::new(copy.particles.pos[slot]);
+                    }
+                }
+                copy
+            })
+            .collect();
+    }
+
+    pub fn stop(&mut self) {
+        self.copies.clear();
+    }
+
+    // Steps every copy along with the main world; call once per tick. Copies
+    // of a scene that has since been replaced are dropped.
+    pub fn step(&mut self, world: &World, trail_limit: Option<usize>) {
+        if self
+            .copies
+            .first()
+            .is_some_and(|copy| copy.particles.entities() != world.particles.entities())
+        {
+            self.stop();
+        }
+        for copy in &mut self.copies {
+            systems::step(copy);
+            systems::record_trails(copy, trail_limit);
+            // Only the main world drives sounds and other event consumers
+            copy.events.clear();
+        }
+    }
+
+    // Root mean square distance of the copies' particles from the main
+    // world's.
+    fn spread_now(&self, world: &World) -> Option<f32> {
+        let ours = &world.particles.pos;
+        let (sum, n) = self
+            .copies
+            .iter()
+            .filter(|copy| copy.particles.len() == ours.len())
+            .flat_map(|copy| copy.particles.pos.iter().zip(ours))
+            .fold((0., 0), |(sum, n), (a, b)| {
+                (sum + a.distance_squared(*b), n + 1)
+            });
+        (n > 0).then(|| (sum / n as f32).sqrt())
+    }
+
+    // Trails and particles of every copy, faded.
+    pub fn draw_copies(&self, theme: &Theme, queue: &mut Translucent) {
+        for copy in &self.copies {
+            let particles = &copy.particles;
+            for (entity, trail) in copy.trails.iter() {
+                let Some(slot) = particles.slot(entity) else {
+                    continue;
+                };
+                let base = theme.particle(particles.particle[slot]);
+                let color = Color { a: 0.25, ..base };
+                for (a, b) in trail.points.iter().zip(trail.points.iter().skip(1)) {
+                    queue.line(*a, *b, color);
+                }
+                queue.sphere(particles.pos[slot], 1., Color { a: 0.4, ..base });
+            }
+        }
+    }
+
+    pub fn draw_panel(&mut self, world: &World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        dock::window(
+            "ensemble.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+            vec2(theme.scaled(320.), theme.scaled(260.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            ui.slider(
+                hash!(),
+                tr("ensemble.count"),
+                1.0..MAX_COPIES,
+                &mut self.count,
+            );
+            ui.slider(hash!(), tr("ensemble.spread"), 0.01..2.0, &mut self.spread);
+            if ui.button(None, trf("ensemble.seed", &[&self.seed]).as_str()) {
+                self.seed += MAX_COPIES as u64;
+            }
+            if ui.button(None, tr("ensemble.start")) {
+                self.start(world);
+            }
+            if !self.copies.is_empty() {
+                ui.same_line(0.);
+                if ui.button(None, tr("ensemble.stop")) {
+                    self.stop();
+                }
+            }
+            ui.separator();
+            match self.spread_now(world) {
+                Some(spread) => {
+                    let label = trf(
+                        "ensemble.running",
+                        &[&self.copies.len(), &format!("{:.2}", spread)],
+                    );
+                    ui.label(None, &label);
+                }
+                None => ui.label(None, tr("ensemble.idle")),
+            }
+        });
+    }
+}
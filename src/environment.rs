@@ -0,0 +1,131 @@
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+use crate::theme::Theme;
+
+const STAR_COUNT: usize = 800;
+// Stars sit on a sphere around the camera, inside the camera's far plane.
+const STAR_DISTANCE: f32 = 5000.;
+const GRADIENT_BANDS: usize = 48;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Skybox {
+    Off,
+    Starfield,
+    Gradient,
+}
+
+impl Skybox {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Skybox::Off => "off",
+            Skybox::Starfield => "starfield",
+            Skybox::Gradient => "gradient",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Skybox> {
+        [Skybox::Off, Skybox::Starfield, Skybox::Gradient]
+            .into_iter()
+            .find(|s| s.code() == code)
+    }
+
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            Skybox::Off => "environment.skybox.off",
+            Skybox::Starfield => "environment.skybox.starfield",
+            Skybox::Gradient => "environment.skybox.gradient",
+        }
+    }
+
+    pub fn next(&self) -> Skybox {
+        match self {
+            Skybox::Off => Skybox::Starfield,
+            Skybox::Starfield => Skybox::Gradient,
+            Skybox::Gradient => Skybox::Off,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Fog {
+    pub enabled: bool,
+    // Distances from the camera where fog begins and where it is fully opaque
+    pub start: f32,
+    pub end: f32,
+}
+
+pub struct Environment {
+    stars: Vec<(Vec3, f32)>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        let stars = (0..STAR_COUNT)
+            .map(|_| {
+                // Uniform direction on the sphere
+                let z: f32 = gen_range(-1., 1.);
+                let angle: f32 = gen_range(0., std::f32::consts::TAU);
+                let r = (1. - z * z).sqrt();
+                let dir = vec3(r * angle.cos(), z, r * angle.sin());
+                (dir, gen_range(0.3, 1.))
+            })
+            .collect();
+        Environment { stars }
+    }
+
+    // Screen-space part of the skybox, drawn before the 3D camera is set.
+    pub fn draw_background(&self, skybox: Skybox, theme: &Theme) {
+        if skybox != Skybox::Gradient {
+            return;
+        }
+        let top = theme.background;
+        let bottom = mix(theme.background, theme.grid_lines, 0.35);
+        let band = screen_height() / GRADIENT_BANDS as f32;
+        for i in 0..GRADIENT_BANDS {
+            let t = i as f32 / (GRADIENT_BANDS - 1) as f32;
+            draw_rectangle(
+                0.,
+                i as f32 * band,
+                screen_width(),
+                band + 1.,
+                mix(top, bottom, t),
+            );
+        }
+    }
+
+    pub fn draw_stars(&self, skybox: Skybox, eye: Vec3, theme: &Theme) {
+        if skybox != Skybox::Starfield {
+            return;
+        }
+        for (dir, brightness) in &self.stars {
+            let size = 6. + 6. * brightness;
+            let color = mix(theme.background, theme.selection, *brightness);
+            draw_cube(
+                eye + *dir * STAR_DISTANCE,
+                vec3(size, size, size),
+                None,
+                color,
+            );
+        }
+    }
+}
+
+impl Fog {
+    pub fn apply(&self, color: Color, pos: Vec3, eye: Vec3, background: Color) -> Color {
+        if !self.enabled {
+            return color;
+        }
+        let t = ((pos.distance(eye) - self.start) / (self.end - self.start)).clamp(0., 1.);
+        mix(color, background, t)
+    }
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    Color::new(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    )
+}
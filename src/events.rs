@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use crate::ecs::Entity;
+use crate::particle::{Particle, PARTICLE_RADIUS};
+use crate::world::World;
+
+// An electron closer than this to a proton counts as bound to the atom.
+pub const BOUND_RADIUS: f32 = 150.;
+// A bound electron farther than this from every proton has been ionized.
+pub const IONIZATION_RADIUS: f32 = 300.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Event {
+    Spawned(Entity),
+    Collision(Entity, Entity),
+    Ionized(Entity),
+    DetectorHit(Entity),
+    // Both entities are gone by the time this is read
+    Annihilated(Entity, Entity),
+}
+
+// State needed to report edge-triggered events once rather than every tick.
+#[derive(Clone, Default, Debug)]
+pub struct EventTracker {
+    contacts: HashSet<(Entity, Entity)>,
+    bound: HashSet<Entity>,
+}
+
+pub fn detect_events(world: &mut World) {
+    let particles = &world.particles;
+    let entities = particles.entities();
+    let tracker = &mut world.tracker;
+
+    // Collisions are reported in slot order rather than the set's, so a replay
+    // sees them in the same order
+    let mut contacts = HashSet::new();
+    for i in 0..particles.len() {
+        for j in (i + 1)..particles.len() {
+            if particles.pos[i].distance(particles.pos[j]) < 2. * PARTICLE_RADIUS {
+                let pair = (entities[i], entities[j]);
+                if !tracker.contacts.contains(&pair) {
+                    world.events.push(Event::Collision(pair.0, pair.1));
+                }
+                contacts.insert(pair);
+            }
+        }
+    }
+    tracker.contacts = contacts;
+
+    for (i, entity) in entities.iter().enumerate() {
+        if particles.particle[i] != Particle::Electron {
+            continue;
+        }
+        let nearest = particles
+            .nearest(i, Particle::Proton)
+            .map(|j| particles.pos[j].distance(particles.pos[i]))
+            .unwrap_or(f32::INFINITY);
+
+        if nearest < BOUND_RADIUS {
+            tracker.bound.insert(*entity);
+        } else if nearest > IONIZATION_RADIUS && tracker.bound.remove(entity) {
+            world.events.push(Event::Ionized(*entity));
+        }
+    }
+    tracker
+        .bound
+        .retain(|entity| particles.slot(*entity).is_some());
+}
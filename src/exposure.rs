@@ -0,0 +1,114 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::trf;
+use crate::settings::Settings;
+use crate::theme::Theme;
+use crate::toast;
+
+// Opacity each visit adds, so frequently visited places saturate slowly
+const DEPOSIT: f32 = 0.06;
+const DOT_SIZE: f32 = 1.5;
+
+// "Long exposure": every tick the particle positions are drawn into an
+// offscreen texture that is never cleared, seen from the camera pose at the
+// moment the exposure started, like a photographic plate left open.
+pub struct Exposure {
+    camera: Option<Camera3D>,
+    target: Option<RenderTarget>,
+}
+
+impl Exposure {
+    pub fn new() -> Exposure {
+        Exposure {
+            camera: None,
+            target: None,
+        }
+    }
+
+    pub fn toggle(&mut self, camera: &Camera3D) {
+        if self.camera.take().is_some() {
+            return;
+        }
+        let (width, height) = (screen_width() as u32, screen_height() as u32);
+        let target = match self.target {
+            Some(target)
+                if (
+                    target.texture.width() as u32,
+                    target.texture.height() as u32,
+                ) == (width, height) =>
+            {
+                target
+            }
+            _ => {
+                if let Some(old) = self.target.take() {
+                    old.delete();
+                }
+                render_target(width, height)
+            }
+        };
+        let camera = Camera3D {
+            render_target: Some(target),
+            ..*camera
+        };
+        set_camera(&camera);
+        clear_background(Color::new(0., 0., 0., 0.));
+        self.camera = Some(camera);
+        self.target = Some(target);
+    }
+
+    // Call once per simulation tick.
+    pub fn accumulate(&self, world: &World, theme: &Theme) {
+        let Some(camera) = &self.camera else {
+            return;
+        };
+        set_camera(camera);
+        let particles = &world.particles;
+        for (particle, pos) in particles.particle.iter().zip(&particles.pos) {
+            let color = Color {
+                a: DEPOSIT,
+                ..theme.particle(*particle)
+            };
+            draw_cube(*pos, Vec3::splat(DOT_SIZE), None, color);
+        }
+    }
+
+    pub fn draw(&self) {
+        let (Some(_), Some(target)) = (&self.camera, self.target) else {
+            return;
+        };
+        draw_texture_ex(
+            target.texture,
+            0.,
+            0.,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(screen_width(), screen_height())),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    // Saves the plate as a PNG next to the settings file.
+    pub fn save(&self) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let path = Settings::path().with_file_name("exposure.png");
+        if let Some(dir) = path.parent() {
+            if let Err(err) = std::fs::create_dir_all(dir) {
+                log::warn!("could not create {}: {}", dir.display(), err);
+                return;
+            }
+        }
+        target
+            .texture
+            .get_texture_data()
+            .export_png(&path.to_string_lossy());
+        let message = trf("exposure.saved", &[&path.display()]);
+        log::info!("{}", message);
+        toast::info(message);
+    }
+}
@@ -0,0 +1,258 @@
+// C interface to the simulation core, for embedding it in other frontends.
+// Build with `cargo build --release --features ffi` and include
+// `include/rutherford.h`. Every function takes the handle returned by
+// `rutherford_sim_new` or `rutherford_sim_from_scenario`; none of them are
+// thread-safe on the same handle.
+//
+// A panic must not unwind into C, so every body runs under `catch_unwind`
+// and a panic comes back as the function's error value: null, -1, or the
+// largest value of an unsigned return. A handle whose world panicked
+// part-way through a change is poisoned, and every later call on it but
+// `rutherford_sim_free` fails the same way.
+
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use macroquad::prelude::*;
+
+use crate::particle::{Particle, TRAIL_LENGTH};
+use crate::scenario::Scenario;
+use crate::systems;
+use crate::world::World;
+
+// Opaque to C
+pub struct Simulation {
+    world: World,
+    poisoned: bool,
+}
+
+fn boxed(world: World) -> *mut Simulation {
+    Box::into_raw(Box::new(Simulation {
+        world,
+        poisoned: false,
+    }))
+}
+
+// Runs `body`, turning a panic into `failed`
+fn guarded<T>(name: &str, failed: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| {
+        log::error!("{}: panicked", name);
+        failed
+    })
+}
+
+// Runs `body` on the world of a live, unpoisoned handle, poisoning it if
+// `body` panics. `absent` is returned for a null handle.
+unsafe fn changing<T>(
+    sim: *mut Simulation,
+    name: &str,
+    absent: T,
+    failed: T,
+    body: impl FnOnce(&mut World) -> T,
+) -> T {
+    let Some(sim) = sim.as_mut() else {
+        return absent;
+    };
+    if sim.poisoned {
+        return failed;
+    }
+    let world = &mut sim.world;
+    match panic::catch_unwind(AssertUnwindSafe(|| body(world))) {
+        Ok(value) => value,
+        Err(_) => {
+            log::error!("{}: panicked, the simulation can only be freed now", name);
+            sim.poisoned = true;
+            failed
+        }
+    }
+}
+
+// Same as `changing`, for calls that only read the world and so leave it
+// as it was
+unsafe fn reading<T>(
+    sim: *const Simulation,
+    name: &str,
+    absent: T,
+    failed: T,
+    body: impl FnOnce(&World) -> T,
+) -> T {
+    match sim.as_ref() {
+        None => absent,
+        Some(sim) if sim.poisoned => failed,
+        Some(sim) => guarded(name, failed, || body(&sim.world)),
+    }
+}
+
+// Writes `x y z` triples for up to `capacity` particles and returns how many
+// particles were written
+unsafe fn copy_vectors(values: &[Vec3], out: *mut f32, capacity: usize) -> usize {
+    if out.is_null() {
+        return 0;
+    }
+    let count = values.len().min(capacity);
+    let out = std::slice::from_raw_parts_mut(out, count * 3);
+    for (chunk, value) in out.chunks_exact_mut(3).zip(values) {
+        chunk.copy_from_slice(&[value.x, value.y, value.z]);
+    }
+    count
+}
+
+/// Creates an empty simulation, or returns null on a panic.
+#[no_mangle]
+pub extern "C" fn rutherford_sim_new() -> *mut Simulation {
+    guarded(
+        "rutherford_sim_new",
+        ptr::null_mut(),
+        || boxed(World::new()),
+    )
+}
+
+/// Creates a simulation from scene file text, or returns null if it does not
+/// parse or building it panics.
+///
+/// # Safety
+///
+/// `text` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_from_scenario(text: *const c_char) -> *mut Simulation {
+    if text.is_null() {
+        return ptr::null_mut();
+    }
+    let name = "rutherford_sim_from_scenario";
+    guarded(name, ptr::null_mut(), || {
+        let text = CStr::from_ptr(text).to_string_lossy();
+        match Scenario::parse(&text) {
+            Ok(scenario) => boxed(scenario.build()),
+            Err(err) => {
+                log::warn!("{}: {}", name, err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Frees a simulation, poisoned or not. Null is ignored.
+///
+/// # Safety
+///
+/// `sim` must come from this library and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_free(sim: *mut Simulation) {
+    if !sim.is_null() {
+        guarded("rutherford_sim_free", (), || drop(Box::from_raw(sim)));
+    }
+}
+
+/// Adds a particle by species code ("electron", "alpha", ...) or custom
+/// species name and returns its slot, or -1 for an unknown species or on a
+/// panic.
+///
+/// # Safety
+///
+/// `sim` must be a live handle and `species` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_spawn(
+    sim: *mut Simulation,
+    species: *const c_char,
+    x: f32,
+    y: f32,
+    z: f32,
+) -> i64 {
+    if species.is_null() {
+        return -1;
+    }
+    changing(sim, "rutherford_sim_spawn", -1, -1, |world| {
+        let Some(particle) = Particle::from_code(&CStr::from_ptr(species).to_string_lossy()) else {
+            return -1;
+        };
+        let entity = world.spawn_particle(particle, Some(vec3(x, y, z)));
+        world.particles.slot(entity).map_or(-1, |slot| slot as i64)
+    })
+}
+
+/// Runs up to `ticks` ticks, stopping early once the state stops being sane,
+/// and returns how many ran, or `u64::MAX` on a panic.
+///
+/// # Safety
+///
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_step(sim: *mut Simulation, ticks: u64) -> u64 {
+    changing(sim, "rutherford_sim_step", 0, u64::MAX, |world| {
+        systems::fast_forward(world, ticks, Some(TRAIL_LENGTH))
+    })
+}
+
+/// Number of particles, which is the row count of the state arrays, or
+/// `usize::MAX` on a panic.
+///
+/// # Safety
+///
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_particle_count(sim: *const Simulation) -> usize {
+    reading(
+        sim,
+        "rutherford_sim_particle_count",
+        0,
+        usize::MAX,
+        |world| world.particle_count(),
+    )
+}
+
+/// Copies the positions of up to `capacity` particles to `out` as `x y z`
+/// triples in slot order, and returns how many particles were copied, or
+/// `usize::MAX` on a panic.
+///
+/// # Safety
+///
+/// `sim` must be a live handle and `out` must have room for `capacity * 3`
+/// floats.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_get_positions(
+    sim: *const Simulation,
+    out: *mut f32,
+    capacity: usize,
+) -> usize {
+    reading(
+        sim,
+        "rutherford_sim_get_positions",
+        0,
+        usize::MAX,
+        |world| copy_vectors(&world.particles.pos, out, capacity),
+    )
+}
+
+/// Same as `rutherford_sim_get_positions`, for velocities.
+///
+/// # Safety
+///
+/// As for `rutherford_sim_get_positions`.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_get_velocities(
+    sim: *const Simulation,
+    out: *mut f32,
+    capacity: usize,
+) -> usize {
+    reading(
+        sim,
+        "rutherford_sim_get_velocities",
+        0,
+        usize::MAX,
+        |world| copy_vectors(&world.particles.vel, out, capacity),
+    )
+}
+
+/// Removes every particle and returns how many there were, or `usize::MAX`
+/// on a panic.
+///
+/// # Safety
+///
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rutherford_sim_clear(sim: *mut Simulation) -> usize {
+    changing(sim, "rutherford_sim_clear", 0, usize::MAX, |world| {
+        world.clear_particles()
+    })
+}
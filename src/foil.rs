@@ -0,0 +1,166 @@
+use macroquad::prelude::*;
+
+use crate::ecs::Entity;
+use crate::particle::{get_charge, get_mass, Particle};
+use crate::world::World;
+
+// Nuclei per side of a foil's square patch, and their spacing
+pub const FOIL_SIDE: usize = 3;
+pub const FOIL_SPACING: f32 = 30.;
+// An alpha passing closer than this to a foil nucleus is tagged with the foil
+pub const INTERACTION_RADIUS: f32 = 40.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Element {
+    Aluminium,
+    Gold,
+    Platinum,
+}
+
+pub const ELEMENTS: [Element; 3] = [Element::Aluminium, Element::Gold, Element::Platinum];
+
+impl Element {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Element::Aluminium => "aluminium",
+            Element::Gold => "gold",
+            Element::Platinum => "platinum",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Element> {
+        ELEMENTS.iter().copied().find(|e| e.code() == code)
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Element::Aluminium => "Al",
+            Element::Gold => "Au",
+            Element::Platinum => "Pt",
+        }
+    }
+
+    pub fn atomic_number(&self) -> u32 {
+        match self {
+            Element::Aluminium => 13,
+            Element::Gold => 79,
+            Element::Platinum => 78,
+        }
+    }
+
+    pub fn mass_number(&self) -> u32 {
+        match self {
+            Element::Aluminium => 27,
+            Element::Gold => 197,
+            Element::Platinum => 195,
+        }
+    }
+
+    pub fn next(&self) -> Element {
+        let index = ELEMENTS.iter().position(|e| e == self).unwrap_or(0);
+        ELEMENTS[(index + 1) % ELEMENTS.len()]
+    }
+}
+
+// A square patch of nuclei facing the beam (+x), centered on `center`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Foil {
+    pub element: Element,
+    pub center: Vec3,
+    pub nuclei: Vec<Entity>,
+}
+
+// Which foil an alpha came closest to, and how close it got.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Interaction {
+    pub foil: usize,
+    pub closest: f32,
+}
+
+// Spawns a foil's nuclei and returns the foil's index in `world.foils`. In
+// 2D mode the patch is a single column, as the others would land on top of
+// it.
+pub fn add_foil(world: &mut World, element: Element, center: Vec3) -> usize {
+    let half = (FOIL_SIDE - 1) as f32 / 2.;
+    let columns = if world.planar {
+        FOIL_SIDE / 2..FOIL_SIDE / 2 + 1
+    } else {
+        0..FOIL_SIDE
+    };
+    world.foils.push(Foil {
+        element,
+        center,
+        nuclei: Vec::new(),
+    });
+    let index = world.foils.len() - 1;
+    for row in 0..FOIL_SIDE {
+        for column in columns.clone() {
+            let offset = vec3(0., row as f32 - half, column as f32 - half) * FOIL_SPACING;
+            add_nucleus(world, index, center + offset);
+        }
+    }
+    index
+}
+
+// Spawns one nucleus of the foil at `index` at `pos`.
+pub fn add_nucleus(world: &mut World, index: usize, pos: Vec3) -> Entity {
+    let element = world.foils[index].element;
+    let entity = world.spawn_particle(Particle::Proton, Some(pos));
+    if let Some(slot) = world.particles.slot(entity) {
+        world.particles.charge[slot] =
+            get_charge(Particle::Proton) * element.atomic_number() as f32;
+        world.particles.mass[slot] = get_mass(Particle::Proton) * element.mass_number() as f32;
+    }
+    world.foils[index].nuclei.push(entity);
+    entity
+}
+
+// Foil the nucleus `entity` belongs to, if any.
+pub fn foil_of(world: &World, entity: Entity) -> Option<usize> {
+    world
+        .foils
+        .iter()
+        .position(|foil| foil.nuclei.contains(&entity))
+}
+
+// Tags every alpha with the foil whose nucleus it has come closest to.
+pub fn tag_interactions(world: &mut World) {
+    if world.foils.is_empty() {
+        return;
+    }
+    let particles = &world.particles;
+    for (i, entity) in particles.entities().iter().enumerate() {
+        if particles.particle[i] != Particle::Alpha {
+            continue;
+        }
+        let nearest = world
+            .foils
+            .iter()
+            .enumerate()
+            .flat_map(|(index, foil)| foil.nuclei.iter().map(move |nucleus| (index, nucleus)))
+            .filter_map(|(index, nucleus)| {
+                let slot = particles.slot(*nucleus)?;
+                Some((index, particles.pos[slot].distance(particles.pos[i])))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((foil, distance)) = nearest else {
+            continue;
+        };
+        if distance > INTERACTION_RADIUS {
+            continue;
+        }
+        let closer = world
+            .interactions
+            .get(*entity)
+            .is_none_or(|tag| distance < tag.closest);
+        if closer {
+            world.interactions.insert(
+                *entity,
+                Interaction {
+                    foil,
+                    closest: distance,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use macroquad::prelude::*;
+
+// The six planes bounding what a camera sees, each as a unit normal pointing
+// inwards and its offset, taken from the rows of the view-projection matrix
+// (Gribb and Hartmann).
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    pub fn new(camera: &Camera3D) -> Frustum {
+        let m = camera.matrix();
+        let (x, y, z, w) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z]
+            .map(|plane| plane / plane.truncate().length().max(f32::MIN_POSITIVE));
+        Frustum { planes }
+    }
+
+    // False only when the sphere is wholly outside; one straddling an edge
+    // or a corner counts as visible.
+    pub fn sees(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+    }
+
+    // Whether any of `points` might be in view, judged by the sphere around
+    // their bounding box.
+    pub fn sees_points(&self, points: impl Iterator<Item = Vec3>) -> bool {
+        let (low, high) = points.fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(low, high), p| (low.min(p), high.max(p)),
+        );
+        low.x <= high.x && self.sees((low + high) / 2., (high - low).length() / 2.)
+    }
+}
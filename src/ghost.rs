@@ -0,0 +1,317 @@
+use macroquad::prelude::*;
+
+use crate::ecs::Entity;
+use crate::particle::Particle;
+use crate::systems::COULOMB_K;
+use crate::world::World;
+
+// Newton iterations when solving Kepler's equation
+const KEPLER_ITERATIONS: usize = 30;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Branch {
+    Ellipse,
+    // Attracted by the target but fast enough to get away
+    Attractive,
+    // The Rutherford case
+    Repulsive,
+}
+
+// A Kepler orbit around a target held fixed at `focus`, in the plane spanned
+// by `p` (towards closest approach) and `q`. Worked in f64, as Coulomb
+// constants in simulation units overflow the precision of f32 quickly.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Conic {
+    branch: Branch,
+    focus: DVec3,
+    p: DVec3,
+    q: DVec3,
+    a: f64,
+    e: f64,
+    // Mean motion and the mean anomaly at spawn
+    n: f64,
+    m0: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Path {
+    Straight { start: Vec3, vel: Vec3 },
+    Conic(Conic),
+}
+
+// A particle that follows a prescribed path instead of the forces, for
+// comparing simulated trajectories with theory. Ghosts exert no forces and
+// nothing acts on them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ghost {
+    pub particle: Particle,
+    pub path: Path,
+    // Ticks since it was spawned
+    pub age: u64,
+}
+
+impl Branch {
+    fn code(&self) -> &'static str {
+        match self {
+            Branch::Ellipse => "ellipse",
+            Branch::Attractive => "attractive",
+            Branch::Repulsive => "repulsive",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Branch> {
+        match code {
+            "ellipse" => Some(Branch::Ellipse),
+            "attractive" => Some(Branch::Attractive),
+            "repulsive" => Some(Branch::Repulsive),
+            _ => None,
+        }
+    }
+}
+
+impl Ghost {
+    // The species, age and path as one line of fields:
+    //
+    //     alpha 120 straight <start> <vel>
+    //     alpha 120 repulsive <focus> <p> <q> <a> <e> <n> <m0>
+    //
+    // with each vector written as its three components.
+    pub fn to_text(&self) -> String {
+        let head = format!("{} {}", self.particle.name(), self.age);
+        match self.path {
+            Path::Straight { start: s, vel: v } => format!(
+                "{} straight {} {} {} {} {} {}",
+                head, s.x, s.y, s.z, v.x, v.y, v.z
+            ),
+            Path::Conic(c) => format!(
+                "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+                head,
+                c.branch.code(),
+                c.focus.x,
+                c.focus.y,
+                c.focus.z,
+                c.p.x,
+                c.p.y,
+                c.p.z,
+                c.q.x,
+                c.q.y,
+                c.q.z,
+                c.a,
+                c.e,
+                c.n,
+                c.m0
+            ),
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Ghost, String> {
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        let [code, age, kind, numbers @ ..] = fields.as_slice() else {
+            return Err(format!("'{}' is not a ghost", value));
+        };
+        let particle =
+            Particle::from_code(code).ok_or_else(|| format!("unknown particle '{}'", code))?;
+        let age = age
+            .parse::<u64>()
+            .map_err(|_| format!("'{}' is not a whole number", age))?;
+        let numbers = numbers
+            .iter()
+            .map(|field| {
+                field
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{}' is not a number", field))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        let path = match (*kind, numbers.as_slice()) {
+            ("straight", [x, y, z, vx, vy, vz]) => Path::Straight {
+                start: dvec3(*x, *y, *z).as_vec3(),
+                vel: dvec3(*vx, *vy, *vz).as_vec3(),
+            },
+            (branch, [fx, fy, fz, px, py, pz, qx, qy, qz, a, e, n, m0]) => {
+                let branch = Branch::from_code(branch)
+                    .ok_or_else(|| format!("unknown ghost path '{}'", branch))?;
+                Path::Conic(Conic {
+                    branch,
+                    focus: dvec3(*fx, *fy, *fz),
+                    p: dvec3(*px, *py, *pz),
+                    q: dvec3(*qx, *qy, *qz),
+                    a: *a,
+                    e: *e,
+                    n: *n,
+                    m0: *m0,
+                })
+            }
+            _ => return Err(format!("'{}' is not a ghost", value)),
+        };
+        Ok(Ghost {
+            particle,
+            path,
+            age,
+        })
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position_at(self.age as f32)
+    }
+
+    pub fn position_at(&self, ticks: f32) -> Vec3 {
+        match self.path {
+            Path::Straight { start, vel } => start + vel * ticks,
+            Path::Conic(conic) => conic.position_at(ticks as f64).as_vec3(),
+        }
+    }
+}
+
+impl Conic {
+    // Orbit of a particle at `pos` moving at `vel` under the pure Coulomb
+    // force of a target at `focus`. None when it would be a straight radial
+    // line or too close to a parabola to tell the branches apart.
+    fn new(pos: Vec3, vel: Vec3, focus: Vec3, charge_product: f32, mass: f32) -> Option<Conic> {
+        let (r, v, focus) = ((pos - focus).as_dvec3(), vel.as_dvec3(), focus.as_dvec3());
+        // Acceleration is -mu r / |r|³, so mu < 0 pushes away
+        let mu = -(COULOMB_K as f64) * charge_product as f64 / mass as f64;
+        let distance = r.length();
+        let h = r.cross(v);
+        if distance == 0. || h.length() < 1e-9 * distance * v.length().max(1e-12) || mu == 0. {
+            return None;
+        }
+        let energy = v.length_squared() / 2. - mu / distance;
+        let e = (1. + 2. * energy * h.length_squared() / (mu * mu))
+            .max(0.)
+            .sqrt();
+        if (e - 1.).abs() < 1e-6 {
+            return None;
+        }
+        let a = (mu / (2. * energy)).abs();
+        let n = (mu.abs() / (a * a * a)).sqrt();
+        let branch = match (mu > 0., e < 1.) {
+            (true, true) => Branch::Ellipse,
+            (true, false) => Branch::Attractive,
+            (false, _) => Branch::Repulsive,
+        };
+        // Anomaly now, negative on the way in
+        let receding = r.dot(v) >= 0.;
+        let sign = if receding { 1. } else { -1. };
+        let anomaly = match branch {
+            Branch::Ellipse => sign * ((1. - distance / a) / e).clamp(-1., 1.).acos(),
+            Branch::Attractive => sign * ((distance / a + 1.) / e).max(1.).acosh(),
+            Branch::Repulsive => sign * ((distance / a - 1.) / e).max(1.).acosh(),
+        };
+        let m0 = match branch {
+            Branch::Ellipse => anomaly - e * anomaly.sin(),
+            Branch::Attractive => e * anomaly.sinh() - anomaly,
+            Branch::Repulsive => e * anomaly.sinh() + anomaly,
+        };
+        let mut conic = Conic {
+            branch,
+            focus,
+            p: DVec3::X,
+            q: DVec3::Y,
+            a,
+            e,
+            n,
+            m0,
+        };
+        // Turn the perifocal frame so the anomaly lands on `r`
+        let (x, y) = conic.perifocal(anomaly);
+        let angle = y.atan2(x);
+        let normal = h.normalize();
+        let radial = r / distance;
+        conic.p = radial * angle.cos() - normal.cross(radial) * angle.sin();
+        conic.q = normal.cross(conic.p);
+        Some(conic)
+    }
+
+    fn perifocal(&self, anomaly: f64) -> (f64, f64) {
+        let (a, e) = (self.a, self.e);
+        match self.branch {
+            Branch::Ellipse => (
+                a * (anomaly.cos() - e),
+                a * (1. - e * e).sqrt() * anomaly.sin(),
+            ),
+            Branch::Attractive => (
+                a * (e - anomaly.cosh()),
+                a * (e * e - 1.).sqrt() * anomaly.sinh(),
+            ),
+            Branch::Repulsive => (
+                a * (e + anomaly.cosh()),
+                a * (e * e - 1.).sqrt() * anomaly.sinh(),
+            ),
+        }
+    }
+
+    // Solves Kepler's equation for the anomaly at mean anomaly `m` by Newton's
+    // method.
+    fn anomaly(&self, m: f64) -> f64 {
+        let e = self.e;
+        let mut x = match self.branch {
+            Branch::Ellipse => m,
+            _ => (m / e).asinh(),
+        };
+        for _ in 0..KEPLER_ITERATIONS {
+            let (f, df) = match self.branch {
+                Branch::Ellipse => (x - e * x.sin() - m, 1. - e * x.cos()),
+                Branch::Attractive => (e * x.sinh() - x - m, e * x.cosh() - 1.),
+                Branch::Repulsive => (e * x.sinh() + x - m, e * x.cosh() + 1.),
+            };
+            let step = f / df;
+            x -= step;
+            if step.abs() < 1e-12 {
+                break;
+            }
+        }
+        x
+    }
+
+    fn position_at(&self, ticks: f64) -> DVec3 {
+        let (x, y) = self.perifocal(self.anomaly(self.m0 + self.n * ticks));
+        self.focus + self.p * x + self.q * y
+    }
+}
+
+// A ghost of `entity` carrying on in a straight line, as it would with no
+// forces at all.
+pub fn straight(world: &World, entity: Entity) -> Option<Ghost> {
+    let particles = &world.particles;
+    let i = particles.slot(entity)?;
+    Some(Ghost {
+        particle: particles.particle[i],
+        path: Path::Straight {
+            start: particles.pos[i],
+            vel: particles.vel[i],
+        },
+        age: 0,
+    })
+}
+
+// A ghost of `entity` on the ideal orbit around the nearest proton: pure
+// Coulomb, no other bodies, and the nucleus never recoiling. For an alpha
+// heading past a nucleus that is the Rutherford hyperbola. None under any
+// other force law, which has no such closed form.
+pub fn rutherford(world: &World, entity: Entity) -> Option<Ghost> {
+    if !world.force_model.is_coulomb() {
+        return None;
+    }
+    let particles = &world.particles;
+    let i = particles.slot(entity)?;
+    let target = particles.nearest(i, Particle::Proton)?;
+    let conic = Conic::new(
+        particles.pos[i],
+        particles.vel[i] - particles.vel[target],
+        particles.pos[target],
+        particles.charge[i] * particles.charge[target],
+        particles.mass[i],
+    )?;
+    Some(Ghost {
+        particle: particles.particle[i],
+        path: Path::Conic(conic),
+        age: 0,
+    })
+}
+
+// Moves every ghost one tick along its path.
+pub fn advance(world: &mut World) {
+    for ghost in &mut world.ghosts {
+        ghost.age += 1;
+    }
+}
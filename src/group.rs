@@ -0,0 +1,21 @@
+use macroquad::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GroupId(pub usize);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Group {
+    pub name: String,
+    pub color: Option<Color>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GroupStats {
+    pub group: GroupId,
+    pub count: usize,
+    pub mean_kinetic_energy: f32,
+}
+
+pub fn kinetic_energy(mass: f32, vel: Vec3) -> f32 {
+    0.5 * mass * vel.length_squared()
+}
@@ -0,0 +1,67 @@
+use egui_macroquad::egui::{self, Color32};
+use macroquad::prelude::*;
+
+use crate::theme::Theme;
+
+// Panels that need real widgets, tables or plots are built with egui, drawn
+// over the scene after macroquad's own UI. These keep the two looking and
+// behaving alike.
+
+pub fn color(color: Color) -> Color32 {
+    let [r, g, b, a]: [u8; 4] = color.into();
+    Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+// Styles egui after the theme; call at the start of every egui frame.
+pub fn style(ctx: &egui::Context, theme: &Theme) {
+    let scale = theme.scaled(1.);
+    if ctx.pixels_per_point() != scale {
+        ctx.set_pixels_per_point(scale);
+    }
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(color(theme.text));
+    visuals.window_fill = color(Color {
+        a: theme.panel.a.max(0.85),
+        ..theme.panel
+    });
+    ctx.set_visuals(visuals);
+}
+
+// Whether the pointer is over an egui window or dragging one of its widgets,
+// so the click is not also a pick in the scene.
+pub fn wants_mouse() -> bool {
+    let mut wants = false;
+    egui_macroquad::cfg(|ctx| wants = ctx.wants_pointer_input() || ctx.is_pointer_over_area());
+    wants
+}
+
+// Whether an egui text field has focus, so typing into it doesn't trigger
+// key bindings.
+pub fn wants_keyboard() -> bool {
+    let mut wants = false;
+    egui_macroquad::cfg(|ctx| wants = ctx.wants_keyboard_input());
+    wants
+}
+
+// A line plot of `points` in `color`, named for the legend.
+pub fn line(name: &str, points: Vec<[f64; 2]>, color: Color) -> egui::plot::Line {
+    egui::plot::Line::new(egui::plot::PlotPoints::new(points))
+        .name(name)
+        .color(self::color(color))
+}
+
+// A bar chart of `(position, height)` pairs in `color`, the bars `width` wide.
+pub fn bars(
+    name: &str,
+    bars: impl IntoIterator<Item = (f64, f64)>,
+    width: f64,
+    color: Color,
+) -> egui::plot::BarChart {
+    let bars = bars
+        .into_iter()
+        .map(|(x, height)| egui::plot::Bar::new(x, height).width(width))
+        .collect();
+    egui::plot::BarChart::new(bars)
+        .name(name)
+        .color(self::color(color))
+}
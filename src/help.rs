@@ -0,0 +1,91 @@
+use macroquad::prelude::*;
+
+use crate::i18n::tr;
+use crate::keys::{self, Action, BINDINGS, CATEGORIES, MOUSE};
+use crate::theme::Theme;
+
+const COLUMN_WIDTH: f32 = 330.;
+const KEY_WIDTH: f32 = 120.;
+
+// Full-screen list of every shortcut, built from `keys::BINDINGS` so a new
+// binding shows up here without further work.
+pub struct HelpOverlay {
+    pub visible: bool,
+}
+
+impl HelpOverlay {
+    pub fn new() -> HelpOverlay {
+        HelpOverlay { visible: false }
+    }
+
+    // Keys for each action in `category`, in binding order, with the keys of
+    // an action joined together.
+    fn entries(category: keys::Category) -> Vec<(String, &'static str)> {
+        let mut entries: Vec<(Action, String, &'static str)> = Vec::new();
+        for binding in BINDINGS.iter().filter(|b| b.category == category) {
+            match entries
+                .iter_mut()
+                .find(|(action, ..)| *action == binding.action)
+            {
+                Some((_, chords, _)) => {
+                    chords.push_str(" / ");
+                    chords.push_str(&keys::chord(binding));
+                }
+                None => entries.push((binding.action, keys::chord(binding), binding.help_key)),
+            }
+        }
+        let mouse = MOUSE
+            .iter()
+            .filter(|(c, ..)| *c == category)
+            .map(|(_, control, help)| (tr(control).to_string(), *help));
+        entries
+            .into_iter()
+            .map(|(_, chords, help)| (chords, help))
+            .chain(mouse)
+            .collect()
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        draw_rectangle(0., 0., screen_width(), screen_height(), theme.panel);
+        let font_size = theme.text_size(18.);
+        let line = font_size * 1.15;
+        let (left, top) = (theme.scaled(20.), theme.scaled(40.));
+        draw_text(
+            tr("help.title"),
+            left,
+            top,
+            theme.text_size(30.),
+            theme.text,
+        );
+
+        // Lines fill a column top to bottom and spill into the next one
+        let (mut x, mut y) = (left, top + line * 1.5);
+        let next_line = |y: &mut f32, x: &mut f32| {
+            *y += line;
+            if *y > screen_height() - line {
+                *y = top + line * 1.5;
+                *x += theme.scaled(COLUMN_WIDTH);
+            }
+        };
+        for category in CATEGORIES {
+            draw_text(tr(category.name_key()), x, y, font_size, theme.selection);
+            next_line(&mut y, &mut x);
+            for (chords, help) in Self::entries(category) {
+                draw_text(&chords, x, y, font_size, theme.warning);
+                draw_text(
+                    tr(help),
+                    x + theme.scaled(KEY_WIDTH),
+                    y,
+                    font_size,
+                    theme.text,
+                );
+                next_line(&mut y, &mut x);
+            }
+            next_line(&mut y, &mut x);
+        }
+    }
+}
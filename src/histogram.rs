@@ -0,0 +1,54 @@
+// Fixed-width bins spanning the observed range of the samples.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Histogram {
+    pub min: f32,
+    pub max: f32,
+    pub counts: Vec<u32>,
+}
+
+impl Histogram {
+    // Non-finite samples are skipped.
+    pub fn new(samples: impl IntoIterator<Item = f32>, bins: usize) -> Histogram {
+        let samples: Vec<f32> = samples.into_iter().filter(|s| s.is_finite()).collect();
+        let mut histogram = Histogram {
+            min: samples.iter().copied().fold(f32::INFINITY, f32::min),
+            max: samples.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            counts: vec![0; bins.max(1)],
+        };
+        if samples.is_empty() {
+            (histogram.min, histogram.max) = (0., 0.);
+            return histogram;
+        }
+
+        let width = histogram.bin_width();
+        let last = histogram.counts.len() - 1;
+        for sample in samples {
+            let bin = if width > 0. {
+                (((sample - histogram.min) / width) as usize).min(last)
+            } else {
+                0
+            };
+            histogram.counts[bin] += 1;
+        }
+        histogram
+    }
+
+    pub fn bin_width(&self) -> f32 {
+        (self.max - self.min) / self.counts.len() as f32
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    // One `bin_start,bin_end,count` row per bin, after a header row.
+    pub fn to_csv(&self) -> String {
+        let width = self.bin_width();
+        let mut csv = String::from("bin_start,bin_end,count\n");
+        for (i, count) in self.counts.iter().enumerate() {
+            let start = self.min + width * i as f32;
+            csv.push_str(&format!("{},{},{}\n", start, start + width, count));
+        }
+        csv
+    }
+}
@@ -0,0 +1,53 @@
+use crate::world::World;
+
+const MAX_DEPTH: usize = 100;
+
+// Undo stack of whole-world snapshots, recorded just before each scene edit.
+#[derive(Clone, Default, Debug)]
+pub struct History {
+    undo: Vec<World>,
+    redo: Vec<World>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    pub fn record(&mut self, world: &World) {
+        if self.undo.len() == MAX_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push(world.clone());
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, world: &mut World) -> bool {
+        let Some(previous) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(std::mem::replace(world, previous));
+        true
+    }
+
+    pub fn redo(&mut self, world: &mut World) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(world, next));
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
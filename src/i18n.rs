@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Language {
+    English,
+    Spanish,
+}
+
+pub const LANGUAGES: [Language; 2] = [Language::English, Language::Spanish];
+
+impl Language {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        LANGUAGES.iter().copied().find(|l| l.code() == code)
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../lang/en.txt"),
+            Language::Spanish => include_str!("../lang/es.txt"),
+        }
+    }
+}
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static CATALOGS: OnceLock<Vec<HashMap<&'static str, &'static str>>> = OnceLock::new();
+
+fn catalogs() -> &'static [HashMap<&'static str, &'static str>] {
+    CATALOGS.get_or_init(|| LANGUAGES.iter().map(|l| parse(l.source())).collect())
+}
+
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once(" = ")?;
+            Some((key.trim(), value))
+        })
+        .collect()
+}
+
+pub fn set_language(language: Language) {
+    let index = LANGUAGES.iter().position(|l| *l == language).unwrap_or(0);
+    CURRENT.store(index, Ordering::Relaxed);
+}
+
+pub fn next_language() -> Language {
+    let index = (CURRENT.load(Ordering::Relaxed) + 1) % LANGUAGES.len();
+    CURRENT.store(index, Ordering::Relaxed);
+    LANGUAGES[index]
+}
+
+// Looks up `key` in the current language, falling back to English and then
+// to the key itself so missing entries stay visible rather than blank.
+pub fn tr(key: &'static str) -> &'static str {
+    let catalogs = catalogs();
+    catalogs[CURRENT.load(Ordering::Relaxed)]
+        .get(key)
+        .or_else(|| catalogs[0].get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+pub fn trf(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut parts = tr(key).split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
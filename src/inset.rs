@@ -0,0 +1,109 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::world::World;
+
+use crate::theme::Theme;
+
+const INSET_SIZE: u32 = 256;
+const MARGIN: f32 = 10.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InsetView {
+    Off,
+    // Orthographic, looking straight down the y axis
+    TopDown,
+    // Perspective, looking along the x axis like the beam does
+    Beam,
+}
+
+impl InsetView {
+    pub fn next(&self) -> InsetView {
+        match self {
+            InsetView::Off => InsetView::TopDown,
+            InsetView::TopDown => InsetView::Beam,
+            InsetView::Beam => InsetView::Off,
+        }
+    }
+
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            InsetView::Off => "inset.off",
+            InsetView::TopDown => "inset.top_down",
+            InsetView::Beam => "inset.beam",
+        }
+    }
+}
+
+// A second camera rendered into a small texture and shown in the top right
+// corner while the main camera flies freely.
+pub struct Inset {
+    pub view: InsetView,
+    target: RenderTarget,
+}
+
+impl Inset {
+    pub fn new() -> Inset {
+        let target = render_target(INSET_SIZE, INSET_SIZE);
+        target.texture.set_filter(FilterMode::Linear);
+        Inset {
+            view: InsetView::Off,
+            target,
+        }
+    }
+
+    // Frames the whole atom; falls back to the origin when the world is empty.
+    fn camera(&self, world: &World) -> Camera3D {
+        let (center, radius) = world.bounding_sphere().unwrap_or((Vec3::ZERO, 0.));
+        let extent = radius.max(50.) * 2.5;
+        let (position, up, projection, fovy) = match self.view {
+            InsetView::TopDown => (
+                center + vec3(0., radius + 100., 0.),
+                vec3(0., 0., -1.),
+                Projection::Orthographics,
+                extent,
+            ),
+            _ => (
+                center - vec3(extent, 0., 0.),
+                vec3(0., 1., 0.),
+                Projection::Perspective,
+                45_f32.to_radians(),
+            ),
+        };
+        Camera3D {
+            position,
+            target: center,
+            up,
+            fovy,
+            projection,
+            aspect: Some(1.),
+            render_target: Some(self.target),
+            ..Default::default()
+        }
+    }
+
+    pub fn render(&self, world: &World, draw: impl Fn(&Camera3D)) {
+        if self.view != InsetView::Off {
+            draw(&self.camera(world));
+        }
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if self.view == InsetView::Off {
+            return;
+        }
+        let size = theme.scaled(INSET_SIZE as f32);
+        let x = screen_width() - size - MARGIN;
+        draw_texture_ex(
+            self.target.texture,
+            x,
+            MARGIN,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(size, size)),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        draw_rectangle_lines(x, MARGIN, size, size, theme.scaled(2.), theme.text);
+    }
+}
@@ -0,0 +1,137 @@
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::particle::{get_charge, Particle};
+use rutherford_atomic_model::units::{self, SPEED_OF_LIGHT};
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Only the strongest pulls are listed in the force table
+const LISTED: usize = 8;
+
+fn vector(v: Vec3) -> String {
+    format!("({:.3e}, {:.3e}, {:.3e})", v.x, v.y, v.z)
+}
+
+// Everything known about the selected particle in one table, with the forces
+// acting on it, strongest first, in a second.
+pub struct Inspector {
+    pub visible: bool,
+}
+
+impl Inspector {
+    pub fn new() -> Inspector {
+        Inspector { visible: false }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        world: &World,
+        selected: Option<Entity>,
+        theme: &Theme,
+    ) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(360.), theme.scaled(420.));
+        let default = Rect::new(theme.scaled(10.), theme.scaled(200.), width, height);
+        dock::egui_window("inspector.title", default)
+            .open(&mut self.visible)
+            .show(ctx, |ui| {
+                let particles = &world.particles;
+                let Some((entity, i)) =
+                    selected.and_then(|entity| Some((entity, particles.slot(entity)?)))
+                else {
+                    ui.label(tr("inspector.empty"));
+                    return;
+                };
+                let speed = particles.vel[i].length();
+                let group = world
+                    .membership
+                    .get(entity)
+                    .map_or(tr("inspector.ungrouped"), |id| {
+                        world.groups[id.0].name.as_str()
+                    });
+                let rows = [
+                    (
+                        tr("inspector.particle"),
+                        format!("{} #{}", particles.particle[i].name(), entity.index()),
+                    ),
+                    (tr("inspector.group"), group.to_string()),
+                    (tr("inspector.position"), vector(particles.pos[i])),
+                    (tr("inspector.velocity"), vector(particles.vel[i])),
+                    (
+                        tr("inspector.speed"),
+                        trf(
+                            "inspector.speed_value",
+                            &[
+                                &format!("{:.3e}", speed),
+                                &format!("{:.4}", speed / SPEED_OF_LIGHT),
+                            ],
+                        ),
+                    ),
+                    (tr("inspector.acceleration"), vector(particles.acc[i])),
+                    (tr("inspector.mass"), format!("{:.4e}", particles.mass[i])),
+                    (
+                        tr("inspector.charge"),
+                        format!(
+                            "{:+.2} e",
+                            particles.charge[i] / get_charge(Particle::Proton)
+                        ),
+                    ),
+                    (
+                        tr("inspector.kinetic"),
+                        format!(
+                            "{:.4} MeV",
+                            units::kinetic_mev(particles.mass[i], speed, false)
+                        ),
+                    ),
+                    (
+                        tr("inspector.frequency"),
+                        diagnostics::orbital_frequency(world, entity)
+                            .map_or("-".to_string(), |f| format!("{:.3e}", f)),
+                    ),
+                    (
+                        tr("inspector.angular"),
+                        diagnostics::angular_momentum_about_nucleus(world, entity)
+                            .map_or("-".to_string(), |l| format!("{:.3e}", l.length())),
+                    ),
+                ];
+                egui::Grid::new("inspector_properties")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (name, value) in rows {
+                            ui.label(name);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+
+                let mut forces = diagnostics::force_contributions(world, entity);
+                forces.sort_by(|a, b| b.1.length().total_cmp(&a.1.length()));
+                forces.truncate(LISTED);
+                ui.separator();
+                ui.heading(tr("inspector.forces"));
+                egui::Grid::new("inspector_forces")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (other, force) in forces {
+                            let species = particles
+                                .slot(other)
+                                .map_or("?".to_string(), |slot| particles.particle[slot].name());
+                            ui.label(format!("{} #{}", species, other.index()));
+                            ui.label(format!("{:.3e}", force.length()));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
@@ -0,0 +1,57 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::world::World;
+
+// Just enough JSON writing for the HTTP API and telemetry stream, which only
+// ever send flat objects of numbers and strings.
+
+pub fn string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// JSON has no NaN or infinity, so those become null.
+pub fn number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+// For charges, masses and energies, which are tiny in SI units
+pub fn scientific(value: f32) -> String {
+    if value.is_finite() {
+        format!("{:e}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+pub fn vector(v: Vec3, format: fn(f32) -> String) -> String {
+    format!("[{},{},{}]", format(v.x), format(v.y), format(v.z))
+}
+
+pub fn particle(world: &World, slot: usize) -> String {
+    let particles = &world.particles;
+    format!(
+        "{{\"id\":{},\"species\":{},\"charge\":{},\"mass\":{},\"pos\":{},\"vel\":{}}}",
+        particles.entities()[slot].index(),
+        string(&particles.particle[slot].name()),
+        scientific(particles.charge[slot]),
+        scientific(particles.mass[slot]),
+        vector(particles.pos[slot], number),
+        vector(particles.vel[slot], number)
+    )
+}
@@ -0,0 +1,366 @@
+use macroquad::prelude::*;
+
+// Everything the keyboard can do. The main loop asks for actions rather than
+// keys, so `BINDINGS` is the one place a shortcut is defined and the help
+// overlay can list them all.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    ToggleMouse,
+    FocusSelection,
+    FrameAll,
+    InsetView,
+    EncounterZoom,
+    StereoMode,
+    StereoNarrower,
+    StereoWider,
+    SpawnElectron,
+    SpawnProton,
+    SpawnNeutron,
+    SpawnPanel,
+    ClearParticles,
+    Presets,
+    Foils,
+    Ghosts,
+    Bubbles,
+    Replicate,
+    Placement,
+    Sources,
+    Magnets,
+    Millikan,
+    Duplicate,
+    DuplicateGroup,
+    Groups,
+    Pause,
+    PhysicsThread,
+    PhysicsThreadUnthrottled,
+    Compare,
+    Chaos,
+    Ensemble,
+    Undo,
+    Redo,
+    Console,
+    Challenge,
+    NextChallenge,
+    Presentation,
+    NextSlide,
+    PreviousSlide,
+    ClampProblem,
+    RemoveProblem,
+    RewindProblem,
+    Measure,
+    Detector,
+    Interactions,
+    Forces,
+    Momentum,
+    Recoil,
+    Energy,
+    Virial,
+    Histograms,
+    ExportHistograms,
+    Report,
+    PhasePlot,
+    OrbitMeter,
+    Sweep,
+    Annotations,
+    ClipPlane,
+    Inspector,
+    Labels,
+    Exposure,
+    SaveExposure,
+    SaveScene,
+    LoadScene,
+    Checkpoints,
+    Bookmarks,
+    Minimap,
+    Skybox,
+    DisplayPanel,
+    Workspace,
+    Visibility,
+    FrameStats,
+    Profiler,
+    Log,
+    ClearLog,
+    Help,
+    Tutorial,
+    ContinueTutorial,
+    SkipTutorial,
+    Language,
+    Theme,
+    UiScaleUp,
+    UiScaleDown,
+    Fullscreen,
+    Planar,
+    Mute,
+    VolumeDown,
+    VolumeUp,
+    Sonify,
+    SonifyMode,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Category {
+    Camera,
+    Spawning,
+    Time,
+    Tools,
+    View,
+    App,
+}
+
+pub const CATEGORIES: [Category; 6] = [
+    Category::Camera,
+    Category::Spawning,
+    Category::Time,
+    Category::Tools,
+    Category::View,
+    Category::App,
+];
+
+impl Category {
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            Category::Camera => "keys.category.camera",
+            Category::Spawning => "keys.category.spawning",
+            Category::Time => "keys.category.time",
+            Category::Tools => "keys.category.tools",
+            Category::View => "keys.category.view",
+            Category::App => "keys.category.app",
+        }
+    }
+}
+
+// Which modifiers must be held. `Any` ignores them, `None` refuses them so
+// that a plain key and its Shift or Ctrl variant never both fire.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Modifier {
+    Any,
+    None,
+    Shift,
+    Ctrl,
+    CtrlShift,
+}
+
+pub struct Binding {
+    pub action: Action,
+    pub key: KeyCode,
+    pub modifier: Modifier,
+    pub category: Category,
+    pub help_key: &'static str,
+}
+
+const fn bind(
+    action: Action,
+    key: KeyCode,
+    modifier: Modifier,
+    category: Category,
+    help_key: &'static str,
+) -> Binding {
+    Binding {
+        action,
+        key,
+        modifier,
+        category,
+        help_key,
+    }
+}
+
+// Several keys may share an action; they are listed together in the help.
+#[rustfmt::skip]
+pub const BINDINGS: &[Binding] = {
+    use Action as A;
+    use Category as C;
+    use KeyCode as K;
+    use Modifier as M;
+    &[
+        bind(A::MoveForward, K::W, M::None, C::Camera, "keys.move_forward"),
+        bind(A::MoveBack, K::S, M::None, C::Camera, "keys.move_back"),
+        bind(A::MoveLeft, K::A, M::None, C::Camera, "keys.move_left"),
+        bind(A::MoveRight, K::D, M::None, C::Camera, "keys.move_right"),
+        bind(A::MoveUp, K::Space, M::None, C::Camera, "keys.move_up"),
+        bind(A::MoveDown, K::Z, M::None, C::Camera, "keys.move_down"),
+        bind(A::ToggleMouse, K::Tab, M::Any, C::Camera, "keys.toggle_mouse"),
+        bind(A::FocusSelection, K::F, M::Any, C::Camera, "keys.focus"),
+        bind(A::FrameAll, K::Home, M::Any, C::Camera, "keys.frame_all"),
+        bind(A::InsetView, K::P, M::None, C::Camera, "keys.inset"),
+        bind(A::EncounterZoom, K::P, M::Shift, C::Camera, "keys.encounter_zoom"),
+        bind(A::StereoMode, K::V, M::Any, C::Camera, "keys.stereo"),
+        bind(A::StereoNarrower, K::LeftBracket, M::Any, C::Camera, "keys.stereo_narrower"),
+        bind(A::StereoWider, K::RightBracket, M::Any, C::Camera, "keys.stereo_wider"),
+        bind(A::SpawnElectron, K::Key1, M::Any, C::Spawning, "keys.spawn_electron"),
+        bind(A::SpawnProton, K::Key2, M::Any, C::Spawning, "keys.spawn_proton"),
+        bind(A::SpawnNeutron, K::Key3, M::Any, C::Spawning, "keys.spawn_neutron"),
+        bind(A::SpawnPanel, K::Slash, M::Any, C::Spawning, "keys.spawn_panel"),
+        bind(A::ClearParticles, K::Delete, M::Any, C::Spawning, "keys.clear"),
+        bind(A::Presets, K::Backslash, M::Any, C::Spawning, "keys.presets"),
+        bind(A::Foils, K::Apostrophe, M::Any, C::Spawning, "keys.foils"),
+        bind(A::Ghosts, K::Key4, M::Any, C::Spawning, "keys.ghosts"),
+        bind(A::Bubbles, K::Key5, M::Any, C::Spawning, "keys.bubbles"),
+        bind(A::Replicate, K::Key6, M::Any, C::Spawning, "keys.replicate"),
+        bind(A::Placement, K::Key7, M::Any, C::Spawning, "keys.placement"),
+        bind(A::Sources, K::Key8, M::Any, C::Spawning, "keys.sources"),
+        bind(A::Magnets, K::Key9, M::Any, C::Spawning, "keys.magnets"),
+        bind(A::Millikan, K::Key0, M::Any, C::Spawning, "keys.millikan"),
+        bind(A::Duplicate, K::D, M::Ctrl, C::Spawning, "keys.duplicate"),
+        bind(A::DuplicateGroup, K::D, M::CtrlShift, C::Spawning, "keys.duplicate_group"),
+        bind(A::Groups, K::G, M::Any, C::Spawning, "keys.groups"),
+        bind(A::Pause, K::End, M::Any, C::Time, "keys.pause"),
+        bind(A::Pause, K::Pause, M::Any, C::Time, "keys.pause"),
+        bind(A::PhysicsThread, K::Insert, M::None, C::Time, "keys.physics_thread"),
+        bind(A::PhysicsThreadUnthrottled, K::Insert, M::Shift, C::Time, "keys.physics_unthrottled"),
+        bind(A::Compare, K::K, M::None, C::Time, "keys.compare"),
+        bind(A::Chaos, K::K, M::Shift, C::Time, "keys.chaos"),
+        bind(A::Ensemble, K::K, M::Ctrl, C::Time, "keys.ensemble"),
+        bind(A::Undo, K::Z, M::Ctrl, C::Time, "keys.undo"),
+        bind(A::Redo, K::Z, M::CtrlShift, C::Time, "keys.redo"),
+        bind(A::Console, K::GraveAccent, M::Any, C::Time, "keys.console"),
+        bind(A::Challenge, K::F6, M::Any, C::Time, "keys.challenge"),
+        bind(A::NextChallenge, K::Enter, M::Any, C::Time, "keys.next_challenge"),
+        bind(A::Presentation, K::F5, M::Any, C::Time, "keys.presentation"),
+        bind(A::NextSlide, K::Right, M::Any, C::Time, "keys.next_slide"),
+        bind(A::NextSlide, K::PageDown, M::Any, C::Time, "keys.next_slide"),
+        bind(A::PreviousSlide, K::Left, M::Any, C::Time, "keys.previous_slide"),
+        bind(A::PreviousSlide, K::PageUp, M::Any, C::Time, "keys.previous_slide"),
+        bind(A::ClampProblem, K::C, M::Any, C::Time, "keys.clamp"),
+        bind(A::RemoveProblem, K::X, M::Any, C::Time, "keys.remove"),
+        bind(A::RewindProblem, K::B, M::Any, C::Time, "keys.rewind"),
+        bind(A::Measure, K::R, M::Any, C::Tools, "keys.measure"),
+        bind(A::Detector, K::Semicolon, M::Any, C::Tools, "keys.detector"),
+        bind(A::Interactions, K::Comma, M::Any, C::Tools, "keys.interactions"),
+        bind(A::Forces, K::Period, M::Any, C::Tools, "keys.forces"),
+        bind(A::Momentum, K::Y, M::None, C::Tools, "keys.momentum"),
+        bind(A::Recoil, K::Y, M::Shift, C::Tools, "keys.recoil"),
+        bind(A::Energy, K::E, M::None, C::Tools, "keys.energy"),
+        bind(A::Virial, K::E, M::Shift, C::Tools, "keys.virial"),
+        bind(A::Histograms, K::F9, M::None, C::Tools, "keys.histograms"),
+        bind(A::ExportHistograms, K::F9, M::Shift, C::Tools, "keys.export_histograms"),
+        bind(A::Report, K::F9, M::Ctrl, C::Tools, "keys.report"),
+        bind(A::PhasePlot, K::F7, M::None, C::Tools, "keys.phase_plot"),
+        bind(A::OrbitMeter, K::F7, M::Shift, C::Tools, "keys.orbit_meter"),
+        bind(A::Sweep, K::J, M::Any, C::Tools, "keys.sweep"),
+        bind(A::Annotations, K::U, M::Any, C::Tools, "keys.annotations"),
+        bind(A::ClipPlane, K::I, M::None, C::Tools, "keys.clip"),
+        bind(A::Inspector, K::I, M::Shift, C::Tools, "keys.inspector"),
+        bind(A::Labels, K::I, M::Ctrl, C::View, "keys.labels"),
+        bind(A::Exposure, K::F12, M::None, C::Tools, "keys.exposure"),
+        bind(A::SaveExposure, K::F12, M::Shift, C::Tools, "keys.save_exposure"),
+        bind(A::SaveScene, K::S, M::Ctrl, C::Tools, "keys.save_scene"),
+        bind(A::LoadScene, K::O, M::Ctrl, C::Tools, "keys.load_scene"),
+        bind(A::Checkpoints, K::O, M::CtrlShift, C::Tools, "keys.checkpoints"),
+        bind(A::Bookmarks, K::E, M::Ctrl, C::Tools, "keys.bookmarks"),
+        bind(A::Minimap, K::N, M::Any, C::View, "keys.minimap"),
+        bind(A::Skybox, K::F8, M::Any, C::View, "keys.skybox"),
+        bind(A::DisplayPanel, K::F10, M::None, C::View, "keys.display"),
+        bind(A::Workspace, K::F10, M::Shift, C::View, "keys.workspace"),
+        bind(A::Visibility, K::F10, M::Ctrl, C::View, "keys.species"),
+        bind(A::FrameStats, K::F2, M::Any, C::View, "keys.frame_stats"),
+        bind(A::Profiler, K::F3, M::Any, C::View, "keys.profiler"),
+        bind(A::Log, K::F4, M::None, C::View, "keys.log"),
+        bind(A::ClearLog, K::F4, M::Shift, C::View, "keys.clear_log"),
+        bind(A::Theme, K::T, M::Any, C::View, "keys.theme"),
+        bind(A::UiScaleUp, K::Equal, M::Ctrl, C::View, "keys.ui_scale_up"),
+        bind(A::UiScaleDown, K::Minus, M::Ctrl, C::View, "keys.ui_scale_down"),
+        bind(A::Fullscreen, K::F11, M::Any, C::View, "keys.fullscreen"),
+        bind(A::Planar, K::P, M::Ctrl, C::View, "keys.planar"),
+        bind(A::Help, K::F1, M::Any, C::App, "keys.help"),
+        bind(A::Tutorial, K::H, M::Any, C::App, "keys.tutorial"),
+        bind(A::ContinueTutorial, K::Enter, M::Any, C::App, "keys.continue_tutorial"),
+        bind(A::SkipTutorial, K::Backspace, M::Any, C::App, "keys.skip_tutorial"),
+        bind(A::Language, K::L, M::Any, C::App, "keys.language"),
+        bind(A::Mute, K::M, M::Any, C::App, "keys.mute"),
+        bind(A::VolumeDown, K::Minus, M::None, C::App, "keys.volume_down"),
+        bind(A::VolumeUp, K::Equal, M::None, C::App, "keys.volume_up"),
+        bind(A::Sonify, K::O, M::None, C::App, "keys.sonify"),
+        bind(A::SonifyMode, K::O, M::Shift, C::App, "keys.sonify_mode"),
+        bind(A::Quit, K::Q, M::Any, C::App, "keys.quit"),
+        bind(A::Quit, K::Escape, M::Any, C::App, "keys.quit"),
+    ]
+};
+
+// Mouse controls have no binding to look up, but belong in the help too.
+pub const MOUSE: &[(Category, &str, &str)] = &[
+    (Category::Camera, "keys.mouse.move", "keys.mouse.look"),
+    (Category::Tools, "keys.mouse.left", "keys.mouse.select"),
+    (Category::Tools, "keys.mouse.drag", "keys.mouse.annotate"),
+    (Category::Tools, "keys.mouse.drag", "keys.mouse.place"),
+];
+
+// Keyboard state for one frame. While `blocked`, for instance when the
+// console is taking text, nothing is reported.
+pub struct Keys {
+    blocked: bool,
+    shift: bool,
+    ctrl: bool,
+}
+
+impl Keys {
+    pub fn new(blocked: bool) -> Keys {
+        Keys {
+            blocked,
+            shift: is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+            ctrl: is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl),
+        }
+    }
+
+    fn matches(&self, binding: &Binding) -> bool {
+        match binding.modifier {
+            Modifier::Any => true,
+            Modifier::None => !self.shift && !self.ctrl,
+            Modifier::Shift => self.shift && !self.ctrl,
+            Modifier::Ctrl => self.ctrl && !self.shift,
+            Modifier::CtrlShift => self.ctrl && self.shift,
+        }
+    }
+
+    fn any(&self, action: Action, key_state: fn(KeyCode) -> bool) -> bool {
+        !self.blocked
+            && BINDINGS
+                .iter()
+                .any(|b| b.action == action && self.matches(b) && key_state(b.key))
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.any(action, is_key_pressed)
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        self.any(action, is_key_down)
+    }
+}
+
+pub fn key_name(key: KeyCode) -> String {
+    let name = match key {
+        KeyCode::GraveAccent => "`",
+        KeyCode::Minus => "-",
+        KeyCode::Equal => "=",
+        KeyCode::LeftBracket => "[",
+        KeyCode::RightBracket => "]",
+        KeyCode::Semicolon => ";",
+        KeyCode::Apostrophe => "'",
+        KeyCode::Comma => ",",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::LeftControl => "Ctrl",
+        _ => {
+            let name = format!("{:?}", key);
+            return name.strip_prefix("Key").unwrap_or(&name).to_string();
+        }
+    };
+    name.to_string()
+}
+
+pub fn chord(binding: &Binding) -> String {
+    let prefix = match binding.modifier {
+        Modifier::Any | Modifier::None => "",
+        Modifier::Shift => "Shift+",
+        Modifier::Ctrl => "Ctrl+",
+        Modifier::CtrlShift => "Ctrl+Shift+",
+    };
+    format!("{}{}", prefix, key_name(binding.key))
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::world::World;
+
+use crate::selection;
+use crate::theme::Theme;
+use crate::visibility::SpeciesVisibility;
+
+// Particles smaller than this many pixels across get no label
+const MIN_PIXELS: f32 = 3.;
+// More than this and the overlay is noise however it is laid out
+const MAX_LABELS: usize = 150;
+// Screen cells particles are bucketed into for the occlusion test
+const CELL: f32 = 32.;
+
+// Labels placed so far this frame. Each one is tried at a few spots around
+// its anchor and put at the first that overlaps nothing already placed; when
+// none is free it is dropped, so earlier, more important labels win.
+pub struct Layout {
+    placed: Vec<Rect>,
+}
+
+impl Layout {
+    pub fn new() -> Layout {
+        Layout { placed: Vec::new() }
+    }
+
+    // Top left of a free spot for a `size` label next to a disc of `radius`
+    // around `anchor`, or `None` when there is none.
+    pub fn place(&mut self, anchor: Vec2, radius: f32, size: Vec2) -> Option<Vec2> {
+        let gap = radius + 4.;
+        let spots = [
+            vec2(gap, -size.y / 2.),
+            vec2(-gap - size.x, -size.y / 2.),
+            vec2(-size.x / 2., -gap - size.y),
+            vec2(-size.x / 2., gap),
+        ];
+        let screen = Rect::new(0., 0., screen_width(), screen_height());
+        let spot = spots
+            .into_iter()
+            .map(|offset| Rect::new(anchor.x + offset.x, anchor.y + offset.y, size.x, size.y))
+            .find(|rect| {
+                screen.contains(rect.point())
+                    && screen.contains(rect.point() + rect.size())
+                    && !self.placed.iter().any(|other| other.overlaps(rect))
+            })?;
+        self.placed.push(spot);
+        Some(spot.point())
+    }
+}
+
+// Draws `text` through the layout, beside the disc around `anchor`.
+pub fn draw(layout: &mut Layout, text: &str, anchor: Vec2, radius: f32, size: f32, color: Color) {
+    let measured = measure_text(text, None, size as u16, 1.);
+    let dimensions = vec2(measured.width, size);
+    if let Some(at) = layout.place(anchor, radius, dimensions) {
+        draw_text(text, at.x, at.y + measured.offset_y, size, color);
+    }
+}
+
+// Species and index next to each particle big enough to see and not hidden
+// behind a nearer one, the selection and the particle under the cursor first
+// and then the largest on screen, so in a dense scene the overlay thins out
+// rather than piling up.
+pub struct ParticleLabels {
+    pub visible: bool,
+}
+
+impl ParticleLabels {
+    pub fn new() -> ParticleLabels {
+        ParticleLabels { visible: false }
+    }
+
+    pub fn draw(
+        &self,
+        world: &World,
+        camera: &Camera3D,
+        priority: [Option<Entity>; 2],
+        visibility: &SpeciesVisibility,
+        layout: &mut Layout,
+        theme: &Theme,
+    ) {
+        if !self.visible {
+            return;
+        }
+        let particles = &world.particles;
+        let screen = Rect::new(0., 0., screen_width(), screen_height());
+        // On screen and big enough, nearest first for the occlusion test
+        let mut visible: Vec<(Entity, Vec2, f32, f32)> = particles
+            .entities()
+            .iter()
+            .zip(&particles.pos)
+            .zip(&particles.particle)
+            .filter(|(_, particle)| visibility.shows(**particle))
+            .filter_map(|((entity, pos), _)| {
+                let (center, radius) = selection::screen_circle(world, camera, *entity)?;
+                let depth = pos.distance_squared(camera.position);
+                let shown = radius * 2. >= MIN_PIXELS && screen.contains(center);
+                shown.then_some((*entity, center, radius, depth))
+            })
+            .collect();
+        visible.sort_by(|a, b| a.3.total_cmp(&b.3));
+
+        // A particle whose centre is under a nearer one's disc is hidden
+        let mut cells: HashMap<(i32, i32), Vec<(Vec2, f32)>> = HashMap::new();
+        // Clamped to the screen, so a particle right by the camera still
+        // only fills the cells there are
+        let cell = |p: Vec2| {
+            let p = p.clamp(Vec2::ZERO, screen.size());
+            ((p.x / CELL).floor() as i32, (p.y / CELL).floor() as i32)
+        };
+        let mut unoccluded = Vec::new();
+        for (entity, center, radius, _) in visible {
+            let hidden = cells
+                .get(&cell(center))
+                .is_some_and(|discs| discs.iter().any(|(c, r)| c.distance(center) < *r));
+            let (low, high) = (cell(center - radius), cell(center + radius));
+            for x in low.0..=high.0 {
+                for y in low.1..=high.1 {
+                    cells.entry((x, y)).or_default().push((center, radius));
+                }
+            }
+            if !hidden {
+                unoccluded.push((entity, center, radius));
+            }
+        }
+        unoccluded.sort_by(|a, b| {
+            let rank = |entity: &Entity| !priority.contains(&Some(*entity));
+            rank(&a.0).cmp(&rank(&b.0)).then(b.2.total_cmp(&a.2))
+        });
+
+        let size = theme.text_size(16.);
+        for (entity, center, radius) in unoccluded.into_iter().take(MAX_LABELS) {
+            let Some(slot) = particles.slot(entity) else {
+                continue;
+            };
+            let particle = particles.particle[slot];
+            let text = format!("{} #{}", particle.name(), entity.index());
+            draw(
+                layout,
+                &text,
+                center,
+                radius,
+                size,
+                theme.particle(particle),
+            );
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::fs;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::scenario::{ParseError, Scenario};
+
+use crate::i18n::trf;
+use crate::selection;
+use crate::theme::Theme;
+
+const BUILTIN_LESSON: &str = include_str!("../lessons/rutherford.txt");
+
+// Camera placement read from a slide: position, yaw and pitch.
+pub type CameraPose = (Vec3, f32, f32);
+
+#[derive(Clone, Default, Debug)]
+pub struct Slide {
+    pub title: String,
+    pub text: Vec<String>,
+    pub camera: Option<CameraPose>,
+    // World-space arrows drawn over the scene
+    pub arrows: Vec<(Vec3, Vec3)>,
+    pub scenario: Scenario,
+}
+
+// A lesson file is a list of `[slide]` sections. Besides `title`, `text` (one
+// per line of text), `camera = x y z yaw pitch` and `arrow = x y z x y z`, a
+// slide accepts any scenario line and loads the resulting scene.
+#[derive(Clone, Default, Debug)]
+pub struct Lesson {
+    pub slides: Vec<Slide>,
+}
+
+impl Lesson {
+    pub fn builtin() -> Lesson {
+        Lesson::parse(BUILTIN_LESSON).expect("built-in lesson is valid")
+    }
+
+    pub fn load(path: &str) -> Lesson {
+        let parsed = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| Lesson::parse(&source).map_err(|err| err.to_string()));
+        match parsed {
+            Ok(lesson) => lesson,
+            Err(err) => {
+                log::warn!("could not load lesson {}: {}", path, err);
+                Lesson::builtin()
+            }
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Lesson, ParseError> {
+        let mut lesson = Lesson::default();
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| ParseError {
+                line: number + 1,
+                message: message.to_string(),
+            };
+            if line == "[slide]" {
+                lesson.slides.push(Slide::default());
+                continue;
+            }
+            let Some(slide) = lesson.slides.last_mut() else {
+                return Err(error("expected '[slide]' first"));
+            };
+            let (key, value) = line.split_once('=').unwrap_or((line, ""));
+            let value = value.trim();
+            match key.trim() {
+                "title" => slide.title = value.to_string(),
+                "text" => slide.text.push(value.to_string()),
+                "camera" => match numbers(value).as_deref() {
+                    Some([x, y, z, yaw, pitch]) => {
+                        slide.camera = Some((vec3(*x, *y, *z), *yaw, *pitch))
+                    }
+                    _ => return Err(error("expected 'camera = x y z yaw pitch'")),
+                },
+                "arrow" => match numbers(value).as_deref() {
+                    Some([x1, y1, z1, x2, y2, z2]) => slide
+                        .arrows
+                        .push((vec3(*x1, *y1, *z1), vec3(*x2, *y2, *z2))),
+                    _ => return Err(error("expected 'arrow = x y z x y z'")),
+                },
+                _ => {
+                    let scene = Scenario::parse(line).map_err(|err| ParseError {
+                        line: number + 1,
+                        ..err
+                    })?;
+                    if !scene.name.is_empty() {
+                        slide.scenario.name = scene.name;
+                    }
+                    slide.scenario.bodies.extend(scene.bodies);
+                    slide.scenario.foils.extend(scene.foils);
+                }
+            }
+        }
+        Ok(lesson)
+    }
+}
+
+fn numbers(value: &str) -> Option<Vec<f32>> {
+    value.split_whitespace().map(|n| n.parse().ok()).collect()
+}
+
+// Runs a lesson one slide at a time on top of the normal app.
+pub struct Presentation {
+    lesson: Lesson,
+    current: Option<usize>,
+}
+
+impl Presentation {
+    pub fn new(lesson: Lesson) -> Presentation {
+        Presentation {
+            lesson,
+            current: None,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.current.is_some()
+    }
+
+    // Starts from the first slide, or stops when already running.
+    pub fn toggle(&mut self) -> Option<&Slide> {
+        self.current = match self.current {
+            Some(_) => None,
+            None => (!self.lesson.slides.is_empty()).then_some(0),
+        };
+        self.slide()
+    }
+
+    pub fn advance(&mut self, forward: bool) -> Option<&Slide> {
+        let current = self.current?;
+        let next = if forward {
+            (current + 1).min(self.lesson.slides.len() - 1)
+        } else {
+            current.saturating_sub(1)
+        };
+        if next == current {
+            return None;
+        }
+        self.current = Some(next);
+        self.slide()
+    }
+
+    fn slide(&self) -> Option<&Slide> {
+        self.lesson.slides.get(self.current?)
+    }
+
+    pub fn draw(&self, camera: &Camera3D, theme: &Theme) {
+        let (Some(current), Some(slide)) = (self.current, self.slide()) else {
+            return;
+        };
+
+        for (from, to) in &slide.arrows {
+            let (Some(a), Some(b)) = (
+                selection::world_to_screen(camera, *from),
+                selection::world_to_screen(camera, *to),
+            ) else {
+                continue;
+            };
+            let thickness = theme.scaled(3.);
+            draw_line(a.x, a.y, b.x, b.y, thickness, theme.warning);
+            let back = (a - b).normalize_or_zero() * theme.scaled(14.);
+            let side = vec2(-back.y, back.x) * 0.5;
+            draw_triangle(b, b + back + side, b + back - side, theme.warning);
+        }
+
+        let line_height = theme.scaled(26.);
+        let width = theme.scaled(760.);
+        let height = line_height * (slide.text.len() as f32 + 2.8);
+        let x = (screen_width() - width) / 2.;
+        let y = theme.scaled(10.);
+        draw_rectangle(x, y, width, height, theme.panel);
+
+        let left = x + theme.scaled(14.);
+        draw_text(
+            &slide.title,
+            left,
+            y + line_height * 1.2,
+            theme.text_size(34.),
+            theme.text,
+        );
+        for (i, text) in slide.text.iter().enumerate() {
+            draw_text(
+                text,
+                left,
+                y + line_height * (i as f32 + 2.2),
+                theme.text_size(22.),
+                theme.text,
+            );
+        }
+        let progress = trf(
+            "lesson.progress",
+            &[&(current + 1), &self.lesson.slides.len()],
+        );
+        draw_text(
+            &progress,
+            x + width - theme.scaled(260.),
+            y + height - theme.scaled(8.),
+            theme.text_size(18.),
+            theme.warning,
+        );
+    }
+}
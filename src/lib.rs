@@ -0,0 +1,31 @@
+pub mod annihilation;
+pub mod bubble;
+pub mod detector;
+pub mod diagnostics;
+pub mod ecs;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod foil;
+pub mod ghost;
+pub mod group;
+pub mod histogram;
+pub mod history;
+pub mod lz;
+pub mod magnets;
+pub mod medium;
+pub mod particle;
+pub mod portable;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rng;
+pub mod sanity;
+pub mod scenario;
+pub mod snapshot;
+pub mod sources;
+pub mod species;
+pub mod statistics;
+pub mod systems;
+pub mod units;
+pub mod websocket;
+pub mod world;
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use macroquad::prelude::*;
+
+use crate::theme::Theme;
+
+const PANEL_LINES: usize = 12;
+
+static LOGGER: Logger = Logger {
+    panel: Mutex::new(VecDeque::new()),
+};
+
+// Writes everything that passes the level filter to stderr and keeps the most
+// recent warnings and errors around for the on-screen panel.
+struct Logger {
+    panel: Mutex<VecDeque<(Level, String)>>,
+}
+
+impl Logger {
+    // A panic elsewhere must not take the log down with it
+    fn panel(&self) -> MutexGuard<'_, VecDeque<(Level, String)>> {
+        self.panel.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+
+        if record.level() <= Level::Warn {
+            let mut panel = self.panel();
+            if panel.len() == PANEL_LINES {
+                panel.pop_front();
+            }
+            panel.push_back((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init(verbose: bool) {
+    let level = if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    };
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+pub struct LogPanel {
+    pub visible: bool,
+}
+
+impl LogPanel {
+    pub fn new() -> LogPanel {
+        LogPanel { visible: true }
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let panel = LOGGER.panel();
+        let font_size = theme.text_size(20.);
+        let mut y = screen_height() - 10. - panel.len() as f32 * font_size;
+        for (level, message) in panel.iter() {
+            let color = match level {
+                Level::Error => theme.error,
+                _ => theme.warning,
+            };
+            draw_text(
+                format!("{}: {}", level, message).as_str(),
+                10.,
+                y,
+                font_size,
+                color,
+            );
+            y += font_size;
+        }
+    }
+
+    pub fn clear(&self) {
+        LOGGER.panel().clear();
+    }
+}
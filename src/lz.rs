@@ -0,0 +1,107 @@
+// The small LZ77 compressor checkpoints are written with. Scenes are text
+// with long runs of similar numbers, which suit it well.
+
+// Compressed stream: magic, uncompressed length (u32 LE), then groups of up
+// to eight items behind a flag byte whose bit k marks item k as a match. A
+// literal is one byte; a match is a u16 LE offset back into the output and a
+// length byte, plus `MIN_MATCH`.
+const MAGIC: &[u8; 4] = b"RCK1";
+// Input that would only grow is kept as it is, behind this magic instead
+const STORED: &[u8; 4] = b"RCK0";
+const MIN_MATCH: usize = 4;
+const MAX_MATCH: usize = MIN_MATCH + u8::MAX as usize;
+const WINDOW: usize = 1 << 16;
+const HASH_BITS: u32 = 14;
+// A stream claiming to be larger than this is taken to be corrupt
+const MAX_SIZE: usize = 256 << 20;
+
+fn hash(bytes: &[u8]) -> usize {
+    let word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    (word.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+// LZ77 with a single candidate per hash, which is plenty for the repetitive
+// number columns of a scene.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    let mut table = vec![usize::MAX; 1 << HASH_BITS];
+    let mut i = 0;
+    while i < data.len() {
+        let flags = out.len();
+        out.push(0);
+        for bit in 0..8 {
+            if i >= data.len() {
+                break;
+            }
+            let mut length = 0;
+            let mut offset = 0;
+            if i + MIN_MATCH <= data.len() {
+                let slot = hash(&data[i..]);
+                let candidate = table[slot];
+                table[slot] = i;
+                if candidate != usize::MAX && i - candidate < WINDOW {
+                    length = data[candidate..]
+                        .iter()
+                        .zip(&data[i..])
+                        .take(MAX_MATCH)
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    offset = i - candidate;
+                }
+            }
+            if length >= MIN_MATCH {
+                out[flags] |= 1 << bit;
+                out.extend_from_slice(&(offset as u16).to_le_bytes());
+                out.push((length - MIN_MATCH) as u8);
+                i += length;
+            } else {
+                out.push(data[i]);
+                i += 1;
+            }
+        }
+    }
+    if out.len() > STORED.len() + data.len() {
+        let mut stored = STORED.to_vec();
+        stored.extend_from_slice(data);
+        return stored;
+    }
+    out
+}
+
+// None for anything `compress` could not have written.
+pub fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    if let Some(rest) = data.strip_prefix(STORED) {
+        return Some(rest.to_vec());
+    }
+    let rest = data.strip_prefix(MAGIC)?;
+    let length = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+    if length > MAX_SIZE {
+        return None;
+    }
+    let mut input = rest[4..].iter().copied();
+    let mut out = Vec::with_capacity(length);
+    while out.len() < length {
+        let flags = input.next()?;
+        for bit in 0..8 {
+            if out.len() >= length {
+                break;
+            }
+            if flags & (1 << bit) == 0 {
+                out.push(input.next()?);
+                continue;
+            }
+            let offset = u16::from_le_bytes([input.next()?, input.next()?]) as usize;
+            let count = input.next()? as usize + MIN_MATCH;
+            if offset == 0 || offset > out.len() || out.len() + count > length {
+                return None;
+            }
+            // Byte by byte, as a match may overlap what it produces
+            let start = out.len() - offset;
+            for k in 0..count {
+                out.push(out[start + k]);
+            }
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,155 @@
+use macroquad::prelude::*;
+
+use crate::portable;
+use crate::units::SECONDS_PER_TICK;
+use crate::world::World;
+
+// Fixed magnetic field sources, with fields in tesla. Magnetic forces do no
+// work, so instead of adding to the acceleration, which would let a simple
+// integrator pump energy into a gyrating particle, the field turns each
+// velocity by the angle it sweeps in one tick.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Magnet {
+    // Bar magnet: a point dipole along unit `axis`, with `field` on the axis
+    // at `radius` from the center. Inside that radius the field is taken as
+    // uniform at the same strength.
+    Dipole {
+        center: Vec3,
+        axis: Vec3,
+        radius: f32,
+        field: f32,
+    },
+    // Finite solenoid along unit `axis` with `field` at its center. Inside the
+    // bore the field follows the paraxial expansion of the exact on-axis
+    // field, which brings in the narrowing lines at the ends that make a
+    // magnetic mirror; outside it is zero, as for an ideal solenoid.
+    Solenoid {
+        center: Vec3,
+        axis: Vec3,
+        radius: f32,
+        length: f32,
+        field: f32,
+    },
+}
+
+impl Magnet {
+    pub fn field(&self, pos: Vec3) -> Vec3 {
+        match *self {
+            Magnet::Dipole {
+                center,
+                axis,
+                radius,
+                field,
+            } => {
+                let r = pos - center;
+                let distance = r.length();
+                if distance <= radius {
+                    return axis * field;
+                }
+                let unit = r / distance;
+                let ratio = radius / distance;
+                let scale = field * ratio * ratio * ratio / 2.;
+                (unit * (3. * axis.dot(unit)) - axis) * scale
+            }
+            Magnet::Solenoid {
+                center,
+                axis,
+                radius,
+                length,
+                field,
+            } => {
+                let r = pos - center;
+                let z = r.dot(axis);
+                let across = r - axis * z;
+                let rho = across.length();
+                if rho > radius {
+                    return Vec3::ZERO;
+                }
+                // B(z) ∝ cos θ₁ - cos θ₂ over the two ends, scaled to `field`
+                // at the center
+                let shape = |z: f32| {
+                    let (a, b) = (z + length / 2., z - length / 2.);
+                    a / (radius * radius + a * a).sqrt() - b / (radius * radius + b * b).sqrt()
+                };
+                let scale = field / shape(0.);
+                // dB/dz, and the radial part -ρ/2 dB/dz that keeps B free of
+                // divergence
+                let slope = {
+                    let end = |a: f32| {
+                        let square = radius * radius + a * a;
+                        radius * radius / (square * square.sqrt())
+                    };
+                    scale * (end(z + length / 2.) - end(z - length / 2.))
+                };
+                axis * (scale * shape(z)) - across * (slope / 2.)
+            }
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        match *self {
+            Magnet::Dipole { center, .. } | Magnet::Solenoid { center, .. } => center,
+        }
+    }
+}
+
+pub fn field_at(magnets: &[Magnet], pos: Vec3) -> Vec3 {
+    magnets
+        .iter()
+        .fold(Vec3::ZERO, |sum, magnet| sum + magnet.field(pos))
+}
+
+// Angular velocity in radians per tick that a field turns a particle of
+// `charge` and `mass` with.
+pub fn gyration(field: Vec3, charge: f32, mass: f32) -> Vec3 {
+    // dv/dt = q/m v × B = (-q/m B) × v
+    -field * (charge / mass * SECONDS_PER_TICK)
+}
+
+// Turns every velocity about the field where its particle is.
+pub fn rotate(world: &mut World) {
+    if world.magnets.is_empty() {
+        return;
+    }
+    let particles = &mut world.particles;
+    for i in 0..particles.len() {
+        let field = field_at(&world.magnets, particles.pos[i]);
+        let omega = gyration(field, particles.charge[i], particles.mass[i]);
+        let angle = omega.length();
+        if angle > 0. {
+            // `Quat::from_axis_angle`, with the portable sine and cosine
+            let (sin, cos) = portable::sin_cos(angle / 2.);
+            let axis = omega / angle * sin;
+            particles.vel[i] = Quat::from_xyzw(axis.x, axis.y, axis.z, cos) * particles.vel[i];
+        }
+    }
+}
+
+// Points along the field line through `start`, followed in the direction of
+// the field for at most `steps` steps of `step` or until it fades out or
+// runs into `stop`.
+pub fn field_line(
+    magnets: &[Magnet],
+    start: Vec3,
+    step: f32,
+    steps: usize,
+    stop: impl Fn(Vec3) -> bool,
+) -> Vec<Vec3> {
+    let mut points = vec![start];
+    let mut pos = start;
+    for _ in 0..steps {
+        // Midpoint rule, so closed lines close
+        let Some(dir) = field_at(magnets, pos).try_normalize() else {
+            break;
+        };
+        let Some(mid) = field_at(magnets, pos + dir * (step / 2.)).try_normalize() else {
+            break;
+        };
+        pos += mid * step;
+        points.push(pos);
+        if stop(pos) {
+            break;
+        }
+    }
+    points
+}
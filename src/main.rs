@@ -1,129 +1,215 @@
-use glam::vec3;
-use macroquad::ui::{
-    hash, root_ui,
-    widgets::{self, Group},
-};
-use macroquad::{prelude::*, rand::gen_range};
+use std::panic::{self, AssertUnwindSafe};
 
-const MOVE_SPEED: f32 = 0.1;
-const LOOK_SPEED: f32 = 0.1;
-
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum Particle {
-    Electron,
-    Proton,
-    Neutron,
-}
+use macroquad::miniquad::conf::Platform;
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-struct Charge {
-    particle: Particle,
-    mass: f32,
-    pos: Vec3,
-    vel: Vec3,
-    acc: Vec3,
-    trail: [Vec3; 50],
-}
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::history::History;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::rng::Rng;
+use rutherford_atomic_model::sanity::SanityChecker;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::statistics::Statistics;
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::world::World;
 
-impl Charge {
-    fn new(particle: Particle, pos: Option<Vec3>) -> Charge {
-        let pos = match pos {
-            Some(vec) => vec,
-            None => gen_random_vector(-10., 10.),
-        };
-
-        Charge {
-            particle,
-            mass: get_mass(particle),
-            pos,
-            vel: vec3(0., 0., 0.),
-            acc: vec3(0., 0., 0.),
-            trail: [pos; 50],
-        }
-    }
+mod angular;
+mod annotate;
+mod api;
+mod audio;
+mod bookmarks;
+mod challenge;
+mod chaos;
+mod checkpoint;
+mod classroom;
+mod cli;
+mod clip;
+mod compare;
+mod console;
+mod crash;
+mod distributions;
+mod dock;
+mod energy;
+mod ensemble;
+mod environment;
+mod exposure;
+mod frustum;
+mod gui;
+mod help;
+mod i18n;
+mod inset;
+mod inspector;
+mod json;
+mod keys;
+mod labels;
+mod lesson;
+mod logging;
+mod measure;
+mod millikan;
+mod minimap;
+mod momentum;
+mod orbit;
+mod panels;
+mod phase;
+mod placement;
+mod presets;
+mod profiler;
+mod recoil;
+mod reload;
+mod render;
+mod replicate;
+mod report;
+mod scene;
+mod selection;
+mod settings;
+mod slowmo;
+mod spawn;
+mod spectrometer;
+mod stats;
+mod stereo;
+mod sweep;
+mod telemetry;
+mod theme;
+mod toast;
+mod translucent;
+mod tutorial;
+mod unit_field;
+mod virial;
+mod visibility;
+mod worker;
+mod zoom;
 
-    fn get_acc(&self, charge_vec: &Vec<Charge>) -> Vec3 {
-        let k: f32 = 9f32 * 10f32.powf(9.);
-
-        charge_vec
-            .iter()
-            .filter(|e| *e != self)
-            .map(|e| {
-                let unit_acc = |x: f32, y: f32| {
-                    if (y - x).abs() != 0. {
-                        k * get_charge(self.particle) * get_charge(e.particle)
-                            / ((y - x).abs() * self.mass)
-                    } else {
-                        0.
-                    }
-                };
-                // println!(
-                //     "acc: {:?}, vec: {:?}, pos: {:?}",
-                //     self.acc, self.vel, self.pos
-                // );
-
-                vec3(
-                    unit_acc(self.pos.x, e.pos.x),
-                    unit_acc(self.pos.y, e.pos.y),
-                    unit_acc(self.pos.z, e.pos.z),
-                )
-            })
-            .reduce(|e, acc| e + acc)
-            .unwrap()
-    }
-
-    fn update(&mut self, charge_vec: &Vec<Charge>) {
-        self.acc = self.get_acc(&charge_vec);
-        self.vel += self.acc;
-        self.pos += self.vel;
-    }
-}
-
-fn get_charge(particle: Particle) -> f32 {
-    let q: f32 = 1.6 * 10f32.powf(-19.);
-    match particle {
-        Particle::Electron => -q,
-        Particle::Proton => q,
-        Particle::Neutron => 0.,
-    }
-}
+use annotate::Annotations;
+use api::Api;
+use audio::{Audio, Sonifier, SonifyMode};
+use bookmarks::Bookmarks;
+use challenge::Quiz;
+use chaos::Chaos;
+use checkpoint::Checkpoints;
+use classroom::{Student, Teacher};
+use cli::Args;
+use clip::ClipPlane;
+use compare::Comparison;
+use console::Console;
+use distributions::Distributions;
+use dock::WorkspacePanel;
+use energy::EnergyPanel;
+use ensemble::Ensemble;
+use environment::Environment;
+use exposure::Exposure;
+use help::HelpOverlay;
+use inset::Inset;
+use inspector::Inspector;
+use keys::{Action, Keys};
+use labels::{Layout, ParticleLabels};
+use lesson::{Lesson, Presentation};
+use logging::LogPanel;
+use measure::Measure;
+use millikan::MillikanPanel;
+use minimap::Minimap;
+use momentum::MomentumPanel;
+use orbit::OrbitMeter;
+use panels::{
+    BubblePanel, DetectorPanel, DisplayPanel, FoilPanel, GhostPanel, GroupPanel, InteractionPanel,
+    MagnetPanel, SourcePanel,
+};
+use phase::PhasePlot;
+use placement::Placement;
+use presets::PresetPanel;
+use profiler::{Profiler, Section};
+use recoil::RecoilPanel;
+use reload::HotReload;
+use replicate::ReplicatePanel;
+use settings::{Settings, MAX_UI_SCALE, MIN_UI_SCALE};
+use slowmo::SlowMotion;
+use spawn::SpawnPanel;
+use stats::FrameStats;
+use stereo::{Stereo, StereoMode};
+use sweep::Sweep;
+use telemetry::Telemetry;
+use theme::Theme;
+use toast::DriftWatch;
+use tutorial::{Observation, Tutorial};
+use virial::VirialMonitor;
+use visibility::SpeciesVisibility;
+use worker::Worker;
+use zoom::EncounterZoom;
 
-fn get_mass(particle: Particle) -> f32 {
-    let em: f32 = 9.1 * 10f32.powf(-27.);
-    let pm: f32 = 1.6 * 10f32.powf(-27.);
-    match particle {
-        Particle::Electron => em,
-        _ => pm,
-    }
-}
+const MOVE_SPEED: f32 = 0.1;
+const LOOK_SPEED: f32 = 0.1;
 
-fn gen_random_vector(start: f32, end: f32) -> Vec3 {
-    let get_rand = || {
-        let num = gen_range(start, end);
-        if num == 0. {
-            return start;
-        }
-        num
-    };
-    return vec3(get_rand(), get_rand(), get_rand());
+// How a frame ended
+enum Frame {
+    Continue,
+    Quit,
+    // The physics thread died; its panic message is already recorded
+    Crashed,
 }
 
 fn conf() -> Conf {
+    let settings = Settings::load();
     Conf {
         window_title: String::from("Rutherford Atomic Model"),
-        window_width: 2360,
-        window_height: 1368,
+        window_width: settings.window_width,
+        window_height: settings.window_height,
         // high_dpi: true,
-        // fullscreen: true,
+        fullscreen: settings.fullscreen,
+        platform: Platform {
+            swap_interval: Some(if settings.vsync { 1 } else { 0 }),
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
 
+// Copies the selected particle `offset` away, or with `whole_group` every
+// particle in its group. Returns the copy of the selected particle, which
+// becomes the selection so that repeating it steps out a row.
+fn duplicate(
+    world: &mut World,
+    selected: Option<Entity>,
+    whole_group: bool,
+    offset: Vec3,
+) -> Option<Entity> {
+    let Some(entity) = selected.filter(|entity| world.particles.slot(*entity).is_some()) else {
+        toast::warning(i18n::tr("toast.select_to_duplicate").to_string());
+        return None;
+    };
+    let group = world
+        .membership
+        .get(entity)
+        .copied()
+        .filter(|_| whole_group);
+    let entities = match group {
+        Some(id) => world.members(id),
+        None => vec![entity],
+    };
+    let copies = world.duplicate(&entities, offset);
+    toast::info(i18n::trf("toast.duplicated", &[&copies.len()]));
+    entities
+        .iter()
+        .position(|member| *member == entity)
+        .map(|i| copies[i])
+}
+
 #[macroquad::main(conf)]
 async fn main() {
-    let mut x = 0.0;
-    let mut switch = false;
-    let bounds = 8.0;
+    let args = Args::parse();
+    logging::init(args.verbose);
+    crash::install_hook();
+    let mut settings = Settings::load();
+    i18n::set_language(settings.language);
+    if let Some(code) = &args.lang {
+        match i18n::Language::from_code(code) {
+            Some(language) => {
+                i18n::set_language(language);
+                settings.language = language;
+            }
+            None => log::warn!("unknown language '{}'", code),
+        }
+    }
 
     let world_up = vec3(0.0, 1.0, 0.0);
     let mut yaw: f32 = 1.18;
@@ -136,7 +222,6 @@ async fn main() {
     )
     .normalize();
     let mut right = front.cross(world_up).normalize();
-    let mut up;
 
     let mut position = vec3(0.0, 50.0, 0.0);
     let mut last_mouse_position: Vec2 = mouse_position().into();
@@ -145,111 +230,1095 @@ async fn main() {
     set_cursor_grab(grabbed);
     show_mouse(false);
 
-    let mut charge_vec = vec![
-        Charge::new(Particle::Electron, Some(vec3(200., 00., 0.))),
-        Charge::new(Particle::Proton, Some(vec3(100., 0., 00.))),
-    ];
+    spawn::load();
+    let mut world = World::new();
+    if !args.empty {
+        world.spawn_particle(Particle::Electron, Some(vec3(200., 00., 0.)));
+        world.spawn_particle(Particle::Proton, Some(vec3(100., 0., 00.)));
+    }
+
+    let mut profiler = Profiler::new();
+    let mut frame_stats = FrameStats::new();
+    let mut log_panel = LogPanel::new();
+    let mut sanity = SanityChecker::new();
+    let mut history = History::new();
+    let mut group_panel = GroupPanel::new();
+    let mut display_panel = DisplayPanel::new();
+    let mut workspace_panel = WorkspacePanel::new();
+    let mut inspector = Inspector::new();
+    let mut particle_labels = ParticleLabels::new();
+    let mut species_visibility = SpeciesVisibility::new();
+    let mut slow_motion = SlowMotion::new();
+    dock::restore();
+    let mut audio = Audio::new().await;
+    let mut sonifier = Sonifier::new().await;
+    let mut selected = None;
+    // How far Ctrl+D puts a copy from the original; set with the console
+    let mut duplicate_offset = vec3(50., 0., 0.);
+    let environment = Environment::new();
+    let mut stereo = Stereo::new();
+    let mut inset = Inset::new();
+    let mut zoom = EncounterZoom::new();
+    let mut minimap = Minimap::new();
+    let mut measure = Measure::new();
+    let mut clip = ClipPlane::new();
+    let mut tutorial = Tutorial::new(settings.tutorial_done);
+    let mut quiz = Quiz::new();
+    let mut annotations = Annotations::new();
+    let mut sweep = Sweep::new();
+    let mut phase_plot = PhasePlot::new();
+    let mut orbit_meter = OrbitMeter::new();
+    let mut distributions = Distributions::new();
+    let mut energy_panel = EnergyPanel::new();
+    let mut momentum_panel = MomentumPanel::new();
+    let mut recoil_panel = RecoilPanel::new();
+    let mut bookmarks = Bookmarks::new();
+    let mut virial = VirialMonitor::new();
+    let mut exposure = Exposure::new();
+    let mut detector_panel = DetectorPanel::new();
+    let mut foil_panel = FoilPanel::new();
+    let mut interaction_panel = InteractionPanel::new();
+    let mut ghost_panel = GhostPanel::new();
+    let mut bubble_panel = BubblePanel::new();
+    let mut replicate_panel = ReplicatePanel::new();
+    let mut placement = Placement::new();
+    let mut source_panel = SourcePanel::new();
+    let mut magnet_panel = MagnetPanel::new();
+    let mut millikan_panel = MillikanPanel::new();
+    let mut spawn_panel = SpawnPanel::new();
+    let mut preset_panel = PresetPanel::new();
+    let mut presentation = Presentation::new(match &args.lesson {
+        Some(path) => Lesson::load(path),
+        None => Lesson::builtin(),
+    });
+    let mut comparison: Option<Comparison> = None;
+    let mut chaos = Chaos::new();
+    let mut ensemble = Ensemble::new();
+    let mut worker: Option<Worker> = None;
+    let mut show_forces = false;
+    let mut console = Console::new();
+    let mut help = HelpOverlay::new();
+    let mut drift = DriftWatch::new();
+    let mut hot_reload = HotReload::new();
+    let mut checkpoints = Checkpoints::new();
+    let api = args.http.and_then(Api::start);
+    let mut telemetry = args.telemetry.and_then(Telemetry::start);
+    let mut teacher = args.teach.and_then(Teacher::start);
+    let student = args
+        .follow
+        .as_deref()
+        .zip(args.code.as_deref())
+        .map(|(address, code)| Student::connect(address, code));
+    let mut paused = false;
+    if let Some(path) = &args.scene {
+        let path = std::path::Path::new(path);
+        if let Some(scenario) = scene::load(path, &mut annotations) {
+            world = scenario.build();
+            hot_reload.watch(path, scenario, &world);
+        }
+    }
+    let mut theme = Theme::new(settings.theme, settings.ui_scale);
+    root_ui().push_skin(&theme.skin());
+    // The world as the physics thread and the students last had it, so an
+    // edit made by the UI can be told from a tick
+    let mut known = world.fingerprint();
 
     loop {
-        let delta = get_frame_time();
+        // A panic anywhere in the frame ends up on the crash screen instead
+        // of closing the window with the scene unsaved
+        let frame = panic::catch_unwind(AssertUnwindSafe(|| {
+            let delta = get_frame_time();
+            frame_stats.push(delta);
 
-        if is_key_pressed(KeyCode::Q) || is_key_pressed(KeyCode::Escape) {
-            break;
-        }
-        if is_key_pressed(KeyCode::Tab) {
-            grabbed = !grabbed;
-            set_cursor_grab(grabbed);
-            show_mouse(!grabbed);
-        }
+            if Keys::new(false).pressed(Action::Console) {
+                console.toggle();
+            }
+            // While the console or an egui text field has the keyboard,
+            // shortcuts stay quiet. Read before updating so the Escape that
+            // closes it doesn't also quit
+            let typing = console.visible || gui::wants_keyboard();
+            let console_command = console.update();
+            let keys = Keys::new(typing);
 
-        if is_key_down(KeyCode::W) {
-            position += front * MOVE_SPEED;
-        }
-        if is_key_down(KeyCode::S) {
-            position -= front * MOVE_SPEED;
-        }
-        if is_key_down(KeyCode::A) {
-            position -= right * MOVE_SPEED;
-        }
-        if is_key_down(KeyCode::D) {
-            position += right * MOVE_SPEED;
-        }
-        if is_key_down(KeyCode::Space) {
-            position.y += MOVE_SPEED;
-        }
-        if is_key_down(KeyCode::LeftControl) {
-            position.y -= MOVE_SPEED;
-        }
-        if is_key_pressed(KeyCode::Key1) {
-            charge_vec.append(&mut vec![Charge::new(Particle::Electron, None)]);
-        }
-        if is_key_pressed(KeyCode::Key2) {
-            charge_vec.append(&mut vec![Charge::new(Particle::Proton, None)]);
-        }
-        if is_key_pressed(KeyCode::Key3) {
-            charge_vec.append(&mut vec![Charge::new(Particle::Neutron, None)]);
-        }
+            if keys.pressed(Action::Quit) {
+                return Frame::Quit;
+            }
+            if keys.pressed(Action::Help) {
+                help.visible = !help.visible;
+            }
+            if keys.pressed(Action::ToggleMouse) {
+                grabbed = !grabbed;
+                set_cursor_grab(grabbed);
+                show_mouse(!grabbed);
+            }
+            if keys.pressed(Action::ClearLog) {
+                log_panel.clear();
+            }
+            if keys.pressed(Action::Log) {
+                log_panel.visible = !log_panel.visible;
+            }
+            if keys.pressed(Action::UiScaleUp) || keys.pressed(Action::UiScaleDown) {
+                let step = if keys.pressed(Action::UiScaleUp) {
+                    0.1
+                } else {
+                    -0.1
+                };
+                settings.ui_scale = (settings.ui_scale + step).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+                theme.ui_scale = settings.ui_scale;
+                root_ui().pop_skin();
+                root_ui().push_skin(&theme.skin());
+                settings.save();
+                log::info!(
+                    "{}",
+                    i18n::trf("ui_scale.changed", &[&(settings.ui_scale * 100.).round()])
+                );
+            }
+            if keys.pressed(Action::Mute) {
+                audio.muted = !audio.muted;
+            }
+            if keys.pressed(Action::VolumeDown) {
+                audio.change_volume(-0.1);
+            }
+            if keys.pressed(Action::VolumeUp) {
+                audio.change_volume(0.1);
+            }
+            if keys.pressed(Action::SonifyMode) {
+                sonifier.toggle_mode();
+            }
+            if keys.pressed(Action::Sonify) {
+                sonifier.enabled = !sonifier.enabled;
+            }
+            if keys.pressed(Action::Language) {
+                settings.language = i18n::next_language();
+                settings.save();
+                log::info!(
+                    "{}",
+                    i18n::trf("language.changed", &[&i18n::tr("language.name")])
+                );
+            }
+            if keys.pressed(Action::Theme) {
+                theme = theme.next();
+                root_ui().pop_skin();
+                root_ui().push_skin(&theme.skin());
+                settings.theme = theme.kind;
+                settings.save();
+                log::info!(
+                    "{}",
+                    i18n::trf("theme.changed", &[&i18n::tr(theme.name_key())])
+                );
+            }
+            if keys.pressed(Action::Skybox) {
+                settings.skybox = settings.skybox.next();
+                settings.save();
+            }
+            if keys.pressed(Action::StereoMode) {
+                stereo.mode = stereo.mode.next();
+                log::info!("{}", i18n::tr(stereo.mode.name_key()));
+            }
+            if keys.pressed(Action::StereoNarrower) {
+                stereo.change_separation(-0.5);
+            }
+            if keys.pressed(Action::StereoWider) {
+                stereo.change_separation(0.5);
+            }
+            if keys.pressed(Action::EncounterZoom) {
+                zoom.toggle();
+            }
+            if keys.pressed(Action::InsetView) {
+                inset.view = inset.view.next();
+                log::info!("{}", i18n::tr(inset.view.name_key()));
+            }
+            if keys.pressed(Action::Planar) {
+                history.record(&world);
+                world.set_planar(!world.planar);
+                if world.planar {
+                    let (center, radius) = world.bounding_sphere().unwrap_or((Vec3::ZERO, 0.));
+                    position = selection::framing_position(center, radius, -Vec3::Z);
+                }
+                let key = if world.planar {
+                    "toast.planar_on"
+                } else {
+                    "toast.planar_off"
+                };
+                toast::info(i18n::tr(key).to_string());
+            }
+            // F focuses the selection (or everything when nothing is selected),
+            // Home always frames every particle
+            if keys.pressed(Action::FocusSelection) || keys.pressed(Action::FrameAll) {
+                let focus = selected
+                    .filter(|_| !keys.pressed(Action::FrameAll))
+                    .and_then(|entity| world.particles.slot(entity))
+                    .map(|slot| (world.particles.pos[slot], 0.));
+                if let Some((center, radius)) = focus.or_else(|| world.bounding_sphere()) {
+                    position = selection::framing_position(center, radius, front);
+                }
+            }
+            if keys.pressed(Action::Pause) {
+                paused = !paused;
+                if let Some(worker) = &worker {
+                    worker.set_paused(paused);
+                }
+            }
+            // Insert moves physics onto a background thread and back, Shift+Insert
+            // lets it run as fast as it can
+            let unthrottled = keys.pressed(Action::PhysicsThreadUnthrottled);
+            if keys.pressed(Action::PhysicsThread) || unthrottled {
+                match worker.take() {
+                    Some(running) => {
+                        if let Some(last) = running.stop() {
+                            world = last;
+                        }
+                    }
+                    None => {
+                        comparison = None;
+                        chaos.stop();
+                        ensemble.stop();
+                        let started =
+                            Worker::start(world.clone(), settings.trails.limit(), unthrottled);
+                        started.set_paused(paused);
+                        worker = Some(started);
+                    }
+                }
+            }
+            if keys.pressed(Action::Bookmarks) {
+                bookmarks.visible = !bookmarks.visible;
+            }
+            if keys.pressed(Action::Checkpoints) {
+                checkpoints.visible = !checkpoints.visible;
+            }
+            if keys.pressed(Action::Presets) {
+                preset_panel.visible = !preset_panel.visible;
+            }
+            if keys.pressed(Action::SpawnPanel) {
+                spawn_panel.visible = !spawn_panel.visible;
+            }
+            if keys.pressed(Action::Forces) {
+                show_forces = !show_forces;
+            }
+            if keys.pressed(Action::Interactions) {
+                interaction_panel.visible = !interaction_panel.visible;
+            }
+            if keys.pressed(Action::Ghosts) {
+                ghost_panel.visible = !ghost_panel.visible;
+            }
+            if keys.pressed(Action::Bubbles) {
+                bubble_panel.visible = !bubble_panel.visible;
+            }
+            if keys.pressed(Action::Replicate) {
+                replicate_panel.visible = !replicate_panel.visible;
+            }
+            if keys.pressed(Action::Placement) {
+                placement.visible = !placement.visible;
+            }
+            if keys.pressed(Action::Sources) {
+                source_panel.visible = !source_panel.visible;
+            }
+            if keys.pressed(Action::Magnets) {
+                magnet_panel.visible = !magnet_panel.visible;
+            }
+            if keys.pressed(Action::Millikan) {
+                millikan_panel.visible = !millikan_panel.visible;
+            }
+            if keys.pressed(Action::Foils) {
+                foil_panel.visible = !foil_panel.visible;
+            }
+            if keys.pressed(Action::Detector) {
+                detector_panel.visible = !detector_panel.visible;
+            }
+            if keys.pressed(Action::Momentum) {
+                momentum_panel.visible = !momentum_panel.visible;
+            }
+            if keys.pressed(Action::Recoil) {
+                recoil_panel.visible = !recoil_panel.visible;
+            }
+            if keys.pressed(Action::Virial) {
+                virial.visible = !virial.visible;
+                virial.reset();
+            }
+            if keys.pressed(Action::Energy) {
+                energy_panel.visible = !energy_panel.visible;
+            }
+            if keys.pressed(Action::ExportHistograms) {
+                distributions.export();
+            }
+            if keys.pressed(Action::Report) {
+                report::write(&world);
+            }
+            if keys.pressed(Action::Histograms) {
+                distributions.visible = !distributions.visible;
+            }
+            if keys.pressed(Action::PhasePlot) {
+                phase_plot.visible = !phase_plot.visible;
+            }
+            if keys.pressed(Action::OrbitMeter) {
+                orbit_meter.visible = !orbit_meter.visible;
+            }
+            if keys.pressed(Action::Sweep) {
+                sweep.visible = !sweep.visible;
+            }
+            if keys.pressed(Action::Annotations) {
+                annotations.visible = !annotations.visible;
+            }
+            if keys.pressed(Action::Tutorial) {
+                tutorial.restart();
+            }
+            if tutorial.active() && keys.pressed(Action::SkipTutorial) {
+                tutorial.skip();
+                settings.tutorial_done = true;
+                settings.save();
+            }
+            if keys.pressed(Action::ClipPlane) {
+                clip.visible = !clip.visible;
+            }
+            if keys.pressed(Action::Measure) {
+                measure.next_tool();
+            }
+            if keys.pressed(Action::Minimap) {
+                minimap.visible = !minimap.visible;
+            }
+            if keys.pressed(Action::Compare) {
+                comparison = match comparison {
+                    Some(_) => None,
+                    None => Some(Comparison::start(&world)),
+                };
+            }
+            if keys.pressed(Action::Ensemble) {
+                ensemble.visible = !ensemble.visible;
+            }
+            if keys.pressed(Action::Chaos) {
+                if chaos.active() {
+                    chaos.stop();
+                    ensemble.stop();
+                } else if !chaos.start(&world, selected) {
+                    toast::warning(i18n::tr("chaos.nothing").to_string());
+                }
+            }
+            if keys.pressed(Action::DisplayPanel) {
+                display_panel.visible = !display_panel.visible;
+            }
+            if keys.pressed(Action::Inspector) {
+                inspector.visible = !inspector.visible;
+            }
+            if keys.pressed(Action::Labels) {
+                particle_labels.visible = !particle_labels.visible;
+            }
+            if keys.pressed(Action::Workspace) {
+                workspace_panel.visible = !workspace_panel.visible;
+            }
+            if keys.pressed(Action::Visibility) {
+                species_visibility.visible = !species_visibility.visible;
+            }
+            if keys.pressed(Action::Fullscreen) {
+                settings.fullscreen = !settings.fullscreen;
+                panels::set_fullscreen(settings.fullscreen);
+                settings.save();
+            }
+            if keys.pressed(Action::Groups) {
+                group_panel.visible = !group_panel.visible;
+            }
+            if keys.pressed(Action::FrameStats) {
+                frame_stats.visible = !frame_stats.visible;
+            }
+            if keys.pressed(Action::Profiler) {
+                profiler.visible = !profiler.visible;
+            }
 
-        let mouse_position: Vec2 = mouse_position().into();
-        let mouse_delta = mouse_position - last_mouse_position;
-        last_mouse_position = mouse_position;
+            if keys.down(Action::MoveForward) {
+                position += front * MOVE_SPEED;
+            }
+            if keys.down(Action::MoveBack) {
+                position -= front * MOVE_SPEED;
+            }
+            if keys.down(Action::MoveLeft) {
+                position -= right * MOVE_SPEED;
+            }
+            if keys.down(Action::MoveRight) {
+                position += right * MOVE_SPEED;
+            }
+            if keys.down(Action::MoveUp) {
+                position.y += MOVE_SPEED;
+            }
+            if keys.down(Action::MoveDown) {
+                position.y -= MOVE_SPEED;
+            }
+            if sanity.has_problems() {
+                if keys.pressed(Action::ClampProblem) {
+                    sanity.clamp(&mut world);
+                }
+                if keys.pressed(Action::RemoveProblem) {
+                    history.record(&world);
+                    sanity.remove(&mut world);
+                }
+                if keys.pressed(Action::RewindProblem) {
+                    sanity.rewind(&mut world);
+                }
+            }
+            let challenge_scene = if keys.pressed(Action::Challenge) {
+                if quiz.active() {
+                    quiz.stop();
+                    None
+                } else {
+                    Some(quiz.start())
+                }
+            } else if quiz.active() && !tutorial.waiting() && keys.pressed(Action::NextChallenge) {
+                quiz.next()
+            } else {
+                None
+            };
+            if let Some(scene) = challenge_scene {
+                history.record(&world);
+                world = scene;
+                sanity.problems.clear();
+                comparison = None;
+                chaos.stop();
+                ensemble.stop();
+            }
+            let slide = if keys.pressed(Action::Presentation) {
+                presentation.toggle()
+            } else if presentation.active() && keys.pressed(Action::NextSlide) {
+                presentation.advance(true)
+            } else if presentation.active() && keys.pressed(Action::PreviousSlide) {
+                presentation.advance(false)
+            } else {
+                None
+            };
+            if let Some(slide) = slide {
+                history.record(&world);
+                world = slide.scenario.build();
+                sanity.problems.clear();
+                comparison = None;
+                chaos.stop();
+                ensemble.stop();
+                if let Some((pose_position, pose_yaw, pose_pitch)) = slide.camera {
+                    position = pose_position;
+                    yaw = pose_yaw;
+                    pitch = pose_pitch;
+                }
+            }
+            if annotations.visible && keys.pressed(Action::Undo) {
+                annotations.undo();
+            } else if keys.pressed(Action::Undo) || keys.pressed(Action::Redo) {
+                let done = if keys.pressed(Action::Redo) {
+                    history.redo(&mut world)
+                } else {
+                    history.undo(&mut world)
+                };
+                if done {
+                    sanity.problems.clear();
+                }
+            }
+            if keys.pressed(Action::ClearParticles) {
+                history.record(&world);
+                let removed = world.clear_particles();
+                sanity.problems.clear();
+                toast::info(i18n::trf("toast.cleared", &[&removed]));
+            }
+            if keys.pressed(Action::SpawnElectron) {
+                history.record(&world);
+                let entity = world.spawn_particle(Particle::Electron, None);
+                world.set_group(entity, group_panel.active);
+            }
+            if keys.pressed(Action::SpawnProton) {
+                history.record(&world);
+                let entity = world.spawn_particle(Particle::Proton, None);
+                world.set_group(entity, group_panel.active);
+            }
+            if keys.pressed(Action::SpawnNeutron) {
+                history.record(&world);
+                let entity = world.spawn_particle(Particle::Neutron, None);
+                world.set_group(entity, group_panel.active);
+            }
+            let whole_group = keys.pressed(Action::DuplicateGroup);
+            if keys.pressed(Action::Duplicate) || whole_group {
+                history.record(&world);
+                let offset = world.constrain(duplicate_offset);
+                if let Some(copy) = duplicate(&mut world, selected, whole_group, offset) {
+                    selected = Some(copy);
+                }
+            }
 
-        yaw += mouse_delta.x * delta * LOOK_SPEED;
-        pitch += mouse_delta.y * delta * -LOOK_SPEED;
+            let mouse_position: Vec2 = mouse_position().into();
+            let mouse_delta = mouse_position - last_mouse_position;
+            last_mouse_position = mouse_position;
 
-        pitch = if pitch > 1.5 { 1.5 } else { pitch };
-        pitch = if pitch < -1.5 { -1.5 } else { pitch };
+            yaw += mouse_delta.x * delta * LOOK_SPEED;
+            pitch += mouse_delta.y * delta * -LOOK_SPEED;
 
-        front = vec3(
-            yaw.cos() * pitch.cos(),
-            pitch.sin(),
-            yaw.sin() * pitch.cos(),
-        )
-        .normalize()
-            * 3.;
+            pitch = if pitch > 1.5 { 1.5 } else { pitch };
+            pitch = if pitch < -1.5 { -1.5 } else { pitch };
+            // 2D mode looks straight down on the plane from +z
+            if world.planar {
+                yaw = -std::f32::consts::FRAC_PI_2;
+                pitch = 0.;
+            }
 
-        right = front.cross(world_up).normalize() * 3.;
-        up = right.cross(front).normalize() * 3.;
+            front = vec3(
+                yaw.cos() * pitch.cos(),
+                pitch.sin(),
+                yaw.sin() * pitch.cos(),
+            )
+            .normalize()
+                * 3.;
 
-        x += if switch { 0.04 } else { -0.04 };
-        if x >= bounds || x <= -bounds {
-            switch = !switch;
-        }
+            right = front.cross(world_up).normalize() * 3.;
+            let up = right.cross(front).normalize() * 3.;
 
-        println!("{:?}", charge_vec.iter().count());
+            clear_background(theme.background);
+            dock::begin_frame(&theme);
+            environment.draw_background(settings.skybox, &theme);
 
-        clear_background(BLACK);
+            // !Implement UI
+            // widgets::Window::new(hash!(), vec2(10., 10.), vec2(100., 100.))
+            //     .label("A")
+            //     .ui(&mut *root_ui(), |ui| {});
 
-        // !Implement UI
-        // widgets::Window::new(hash!(), vec2(10., 10.), vec2(100., 100.))
-        //     .label("A")
-        //     .ui(&mut *root_ui(), |ui| {});
+            // 3D
+            let camera = Camera3D {
+                position,
+                up,
+                target: position + front,
+                ..Default::default()
+            };
 
-        // 3D
-        set_camera(&Camera3D {
-            position,
-            up,
-            target: position + front,
-            ..Default::default()
-        });
+            if keys.pressed(Action::SaveExposure) {
+                exposure.save();
+            }
+            if keys.pressed(Action::Exposure) {
+                exposure.toggle(&camera);
+            }
 
-        draw_grid(2000, 10., BLACK, GRAY);
+            let mouse_free =
+                !grabbed && !root_ui().is_mouse_over(mouse_position) && !gui::wants_mouse();
+            let focus = world
+                .bounding_sphere()
+                .map_or(Vec3::ZERO, |(center, _)| center);
+            annotations.update(&camera, focus, mouse_free);
+            let hovered = (mouse_free && !annotations.visible)
+                .then(|| selection::pick(&world, &camera, &species_visibility))
+                .flatten();
+            if mouse_free && !annotations.visible && is_mouse_button_pressed(MouseButton::Left) {
+                selected = hovered;
+                if let Some(entity) = selected {
+                    measure.pick(entity);
+                }
+            }
+            let spacing = settings.grid.spacing;
+            if placement.drag(&mut world, selected, &camera, mouse_free, spacing) {
+                history.record(&world);
+            }
+            if selected.is_some_and(|entity| !world.is_alive(entity)) {
+                selected = None;
+            }
 
-        let buffer = charge_vec.clone();
-        for charge in &mut charge_vec {
-            charge.update(&buffer);
-            draw_sphere(charge.pos, 2., None, YELLOW);
+            if worker.as_ref().is_some_and(Worker::crashed) {
+                return Frame::Crashed;
+            }
+            profiler.begin(Section::Physics);
+            // Bounds are a setting rather than part of the scene
+            world.bounds = settings.bounds.active();
+            if let Some(comparison) = &mut comparison {
+                comparison.world.bounds = world.bounds;
+            }
+            // Anything the UI changed so far this frame; with the physics
+            // thread running, that wins over its latest snapshot
+            let fingerprint = world.fingerprint();
+            let edited_early = fingerprint != known;
+            // A failed sanity check holds the simulation until the user picks a recovery
+            let ticked = match &worker {
+                Some(worker) if edited_early => {
+                    worker.replace(world.clone(), settings.trails.limit());
+                    known = fingerprint;
+                    false
+                }
+                Some(worker) => match worker.latest() {
+                    Some(snapshot) => {
+                        world = snapshot;
+                        true
+                    }
+                    None => false,
+                },
+                None if !sanity.has_problems()
+                    && !paused
+                    && slow_motion.due(&settings.slow_motion) =>
+                {
+                    systems::step(&mut world);
+                    systems::record_trails(&mut world, settings.trails.limit());
+                    if let Some(comparison) = &mut comparison {
+                        comparison.step();
+                        systems::record_trails(&mut comparison.world, settings.trails.limit());
+                    }
+                    chaos.step(&world);
+                    ensemble.step(&world, settings.trails.limit());
+                    true
+                }
+                None => false,
+            };
+            profiler.begin(Section::Tools);
+            if ticked {
+                known = world.fingerprint();
+                if !sanity.after_tick(&world) {
+                    toast::warning(i18n::tr("toast.halted").to_string());
+                }
+                let statistics = Statistics::gather(&world);
+                slow_motion.update(&settings.slow_motion, &world);
+                drift.update(&statistics);
+                checkpoints.update(&world, delta);
+                if let Some(telemetry) = &mut telemetry {
+                    telemetry.tick(&world, &statistics, selected);
+                }
+                phase_plot.update(&world, selected);
+                orbit_meter.update(&world, selected);
+                energy_panel.update(&world, selected);
+                momentum_panel.update(&world, &statistics, selected);
+                recoil_panel.update(&world);
+                zoom.update(&world, selected);
+                bookmarks.update(&world);
+                virial.update(&statistics);
+                exposure.accumulate(&world, &theme);
+            }
+            let frame_events = std::mem::take(&mut world.events);
+            audio.handle(&frame_events);
+            quiz.update(&world, &frame_events, ticked, delta);
+            distributions.update(&world);
+            sonifier.update(
+                selected.and_then(|entity| match sonifier.mode {
+                    SonifyMode::OrbitalFrequency => diagnostics::orbital_frequency(&world, entity),
+                    SonifyMode::KineticEnergy => {
+                        diagnostics::particle_kinetic_energy(&world, entity)
+                    }
+                }),
+                &audio,
+            );
 
-            // for trail in charge.trail { //! Perf issues
-            //     draw_sphere_wires(trail, 0.1, None, GREEN);
-            // }
-        }
+            profiler.begin(Section::Render);
+            let render_ctx = render::RenderContext {
+                theme: &theme,
+                settings: &settings,
+                environment: &environment,
+                sanity: &sanity,
+                selected,
+                clip: &clip,
+                sweep: &sweep,
+                ensemble: &ensemble,
+                recoil: &recoil_panel,
+                visibility: &species_visibility,
+                eye: position,
+                forces: show_forces,
+            };
+            match &comparison {
+                Some(comparison) => {
+                    let (left, right) = (
+                        render::half_screen(&camera, false),
+                        render::half_screen(&camera, true),
+                    );
+                    render::draw_scene(&world, &render_ctx, &left);
+                    render::draw_scene(&comparison.world, &render_ctx, &right);
+                }
+                None => stereo.render(&camera, |camera| {
+                    render::draw_scene(&world, &render_ctx, camera)
+                }),
+            }
+            inset.render(&world, |camera| {
+                render::draw_scene(&world, &render_ctx, camera)
+            });
+            zoom.advance();
+            zoom.render(&theme);
 
-        // Back to screen space, render some text
-        set_default_camera();
+            // Back to screen space, render some text
+            set_default_camera();
+            // Picking only follows the cursor through the full-screen view
+            if comparison.is_none() && stereo.mode != StereoMode::SideBySide {
+                selection::draw_outlines(&world, &camera, hovered, selected, &theme);
+            }
 
-        let fps = get_fps();
-        draw_text(format!("{}", fps).as_str(), 10., 20., 30., GREEN);
+            profiler.begin(Section::Ui);
+            exposure.draw();
+            let fps = get_fps();
+            draw_text(
+                format!("{}", fps).as_str(),
+                10.,
+                20.,
+                theme.text_size(30.),
+                theme.text,
+            );
+            if let Some(worker) = &worker {
+                let key = if worker.unthrottled {
+                    "worker.unthrottled"
+                } else {
+                    "worker.running"
+                };
+                draw_text(
+                    i18n::tr(key),
+                    10.,
+                    20. + theme.text_size(30.),
+                    theme.text_size(20.),
+                    theme.text,
+                );
+            }
+            if paused {
+                draw_text(
+                    i18n::tr("hud.paused"),
+                    10.,
+                    20. + theme.text_size(30.) + theme.text_size(20.),
+                    theme.text_size(20.),
+                    theme.warning,
+                );
+            }
+            let classroom = match (&teacher, &student) {
+                (Some(teacher), _) => Some(i18n::trf(
+                    "hud.teaching",
+                    &[&teacher.students(), &teacher.code()],
+                )),
+                (None, Some(student)) if student.connected() => {
+                    Some(i18n::tr("hud.following").to_string())
+                }
+                (None, Some(student)) if student.refused() => {
+                    Some(i18n::tr("hud.code_refused").to_string())
+                }
+                (None, Some(_)) => Some(i18n::tr("hud.waiting_teacher").to_string()),
+                (None, None) => None,
+            };
+            if let Some(text) = classroom {
+                draw_text(
+                    &text,
+                    10.,
+                    20. + theme.text_size(30.) + theme.text_size(20.) * 2.,
+                    theme.text_size(20.),
+                    theme.text,
+                );
+            }
+            if let Some(comparison) = &comparison {
+                comparison.draw_labels(&world, &theme);
+            }
+            let observed = Observation {
+                continued: keys.pressed(Action::ContinueTutorial),
+                looked: grabbed && mouse_delta != Vec2::ZERO,
+                moved: [
+                    Action::MoveForward,
+                    Action::MoveLeft,
+                    Action::MoveBack,
+                    Action::MoveRight,
+                ]
+                .iter()
+                .any(|action| keys.down(*action)),
+                particles: world.particle_count(),
+                selected: selected.is_some(),
+                focused: keys.pressed(Action::FocusSelection),
+                minimap: minimap.visible,
+                histograms: distributions.visible,
+                minimap_rect: minimap.rect(&theme),
+            };
+            if tutorial.update(&observed) {
+                settings.tutorial_done = true;
+                settings.save();
+            }
+            tutorial.draw(&observed, &theme);
+            annotations.draw(&camera, &theme);
+            presentation.draw(&camera, &theme);
+            quiz.draw(&theme);
+            inset.draw(&theme);
+            zoom.draw(&theme);
+            if worker.is_none() {
+                slow_motion.draw(&theme);
+            }
+            orbit_meter.draw(&theme);
+            chaos.draw(&theme);
+            recoil_panel.draw(&theme);
+            minimap.draw(&world, &camera, &theme);
+            // Labels placed first keep their spot; the rest fit around them
+            let mut layout = Layout::new();
+            if magnet_panel.label_orbits {
+                spectrometer::draw_orbits(&world, &camera, &mut layout, &theme);
+            }
+            particle_labels.draw(
+                &world,
+                &camera,
+                [selected, hovered],
+                &species_visibility,
+                &mut layout,
+                &theme,
+            );
+            measure.draw(&world, &camera, &theme);
+            profiler.draw(&theme);
+            frame_stats.draw(
+                profiler.millis(Section::Physics),
+                world.particle_count(),
+                world.annihilations,
+                &theme,
+            );
+            log_panel.draw(&theme);
+            render::draw_sanity_prompt(&sanity, &theme);
+            group_panel.draw(&mut world, &theme);
+            if detector_panel.draw(&mut world, &theme) {
+                report::write(&world);
+            }
+            foil_panel.draw(&mut world, &theme);
+            interaction_panel.draw(&mut world, &theme);
+            ghost_panel.draw(&mut world, selected, &theme);
+            bubble_panel.draw(&mut world, selected, &theme);
+            source_panel.draw(&mut world, selected, &theme);
+            magnet_panel.draw(&mut world, selected, &theme);
+            millikan_panel.draw(&mut world, &theme);
+            if let Some(place) = placement.draw(&world, selected, settings.grid.spacing, &theme) {
+                history.record(&world);
+                if let Some(entity) = placement.apply(place, &mut world, selected) {
+                    world.set_group(entity, group_panel.active);
+                    selected = Some(entity);
+                }
+            }
+            if let Some(request) = replicate_panel.draw(&theme) {
+                history.record(&world);
+                replicate_panel.apply(request, &mut world, selected);
+            }
+            if let Some(particle) = spawn_panel.draw(&theme) {
+                history.record(&world);
+                let entity = world.spawn_particle(particle, None);
+                world.set_group(entity, group_panel.active);
+            }
+            clip.draw_panel(&theme);
+            sweep.draw_panel(&theme);
+            ensemble.draw_panel(&world, &theme);
+            let (save_scene, load_scene) = annotations.draw_panel(&theme);
+            if save_scene || keys.pressed(Action::SaveScene) {
+                scene::save(&world, &annotations);
+            }
+            if load_scene || keys.pressed(Action::LoadScene) {
+                let path = scene::path();
+                if let Some(scenario) = scene::load(&path, &mut annotations) {
+                    history.record(&world);
+                    world = scenario.build();
+                    hot_reload.watch(&path, scenario, &world);
+                    sanity.problems.clear();
+                    comparison = None;
+                    chaos.stop();
+                    ensemble.stop();
+                    toast::info(i18n::tr("toast.scene_loaded").to_string());
+                }
+            } else if let Some(reloaded) = hot_reload.poll(&world, &mut annotations) {
+                history.record(&world);
+                world = reloaded;
+                sanity.problems.clear();
+                comparison = None;
+                chaos.stop();
+                ensemble.stop();
+            }
+            if let Some(restored) = checkpoints.draw(&world, &theme) {
+                history.record(&world);
+                world = restored;
+                sanity.problems.clear();
+                comparison = None;
+                chaos.stop();
+                ensemble.stop();
+                toast::info(i18n::tr("checkpoints.restored").to_string());
+            }
+            if let Some(rewound) = bookmarks.draw(&theme) {
+                history.record(&world);
+                world = rewound;
+                sanity.problems.clear();
+                comparison = None;
+                chaos.stop();
+                ensemble.stop();
+            }
+            workspace_panel.draw(&theme);
+            let mut settings_changed = false;
+            egui_macroquad::ui(|ctx| {
+                gui::style(ctx, &theme);
+                settings_changed = display_panel.draw(ctx, &mut settings, &theme);
+                inspector.draw(ctx, &world, selected, &theme);
+                species_visibility.draw(ctx, &world, &theme);
+                energy_panel.draw(ctx, &world, &theme);
+                phase_plot.draw(ctx, &theme);
+                distributions.draw(ctx, &theme);
+                momentum_panel.draw(ctx, &theme);
+                virial.draw(ctx, &theme);
+                if let Some(detector) = world.detector.as_ref().filter(|_| detector_panel.plot) {
+                    angular::draw_distribution(ctx, detector, &theme);
+                }
+            });
+            if settings_changed {
+                settings.save();
+            }
+            let console_command = console.draw(&theme).or(console_command);
+            let mut preset_request = preset_panel.draw(&theme);
+            match console_command {
+                Some(console::Command::FastForward(ticks)) => {
+                    if sanity.has_problems() {
+                        console.print(i18n::tr("console.halted").to_string());
+                    } else {
+                        history.record(&world);
+                        let ran = systems::fast_forward(&mut world, ticks, settings.trails.limit());
+                        sanity.after_tick(&world);
+                        console.print(i18n::trf("console.advanced", &[&ran, &ticks]));
+                    }
+                }
+                Some(console::Command::Substep(enabled, threshold, max)) => {
+                    let substepping = &mut world.substepping;
+                    if let Some(enabled) = enabled {
+                        substepping.enabled = enabled;
+                    }
+                    if let Some(threshold) = threshold {
+                        substepping.threshold = threshold;
+                    }
+                    if let Some(max) = max {
+                        substepping.max_substeps = max.max(1);
+                    }
+                    let state = if substepping.enabled {
+                        i18n::tr("console.on")
+                    } else {
+                        i18n::tr("console.off")
+                    };
+                    console.print(i18n::trf(
+                        "console.substep",
+                        &[
+                            &state,
+                            &substepping.threshold,
+                            &substepping.max_substeps,
+                            &world.last_substeps,
+                        ],
+                    ));
+                }
+                Some(console::Command::Reload(mode)) => {
+                    if let Some(mode) = mode {
+                        hot_reload.mode = mode;
+                    }
+                    let watched = hot_reload.path().map_or_else(
+                        || i18n::tr("reload.nothing").to_string(),
+                        |path| path.display().to_string(),
+                    );
+                    console.print(i18n::trf(
+                        "reload.state",
+                        &[&hot_reload.mode.code(), &watched],
+                    ));
+                }
+                Some(console::Command::Seed(seed)) => {
+                    if let Some(seed) = seed {
+                        world.rng = seed.map(Rng::new);
+                    }
+                    match &world.rng {
+                        Some(rng) => console.print(i18n::trf("console.seeded", &[&rng.state()])),
+                        None => console.print(i18n::tr("console.unseeded").to_string()),
+                    }
+                }
+                Some(console::Command::Clear) => {
+                    history.record(&world);
+                    let removed = world.clear_particles();
+                    sanity.problems.clear();
+                    console.print(i18n::trf("toast.cleared", &[&removed]));
+                }
+                Some(console::Command::Duplicate(offset)) => {
+                    if let Some(offset) = offset {
+                        duplicate_offset = offset;
+                    }
+                    history.record(&world);
+                    let offset = world.constrain(duplicate_offset);
+                    if let Some(copy) = duplicate(&mut world, selected, false, offset) {
+                        selected = Some(copy);
+                    }
+                }
+                Some(console::Command::Preset(code, seed)) => {
+                    preset_request = presets::find(&code).map(|preset| (preset, seed));
+                }
+                None => {}
+            }
+            for request in api.iter().flat_map(Api::poll) {
+                let path = request.path.clone();
+                match (request.method.as_str(), path.as_str()) {
+                    ("GET", "/") => {
+                        request.respond(200, format!("{{\"endpoints\":{}}}", api::ENDPOINTS))
+                    }
+                    ("GET", "/particles") => request.respond(200, api::particles_json(&world)),
+                    ("GET", "/stats") => request.respond(200, api::stats_json(&world, paused)),
+                    ("POST", "/pause") | ("POST", "/resume") => {
+                        paused = path == "/pause";
+                        if let Some(worker) = &worker {
+                            worker.set_paused(paused);
+                        }
+                        request.respond(200, api::stats_json(&world, paused));
+                    }
+                    ("POST", "/scenario") => match Scenario::parse(&request.body) {
+                        Ok(scenario) => {
+                            history.record(&world);
+                            world = scenario.build();
+                            sanity.problems.clear();
+                            comparison = None;
+                            chaos.stop();
+                            ensemble.stop();
+                            request.respond(200, api::stats_json(&world, paused));
+                        }
+                        Err(err) => request.respond(400, api::error_json(&err.to_string())),
+                    },
+                    ("POST", preset) if preset.starts_with("/preset/") => {
+                        let seed = request.query_value("seed").and_then(|s| s.parse().ok());
+                        match presets::find(&preset["/preset/".len()..]) {
+                            Some(preset) => {
+                                preset_request = Some((preset, seed));
+                                request.respond(200, "{\"loaded\":true}".to_string());
+                            }
+                            None => request.respond(404, api::error_json("unknown preset")),
+                        }
+                    }
+                    _ => request.respond(404, api::error_json("unknown endpoint")),
+                }
+            }
+            if let Some((preset, seed)) = preset_request {
+                history.record(&world);
+                world = preset.build(seed);
+                sanity.problems.clear();
+                comparison = None;
+                chaos.stop();
+                ensemble.stop();
+                preset_panel.last_seed = seed;
+                let caption = i18n::tr(preset.caption_key);
+                match seed {
+                    Some(seed) => toast::info(i18n::trf("presets.randomized", &[&caption, &seed])),
+                    None => toast::info(caption.to_string()),
+                }
+            }
+            // A student's scene is whatever the teacher last sent; bodies are
+            // moved in place when the scene is unchanged so trails survive
+            let mut synced = false;
+            if let Some(snapshot) = student.as_ref().and_then(Student::take) {
+                if classroom::mirror(&mut world, snapshot.world) {
+                    selected = None;
+                    sanity.problems.clear();
+                    comparison = None;
+                    chaos.stop();
+                    ensemble.stop();
+                }
+                paused = snapshot.paused;
+                if let Some(worker) = &worker {
+                    worker.set_paused(paused);
+                }
+                synced = true;
+            }
+            // An edit to the app's copy of the world wins over whatever the
+            // background thread did since its last snapshot
+            let fingerprint = world.fingerprint();
+            let edited_late = fingerprint != known;
+            known = fingerprint;
+            if let Some(worker) = &worker {
+                if edited_late || synced {
+                    worker.replace(world.clone(), settings.trails.limit());
+                }
+            }
+            let edited = edited_early || edited_late;
+            if let Some(teacher) = &mut teacher {
+                teacher.update(&world, paused, edited);
+            }
+            egui_macroquad::draw();
+            toast::draw(delta, &theme);
+            help.draw(&theme);
+            profiler.end();
+            Frame::Continue
+        }));
+        match frame {
+            Ok(Frame::Continue) => {}
+            Ok(Frame::Quit) => break,
+            Ok(Frame::Crashed) | Err(_) => {
+                crash::show(&world, &annotations, &theme).await;
+                break;
+            }
+        }
 
         next_frame().await
     }
@@ -0,0 +1,121 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::{tr, trf};
+use crate::selection;
+use crate::theme::Theme;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tool {
+    Ruler,
+    Protractor,
+}
+
+impl Tool {
+    fn points(&self) -> usize {
+        match self {
+            Tool::Ruler => 2,
+            Tool::Protractor => 3,
+        }
+    }
+}
+
+// Distance between two clicked particles, or the angle at the middle one of
+// three. Particles are tracked by entity so the readout stays live.
+pub struct Measure {
+    pub tool: Option<Tool>,
+    picks: Vec<Entity>,
+}
+
+impl Measure {
+    pub fn new() -> Measure {
+        Measure {
+            tool: None,
+            picks: Vec::new(),
+        }
+    }
+
+    pub fn next_tool(&mut self) {
+        self.tool = match self.tool {
+            None => Some(Tool::Ruler),
+            Some(Tool::Ruler) => Some(Tool::Protractor),
+            Some(Tool::Protractor) => None,
+        };
+        self.picks.clear();
+    }
+
+    // Starts over once the tool already has all its points.
+    pub fn pick(&mut self, entity: Entity) {
+        let Some(tool) = self.tool else {
+            return;
+        };
+        if self.picks.len() >= tool.points() {
+            self.picks.clear();
+        }
+        self.picks.push(entity);
+    }
+
+    pub fn draw(&mut self, world: &World, camera: &Camera3D, theme: &Theme) {
+        let Some(tool) = self.tool else {
+            return;
+        };
+        self.picks.retain(|entity| world.is_alive(*entity));
+
+        let points: Vec<Vec3> = self
+            .picks
+            .iter()
+            .filter_map(|entity| world.particles.slot(*entity))
+            .map(|slot| world.particles.pos[slot])
+            .collect();
+        let screen: Vec<Option<Vec2>> = points
+            .iter()
+            .map(|p| selection::world_to_screen(camera, *p))
+            .collect();
+
+        for pair in screen.windows(2) {
+            if let [Some(a), Some(b)] = pair {
+                draw_line(a.x, a.y, b.x, b.y, theme.scaled(2.), theme.selection);
+            }
+        }
+
+        let size = theme.text_size(20.);
+        let label = match (tool, points.as_slice()) {
+            (Tool::Ruler, [a, b]) => Some((
+                screen[1],
+                trf("measure.distance", &[&format!("{:.2}", a.distance(*b))]),
+            )),
+            (Tool::Protractor, [a, vertex, b]) => {
+                let angle = (*a - *vertex).angle_between(*b - *vertex).to_degrees();
+                Some((screen[1], trf("measure.angle", &[&format!("{:.1}", angle)])))
+            }
+            _ => None,
+        };
+        match label {
+            Some((Some(at), text)) => draw_text(&text, at.x + 8., at.y - 8., size, theme.text),
+            Some((None, _)) => {}
+            None => {
+                let remaining = tool.points() - points.len();
+                draw_text(
+                    &trf("measure.pick", &[&remaining]),
+                    theme.scaled(10.),
+                    theme.scaled(60.),
+                    size,
+                    theme.text,
+                );
+            }
+        }
+        let name = match tool {
+            Tool::Ruler => "measure.ruler",
+            Tool::Protractor => "measure.protractor",
+        };
+        draw_text(
+            tr(name),
+            theme.scaled(10.),
+            theme.scaled(40.),
+            size,
+            theme.text,
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use macroquad::prelude::*;
+
+use crate::portable;
+use crate::world::World;
+
+// Uniform gravity and a viscous medium slowing everything down in proportion
+// to its speed, as for the oil drops of Millikan's experiment. Both are in
+// simulation units: gravity in units per tick², drag as the fraction of
+// velocity lost per tick in the limit of a weak medium.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Medium {
+    pub gravity: Vec3,
+    pub drag: f32,
+}
+
+// Slows every particle down by the drag. Applied as the exact decay over the
+// tick, e^-drag, rather than a force, so a thick medium can't overshoot and
+// turn particles around.
+pub fn damp(world: &mut World) {
+    let drag = world.medium.drag;
+    if drag <= 0. {
+        return;
+    }
+    let decay = portable::exp(-drag);
+    for vel in world.particles.vel.iter_mut() {
+        *vel *= decay;
+    }
+}
+
+// Potential energy of every particle in the gravitational field, zero at the
+// origin.
+pub fn potential_energy(world: &World) -> f32 {
+    let gravity = world.medium.gravity;
+    let particles = &world.particles;
+    particles
+        .pos
+        .iter()
+        .zip(&particles.mass)
+        .map(|(pos, mass)| -mass * gravity.dot(*pos))
+        .sum()
+}
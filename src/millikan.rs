@@ -0,0 +1,125 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::particle::{get_charge, Particle};
+use rutherford_atomic_model::sources::Source;
+use rutherford_atomic_model::units::{self, Quantity};
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+use crate::unit_field::UnitField;
+
+// Highest voltage on the slider; anything above can still be typed in
+const MAX_VOLTAGE: f32 = 1e6;
+// A drop counts as hovering when it drifts along the field at less than this
+// fraction of the speed it would fall at with the plates off
+const HOVER_FRACTION: f32 = 0.02;
+
+// Millikan's oil-drop experiment: the voltage across the first capacitor, and
+// for every drop between its plates the charge that balances gravity and drag
+// at the speed it is drifting at, q = m (γv - g) / E along the field. Once
+// each drop has settled to a steady speed the charges come out as whole
+// multiples of e, and a hovering drop needs no drag to explain.
+pub struct MillikanPanel {
+    pub visible: bool,
+    voltage_field: UnitField,
+}
+
+impl MillikanPanel {
+    pub fn new() -> MillikanPanel {
+        MillikanPanel {
+            visible: false,
+            voltage_field: UnitField::new(),
+        }
+    }
+
+    pub fn draw(&mut self, world: &mut World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let e = get_charge(Particle::Proton);
+        let medium = world.medium;
+        let particles = &world.particles;
+        let capacitor = world
+            .sources
+            .iter_mut()
+            .find(|source| matches!(source, Source::Capacitor { .. }));
+        dock::window(
+            "millikan.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+            vec2(theme.scaled(340.), theme.scaled(360.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let Some(capacitor) = capacitor else {
+                ui.label(None, tr("millikan.no_capacitor"));
+                return;
+            };
+            let gap = *capacitor;
+            let Source::Capacitor {
+                normal,
+                separation,
+                voltage,
+                ..
+            } = capacitor
+            else {
+                return;
+            };
+            ui.slider(hash!(), tr("millikan.voltage"), 0.0..MAX_VOLTAGE, voltage);
+            self.voltage_field.ui(
+                ui,
+                hash!(),
+                voltage,
+                |text| match units::parse_quantity(text, Quantity::Voltage)? {
+                    Quantity::Voltage(volts) => Some(volts),
+                    _ => None,
+                },
+                |volts| format!("{:.0} V", volts),
+            );
+            let field = units::field_from_volts(*voltage, *separation);
+            let fall = medium.gravity.dot(*normal);
+            let terminal = if medium.drag > 0. {
+                fall.abs() / medium.drag
+            } else {
+                f32::INFINITY
+            };
+            ui.separator();
+            let mut any = false;
+            for slot in 0..particles.len() {
+                if particles.charge[slot] == 0. || !gap.between_plates(particles.pos[slot]) {
+                    continue;
+                }
+                any = true;
+                let drift = particles.vel[slot].dot(*normal);
+                let name = particles.particle[slot].name();
+                if field == 0. {
+                    ui.label(None, &trf("millikan.no_field", &[&name]));
+                    continue;
+                }
+                let charge = particles.mass[slot] * (medium.drag * drift - fall) / field / e;
+                let key = if drift.abs() < HOVER_FRACTION * terminal {
+                    "millikan.hovering"
+                } else {
+                    "millikan.drifting"
+                };
+                ui.label(
+                    None,
+                    &trf(
+                        key,
+                        &[
+                            &name,
+                            &format!("{:.2}", charge),
+                            &format!("{:.0}", charge.round()),
+                        ],
+                    ),
+                );
+            }
+            if !any {
+                ui.label(None, tr("millikan.empty"));
+            }
+        });
+    }
+}
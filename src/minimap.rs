@@ -0,0 +1,75 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::world::World;
+
+use crate::theme::Theme;
+
+const MAP_SIZE: f32 = 200.;
+const MARGIN: f32 = 10.;
+
+// Top-down overview of every particle and the camera, in the bottom right
+// corner, so the atom can be found again after flying away.
+pub struct Minimap {
+    pub visible: bool,
+}
+
+impl Minimap {
+    pub fn new() -> Minimap {
+        Minimap { visible: false }
+    }
+
+    pub fn rect(&self, theme: &Theme) -> Rect {
+        let size = theme.scaled(MAP_SIZE);
+        Rect::new(
+            screen_width() - size - MARGIN,
+            screen_height() - size - MARGIN,
+            size,
+            size,
+        )
+    }
+
+    pub fn draw(&self, world: &World, camera: &Camera3D, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let rect = self.rect(theme);
+        let (origin, size) = (rect.point(), rect.w);
+        draw_rectangle(origin.x, origin.y, size, size, theme.panel);
+        draw_rectangle_lines(origin.x, origin.y, size, size, theme.scaled(2.), theme.text);
+
+        // Fit both the particles and the camera, looking down the y axis
+        let eye = camera.position.xz();
+        let (center, radius) = match world.bounding_sphere() {
+            Some((center, radius)) => {
+                let center = center.xz();
+                let mid = (center + eye) / 2.;
+                (mid, (radius + center.distance(mid)).max(eye.distance(mid)))
+            }
+            None => (eye, 0.),
+        };
+        let scale = size / 2. / (radius.max(50.) * 1.2);
+        let to_map = |p: Vec2| origin + Vec2::splat(size / 2.) + (p - center) * scale;
+
+        for (particle, pos) in world.particles.particle.iter().zip(&world.particles.pos) {
+            let p = to_map(pos.xz());
+            draw_circle(p.x, p.y, theme.scaled(2.), theme.particle(*particle));
+        }
+
+        let facing = (camera.target - camera.position).xz().normalize_or_zero();
+        let aspect = camera.aspect.unwrap_or(screen_width() / screen_height());
+        let half_fov = ((camera.fovy / 2.).tan() * aspect).atan();
+        let reach = size * 0.3;
+        let p = to_map(eye);
+        for angle in [-half_fov, half_fov] {
+            let (sin, cos) = angle.sin_cos();
+            let dir = vec2(
+                facing.x * cos - facing.y * sin,
+                facing.x * sin + facing.y * cos,
+            );
+            let edge = p + dir * reach;
+            draw_line(p.x, p.y, edge.x, edge.y, theme.scaled(1.), theme.selection);
+        }
+        draw_circle(p.x, p.y, theme.scaled(3.), theme.selection);
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::statistics::Statistics;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::gui;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+const HISTORY: usize = 600;
+
+// Total angular momentum about the center of mass and its drift since the
+// particle set last changed. An exact integrator keeps it constant, so the
+// drift is a measure of integration error.
+pub struct MomentumPanel {
+    pub visible: bool,
+    // Momentum and particle count the drift is measured against
+    reference: Option<(Vec3, usize)>,
+    drift: VecDeque<f32>,
+    total: Vec3,
+    selected: Option<Vec3>,
+}
+
+impl MomentumPanel {
+    pub fn new() -> MomentumPanel {
+        MomentumPanel {
+            visible: false,
+            reference: None,
+            drift: VecDeque::with_capacity(HISTORY),
+            total: Vec3::ZERO,
+            selected: None,
+        }
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World, statistics: &Statistics, selected: Option<Entity>) {
+        self.total = statistics.angular_momentum;
+        self.selected =
+            selected.and_then(|entity| diagnostics::angular_momentum_about_nucleus(world, entity));
+
+        let count = world.particle_count();
+        let reference = match self.reference {
+            Some((reference, n)) if n == count => reference,
+            _ => {
+                self.reference = Some((self.total, count));
+                self.drift.clear();
+                self.total
+            }
+        };
+        let scale = reference.length();
+        let drift = if scale > 0. {
+            (self.total - reference).length() / scale
+        } else {
+            0.
+        };
+        if self.drift.len() == HISTORY {
+            self.drift.pop_front();
+        }
+        self.drift.push_back(drift);
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(340.), theme.scaled(200.));
+        let x = theme.scaled(690.);
+        let y = screen_height() - height - theme.scaled(10.);
+        let vector = |v: Vec3| format!("({:.2e}, {:.2e}, {:.2e})", v.x, v.y, v.z);
+        dock::egui_window("momentum.title", Rect::new(x, y, width, height))
+            .open(&mut self.visible)
+            .show(ctx, |ui| {
+                ui.label(trf("momentum.total", &[&vector(self.total)]));
+                match self.selected {
+                    Some(l) => ui.label(trf("momentum.selected", &[&vector(l)])),
+                    None => ui.label(tr("momentum.no_selection")),
+                };
+                let latest = self.drift.back().copied().unwrap_or(0.);
+                ui.colored_label(
+                    gui::color(theme.warning),
+                    trf("momentum.drift", &[&format!("{:.3}", latest * 100.)]),
+                );
+                egui::plot::Plot::new("momentum_drift")
+                    .height(100.)
+                    .include_x(0.)
+                    .include_x((HISTORY - 1) as f64)
+                    .include_y(0.)
+                    .show_x(false)
+                    .y_axis_formatter(|d, _| format!("{:.1e}", d))
+                    .show(ui, |plot| {
+                        let points = self
+                            .drift
+                            .iter()
+                            .enumerate()
+                            .map(|(i, d)| [i as f64, *d as f64])
+                            .collect();
+                        plot.line(gui::line(tr("momentum.title"), points, theme.warning));
+                    });
+            });
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::units::SECONDS_PER_TICK;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Completed orbits kept for the decay readout and plot
+const HISTORY: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Orbit {
+    // In ticks
+    period: f32,
+    eccentricity: f32,
+    semi_major: f32,
+}
+
+// Period, frequency and eccentricity of the selected particle's orbit around
+// its nearest proton, measured by counting the turns it winds through: each
+// time the angle swept passes another 2π an orbit is complete, timed to a
+// fraction of a tick. The orbits measured so far are kept, so a shrinking
+// orbit, such as one losing energy to drag, shows as a falling period.
+pub struct OrbitMeter {
+    pub visible: bool,
+    entity: Option<Entity>,
+    // Ticks since the particle was selected
+    ticks: u64,
+    // Position relative to the nucleus on the last tick
+    last: Option<Vec3>,
+    // Angle swept since the last completed orbit, and when that orbit ended
+    winding: f32,
+    started: Option<f32>,
+    // Closest and furthest approach during the current orbit
+    range: (f32, f32),
+    orbits: VecDeque<Orbit>,
+}
+
+impl OrbitMeter {
+    pub fn new() -> OrbitMeter {
+        OrbitMeter {
+            visible: false,
+            entity: None,
+            ticks: 0,
+            last: None,
+            winding: 0.,
+            started: None,
+            range: (f32::INFINITY, 0.),
+            orbits: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.ticks = 0;
+        self.last = None;
+        self.winding = 0.;
+        self.started = None;
+        self.range = (f32::INFINITY, 0.);
+        self.orbits.clear();
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World, selected: Option<Entity>) {
+        if selected != self.entity {
+            self.entity = selected;
+            self.reset();
+        }
+        let particles = &world.particles;
+        let Some((i, nucleus)) = selected
+            .and_then(|entity| particles.slot(entity))
+            .and_then(|i| Some((i, particles.nearest(i, Particle::Proton)?)))
+        else {
+            self.reset();
+            return;
+        };
+        self.ticks += 1;
+        let r = particles.pos[i] - particles.pos[nucleus];
+        let distance = r.length();
+        self.range = (self.range.0.min(distance), self.range.1.max(distance));
+        let Some(last) = self.last.replace(r) else {
+            return;
+        };
+        // Measured around the angular momentum, so the sense of the orbit
+        // doesn't matter and a particle swinging back and forth winds nowhere
+        let v = particles.vel[i] - particles.vel[nucleus];
+        let Some(axis) = r.cross(v).try_normalize() else {
+            return;
+        };
+        let step = last.cross(r).dot(axis).atan2(last.dot(r));
+        let before = self.winding;
+        self.winding += step;
+        if self.winding < std::f32::consts::TAU {
+            return;
+        }
+        // Time at which the winding passed 2π, between the last tick and this
+        let fraction = (std::f32::consts::TAU - before) / step;
+        let at = (self.ticks - 1) as f32 + fraction;
+        self.winding -= std::f32::consts::TAU;
+        // The first crossing only starts the clock, as the orbit before it
+        // began wherever the particle happened to be selected
+        if let Some(started) = self.started.replace(at) {
+            let (near, far) = self.range;
+            if self.orbits.len() == HISTORY {
+                self.orbits.pop_front();
+            }
+            self.orbits.push_back(Orbit {
+                period: at - started,
+                eccentricity: (far - near) / (far + near),
+                semi_major: (far + near) / 2.,
+            });
+        }
+        self.range = (distance, distance);
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(340.), theme.scaled(240.));
+        let x = theme.scaled(340.);
+        let y = screen_height() - height - theme.scaled(10.);
+        let Rect {
+            x,
+            y,
+            w: width,
+            h: height,
+        } = dock::place("orbit.title", Rect::new(x, y, width, height));
+        draw_rectangle(x, y, width, height, theme.panel);
+        let size = theme.text_size(18.);
+        let left = x + 6.;
+        draw_text(tr("orbit.title"), left, y + size, size, theme.text);
+
+        let (Some(first), Some(last)) = (self.orbits.front(), self.orbits.back()) else {
+            let key = if self.last.is_some() {
+                "orbit.waiting"
+            } else {
+                "orbit.empty"
+            };
+            draw_text(tr(key), left, y + size * 2.2, size, theme.text);
+            return;
+        };
+        let seconds = last.period * SECONDS_PER_TICK;
+        let lines = [
+            trf(
+                "orbit.period",
+                &[&format!("{:.1}", last.period), &format!("{:.3e}", seconds)],
+            ),
+            trf("orbit.frequency", &[&format!("{:.3e}", 1. / seconds)]),
+            trf(
+                "orbit.shape",
+                &[
+                    &format!("{:.3}", last.eccentricity),
+                    &format!("{:.1}", last.semi_major),
+                ],
+            ),
+            trf(
+                "orbit.change",
+                &[
+                    &self.orbits.len(),
+                    &format!("{:+.2}", (last.period / first.period - 1.) * 100.),
+                ],
+            ),
+        ];
+        for (k, line) in lines.iter().enumerate() {
+            draw_text(line, left, y + size * (k as f32 + 2.2), size, theme.text);
+        }
+
+        // Period of each orbit in turn, scaled to the range seen
+        if self.orbits.len() < 2 {
+            return;
+        }
+        let (low, high) = self
+            .orbits
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(low, high), orbit| {
+                (low.min(orbit.period), high.max(orbit.period))
+            });
+        let span = (high - low).max(high * 1e-3);
+        let plot = Rect::new(
+            left,
+            y + size * 6.6,
+            width - 12.,
+            height - size * 6.6 - theme.scaled(8.),
+        );
+        let to_screen = |k: usize, period: f32| {
+            vec2(
+                plot.x + k as f32 / (self.orbits.len() - 1) as f32 * plot.w,
+                plot.y + plot.h - (period - low) / span * plot.h,
+            )
+        };
+        for k in 1..self.orbits.len() {
+            let a = to_screen(k - 1, self.orbits[k - 1].period);
+            let b = to_screen(k, self.orbits[k].period);
+            draw_line(a.x, a.y, b.x, b.y, theme.scaled(1.5), theme.selection);
+        }
+    }
+}
@@ -0,0 +1,1254 @@
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use crate::compare;
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::settings::{
+    Quality, Settings, MAX_BOUNDS, MAX_GRID_SLICES, MAX_SLOW_MOTION_DISTANCE, MAX_SLOW_MOTION_RATE,
+    MAX_TRAIL_LENGTH, MIN_BOUNDS, MIN_SLOW_MOTION_DISTANCE, MIN_SLOW_MOTION_RATE, QUALITY_PRESETS,
+    RESOLUTIONS,
+};
+use crate::theme::Theme;
+use crate::toast;
+use crate::unit_field::{self, UnitField};
+
+use rutherford_atomic_model::bubble::TimeBubble;
+use rutherford_atomic_model::detector::{Detector, DetectorShape};
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::foil::{self, Element, FOIL_SIDE, FOIL_SPACING};
+use rutherford_atomic_model::ghost;
+use rutherford_atomic_model::group::GroupId;
+use rutherford_atomic_model::magnets::{self, Magnet};
+use rutherford_atomic_model::particle::{
+    get_charge, get_mass, Particle, PARTICLES, PARTICLE_RADIUS,
+};
+use rutherford_atomic_model::sources::Source;
+use rutherford_atomic_model::systems::INTEGRATORS;
+use rutherford_atomic_model::units::{self, Quantity};
+use rutherford_atomic_model::world::World;
+
+const GROUP_COLORS: [Color; 6] = [RED, GREEN, SKYBLUE, ORANGE, VIOLET, PINK];
+
+pub struct GroupPanel {
+    pub visible: bool,
+    // Group that newly spawned particles are tagged into
+    pub active: Option<GroupId>,
+    new_name: String,
+}
+
+impl GroupPanel {
+    pub fn new() -> GroupPanel {
+        GroupPanel {
+            visible: false,
+            active: None,
+            new_name: String::new(),
+        }
+    }
+
+    pub fn draw(&mut self, world: &mut World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let stats = world.group_stats();
+        dock::window(
+            "groups.title",
+            hash!(),
+            vec2(10., theme.scaled(200.)),
+            vec2(theme.scaled(360.), theme.scaled(300.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            ui.input_text(hash!(), tr("groups.name"), &mut self.new_name);
+            if ui.button(None, tr("groups.add")) && !self.new_name.trim().is_empty() {
+                self.active = Some(world.add_group(self.new_name.trim()));
+                self.new_name.clear();
+            }
+            ui.separator();
+
+            let active = match self.active {
+                Some(id) => world.groups[id.0].name.as_str(),
+                None => tr("groups.none"),
+            };
+            ui.label(None, &trf("groups.spawning_into", &[&active]));
+            if ui.button(None, tr("groups.spawn_ungrouped")) {
+                self.active = None;
+            }
+            ui.separator();
+
+            for entry in &stats {
+                let group = &mut world.groups[entry.group.0];
+                ui.label(
+                    None,
+                    &trf(
+                        "groups.summary",
+                        &[
+                            &group.name,
+                            &entry.count,
+                            &format!("{:.3e}", entry.mean_kinetic_energy),
+                        ],
+                    ),
+                );
+                if ui.button(None, trf("groups.spawn_into", &[&group.name]).as_str()) {
+                    self.active = Some(entry.group);
+                }
+                ui.same_line(0.);
+                if ui.button(None, trf("groups.color", &[&group.name]).as_str()) {
+                    group.color = next_color(group.color);
+                }
+            }
+        });
+    }
+}
+
+fn next_color(color: Option<Color>) -> Option<Color> {
+    let index = match color {
+        Some(color) => GROUP_COLORS.iter().position(|c| *c == color),
+        None => None,
+    };
+    match index {
+        None => Some(GROUP_COLORS[0]),
+        Some(i) if i + 1 < GROUP_COLORS.len() => Some(GROUP_COLORS[i + 1]),
+        Some(_) => None,
+    }
+}
+
+// Foils are placed side by side along z, this far apart
+const FOIL_GAP: f32 = 150.;
+// Alphas start this far upstream of their foil
+const BEAM_START: f32 = 300.;
+// Deflections above this count as scattered in the foil summary
+const SCATTERED_ANGLE: f32 = 10.;
+
+pub struct FoilPanel {
+    pub visible: bool,
+    element: Element,
+    // Beam kinetic energy in MeV
+    energy: f32,
+    relativistic: bool,
+    energy_field: UnitField,
+}
+
+impl FoilPanel {
+    pub fn new() -> FoilPanel {
+        FoilPanel {
+            visible: false,
+            element: Element::Gold,
+            // Polonium-210, Geiger and Marsden's source
+            energy: 5.3,
+            relativistic: false,
+            energy_field: UnitField::new(),
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        units::speed_from_mev(get_mass(Particle::Alpha), self.energy, self.relativistic)
+    }
+
+    // One alpha per foil, aimed at a random point across the foil's patch.
+    fn fire(&self, world: &mut World) {
+        let speed = self.speed();
+        let reach = FOIL_SIDE as f32 * FOIL_SPACING / 2.;
+        let centers: Vec<Vec3> = world.foils.iter().map(|foil| foil.center).collect();
+        for center in centers {
+            let offset = vec3(
+                -BEAM_START,
+                rand::gen_range(-reach, reach),
+                rand::gen_range(-reach, reach),
+            );
+            let entity = world.spawn_particle(Particle::Alpha, Some(center + offset));
+            if let Some(slot) = world.particles.slot(entity) {
+                world.particles.vel[slot] = vec3(speed, 0., 0.);
+            }
+        }
+        toast::info(trf("toast.alphas_fired", &[&world.foils.len()]));
+    }
+
+    pub fn draw(&mut self, world: &mut World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        // Tagged alphas, scattered alphas and summed deflection per foil
+        let mut summary = vec![(0, 0, 0.); world.foils.len()];
+        for (entity, tag) in world.interactions.iter() {
+            let Some(slot) = world.particles.slot(entity) else {
+                continue;
+            };
+            let vel = world.particles.vel[slot];
+            let deflection = if vel == Vec3::ZERO {
+                0.
+            } else {
+                Vec3::X.angle_between(vel).to_degrees()
+            };
+            let entry = &mut summary[tag.foil];
+            entry.0 += 1;
+            if deflection > SCATTERED_ANGLE {
+                entry.1 += 1;
+            }
+            entry.2 += deflection;
+        }
+
+        dock::window(
+            "foils.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(200.)),
+            vec2(theme.scaled(360.), theme.scaled(450.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let element = trf(
+                "foils.element",
+                &[
+                    &tr(element_key(self.element)),
+                    &self.element.atomic_number(),
+                ],
+            );
+            if ui.button(None, element.as_str()) {
+                self.element = self.element.next();
+            }
+            if ui.button(None, tr("foils.add")) {
+                let center = vec3(0., 0., FOIL_GAP * world.foils.len() as f32);
+                foil::add_foil(world, self.element, center);
+            }
+            ui.slider(hash!(), tr("foils.energy"), 0.5..20.0, &mut self.energy);
+            let relativistic = self.relativistic;
+            self.energy_field.ui(
+                ui,
+                hash!(),
+                &mut self.energy,
+                |text| beam_energy(text, relativistic),
+                |energy| format!("{:.3} MeV", energy),
+            );
+            ui.checkbox(hash!(), tr("foils.relativistic"), &mut self.relativistic);
+            let speed = self.speed();
+            ui.label(
+                None,
+                &trf(
+                    "foils.speed",
+                    &[
+                        &format!("{:.3}", speed),
+                        &format!("{:.4}", speed / units::SPEED_OF_LIGHT),
+                    ],
+                ),
+            );
+            let alpha = (get_mass(Particle::Alpha), get_charge(Particle::Alpha));
+            let nucleus = |element: Element| {
+                (
+                    get_mass(Particle::Proton) * element.mass_number() as f32,
+                    get_charge(Particle::Proton) * element.atomic_number() as f32,
+                )
+            };
+            if let Some(distance) =
+                units::closest_approach(self.energy, alpha, nucleus(self.element))
+            {
+                ui.label(
+                    None,
+                    &trf(
+                        "foils.closest",
+                        &[&self.element.symbol(), &format!("{:.1}", distance)],
+                    ),
+                );
+            }
+            if ui.button(None, tr("foils.fire")) {
+                self.fire(world);
+            }
+            ui.separator();
+
+            if world.foils.is_empty() {
+                ui.label(None, tr("foils.none"));
+            }
+            for (foil, (tagged, scattered, deflection)) in world.foils.iter().zip(&summary) {
+                let mean = if *tagged > 0 {
+                    deflection / *tagged as f32
+                } else {
+                    0.
+                };
+                ui.label(
+                    None,
+                    &trf(
+                        "foils.summary",
+                        &[
+                            &foil.element.symbol(),
+                            &foil.element.atomic_number(),
+                            tagged,
+                            scattered,
+                            &format!("{:.1}", mean),
+                        ],
+                    ),
+                );
+            }
+        });
+    }
+}
+
+// Alpha energy in MeV from what was typed into the energy field, which may
+// give a speed instead and name the alpha, as in "5 MeV alpha" or "0.05c".
+fn beam_energy(text: &str, relativistic: bool) -> Option<f32> {
+    let (quantity, particle) = units::parse_beam(text, Quantity::Energy)?;
+    if particle.is_some_and(|particle| particle != Particle::Alpha) {
+        return None;
+    }
+    let mass = get_mass(Particle::Alpha);
+    match quantity {
+        Quantity::Energy(energy) if energy > 0. => Some(energy),
+        Quantity::Speed(speed) if speed > 0. && speed < units::SPEED_OF_LIGHT => {
+            Some(units::kinetic_mev(mass, speed, relativistic))
+        }
+        _ => None,
+    }
+}
+
+fn element_key(element: Element) -> &'static str {
+    match element {
+        Element::Aluminium => "element.aluminium",
+        Element::Gold => "element.gold",
+        Element::Platinum => "element.platinum",
+    }
+}
+
+// Screening length the checkbox starts from, about half the spacing of foil
+// nuclei so neighbouring atoms barely feel each other
+const DEFAULT_SCREENING: f32 = FOIL_SPACING / 2.;
+
+// Grid of checkboxes, one per species pair, for switching pair forces off,
+// the screening and size of every charge, and the force law itself.
+pub struct InteractionPanel {
+    pub visible: bool,
+    screening_field: UnitField,
+    radius_field: UnitField,
+}
+
+impl InteractionPanel {
+    pub fn new() -> InteractionPanel {
+        InteractionPanel {
+            visible: false,
+            screening_field: UnitField::new(),
+            radius_field: UnitField::new(),
+        }
+    }
+
+    pub fn draw(&mut self, world: &mut World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        dock::window(
+            "interactions.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(200.)),
+            vec2(theme.scaled(340.), theme.scaled(580.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let matrix = &mut world.pair_forces;
+            for (i, row) in PARTICLES.iter().enumerate() {
+                ui.label(None, &format!("{:>2}:", row.symbol()));
+                for (j, column) in PARTICLES.iter().enumerate() {
+                    ui.same_line(0.);
+                    let mut enabled = matrix.allows(*row, *column);
+                    ui.checkbox(hash!(("interaction", i, j)), column.symbol(), &mut enabled);
+                    if enabled != matrix.allows(*row, *column) {
+                        matrix.set(*row, *column, enabled);
+                    }
+                }
+            }
+            ui.separator();
+            ui.label(None, tr("interactions.note"));
+            if !matrix.all_enabled() && ui.button(None, tr("interactions.reset")) {
+                *matrix = Default::default();
+            }
+            ui.separator();
+            let model = &mut world.force_model;
+            let mut screened = model.screening.is_some();
+            ui.checkbox(hash!(), tr("interactions.screening"), &mut screened);
+            if screened != model.screening.is_some() {
+                model.screening = screened.then_some(DEFAULT_SCREENING);
+            }
+            if let Some(length) = &mut model.screening {
+                ui.slider(
+                    hash!(),
+                    tr("interactions.screening_length"),
+                    5.0..500.0,
+                    length,
+                );
+                self.screening_field.ui(
+                    ui,
+                    hash!(),
+                    length,
+                    unit_field::length,
+                    unit_field::format_length,
+                );
+            }
+            let mut finite = model.nuclear_radius.is_some();
+            ui.checkbox(hash!(), tr("interactions.finite_size"), &mut finite);
+            if finite != model.nuclear_radius.is_some() {
+                model.nuclear_radius = finite.then_some(PARTICLE_RADIUS);
+            }
+            if let Some(radius) = &mut model.nuclear_radius {
+                ui.slider(
+                    hash!(),
+                    tr("interactions.nuclear_radius"),
+                    0.5..50.0,
+                    radius,
+                );
+                self.radius_field.ui(
+                    ui,
+                    hash!(),
+                    radius,
+                    unit_field::length,
+                    unit_field::format_length,
+                );
+            }
+            ui.separator();
+            let law = trf("interactions.law", &[&model.law.symbol()]);
+            if ui.button(None, law.as_str()) {
+                model.law = model.law.next();
+            }
+            ui.slider(
+                hash!(),
+                tr("interactions.strength"),
+                0.1..10.0,
+                &mut model.strength,
+            );
+            if !model.is_coulomb() {
+                ui.label(None, tr("interactions.modified"));
+                if ui.button(None, tr("interactions.coulomb")) {
+                    *model = Default::default();
+                }
+            }
+            ui.separator();
+            let names: Vec<&str> = INTEGRATORS
+                .iter()
+                .map(|integrator| tr(compare::integrator_key(*integrator)))
+                .collect();
+            let mut index = INTEGRATORS
+                .iter()
+                .position(|integrator| *integrator == world.integrator)
+                .unwrap_or(0);
+            ui.combo_box(hash!(), tr("interactions.integrator"), &names, &mut index);
+            world.integrator = INTEGRATORS[index];
+            ui.label(None, tr(compare::integrator_note_key(world.integrator)));
+        });
+    }
+}
+
+pub struct DetectorPanel {
+    pub visible: bool,
+    // Show the angular distribution chart
+    pub plot: bool,
+    distance_field: UnitField,
+    extent_field: UnitField,
+}
+
+impl DetectorPanel {
+    pub fn new() -> DetectorPanel {
+        DetectorPanel {
+            visible: false,
+            plot: false,
+            distance_field: UnitField::new(),
+            extent_field: UnitField::new(),
+        }
+    }
+
+    // Returns true when a report is asked for.
+    pub fn draw(&mut self, world: &mut World, theme: &Theme) -> bool {
+        if !self.visible {
+            return false;
+        }
+
+        let mut report = false;
+        dock::window(
+            "detector.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(200.)),
+            vec2(theme.scaled(320.), theme.scaled(300.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let mut enabled = world.detector.is_some();
+            ui.checkbox(hash!(), tr("detector.enabled"), &mut enabled);
+            if enabled != world.detector.is_some() {
+                world.detector = enabled.then(|| Detector::new(DetectorShape::Plate));
+            }
+            let Some(detector) = &mut world.detector else {
+                return;
+            };
+
+            let shape = match detector.shape {
+                DetectorShape::Plate => tr("detector.plate"),
+                DetectorShape::Cylinder => tr("detector.cylinder"),
+                DetectorShape::Sphere => tr("detector.sphere"),
+            };
+            if ui.button(None, shape) {
+                detector.shape = match detector.shape {
+                    DetectorShape::Plate => DetectorShape::Cylinder,
+                    DetectorShape::Cylinder => DetectorShape::Sphere,
+                    DetectorShape::Sphere => DetectorShape::Plate,
+                };
+            }
+            ui.slider(
+                hash!(),
+                tr("detector.distance"),
+                20.0..1000.0,
+                &mut detector.distance,
+            );
+            self.distance_field.ui(
+                ui,
+                hash!(),
+                &mut detector.distance,
+                unit_field::length,
+                unit_field::format_length,
+            );
+            ui.slider(
+                hash!(),
+                tr("detector.extent"),
+                10.0..1000.0,
+                &mut detector.extent,
+            );
+            self.extent_field.ui(
+                ui,
+                hash!(),
+                &mut detector.extent,
+                unit_field::length,
+                unit_field::format_length,
+            );
+            let mut bins = detector.bins as f32;
+            ui.slider(hash!(), tr("detector.bins"), 2.0..90.0, &mut bins);
+            detector.bins = bins.round() as usize;
+            ui.checkbox(hash!(), tr("detector.show_plot"), &mut self.plot);
+            ui.label(None, &trf("detector.hits", &[&detector.hits.len()]));
+            if ui.button(None, tr("detector.clear")) {
+                detector.clear();
+            }
+            ui.same_line(0.);
+            report = ui.button(None, tr("detector.report"));
+        });
+        report
+    }
+}
+
+// Spawns ghosts of the selected particle: one carrying straight on and one on
+// the ideal orbit around its nearest nucleus, see `ghost`.
+pub struct GhostPanel {
+    pub visible: bool,
+}
+
+impl GhostPanel {
+    pub fn new() -> GhostPanel {
+        GhostPanel { visible: false }
+    }
+
+    pub fn draw(&mut self, world: &mut World, selected: Option<Entity>, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        dock::window(
+            "ghosts.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(580.)),
+            vec2(theme.scaled(300.), theme.scaled(170.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            ui.label(None, &trf("ghosts.count", &[&world.ghosts.len()]));
+            let Some(entity) = selected else {
+                ui.label(None, tr("ghosts.select"));
+                return;
+            };
+            let mut spawned = None;
+            if ui.button(None, tr("ghosts.straight")) {
+                spawned = Some(ghost::straight(world, entity));
+            }
+            if ui.button(None, tr("ghosts.rutherford")) {
+                spawned = Some(ghost::rutherford(world, entity));
+            }
+            match spawned {
+                Some(Some(ghost)) => world.ghosts.push(ghost),
+                Some(None) => toast::warning(tr("ghosts.no_orbit").to_string()),
+                None => {}
+            }
+            if ui.button(None, tr("ghosts.clear")) {
+                world.ghosts.clear();
+            }
+        });
+    }
+}
+
+// Radius a new time bubble starts with, enough to hold a nucleus and the
+// electrons around it
+const DEFAULT_BUBBLE_RADIUS: f32 = 30.;
+
+// Places spheres in which time runs slower or stands still, see
+// `bubble::TimeBubble`.
+pub struct BubblePanel {
+    pub visible: bool,
+    // Radius entry for each bubble
+    radius_fields: Vec<UnitField>,
+}
+
+impl BubblePanel {
+    pub fn new() -> BubblePanel {
+        BubblePanel {
+            visible: false,
+            radius_fields: Vec::new(),
+        }
+    }
+
+    pub fn draw(&mut self, world: &mut World, selected: Option<Entity>, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.radius_fields
+            .resize_with(world.bubbles.len(), UnitField::new);
+        dock::window(
+            "bubbles.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(580.)),
+            vec2(theme.scaled(300.), theme.scaled(300.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let center = selected
+                .and_then(|entity| world.particles.slot(entity))
+                .map(|slot| world.particles.pos[slot]);
+            let add = match center {
+                Some(_) => tr("bubbles.add_selected"),
+                None => tr("bubbles.add_origin"),
+            };
+            if ui.button(None, add) {
+                world.bubbles.push(TimeBubble {
+                    center: center.unwrap_or(Vec3::ZERO),
+                    radius: DEFAULT_BUBBLE_RADIUS,
+                    rate: 0.,
+                });
+            }
+            if !world.bubbles.is_empty() && ui.button(None, tr("bubbles.clear")) {
+                world.bubbles.clear();
+            }
+            self.radius_fields
+                .resize_with(world.bubbles.len(), UnitField::new);
+            let mut removed = None;
+            for (i, bubble) in world.bubbles.iter_mut().enumerate() {
+                ui.separator();
+                let c = bubble.center;
+                ui.label(
+                    None,
+                    &trf(
+                        "bubbles.entry",
+                        &[
+                            &(i + 1),
+                            &format!("{:.0}", c.x),
+                            &format!("{:.0}", c.y),
+                            &format!("{:.0}", c.z),
+                        ],
+                    ),
+                );
+                ui.slider(
+                    hash!(("bubble_radius", i)),
+                    tr("bubbles.radius"),
+                    5.0..300.0,
+                    &mut bubble.radius,
+                );
+                self.radius_fields[i].ui(
+                    ui,
+                    hash!(("bubble_radius_field", i)),
+                    &mut bubble.radius,
+                    unit_field::length,
+                    unit_field::format_length,
+                );
+                ui.slider(
+                    hash!(("bubble_rate", i)),
+                    tr("bubbles.rate"),
+                    0.0..1.0,
+                    &mut bubble.rate,
+                );
+                if ui.button(None, tr("bubbles.remove")) {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                world.bubbles.remove(i);
+                self.radius_fields.remove(i);
+            }
+        });
+    }
+}
+
+// Distance between the deflection plates the panel adds
+const PLATE_GAP: f32 = 100.;
+// Surface charge is shown per this many square units
+const DENSITY_AREA: f32 = 1e4;
+
+// Adds fixed charge distributions, see `sources::Source`, and sets their
+// charge. For a capacitor it also measures the charge-to-mass ratio of the
+// selected particle from how hard the field pushes it, as in an e/m
+// experiment.
+pub struct SourcePanel {
+    pub visible: bool,
+    fields: Vec<SourceFields>,
+}
+
+// Typed entries for one source: the charge, or for a capacitor the plate
+// separation, area and voltage
+struct SourceFields {
+    charge: UnitField,
+    separation: UnitField,
+    area: UnitField,
+    voltage: UnitField,
+}
+
+impl SourceFields {
+    fn new() -> SourceFields {
+        SourceFields {
+            charge: UnitField::new(),
+            separation: UnitField::new(),
+            area: UnitField::new(),
+            voltage: UnitField::new(),
+        }
+    }
+}
+
+impl SourcePanel {
+    pub fn new() -> SourcePanel {
+        SourcePanel {
+            visible: false,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn draw(&mut self, world: &mut World, selected: Option<Entity>, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let e = get_charge(Particle::Proton);
+        let probe = selected
+            .and_then(|entity| world.particles.slot(entity))
+            .map(|slot| {
+                let particles = &world.particles;
+                (
+                    particles.pos[slot],
+                    particles.acc[slot],
+                    particles.charge[slot] / particles.mass[slot],
+                )
+            });
+        self.fields
+            .resize_with(world.sources.len(), SourceFields::new);
+        dock::window(
+            "sources.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+            vec2(theme.scaled(320.), theme.scaled(460.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            if ui.button(None, tr("sources.add_plates")) {
+                // Opposite charges above and below the beam line along x,
+                // whose fields add up between the plates and cancel outside
+                let density = 10. * e / DENSITY_AREA;
+                for (side, sign) in [(1., 1.), (-1., -1.)] {
+                    world.sources.push(Source::Plane {
+                        point: vec3(0., side * PLATE_GAP / 2., 0.),
+                        normal: Vec3::Y,
+                        density: sign * density,
+                    });
+                }
+            }
+            if ui.button(None, tr("sources.add_ring")) {
+                world.sources.push(Source::Ring {
+                    center: Vec3::ZERO,
+                    normal: Vec3::X,
+                    radius: 50.,
+                    charge: 20. * e,
+                });
+            }
+            if ui.button(None, tr("sources.add_shell")) {
+                world.sources.push(Source::Shell {
+                    center: Vec3::ZERO,
+                    radius: 80.,
+                    charge: 20. * e,
+                });
+            }
+            if ui.button(None, tr("sources.add_capacitor")) {
+                world.sources.push(Source::Capacitor {
+                    center: Vec3::ZERO,
+                    normal: Vec3::Y,
+                    separation: PLATE_GAP,
+                    area: 4e4,
+                    voltage: 2e6,
+                });
+            }
+            if !world.sources.is_empty() && ui.button(None, tr("sources.clear")) {
+                world.sources.clear();
+            }
+            self.fields
+                .resize_with(world.sources.len(), SourceFields::new);
+            let mut removed = None;
+            let magnets = &world.magnets;
+            for (i, source) in world.sources.iter_mut().enumerate() {
+                ui.separator();
+                let inside = probe.filter(|(pos, _, _)| source.between_plates(*pos));
+                let (key, charge, scale) = match source {
+                    Source::Plane { density, .. } => ("sources.plane", density, e / DENSITY_AREA),
+                    Source::Ring { charge, .. } => ("sources.ring", charge, e),
+                    Source::Shell { charge, .. } => ("sources.shell", charge, e),
+                    Source::Capacitor {
+                        center,
+                        normal,
+                        separation,
+                        area,
+                        voltage,
+                    } => {
+                        ui.label(None, &trf("sources.capacitor", &[&(i + 1)]));
+                        ui.slider(
+                            hash!(("capacitor_separation", i)),
+                            tr("sources.separation"),
+                            10.0..400.0,
+                            separation,
+                        );
+                        let fields = &mut self.fields[i];
+                        fields.separation.ui(
+                            ui,
+                            hash!(("capacitor_separation_field", i)),
+                            separation,
+                            unit_field::length,
+                            unit_field::format_length,
+                        );
+                        ui.slider(
+                            hash!(("capacitor_area", i)),
+                            tr("sources.area"),
+                            1e3..2e5,
+                            area,
+                        );
+                        fields.area.ui(
+                            ui,
+                            hash!(("capacitor_area_field", i)),
+                            area,
+                            unit_field::area,
+                            unit_field::format_area,
+                        );
+                        fields.voltage.ui(
+                            ui,
+                            hash!(("capacitor_voltage", i)),
+                            voltage,
+                            voltage_of,
+                            |volts| format!("{} V", volts),
+                        );
+                        let metres = *separation * units::METRES_PER_UNIT;
+                        let volts_per_metre = *voltage / metres;
+                        ui.label(
+                            None,
+                            &trf("sources.field", &[&format!("{:.3e}", volts_per_metre)]),
+                        );
+                        // With a magnetic field across the plates this is a
+                        // velocity selector: charges moving along E × B at
+                        // E / B feel no net force, whatever their charge
+                        let field = magnets::field_at(magnets, *center);
+                        let across = (field - *normal * field.dot(*normal)).length();
+                        if across > 0. {
+                            let speed = volts_per_metre.abs() / across;
+                            ui.label(
+                                None,
+                                &trf(
+                                    "sources.selector",
+                                    &[
+                                        &format!("{:.3e}", speed),
+                                        &format!("{:.4}", speed / units::LIGHT_METRES_PER_SECOND),
+                                    ],
+                                ),
+                            );
+                        }
+                        if let Some((_, acc, expected)) = inside {
+                            let field = units::field_from_volts(*voltage, *separation);
+                            let measured = acc.dot(*normal) / field;
+                            ui.label(
+                                None,
+                                &trf(
+                                    "sources.charge_to_mass",
+                                    &[&format!("{:.3e}", measured), &format!("{:.3e}", expected)],
+                                ),
+                            );
+                        }
+                        if ui.button(None, tr("sources.remove")) {
+                            removed = Some(i);
+                        }
+                        continue;
+                    }
+                };
+                ui.label(None, &trf(key, &[&(i + 1)]));
+                let mut shown = *charge / scale;
+                let before = shown;
+                ui.slider(
+                    hash!(("source_charge", i)),
+                    tr("sources.charge"),
+                    -50.0..50.0,
+                    &mut shown,
+                );
+                if shown != before {
+                    *charge = shown * scale;
+                }
+                // On the charge itself, so the division above does not
+                // round away what was typed
+                self.fields[i].charge.ui(
+                    ui,
+                    hash!(("source_charge_field", i)),
+                    charge,
+                    |text| unit_field::charge(text).map(|charge| charge * scale),
+                    |charge| unit_field::format_charge(charge / scale),
+                );
+                if ui.button(None, tr("sources.remove")) {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                world.sources.remove(i);
+                self.fields.remove(i);
+            }
+        });
+    }
+}
+
+fn voltage_of(text: &str) -> Option<f32> {
+    match units::parse_quantity(text, Quantity::Voltage)? {
+        Quantity::Voltage(volts) => Some(volts),
+        _ => None,
+    }
+}
+
+// Strength of new magnets, in tesla, and the range of the slider in powers
+// of ten. Nuclear distances and times take enormous fields to bend a track
+// visibly: at this strength an electron at a tenth of light speed circles
+// about 20 fm across.
+const DEFAULT_FIELD: f32 = 1e10;
+const FIELD_EXPONENTS: std::ops::Range<f32> = 6.0..13.0;
+// Gap between the two coils of a magnetic mirror
+const MIRROR_GAP: f32 = 400.;
+
+pub struct MagnetPanel {
+    pub visible: bool,
+    // Whether tracks curling in the field are labeled with their radius and
+    // mass-to-charge ratio, see `spectrometer::draw_orbits`
+    pub label_orbits: bool,
+    // Field strength entry for each magnet
+    field_entries: Vec<UnitField>,
+}
+
+impl MagnetPanel {
+    pub fn new() -> MagnetPanel {
+        MagnetPanel {
+            visible: false,
+            label_orbits: true,
+            field_entries: Vec::new(),
+        }
+    }
+
+    pub fn draw(&mut self, world: &mut World, selected: Option<Entity>, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let probe = selected
+            .and_then(|entity| world.particles.slot(entity))
+            .map(|slot| {
+                let particles = &world.particles;
+                let field = magnets::field_at(&world.magnets, particles.pos[slot]);
+                let omega = magnets::gyration(field, particles.charge[slot], particles.mass[slot]);
+                let vel = particles.vel[slot];
+                // Only the part of the velocity across the field gyrates
+                let across = match field.try_normalize() {
+                    Some(dir) => (vel - dir * vel.dot(dir)).length(),
+                    None => 0.,
+                };
+                (field.length(), across / omega.length())
+            });
+        dock::window(
+            "magnets.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+            vec2(theme.scaled(320.), theme.scaled(420.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            if ui.button(None, tr("magnets.add_dipole")) {
+                world.magnets.push(Magnet::Dipole {
+                    center: Vec3::ZERO,
+                    axis: Vec3::X,
+                    radius: 40.,
+                    field: DEFAULT_FIELD,
+                });
+            }
+            if ui.button(None, tr("magnets.add_solenoid")) {
+                world.magnets.push(Magnet::Solenoid {
+                    center: Vec3::ZERO,
+                    axis: Vec3::X,
+                    radius: 80.,
+                    length: MIRROR_GAP,
+                    field: DEFAULT_FIELD,
+                });
+            }
+            if ui.button(None, tr("magnets.add_mirror")) {
+                // Two short coils on one axis: the field is weak between them
+                // and pinches at each, turning back particles that spiral in
+                // with too little speed along the axis
+                for side in [-1., 1.] {
+                    world.magnets.push(Magnet::Solenoid {
+                        center: vec3(side * MIRROR_GAP / 2., 0., 0.),
+                        axis: Vec3::X,
+                        radius: 80.,
+                        length: 60.,
+                        field: DEFAULT_FIELD,
+                    });
+                }
+            }
+            if !world.magnets.is_empty() && ui.button(None, tr("magnets.clear")) {
+                world.magnets.clear();
+            }
+            self.field_entries
+                .resize_with(world.magnets.len(), UnitField::new);
+            ui.checkbox(hash!(), tr("magnets.label_orbits"), &mut self.label_orbits);
+            if let Some((field, radius)) = probe {
+                ui.separator();
+                ui.label(
+                    None,
+                    &trf("magnets.probe_field", &[&format!("{:.3e}", field)]),
+                );
+                if radius.is_finite() {
+                    ui.label(None, &trf("magnets.larmor", &[&format!("{:.1}", radius)]));
+                }
+            }
+            let mut removed = None;
+            for (i, magnet) in world.magnets.iter_mut().enumerate() {
+                ui.separator();
+                let (key, axis, field) = match magnet {
+                    Magnet::Dipole { axis, field, .. } => ("magnets.dipole", axis, field),
+                    Magnet::Solenoid { axis, field, .. } => ("magnets.solenoid", axis, field),
+                };
+                ui.label(None, &trf(key, &[&(i + 1), &format!("{:.2e}", *field)]));
+                // Taken back from the exponent only when the slider moves,
+                // which keeps a typed field as it was typed
+                let mut exponent = field.log10();
+                let before = exponent;
+                ui.slider(
+                    hash!(("magnet_field", i)),
+                    tr("magnets.strength"),
+                    FIELD_EXPONENTS,
+                    &mut exponent,
+                );
+                if exponent != before {
+                    *field = 10f32.powf(exponent);
+                }
+                self.field_entries[i].ui(
+                    ui,
+                    hash!(("magnet_field_entry", i)),
+                    field,
+                    unit_field::magnetic_field,
+                    unit_field::format_magnetic_field,
+                );
+                if ui.button(None, tr("magnets.flip")) {
+                    *axis = -*axis;
+                }
+                if ui.button(None, tr("magnets.remove")) {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                world.magnets.remove(i);
+                self.field_entries.remove(i);
+            }
+        });
+    }
+}
+
+// macroquad 0.3 has no fullscreen toggle of its own, so go through miniquad.
+pub fn set_fullscreen(fullscreen: bool) {
+    // SAFETY: only called from the main loop, where no other reference to the
+    // internal GL context is alive.
+    let gl = unsafe { get_internal_gl() };
+    gl.quad_context.set_fullscreen(fullscreen);
+}
+
+pub struct DisplayPanel {
+    pub visible: bool,
+    bounds_field: UnitField,
+    slow_motion_field: UnitField,
+    // A setting changed since the last save
+    unsaved: bool,
+}
+
+impl DisplayPanel {
+    pub fn new() -> DisplayPanel {
+        DisplayPanel {
+            visible: false,
+            bounds_field: UnitField::new(),
+            slow_motion_field: UnitField::new(),
+            unsaved: false,
+        }
+    }
+
+    // Returns true when settings changed and should be saved: once a slider
+    // is let go or a text field left, not on every frame of the drag.
+    pub fn draw(&mut self, ctx: &egui::Context, settings: &mut Settings, theme: &Theme) -> bool {
+        let settled = !ctx.input(|input| input.pointer.any_down()) && !ctx.wants_keyboard_input();
+        if !self.visible {
+            return std::mem::take(&mut self.unsaved);
+        }
+
+        let before = *settings;
+        let (bounds_field, slow_motion_field) =
+            (&mut self.bounds_field, &mut self.slow_motion_field);
+        let (width, height) = (theme.scaled(300.), theme.scaled(1000.));
+        let default = Rect::new(theme.scaled(380.), theme.scaled(200.), width, height);
+        dock::egui_window("display.title", default)
+            .open(&mut self.visible)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.label(trf(
+                    "display.current",
+                    &[&screen_width().round(), &screen_height().round()],
+                ));
+                ui.checkbox(&mut settings.fullscreen, tr("display.fullscreen"));
+                ui.checkbox(&mut settings.vsync, tr("display.vsync"));
+                ui.label(tr("display.restart_note"));
+                egui::ComboBox::from_label(tr("display.resolution"))
+                    .selected_text(format!(
+                        "{} x {}",
+                        settings.window_width, settings.window_height
+                    ))
+                    .show_ui(ui, |ui| {
+                        for (width, height) in RESOLUTIONS {
+                            let current =
+                                (width, height) == (settings.window_width, settings.window_height);
+                            let label = format!("{} x {}", width, height);
+                            if ui.selectable_label(current, label).clicked() {
+                                settings.window_width = width;
+                                settings.window_height = height;
+                            }
+                        }
+                    });
+
+                ui.collapsing(tr("quality.title"), |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for preset in QUALITY_PRESETS {
+                            let quality = Quality::preset(preset);
+                            let current = quality == settings.quality;
+                            if ui
+                                .selectable_label(current, tr(preset.name_key()))
+                                .clicked()
+                            {
+                                settings.quality = quality;
+                            }
+                        }
+                    });
+                    let quality = &mut settings.quality;
+                    ui.add(
+                        egui::Slider::new(&mut quality.sphere_detail, 4..=32)
+                            .text(tr("quality.sphere_detail")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut quality.trail_smoothness, 0..=8)
+                            .text(tr("quality.trail_smoothness")),
+                    );
+                });
+
+                ui.collapsing(tr("environment.title"), |ui| {
+                    let skybox = trf("environment.skybox", &[&tr(settings.skybox.name_key())]);
+                    if ui.button(skybox).clicked() {
+                        settings.skybox = settings.skybox.next();
+                    }
+                    let fog = &mut settings.fog;
+                    ui.checkbox(&mut fog.enabled, tr("environment.fog"));
+                    ui.add(
+                        egui::Slider::new(&mut fog.start, 0.0..=2000.0)
+                            .text(tr("environment.fog_start")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut fog.end, 100.0..=5000.0)
+                            .text(tr("environment.fog_end")),
+                    );
+                    fog.end = fog.end.max(fog.start + 1.);
+                });
+
+                ui.collapsing(tr("trails.title"), |ui| {
+                    let trails = &mut settings.trails;
+                    ui.add(
+                        egui::Slider::new(&mut trails.length, 2..=MAX_TRAIL_LENGTH)
+                            .text(tr("trails.length")),
+                    );
+                    ui.checkbox(&mut trails.fade, tr("trails.fade"));
+                    ui.checkbox(&mut trails.persistent, tr("trails.persistent"));
+                    ui.checkbox(&mut trails.electron, tr("trails.electron"));
+                    ui.checkbox(&mut trails.proton, tr("trails.proton"));
+                    ui.checkbox(&mut trails.neutron, tr("trails.neutron"));
+                    ui.checkbox(&mut trails.alpha, tr("trails.alpha"));
+                });
+
+                ui.collapsing(tr("space.title"), |ui| {
+                    let grid = &mut settings.grid;
+                    ui.add(
+                        egui::Slider::new(&mut grid.slices, 1..=MAX_GRID_SLICES)
+                            .text(tr("space.grid_lines")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut grid.spacing, 1.0..=100.0)
+                            .text(tr("space.grid_spacing")),
+                    );
+                    let bounds = &mut settings.bounds;
+                    ui.checkbox(&mut bounds.enabled, tr("space.bounds"));
+                    ui.add(
+                        egui::Slider::new(&mut bounds.half_size, MIN_BOUNDS..=MAX_BOUNDS)
+                            .text(tr("space.bounds_size")),
+                    );
+                    bounds_field.egui(
+                        ui,
+                        &mut bounds.half_size,
+                        |text| {
+                            unit_field::length(text)
+                                .filter(|length| (MIN_BOUNDS..=MAX_BOUNDS).contains(length))
+                        },
+                        unit_field::format_length,
+                    );
+                });
+
+                ui.collapsing(tr("slowmo.title"), |ui| {
+                    let slow_motion = &mut settings.slow_motion;
+                    ui.checkbox(&mut slow_motion.enabled, tr("slowmo.enabled"));
+                    ui.add(
+                        egui::Slider::new(
+                            &mut slow_motion.distance,
+                            MIN_SLOW_MOTION_DISTANCE..=MAX_SLOW_MOTION_DISTANCE,
+                        )
+                        .logarithmic(true)
+                        .text(tr("slowmo.distance")),
+                    );
+                    slow_motion_field.egui(
+                        ui,
+                        &mut slow_motion.distance,
+                        |text| {
+                            unit_field::length(text).filter(|length| {
+                                (MIN_SLOW_MOTION_DISTANCE..=MAX_SLOW_MOTION_DISTANCE)
+                                    .contains(length)
+                            })
+                        },
+                        unit_field::format_length,
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut slow_motion.rate,
+                            MIN_SLOW_MOTION_RATE..=MAX_SLOW_MOTION_RATE,
+                        )
+                        .logarithmic(true)
+                        .text(tr("slowmo.rate")),
+                    );
+                    ui.label(tr("slowmo.note"));
+                });
+            });
+
+        if settings.fullscreen != before.fullscreen {
+            set_fullscreen(settings.fullscreen);
+        }
+        let resized = (settings.window_width, settings.window_height)
+            != (before.window_width, before.window_height);
+        if resized && !settings.fullscreen {
+            request_new_screen_size(settings.window_width as f32, settings.window_height as f32);
+        }
+        self.unsaved |= *settings != before;
+        settled && std::mem::take(&mut self.unsaved)
+    }
+}
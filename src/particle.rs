@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+use crate::ecs::{Entity, SparseIndex};
+use crate::species;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Particle {
+    Electron,
+    Proton,
+    Neutron,
+    // Helium nucleus, the projectile of Rutherford's experiment
+    Alpha,
+    Positron,
+    Antiproton,
+    // Index into the `species` registry
+    Custom(u16),
+}
+
+pub const PARTICLES: [Particle; 6] = [
+    Particle::Electron,
+    Particle::Proton,
+    Particle::Neutron,
+    Particle::Alpha,
+    Particle::Positron,
+    Particle::Antiproton,
+];
+
+impl Particle {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Particle::Electron => "e",
+            Particle::Proton => "p",
+            Particle::Neutron => "n",
+            Particle::Alpha => "α",
+            Particle::Positron => "e+",
+            Particle::Antiproton => "p̄",
+            Particle::Custom(_) => "*",
+        }
+    }
+
+    pub fn antiparticle(&self) -> Option<Particle> {
+        match self {
+            Particle::Electron => Some(Particle::Positron),
+            Particle::Positron => Some(Particle::Electron),
+            Particle::Proton => Some(Particle::Antiproton),
+            Particle::Antiproton => Some(Particle::Proton),
+            _ => None,
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Particle::Custom(_))
+    }
+
+    // Code for built-ins, the user's name for custom species.
+    pub fn name(&self) -> String {
+        match self {
+            Particle::Custom(index) => {
+                species::get(*index).map_or_else(|| self.code().to_string(), |s| s.name)
+            }
+            _ => self.code().to_string(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Particle::Electron => "electron",
+            Particle::Proton => "proton",
+            Particle::Neutron => "neutron",
+            Particle::Alpha => "alpha",
+            Particle::Positron => "positron",
+            Particle::Antiproton => "antiproton",
+            Particle::Custom(_) => "custom",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Particle> {
+        match code {
+            "electron" => Some(Particle::Electron),
+            "proton" => Some(Particle::Proton),
+            "neutron" => Some(Particle::Neutron),
+            "alpha" => Some(Particle::Alpha),
+            "positron" => Some(Particle::Positron),
+            "antiproton" => Some(Particle::Antiproton),
+            name => species::find(name),
+        }
+    }
+}
+
+pub const TRAIL_LENGTH: usize = 50;
+// Upper bound for "persistent" trails, so they cannot grow without limit
+pub const PERSISTENT_TRAIL_LIMIT: usize = 100_000;
+pub const PARTICLE_RADIUS: f32 = 2.;
+
+// Particle state is stored column-wise so the force and integration passes walk
+// contiguous arrays. All columns share the slot order of `index`.
+#[derive(Clone, Default, Debug)]
+pub struct Particles {
+    index: SparseIndex,
+    pub particle: Vec<Particle>,
+    pub mass: Vec<f32>,
+    pub charge: Vec<f32>,
+    pub pos: Vec<Vec3>,
+    pub vel: Vec<Vec3>,
+    pub acc: Vec<Vec3>,
+}
+
+impl Particles {
+    pub fn insert(&mut self, entity: Entity, particle: Particle, pos: Vec3) {
+        let (slot, new) = self.index.insert(entity);
+        if new {
+            self.particle.push(particle);
+            self.mass.push(get_mass(particle));
+            self.charge.push(get_charge(particle));
+            self.pos.push(pos);
+            self.vel.push(vec3(0., 0., 0.));
+            self.acc.push(vec3(0., 0., 0.));
+        } else {
+            self.particle[slot] = particle;
+            self.mass[slot] = get_mass(particle);
+            self.charge[slot] = get_charge(particle);
+            self.pos[slot] = pos;
+            self.vel[slot] = vec3(0., 0., 0.);
+            self.acc[slot] = vec3(0., 0., 0.);
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        let Some(slot) = self.index.remove(entity) else {
+            return false;
+        };
+        self.particle.swap_remove(slot);
+        self.mass.swap_remove(slot);
+        self.charge.swap_remove(slot);
+        self.pos.swap_remove(slot);
+        self.vel.swap_remove(slot);
+        self.acc.swap_remove(slot);
+        true
+    }
+
+    pub fn slot(&self, entity: Entity) -> Option<usize> {
+        self.index.slot(entity)
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        self.index.entities()
+    }
+
+    // Slot of the closest other particle of the given species.
+    pub fn nearest(&self, slot: usize, particle: Particle) -> Option<usize> {
+        let origin = self.pos[slot];
+        (0..self.len())
+            .filter(|j| *j != slot && self.particle[*j] == particle)
+            .min_by(|a, b| {
+                let da = self.pos[*a].distance_squared(origin);
+                let db = self.pos[*b].distance_squared(origin);
+                da.total_cmp(&db)
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Trail {
+    // Oldest first
+    pub points: VecDeque<Vec3>,
+}
+
+impl Trail {
+    pub fn new(pos: Vec3) -> Trail {
+        Trail {
+            points: VecDeque::from([pos]),
+        }
+    }
+
+    // Appends `pos` and drops the oldest points beyond `limit`; `None` keeps
+    // the whole trajectory up to `PERSISTENT_TRAIL_LIMIT`.
+    pub fn push(&mut self, pos: Vec3, limit: Option<usize>) {
+        if self.points.back() != Some(&pos) {
+            self.points.push_back(pos);
+        }
+        let limit = limit.unwrap_or(PERSISTENT_TRAIL_LIMIT).max(1);
+        while self.points.len() > limit {
+            self.points.pop_front();
+        }
+    }
+}
+
+pub fn get_charge(particle: Particle) -> f32 {
+    let q: f32 = 1.6 * 1e-19;
+    match particle {
+        Particle::Electron | Particle::Antiproton => -q,
+        Particle::Positron => q,
+        Particle::Proton => q,
+        Particle::Neutron => 0.,
+        Particle::Alpha => 2. * q,
+        Particle::Custom(index) => species::get(index).map_or(0., |s| s.charge),
+    }
+}
+
+pub fn get_mass(particle: Particle) -> f32 {
+    let em: f32 = 9.1 * 1e-31;
+    let pm: f32 = 1.6 * 1e-27;
+    match particle {
+        Particle::Electron | Particle::Positron => em,
+        Particle::Alpha => 4. * pm,
+        Particle::Custom(index) => species::get(index).map_or(pm, |s| s.mass),
+        _ => pm,
+    }
+}
+
+pub fn gen_random_vector(start: f32, end: f32) -> Vec3 {
+    let get_rand = || {
+        let num = gen_range(start, end);
+        if num == 0. {
+            return start;
+        }
+        num
+    };
+    vec3(get_rand(), get_rand(), get_rand())
+}
@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::gui;
+use crate::i18n::tr;
+use crate::theme::Theme;
+
+const HISTORY: usize = 2000;
+
+// Live plot of the selected particle's radial distance against its radial
+// momentum. Bound orbits trace closed loops; escaping particles run off.
+pub struct PhasePlot {
+    pub visible: bool,
+    entity: Option<Entity>,
+    points: VecDeque<(f32, f32)>,
+}
+
+impl PhasePlot {
+    pub fn new() -> PhasePlot {
+        PhasePlot {
+            visible: false,
+            entity: None,
+            points: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World, selected: Option<Entity>) {
+        if selected != self.entity {
+            self.entity = selected;
+            self.points.clear();
+        }
+        let Some(point) = selected.and_then(|entity| diagnostics::radial_phase(world, entity))
+        else {
+            return;
+        };
+        if self.points.len() == HISTORY {
+            self.points.pop_front();
+        }
+        self.points.push_back(point);
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(320.), theme.scaled(220.));
+        let x = theme.scaled(10.);
+        let y = screen_height() - height - theme.scaled(10.);
+        let mut open = true;
+        dock::egui_window("phase.title", Rect::new(x, y, width, height))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.points.len() < 2 {
+                    ui.label(tr("phase.empty"));
+                    return;
+                }
+                // r runs from zero, p is centred on the axis
+                egui::plot::Plot::new("phase_plot")
+                    .height(200.)
+                    .include_x(0.)
+                    .center_y_axis(true)
+                    .x_axis_formatter(|r, _| format!("r {:.0}", r))
+                    .y_axis_formatter(|p, _| format!("{:.1e}", p))
+                    .show(ui, |plot| {
+                        let points: Vec<[f64; 2]> = self
+                            .points
+                            .iter()
+                            .map(|(r, p)| [*r as f64, *p as f64])
+                            .collect();
+                        let last = points[points.len() - 1];
+                        plot.line(gui::line(tr("phase.title"), points, theme.selection));
+                        plot.points(
+                            egui::plot::Points::new(vec![last])
+                                .radius(3.)
+                                .color(gui::color(theme.warning)),
+                        );
+                    });
+            });
+        self.visible = open;
+    }
+}
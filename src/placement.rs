@@ -0,0 +1,196 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::particle::{Particle, Trail, PARTICLES};
+use rutherford_atomic_model::units::{self, Quantity};
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::selection;
+use crate::theme::Theme;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Place {
+    // Move the selected particle here
+    Move(Vec3),
+    Spawn(Particle, Vec3),
+}
+
+// Nearest point of a grid `spacing` apart that has a point at the origin.
+pub fn snap(pos: Vec3, spacing: f32) -> Vec3 {
+    if spacing > 0. {
+        (pos / spacing).round() * spacing
+    } else {
+        pos
+    }
+}
+
+// Puts `entity` at `pos` and starts its trail over, so the jump isn't drawn.
+fn move_to(world: &mut World, entity: Entity, pos: Vec3) {
+    let pos = world.constrain(pos);
+    if let Some(slot) = world.particles.slot(entity) {
+        world.particles.pos[slot] = pos;
+        world.trails.insert(entity, Trail::new(pos));
+    }
+}
+
+// Exact placement: dragging the selected particle with the mouse, optionally
+// snapped to the grid, and typing in coordinates to move it to or spawn at,
+// for textbook configurations such as an equilateral triangle of charges.
+pub struct Placement {
+    pub visible: bool,
+    pub snap: bool,
+    fields: [String; 3],
+    // Particle the fields were last filled in from
+    shown: Option<Entity>,
+    species: usize,
+    // Particle being dragged, and the normal of the plane through it that it
+    // is dragged in
+    dragging: Option<(Entity, Vec3)>,
+}
+
+impl Placement {
+    pub fn new() -> Placement {
+        Placement {
+            visible: false,
+            snap: false,
+            fields: Default::default(),
+            shown: None,
+            species: 0,
+            dragging: None,
+        }
+    }
+
+    // Drags the selected particle across the view while the panel is open.
+    // Returns true when a drag starts, before anything has moved.
+    pub fn drag(
+        &mut self,
+        world: &mut World,
+        selected: Option<Entity>,
+        camera: &Camera3D,
+        mouse_free: bool,
+        spacing: f32,
+    ) -> bool {
+        if !self.visible || !is_mouse_button_down(MouseButton::Left) {
+            self.dragging = None;
+            return false;
+        }
+        let Some((entity, normal)) = self.dragging else {
+            let start = mouse_free && is_mouse_button_pressed(MouseButton::Left);
+            let Some(entity) = selected.filter(|_| start) else {
+                return false;
+            };
+            // 2D mode keeps to the plane; otherwise the particle slides
+            // across the screen at its own depth
+            let normal = if world.planar {
+                Vec3::Z
+            } else {
+                (camera.target - camera.position).normalize()
+            };
+            self.dragging = Some((entity, normal));
+            return true;
+        };
+        let Some(slot) = world.particles.slot(entity) else {
+            self.dragging = None;
+            return false;
+        };
+        let pos = world.particles.pos[slot];
+        let (origin, dir) = selection::mouse_ray(camera);
+        let facing = dir.dot(normal);
+        if facing.abs() < 1e-4 {
+            return false;
+        }
+        let mut target = origin + dir * ((pos - origin).dot(normal) / facing);
+        if self.snap {
+            target = snap(target, spacing);
+        }
+        if target != pos {
+            move_to(world, entity, target);
+            self.shown = None;
+        }
+        false
+    }
+
+    // Returns a placement to make when one of the buttons is clicked.
+    pub fn draw(
+        &mut self,
+        world: &World,
+        selected: Option<Entity>,
+        spacing: f32,
+        theme: &Theme,
+    ) -> Option<Place> {
+        if !self.visible {
+            return None;
+        }
+        let slot = selected.and_then(|entity| world.particles.slot(entity));
+        if self.shown != selected {
+            if let Some(slot) = slot {
+                let pos = world.particles.pos[slot];
+                self.fields = [pos.x, pos.y, pos.z].map(|value| format!("{:.2}", value));
+            }
+            self.shown = selected;
+        }
+
+        let mut request = None;
+        dock::window(
+            "placement.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+            vec2(theme.scaled(300.), theme.scaled(300.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let snap = trf("placement.snap", &[&spacing]);
+            ui.checkbox(hash!(), &snap, &mut self.snap);
+            ui.label(None, tr("placement.drag"));
+            ui.separator();
+            let [x, y, z] = &mut self.fields;
+            ui.input_text(hash!(), "x", x);
+            ui.input_text(hash!(), "y", y);
+            ui.input_text(hash!(), "z", z);
+            let length = |text: &String| match units::parse_quantity(text, Quantity::Length)? {
+                Quantity::Length(length) => Some(length),
+                _ => None,
+            };
+            let pos = match self.fields.each_ref().map(length) {
+                [Some(x), Some(y), Some(z)] => Some(vec3(x, y, z)),
+                _ => None,
+            };
+            let Some(pos) = pos else {
+                ui.label(None, tr("placement.invalid"));
+                return;
+            };
+            if slot.is_some() && ui.button(None, tr("placement.move")) {
+                request = Some(Place::Move(pos));
+            }
+            let particle = PARTICLES[self.species];
+            if ui.button(None, trf("placement.species", &[&particle.name()]).as_str()) {
+                self.species = (self.species + 1) % PARTICLES.len();
+            }
+            if ui.button(None, tr("placement.spawn")) {
+                request = Some(Place::Spawn(particle, pos));
+            }
+        });
+        request
+    }
+
+    // Carries out `place`, returning the particle it spawned.
+    pub fn apply(
+        &mut self,
+        place: Place,
+        world: &mut World,
+        selected: Option<Entity>,
+    ) -> Option<Entity> {
+        match place {
+            Place::Move(pos) => {
+                if let Some(entity) = selected {
+                    move_to(world, entity, pos);
+                }
+                self.shown = None;
+                None
+            }
+            Place::Spawn(particle, pos) => Some(world.spawn_particle(particle, Some(pos))),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// `exp`, `ln` and `sin_cos` built from nothing but IEEE arithmetic, which rounds the
+// same everywhere. The standard library's versions call into the platform's
+// math library, whose last bits differ between systems, and the force laws
+// use them every tick, so deterministic mode could not promise the same
+// trajectories on every machine with them.
+//
+// Both reduce the argument to a small range and sum a short series there,
+// to within a couple of ulps of the correctly rounded result.
+
+use std::f32::consts::{FRAC_2_PI, LOG2_E, SQRT_2};
+
+// ln 2 split in two: the high part has its low bits clear, so k times it is
+// exact for any exponent k an f32 can have
+const LN2_HI: f32 = 0.693_145_75;
+const LN2_LO: f32 = 1.428_606_8e-6;
+// π / 2 split the same way
+const FRAC_PI_2_HI: f32 = 1.570_312_5;
+const FRAC_PI_2_LO: f32 = 4.838_268e-4;
+
+// 2^k for k in -126..=127, the range of normal exponents
+fn pow2(k: i32) -> f32 {
+    f32::from_bits(((k + 127) as u32) << 23)
+}
+
+pub fn exp(x: f32) -> f32 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 88.73 {
+        return f32::INFINITY;
+    }
+    if x < -103.98 {
+        return 0.;
+    }
+    // x = k ln 2 + r, |r| <= ln 2 / 2
+    let k = (x * LOG2_E).round();
+    let r = (x - k * LN2_HI) - k * LN2_LO;
+    // e^r to the r^7 term, beyond which the terms are below an ulp
+    let mut sum = 1. / 5040.;
+    for factor in [720., 120., 24., 6., 2., 1., 1.] {
+        sum = sum * r + 1. / factor;
+    }
+    // 2^k in two halves, as it may fall outside the normal range on its own
+    let k = k as i32;
+    let half = k / 2;
+    sum * pow2(half) * pow2(k - half)
+}
+
+pub fn ln(x: f32) -> f32 {
+    if x.is_nan() || x < 0. {
+        return f32::NAN;
+    }
+    if x == 0. {
+        return f32::NEG_INFINITY;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+    // Subnormals are brought up into the normal range first
+    let (x, shift) = if x < f32::MIN_POSITIVE {
+        (x * pow2(23), -23)
+    } else {
+        (x, 0)
+    };
+    // x = 2^e m, with m in [√2 / 2, √2)
+    let bits = x.to_bits();
+    let mut e = ((bits >> 23) & 0xff) as i32 - 127 + shift;
+    let mut m = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000);
+    if m > SQRT_2 {
+        m /= 2.;
+        e += 1;
+    }
+    // ln m = 2 atanh s with s = (m - 1) / (m + 1), |s| < 0.172, summed to
+    // the s^9 term
+    let f = m - 1.;
+    let s = f / (2. + f);
+    let s2 = s * s;
+    let mut sum = 1. / 9.;
+    for odd in [7., 5., 3., 1.] {
+        sum = sum * s2 + 1. / odd;
+    }
+    let e = e as f32;
+    e * LN2_HI + (2. * s * sum + e * LN2_LO)
+}
+
+// Sine and cosine together, as rotations want both. Exact enough for the
+// angles a scene uses; far from zero the reduction loses bits as the
+// standard library's does not.
+pub fn sin_cos(x: f32) -> (f32, f32) {
+    if !x.is_finite() {
+        return (f32::NAN, f32::NAN);
+    }
+    // x = k π / 2 + r, |r| <= π / 4
+    let k = (x * FRAC_2_PI).round();
+    let r = (x - k * FRAC_PI_2_HI) - k * FRAC_PI_2_LO;
+    let r2 = r * r;
+    // Both series to the r^9 and r^10 terms
+    let mut sin = 1. / 362_880.;
+    for factor in [-5040., 120., -6., 1.] {
+        sin = sin * r2 + 1. / factor;
+    }
+    let sin = sin * r;
+    let mut cos = -1. / 3_628_800.;
+    for factor in [40_320., -720., 24., -2., 1.] {
+        cos = cos * r2 + 1. / factor;
+    }
+    // Turn the quarter turns back on
+    match (k as i64).rem_euclid(4) {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    }
+}
@@ -0,0 +1,118 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Ready-made scenes shipped with the app, loaded by name from the console or
+// the presets panel.
+pub struct Preset {
+    pub code: &'static str,
+    pub caption_key: &'static str,
+    source: &'static str,
+}
+
+pub const PRESETS: [Preset; 5] = [
+    Preset {
+        code: "muonic_hydrogen",
+        caption_key: "preset.muonic_hydrogen",
+        source: include_str!("../presets/muonic_hydrogen.txt"),
+    },
+    Preset {
+        code: "gold_foil",
+        caption_key: "preset.gold_foil",
+        source: include_str!("../presets/gold_foil.txt"),
+    },
+    Preset {
+        code: "velocity_selector",
+        caption_key: "preset.velocity_selector",
+        source: include_str!("../presets/velocity_selector.txt"),
+    },
+    Preset {
+        code: "mass_spectrometer",
+        caption_key: "preset.mass_spectrometer",
+        source: include_str!("../presets/mass_spectrometer.txt"),
+    },
+    Preset {
+        code: "millikan",
+        caption_key: "preset.millikan",
+        source: include_str!("../presets/millikan.txt"),
+    },
+];
+
+pub fn find(code: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.code == code)
+}
+
+impl Preset {
+    fn scenario(&self) -> Scenario {
+        Scenario::parse(self.source).expect("built-in preset is valid")
+    }
+
+    // The preset as written, or perturbed by `seed`.
+    pub fn build(&self, seed: Option<u64>) -> World {
+        match seed {
+            Some(seed) => self.scenario().randomized(seed).build(),
+            None => self.scenario().build(),
+        }
+    }
+}
+
+// A load and a randomize button for each preset. The seed of the last
+// randomized load is shown so a variation can be brought back with
+// `preset <name> <seed>` in the console.
+pub struct PresetPanel {
+    pub visible: bool,
+    pub last_seed: Option<u64>,
+}
+
+impl PresetPanel {
+    pub fn new() -> PresetPanel {
+        PresetPanel {
+            visible: false,
+            last_seed: None,
+        }
+    }
+
+    // Returns the preset to load and its seed, if any, when a button is
+    // clicked.
+    pub fn draw(&mut self, theme: &Theme) -> Option<(&'static Preset, Option<u64>)> {
+        if !self.visible {
+            return None;
+        }
+        let mut chosen = None;
+        dock::window(
+            "presets.title",
+            hash!(),
+            vec2(theme.scaled(380.), theme.scaled(200.)),
+            vec2(
+                theme.scaled(340.),
+                theme.scaled(60. + 70. * PRESETS.len() as f32),
+            ),
+        )
+        .ui(&mut root_ui(), |ui| {
+            for (i, preset) in PRESETS.iter().enumerate() {
+                ui.label(None, preset.code);
+                if ui.button(None, tr("presets.load")) {
+                    chosen = Some((preset, None));
+                }
+                ui.same_line(0.);
+                if ui.button(None, tr("presets.randomize")) {
+                    chosen = Some((preset, Some(rand::rand() as u64)));
+                }
+                if i + 1 < PRESETS.len() {
+                    ui.separator();
+                }
+            }
+            if let Some(seed) = self.last_seed {
+                ui.separator();
+                ui.label(None, &trf("presets.seed", &[&seed]));
+            }
+        });
+        chosen
+    }
+}
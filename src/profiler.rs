@@ -0,0 +1,84 @@
+use std::time::Instant;
+
+use macroquad::prelude::*;
+
+use crate::i18n::trf;
+use crate::theme::Theme;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Section {
+    Physics,
+    // Statistics, telemetry, the tool panels and audio that follow a tick
+    Tools,
+    Render,
+    Ui,
+}
+
+impl Section {
+    fn label_key(&self) -> &'static str {
+        match self {
+            Section::Physics => "profiler.physics",
+            Section::Tools => "profiler.tools",
+            Section::Render => "profiler.render",
+            Section::Ui => "profiler.ui",
+        }
+    }
+}
+
+const SECTIONS: [Section; 4] = [
+    Section::Physics,
+    Section::Tools,
+    Section::Render,
+    Section::Ui,
+];
+
+// Exponential moving average weight applied to each new sample.
+const SMOOTHING: f32 = 0.1;
+
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    pub visible: bool,
+    millis: [f32; 4],
+    started: Option<(Section, Instant)>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            visible: false,
+            millis: [0.; 4],
+            started: None,
+        }
+    }
+
+    pub fn begin(&mut self, section: Section) {
+        self.end();
+        self.started = Some((section, Instant::now()));
+    }
+
+    pub fn end(&mut self) {
+        if let Some((section, start)) = self.started.take() {
+            let sample = start.elapsed().as_secs_f32() * 1000.;
+            let avg = &mut self.millis[section as usize];
+            *avg += (sample - *avg) * SMOOTHING;
+        }
+    }
+
+    pub fn millis(&self, section: Section) -> f32 {
+        self.millis[section as usize]
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let mut y = 50.;
+        for section in SECTIONS {
+            let millis = format!("{:.2}", self.millis(section));
+            let text = trf(section.label_key(), &[&millis]);
+            draw_text(text.as_str(), 10., y, theme.text_size(24.), theme.text);
+            y += theme.text_size(24.);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use macroquad::prelude::*;
+use numpy::{PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::diagnostics;
+use crate::particle::{Particle, TRAIL_LENGTH};
+use crate::scenario::Scenario;
+use crate::statistics::Statistics;
+use crate::systems;
+use crate::world::World;
+
+// Scripting access to the simulation core, built as a Python extension with
+// `maturin develop --features python`:
+//
+//     from rutherford_atomic_model import Simulation
+//     sim = Simulation.from_scenario(open("gold.scene").read())
+//     sim.step(1000)
+//     print(sim.positions[:, 0])
+//
+// State is exposed as copies in numpy arrays, one row per particle in slot
+// order, which is the same for every array until particles are added or
+// removed.
+#[pyclass]
+pub struct Simulation {
+    world: World,
+}
+
+fn rows(values: &[Vec3]) -> Vec<f32> {
+    values.iter().flat_map(|v| <[f32; 3]>::from(*v)).collect()
+}
+
+fn to_numpy<'py>(py: Python<'py>, values: &[Vec3]) -> PyResult<Bound<'py, PyArray2<f32>>> {
+    PyArray1::from_vec(py, rows(values)).reshape([values.len(), 3])
+}
+
+fn from_numpy(array: PyReadonlyArray2<f32>, count: usize) -> PyResult<Vec<Vec3>> {
+    let array = array.as_array();
+    if array.shape() != [count, 3] {
+        return Err(PyValueError::new_err(format!(
+            "expected an array of shape ({}, 3), got {:?}",
+            count,
+            array.shape()
+        )));
+    }
+    Ok(array
+        .rows()
+        .into_iter()
+        .map(|row| vec3(row[0], row[1], row[2]))
+        .collect())
+}
+
+#[pymethods]
+impl Simulation {
+    #[new]
+    fn new() -> Simulation {
+        Simulation {
+            world: World::new(),
+        }
+    }
+
+    // Same text format as scene files, see `scenario::Scenario`
+    #[staticmethod]
+    fn from_scenario(text: &str) -> PyResult<Simulation> {
+        let scenario =
+            Scenario::parse(text).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        Ok(Simulation {
+            world: scenario.build(),
+        })
+    }
+
+    fn to_scenario(&self) -> String {
+        Scenario::from_world(&self.world).to_text()
+    }
+
+    // Adds a particle by species code or custom species name and returns its
+    // slot
+    #[pyo3(signature = (species, position, velocity = None))]
+    fn spawn(
+        &mut self,
+        species: &str,
+        position: [f32; 3],
+        velocity: Option<[f32; 3]>,
+    ) -> PyResult<usize> {
+        let particle = Particle::from_code(species)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown species '{}'", species)))?;
+        let entity = self
+            .world
+            .spawn_particle(particle, Some(Vec3::from(position)));
+        let slot = self.world.particles.slot(entity).unwrap_or_default();
+        if let Some(velocity) = velocity {
+            self.world.particles.vel[slot] = Vec3::from(velocity);
+        }
+        Ok(slot)
+    }
+
+    fn clear(&mut self) -> usize {
+        self.world.clear_particles()
+    }
+
+    // Runs `ticks` ticks, stopping early if the state stops being sane.
+    // Returns how many ran.
+    #[pyo3(signature = (ticks = 1))]
+    fn step(&mut self, py: Python<'_>, ticks: u64) -> u64 {
+        let world = &mut self.world;
+        py.allow_threads(|| systems::fast_forward(world, ticks, Some(TRAIL_LENGTH)))
+    }
+
+    fn __len__(&self) -> usize {
+        self.world.particle_count()
+    }
+
+    #[getter]
+    fn species(&self) -> Vec<String> {
+        self.world
+            .particles
+            .particle
+            .iter()
+            .map(Particle::name)
+            .collect()
+    }
+
+    #[getter]
+    fn positions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        to_numpy(py, &self.world.particles.pos)
+    }
+
+    #[setter]
+    fn set_positions(&mut self, positions: PyReadonlyArray2<f32>) -> PyResult<()> {
+        self.world.particles.pos = from_numpy(positions, self.world.particle_count())?;
+        Ok(())
+    }
+
+    #[getter]
+    fn velocities<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        to_numpy(py, &self.world.particles.vel)
+    }
+
+    #[setter]
+    fn set_velocities(&mut self, velocities: PyReadonlyArray2<f32>) -> PyResult<()> {
+        self.world.particles.vel = from_numpy(velocities, self.world.particle_count())?;
+        Ok(())
+    }
+
+    #[getter]
+    fn accelerations<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        to_numpy(py, &self.world.particles.acc)
+    }
+
+    #[getter]
+    fn masses<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f32>> {
+        PyArray1::from_slice(py, &self.world.particles.mass)
+    }
+
+    #[getter]
+    fn charges<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f32>> {
+        PyArray1::from_slice(py, &self.world.particles.charge)
+    }
+
+    #[getter]
+    fn kinetic_energy(&self) -> f32 {
+        diagnostics::total_kinetic_energy(&self.world)
+    }
+
+    #[getter]
+    fn potential_energy(&self) -> f32 {
+        diagnostics::total_potential_energy(&self.world)
+    }
+
+    // Every aggregate for the current tick from one pass over the particles,
+    // cheaper than reading the energies one by one
+    #[getter]
+    fn statistics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let statistics = Statistics::gather(&self.world);
+        let dict = PyDict::new(py);
+        dict.set_item("particles", statistics.particles)?;
+        let counts = PyDict::new(py);
+        for (particle, count) in &statistics.counts {
+            counts.set_item(particle.name(), count)?;
+        }
+        dict.set_item("counts", counts)?;
+        dict.set_item("kinetic_energy", statistics.kinetic)?;
+        dict.set_item("potential_energy", statistics.potential)?;
+        dict.set_item("total_energy", statistics.total_energy())?;
+        dict.set_item("charge", statistics.charge)?;
+        dict.set_item("mass", statistics.mass)?;
+        dict.set_item("momentum", <[f32; 3]>::from(statistics.momentum))?;
+        dict.set_item(
+            "angular_momentum",
+            <[f32; 3]>::from(statistics.angular_momentum),
+        )?;
+        dict.set_item(
+            "center_of_mass",
+            statistics
+                .center_of_mass
+                .map(|(center, _)| <[f32; 3]>::from(center)),
+        )?;
+        dict.set_item(
+            "closest_distance",
+            statistics.closest.map(|(_, _, distance)| distance),
+        )?;
+        Ok(dict)
+    }
+
+    #[getter]
+    fn annihilations(&self) -> u64 {
+        self.world.annihilations
+    }
+}
+
+#[pymodule]
+fn rutherford_atomic_model(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Simulation>()
+}
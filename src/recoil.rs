@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::foil::INTERACTION_RADIUS;
+use rutherford_atomic_model::group::kinetic_energy;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::render;
+use crate::theme::Theme;
+
+// An alpha this close to a nucleus starts an encounter, and ends it on leaving
+const ENCOUNTER_RADIUS: f32 = 4. * INTERACTION_RADIUS;
+// Finished encounters whose arrows stay on screen
+const KEEP: usize = 5;
+// Length of the arrow for the largest momentum transfer on screen
+const ARROW_LENGTH: f32 = 60.;
+
+// Velocities of an alpha and the nucleus it is passing, as they were when it
+// came within `ENCOUNTER_RADIUS`.
+struct Encounter {
+    alpha: Entity,
+    nucleus: Entity,
+    alpha_vel: Vec3,
+    nucleus_vel: Vec3,
+    closest: f32,
+}
+
+// What one alpha-nucleus encounter exchanged.
+#[derive(Clone, Copy, Debug)]
+pub struct Recoil {
+    pub alpha: Entity,
+    pub nucleus: Entity,
+    // Momentum the nucleus gained and the alpha's change in momentum; equal
+    // and opposite unless other bodies pulled on either
+    pub transfer: Vec3,
+    pub alpha_change: Vec3,
+    pub alpha_before: f32,
+    pub alpha_after: f32,
+    pub nucleus_gain: f32,
+    // Lab deflection of the alpha, in degrees
+    pub angle: f32,
+    // Fraction of its energy an alpha deflected by `angle` keeps in an
+    // elastic two-body collision with this mass ratio
+    pub expected: f32,
+}
+
+impl Recoil {
+    // How far the momentum changes are from cancelling, relative to the
+    // transfer
+    pub fn imbalance(&self) -> f32 {
+        let scale = self.transfer.length().max(self.alpha_change.length());
+        if scale > 0. {
+            (self.transfer + self.alpha_change).length() / scale
+        } else {
+            0.
+        }
+    }
+}
+
+// E'/E = ((cos θ + √(A² - sin² θ)) / (1 + A))² for a projectile of mass m
+// hitting a target of mass A m at rest, taking the forward root.
+fn elastic_fraction(angle: f32, mass_ratio: f32) -> f32 {
+    let (sin, cos) = angle.sin_cos();
+    let root = (mass_ratio * mass_ratio - sin * sin).max(0.).sqrt();
+    let fraction = (cos + root) / (1. + mass_ratio);
+    fraction * fraction
+}
+
+// Follows alphas past free nuclei, drawing the momentum each nucleus picks
+// up and reporting how the kinetic energy was split between the two.
+pub struct RecoilPanel {
+    pub visible: bool,
+    open: Vec<Encounter>,
+    recent: VecDeque<Recoil>,
+}
+
+impl RecoilPanel {
+    pub fn new() -> RecoilPanel {
+        RecoilPanel {
+            visible: false,
+            open: Vec::new(),
+            recent: VecDeque::with_capacity(KEEP),
+        }
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World) {
+        if !self.visible {
+            self.open.clear();
+            return;
+        }
+        let particles = &world.particles;
+        let mut finished = Vec::new();
+        self.open.retain_mut(|encounter| {
+            let (Some(a), Some(n)) = (
+                particles.slot(encounter.alpha),
+                particles.slot(encounter.nucleus),
+            ) else {
+                return false;
+            };
+            let distance = particles.pos[a].distance(particles.pos[n]);
+            encounter.closest = encounter.closest.min(distance);
+            if distance <= ENCOUNTER_RADIUS {
+                return true;
+            }
+            // Passing far out barely deflects the alpha and isn't worth showing
+            if encounter.closest <= INTERACTION_RADIUS {
+                finished.push(finish(world, encounter, a, n));
+            }
+            false
+        });
+        for recoil in finished {
+            if self.recent.len() == KEEP {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(recoil);
+        }
+
+        for (i, alpha) in particles.entities().iter().enumerate() {
+            if particles.particle[i] != Particle::Alpha
+                || self.open.iter().any(|encounter| encounter.alpha == *alpha)
+            {
+                continue;
+            }
+            let Some(j) = particles.nearest(i, Particle::Proton) else {
+                continue;
+            };
+            let distance = particles.pos[i].distance(particles.pos[j]);
+            if distance <= ENCOUNTER_RADIUS {
+                self.open.push(Encounter {
+                    alpha: *alpha,
+                    nucleus: particles.entities()[j],
+                    alpha_vel: particles.vel[i],
+                    nucleus_vel: particles.vel[j],
+                    closest: distance,
+                });
+            }
+        }
+    }
+
+    // Arrows for the momentum transferred so far in open encounters and in
+    // the last few finished ones: the nucleus' gain at the nucleus and the
+    // alpha's change at the alpha, on the same scale.
+    pub fn draw_arrows(&self, world: &World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let particles = &world.particles;
+        let live = self.open.iter().filter_map(|encounter| {
+            let a = particles.slot(encounter.alpha)?;
+            let n = particles.slot(encounter.nucleus)?;
+            Some((
+                encounter.alpha,
+                encounter.nucleus,
+                particles.mass[a] * (particles.vel[a] - encounter.alpha_vel),
+                particles.mass[n] * (particles.vel[n] - encounter.nucleus_vel),
+            ))
+        });
+        let arrows: Vec<_> = self
+            .recent
+            .iter()
+            .map(|recoil| {
+                (
+                    recoil.alpha,
+                    recoil.nucleus,
+                    recoil.alpha_change,
+                    recoil.transfer,
+                )
+            })
+            .chain(live)
+            .collect();
+        let largest = arrows
+            .iter()
+            .map(|(_, _, alpha, nucleus)| alpha.length().max(nucleus.length()))
+            .fold(0., f32::max);
+        if largest <= 0. || !largest.is_finite() {
+            return;
+        }
+        let scale = ARROW_LENGTH / largest;
+        for (alpha, nucleus, alpha_change, transfer) in arrows {
+            if let Some(n) = particles.slot(nucleus) {
+                render::draw_arrow(particles.pos[n], transfer * scale, theme.proton);
+            }
+            if let Some(a) = particles.slot(alpha) {
+                render::draw_arrow(particles.pos[a], alpha_change * scale, theme.alpha);
+            }
+        }
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(340.), theme.scaled(200.));
+        let x = theme.scaled(1040.);
+        let y = screen_height() - height - theme.scaled(10.);
+        let Rect {
+            x,
+            y,
+            w: width,
+            h: height,
+        } = dock::place("recoil.title", Rect::new(x, y, width, height));
+        draw_rectangle(x, y, width, height, theme.panel);
+        let size = theme.text_size(18.);
+        let line = size * 1.1;
+        let left = x + theme.scaled(6.);
+
+        draw_text(tr("recoil.title"), left, y + line, size, theme.text);
+        let Some(recoil) = self.recent.back() else {
+            draw_text(tr("recoil.none"), left, y + line * 2., size, theme.text);
+            return;
+        };
+        let vector = |v: Vec3| format!("({:.2e}, {:.2e}, {:.2e})", v.x, v.y, v.z);
+        let percent = |part: f32| {
+            if recoil.alpha_before > 0. {
+                format!("{:.2}", 100. * part / recoil.alpha_before)
+            } else {
+                "-".to_string()
+            }
+        };
+        let lines = [
+            trf("recoil.angle", &[&format!("{:.1}", recoil.angle)]),
+            trf("recoil.transfer", &[&vector(recoil.transfer)]),
+            trf("recoil.alpha_change", &[&vector(recoil.alpha_change)]),
+            trf(
+                "recoil.imbalance",
+                &[&format!("{:.2}", recoil.imbalance() * 100.)],
+            ),
+            trf(
+                "recoil.alpha_energy",
+                &[
+                    &format!("{:.3e}", recoil.alpha_before),
+                    &format!("{:.3e}", recoil.alpha_after),
+                ],
+            ),
+            trf(
+                "recoil.split",
+                &[&percent(recoil.alpha_after), &percent(recoil.nucleus_gain)],
+            ),
+            trf(
+                "recoil.expected",
+                &[&format!("{:.2}", recoil.expected * 100.)],
+            ),
+        ];
+        for (k, text) in lines.iter().enumerate() {
+            draw_text(text, left, y + line * (k + 2) as f32, size, theme.text);
+        }
+    }
+}
+
+fn finish(world: &World, encounter: &Encounter, a: usize, n: usize) -> Recoil {
+    let particles = &world.particles;
+    let (alpha_mass, nucleus_mass) = (particles.mass[a], particles.mass[n]);
+    let (alpha_vel, nucleus_vel) = (particles.vel[a], particles.vel[n]);
+    // Measured in the frame the nucleus was moving in beforehand, so a
+    // drifting nucleus still compares with a target at rest
+    let incoming = encounter.alpha_vel - encounter.nucleus_vel;
+    let outgoing = alpha_vel - encounter.nucleus_vel;
+    let angle = incoming.angle_between(outgoing);
+    let mass_ratio = nucleus_mass / alpha_mass;
+    Recoil {
+        alpha: encounter.alpha,
+        nucleus: encounter.nucleus,
+        transfer: nucleus_mass * (nucleus_vel - encounter.nucleus_vel),
+        alpha_change: alpha_mass * (alpha_vel - encounter.alpha_vel),
+        alpha_before: kinetic_energy(alpha_mass, incoming),
+        alpha_after: kinetic_energy(alpha_mass, outgoing),
+        nucleus_gain: kinetic_energy(nucleus_mass, nucleus_vel - encounter.nucleus_vel),
+        angle: angle.to_degrees(),
+        expected: elastic_fraction(angle, mass_ratio),
+    }
+}
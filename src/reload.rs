@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::world::World;
+
+use crate::annotate::Annotations;
+use crate::i18n::trf;
+use crate::scene;
+use crate::toast;
+
+// Seconds between checks of the file's modification time
+const POLL_INTERVAL: f64 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Off,
+    // Start over from the new initial conditions
+    Reset,
+    // Bodies whose line did not change keep their simulated state
+    Preserve,
+}
+
+impl Mode {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Mode::Off => "off",
+            Mode::Reset => "on",
+            Mode::Preserve => "preserve",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Mode> {
+        [Mode::Off, Mode::Reset, Mode::Preserve]
+            .into_iter()
+            .find(|mode| mode.code() == code)
+    }
+}
+
+// Reloads the last scene file that was loaded whenever it changes on disk, so
+// initial conditions can be tuned in an editor while the app runs.
+pub struct HotReload {
+    pub mode: Mode,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    // What the file said last time, and the entity each body became
+    scenario: Scenario,
+    bodies: Vec<Entity>,
+    next_poll: f64,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+// `world` must have just been built from `scenario`, which spawns the bodies
+// first and in order.
+fn body_entities(scenario: &Scenario, world: &World) -> Vec<Entity> {
+    world.particles.entities()[..scenario.bodies.len()].to_vec()
+}
+
+impl HotReload {
+    pub fn new() -> HotReload {
+        HotReload {
+            mode: Mode::Reset,
+            path: None,
+            modified: None,
+            scenario: Scenario::default(),
+            bodies: Vec::new(),
+            next_poll: 0.,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn watch(&mut self, path: &Path, scenario: Scenario, world: &World) {
+        self.modified = modified(path);
+        self.path = Some(path.to_path_buf());
+        self.bodies = body_entities(&scenario, world);
+        self.scenario = scenario;
+    }
+
+    // Returns the reloaded world once the watched file has changed.
+    pub fn poll(&mut self, world: &World, annotations: &mut Annotations) -> Option<World> {
+        if self.mode == Mode::Off || get_time() < self.next_poll {
+            return None;
+        }
+        self.next_poll = get_time() + POLL_INTERVAL;
+        let path = self.path.clone()?;
+        let stamp = modified(&path);
+        if stamp == self.modified {
+            return None;
+        }
+        self.modified = stamp;
+
+        // A half-written or broken file leaves the running scene alone
+        let scenario = scene::load(&path, annotations)?;
+        let mut reloaded = scenario.build();
+        let bodies = body_entities(&scenario, &reloaded);
+        let mut kept = 0;
+        if self.mode == Mode::Preserve {
+            for (i, body) in scenario.bodies.iter().enumerate() {
+                if self.scenario.bodies.get(i) != Some(body) {
+                    continue;
+                }
+                let (old, new) = (self.bodies[i], bodies[i]);
+                let (Some(from), Some(to)) =
+                    (world.particles.slot(old), reloaded.particles.slot(new))
+                else {
+                    continue;
+                };
+                reloaded.particles.pos[to] = world.particles.pos[from];
+                reloaded.particles.vel[to] = world.particles.vel[from];
+                if let Some(trail) = world.trails.get(old) {
+                    reloaded.trails.insert(new, trail.clone());
+                }
+                kept += 1;
+            }
+            reloaded.integrator = world.integrator;
+            reloaded.substepping = world.substepping;
+            reloaded.pair_forces = world.pair_forces.clone();
+        }
+        toast::info(trf(
+            "reload.reloaded",
+            &[&path.display(), &kept, &scenario.bodies.len()],
+        ));
+        self.scenario = scenario;
+        self.bodies = bodies;
+        Some(reloaded)
+    }
+}
@@ -0,0 +1,690 @@
+use macroquad::color::hsl_to_rgb;
+use macroquad::models::{Mesh, Vertex};
+use macroquad::prelude::*;
+
+use crate::clip::ClipPlane;
+use crate::ensemble::Ensemble;
+use crate::environment::Environment;
+use crate::frustum::Frustum;
+use crate::i18n::{tr, trf};
+use crate::recoil::RecoilPanel;
+use crate::selection;
+use crate::settings::Settings;
+use crate::sweep::Sweep;
+use crate::theme::Theme;
+use crate::translucent::Translucent;
+use crate::visibility::SpeciesVisibility;
+
+use rutherford_atomic_model::annihilation::FLASH_TICKS;
+use rutherford_atomic_model::detector::DetectorShape;
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::foil::{FOIL_SIDE, FOIL_SPACING};
+use rutherford_atomic_model::magnets::{self, Magnet};
+use rutherford_atomic_model::sanity::{Fault, SanityChecker};
+use rutherford_atomic_model::sources::{self, Source};
+use rutherford_atomic_model::species;
+use rutherford_atomic_model::world::World;
+
+// Everything the 3D scene passes need besides the world itself.
+#[derive(Clone, Copy)]
+pub struct RenderContext<'a> {
+    pub theme: &'a Theme,
+    pub settings: &'a Settings,
+    pub environment: &'a Environment,
+    pub sanity: &'a SanityChecker,
+    pub selected: Option<Entity>,
+    pub clip: &'a ClipPlane,
+    pub sweep: &'a Sweep,
+    pub ensemble: &'a Ensemble,
+    pub recoil: &'a RecoilPanel,
+    pub visibility: &'a SpeciesVisibility,
+    pub eye: Vec3,
+    // Break the selected particle's force down into per-neighbor arrows
+    pub forces: bool,
+}
+
+impl RenderContext<'_> {
+    fn shade(&self, color: Color, pos: Vec3) -> Color {
+        self.settings
+            .fog
+            .apply(color, pos, self.eye, self.theme.background)
+    }
+}
+
+// Restricts `camera` to the left or right half of the window.
+pub fn half_screen(camera: &Camera3D, right: bool) -> Camera3D {
+    let (width, height) = (screen_width() as i32, screen_height() as i32);
+    let half = width / 2;
+    let viewport = if right {
+        (half, 0, width - half, height)
+    } else {
+        (0, 0, half, height)
+    };
+    Camera3D {
+        viewport: Some(viewport),
+        aspect: Some(viewport.2 as f32 / height as f32),
+        ..*camera
+    }
+}
+
+// Draws the 3D scene through `camera`. May be called several times per frame,
+// e.g. once per eye in stereo modes.
+pub fn draw_scene(world: &World, ctx: &RenderContext, camera: &Camera3D) {
+    set_camera(camera);
+    if camera.render_target.is_some() {
+        clear_background(ctx.theme.background);
+    }
+
+    // Fog is measured from whichever camera is drawing, not the main one
+    let ctx = &RenderContext {
+        eye: camera.position,
+        ..*ctx
+    };
+    let theme = ctx.theme;
+    ctx.environment
+        .draw_stars(ctx.settings.skybox, camera.position, theme);
+    let grid = ctx.settings.grid;
+    draw_grid(grid.slices, grid.spacing, theme.grid_axes, theme.grid_lines);
+    if let Some(half) = ctx.settings.bounds.active() {
+        draw_cube_wires(Vec3::ZERO, Vec3::splat(2. * half), theme.grid_axes);
+    }
+
+    // Only what is drawn is culled; the simulation carries on out of view
+    let frustum = Frustum::new(camera);
+    let queue = &mut Translucent::new(camera.position);
+    draw_trails(world, ctx, &frustum, queue);
+    draw_particles(world, ctx, camera, &frustum);
+    draw_detector(world, theme, queue);
+    draw_foils(world, theme);
+    draw_ghosts(world, theme, queue);
+    draw_bubbles(world, theme, queue);
+    draw_sources(world, theme, queue);
+    draw_magnets(world, theme, queue);
+    draw_flashes(world, theme, queue);
+    ctx.sweep.draw_paths();
+    ctx.ensemble.draw_copies(theme, queue);
+    ctx.recoil.draw_arrows(world, theme);
+    ctx.clip.draw_section(world, theme, queue);
+    draw_problems(world, ctx.sanity, theme);
+    selection::draw_selection(world, ctx.selected, theme);
+    if ctx.forces {
+        draw_forces(world, ctx.selected, theme);
+    }
+    queue.flush();
+}
+
+// Length of the arrow for the strongest force; the rest are scaled to it
+const FORCE_ARROW_LENGTH: f32 = 60.;
+// Neighbors pulling with less than this fraction of the strongest are skipped
+const SIGNIFICANT_FORCE: f32 = 0.01;
+
+// One arrow per significant neighbor, colored from blue (weak) to red
+// (strong), and the resultant in the selection color.
+fn draw_forces(world: &World, selected: Option<Entity>, theme: &Theme) {
+    let Some(entity) = selected else {
+        return;
+    };
+    let Some(slot) = world.particles.slot(entity) else {
+        return;
+    };
+    let origin = world.particles.pos[slot];
+    let forces = diagnostics::force_contributions(world, entity);
+    let resultant = forces
+        .iter()
+        .fold(Vec3::ZERO, |sum, (_, force)| sum + *force);
+    let strongest = forces
+        .iter()
+        .map(|(_, force)| force.length())
+        .fold(resultant.length(), f32::max);
+    if strongest <= 0. || !strongest.is_finite() {
+        return;
+    }
+
+    let scale = FORCE_ARROW_LENGTH / strongest;
+    for (_, force) in &forces {
+        let t = force.length() / strongest;
+        if t < SIGNIFICANT_FORCE {
+            continue;
+        }
+        draw_arrow(
+            origin,
+            *force * scale,
+            hsl_to_rgb(0.66 * (1. - t), 0.9, 0.55),
+        );
+    }
+    draw_arrow(origin, resultant * scale, theme.selection);
+}
+
+pub fn draw_arrow(from: Vec3, vector: Vec3, color: Color) {
+    let length = vector.length();
+    if length <= f32::EPSILON {
+        return;
+    }
+    let tip = from + vector;
+    draw_line_3d(from, tip, color);
+    let direction = vector / length;
+    let side = if direction.y.abs() < 0.9 {
+        direction.cross(Vec3::Y)
+    } else {
+        direction.cross(Vec3::X)
+    }
+    .normalize();
+    let head = (length * 0.2).min(6.);
+    for side in [side, -side, direction.cross(side), -direction.cross(side)] {
+        draw_line_3d(tip, tip - direction * head + side * head * 0.5, color);
+    }
+}
+
+// Level of detail by how big a particle comes out on screen: rings and slices
+// per pixel of its radius, down to a floor, and below `SPRITE_PIXELS` a flat
+// square facing the camera instead of a sphere. Far-off particles in a big
+// scene cost a quad each rather than a full sphere.
+const PIXELS_PER_RING: f32 = 1.5;
+const MIN_RINGS: usize = 4;
+const SPRITE_PIXELS: f32 = 1.5;
+
+// On-screen pixels per world unit at `pos` through `camera`.
+fn pixels_per_unit(camera: &Camera3D, pos: Vec3) -> f32 {
+    let height = camera
+        .viewport
+        .map_or(screen_height(), |viewport| viewport.3 as f32);
+    match camera.projection {
+        Projection::Orthographics => height / camera.fovy,
+        Projection::Perspective => {
+            height / (2. * pos.distance(camera.position) * (camera.fovy / 2.).tan())
+        }
+    }
+}
+
+fn draw_particles(world: &World, ctx: &RenderContext, camera: &Camera3D, frustum: &Frustum) {
+    let (theme, quality) = (ctx.theme, &ctx.settings.quality);
+    let particles = &world.particles;
+    // Sprites all face the same way and go out in as few meshes as fit
+    let forward = (camera.target - camera.position).normalize_or_zero();
+    let right = forward.cross(camera.up).normalize_or_zero();
+    let up = right.cross(forward);
+    let mut sprites = Mesh {
+        vertices: Vec::new(),
+        indices: Vec::new(),
+        texture: None,
+    };
+    for ((entity, pos), particle) in particles
+        .entities()
+        .iter()
+        .zip(&particles.pos)
+        .zip(&particles.particle)
+    {
+        let radius = species::radius(*particle);
+        if !ctx.visibility.shows(*particle) || !ctx.clip.keeps(*pos) || !frustum.sees(*pos, radius)
+        {
+            continue;
+        }
+        let color = world
+            .group_color(*entity)
+            .unwrap_or_else(|| theme.particle(*particle));
+        let color = ctx.shade(color, *pos);
+        let scale = pixels_per_unit(camera, *pos);
+        let pixels = radius * scale;
+        if pixels >= SPRITE_PIXELS {
+            let rings =
+                ((pixels / PIXELS_PER_RING) as usize).clamp(MIN_RINGS, quality.sphere_detail);
+            draw_sphere_ex(
+                *pos,
+                radius,
+                None,
+                color,
+                DrawSphereParams {
+                    rings,
+                    slices: rings,
+                    draw_mode: DrawMode::Triangles,
+                },
+            );
+            continue;
+        }
+        if sprites.vertices.len() + 4 > u16::MAX as usize {
+            draw_mesh(&sprites);
+            sprites.vertices.clear();
+            sprites.indices.clear();
+        }
+        // At least a pixel across however far off, so nothing vanishes
+        let half = radius.max(0.5 / scale);
+        let first = sprites.vertices.len() as u16;
+        for (x, y) in [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+            sprites.vertices.push(Vertex {
+                position: *pos + (right * x + up * y) * half,
+                uv: vec2(x, y),
+                color,
+            });
+        }
+        sprites.indices.extend_from_slice(&[
+            first,
+            first + 1,
+            first + 2,
+            first,
+            first + 2,
+            first + 3,
+        ]);
+    }
+    if !sprites.vertices.is_empty() {
+        draw_mesh(&sprites);
+    }
+}
+
+// Ticks over which a fresh hit fades down to its residual brightness
+const HIT_FADE: f32 = 120.;
+
+fn draw_detector(world: &World, theme: &Theme, queue: &mut Translucent) {
+    let Some(detector) = &world.detector else {
+        return;
+    };
+    let (d, e) = (detector.distance, detector.extent);
+    match detector.shape {
+        DetectorShape::Plate => {
+            let corners = [
+                vec3(d, -e, -e),
+                vec3(d, e, -e),
+                vec3(d, e, e),
+                vec3(d, -e, e),
+            ];
+            for i in 0..4 {
+                queue.line(corners[i], corners[(i + 1) % 4], theme.grid_axes);
+            }
+        }
+        DetectorShape::Sphere => queue.sphere_wires(Vec3::ZERO, d, theme.grid_axes),
+        DetectorShape::Cylinder => {
+            const SEGMENTS: usize = 48;
+            let point = |i: usize, y: f32| {
+                let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                vec3(angle.cos() * d, y, angle.sin() * d)
+            };
+            for i in 0..SEGMENTS {
+                queue.line(point(i, e), point(i + 1, e), theme.grid_axes);
+                queue.line(point(i, -e), point(i + 1, -e), theme.grid_axes);
+                if i % 4 == 0 {
+                    queue.line(point(i, e), point(i, -e), theme.grid_axes);
+                }
+            }
+        }
+    }
+
+    // One bar per angular bin on a ring around the target, height by count
+    let counts = detector.angular_counts();
+    let peak = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let ring = d + 20.;
+    for (bin, count) in counts.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        let angle = (bin as f32 + 0.5) * detector.bin_width();
+        let height = 100. * *count as f32 / peak;
+        let base = vec3(angle.cos() * ring, 0., angle.sin() * ring);
+        queue.cube(
+            base + vec3(0., height / 2., 0.),
+            vec3(4., height, 4.),
+            theme.selection,
+        );
+    }
+
+    for hit in &detector.hits {
+        let age = (detector.tick - hit.tick) as f32;
+        let glow = (1. - age / HIT_FADE).max(0.15);
+        let color = Color {
+            a: glow,
+            ..theme.warning
+        };
+        queue.cube(hit.pos, Vec3::splat(1. + 2. * glow), color);
+    }
+}
+
+// An expanding, fading sphere where each pair annihilated, and its two
+// photons as short wavy segments flying apart.
+fn draw_flashes(world: &World, theme: &Theme, queue: &mut Translucent) {
+    for flash in &world.flashes {
+        let t = flash.age as f32 / FLASH_TICKS as f32;
+        let glow = Color {
+            a: 1. - t,
+            ..theme.warning
+        };
+        queue.sphere_wires(flash.pos, 2. + 10. * t, glow);
+        let side = flash.direction.any_orthonormal_vector();
+        for (photon, sign) in flash.photons().into_iter().zip([1., -1.]) {
+            let back = -flash.direction * sign;
+            let mut last = photon;
+            for k in 1..=8 {
+                let along = k as f32 * 2.;
+                let next = photon + back * along + side * (k as f32 * 1.6).sin() * 2.;
+                queue.line(last, next, glow);
+                last = next;
+            }
+        }
+    }
+}
+
+// Outline of each foil's patch, so the targets read as sheets.
+fn draw_foils(world: &World, theme: &Theme) {
+    let half = FOIL_SIDE as f32 * FOIL_SPACING / 2.;
+    for foil in &world.foils {
+        let c = foil.center;
+        let corners = [
+            c + vec3(0., -half, -half),
+            c + vec3(0., half, -half),
+            c + vec3(0., half, half),
+            c + vec3(0., -half, half),
+        ];
+        for i in 0..4 {
+            draw_line_3d(corners[i], corners[(i + 1) % 4], theme.proton);
+        }
+    }
+}
+
+// Ticks of a ghost's path drawn ahead of it, and the spacing of the samples
+const GHOST_AHEAD: u64 = 600;
+const GHOST_STEP: u64 = 4;
+
+// Ghosts as see-through wireframes, with the whole path they were given so
+// far and a stretch of where it leads.
+fn draw_ghosts(world: &World, theme: &Theme, queue: &mut Translucent) {
+    for ghost in &world.ghosts {
+        let color = Color {
+            a: 0.5,
+            ..theme.particle(ghost.particle)
+        };
+        let points: Vec<Vec3> = (0..=(ghost.age + GHOST_AHEAD) / GHOST_STEP)
+            .map(|k| ghost.position_at((k * GHOST_STEP) as f32))
+            .collect();
+        for pair in points.windows(2) {
+            queue.line(pair[0], pair[1], color);
+        }
+        queue.sphere_wires(ghost.position(), species::radius(ghost.particle), color);
+    }
+}
+
+// Half the side of the patch drawn for an infinite plane, and the lines
+// across it
+const PLANE_EXTENT: f32 = 300.;
+const PLANE_LINES: usize = 12;
+// Segments drawn around a ring
+const RING_SEGMENTS: usize = 64;
+
+// Fixed charge distributions in the proton color when positive and the
+// electron color when negative.
+fn draw_sources(world: &World, theme: &Theme, queue: &mut Translucent) {
+    for source in &world.sources {
+        let base = if source.charge_sign() < 0. {
+            theme.electron
+        } else {
+            theme.proton
+        };
+        let color = Color { a: 0.5, ..base };
+        match *source {
+            Source::Plane { point, normal, .. } => {
+                let u = normal.any_orthonormal_vector();
+                let v = normal.cross(u);
+                for k in 0..=PLANE_LINES {
+                    let t = (k as f32 / PLANE_LINES as f32 * 2. - 1.) * PLANE_EXTENT;
+                    queue.line(
+                        point + u * t - v * PLANE_EXTENT,
+                        point + u * t + v * PLANE_EXTENT,
+                        color,
+                    );
+                    queue.line(
+                        point + v * t - u * PLANE_EXTENT,
+                        point + v * t + u * PLANE_EXTENT,
+                        color,
+                    );
+                }
+            }
+            Source::Ring {
+                center,
+                normal,
+                radius,
+                ..
+            } => {
+                let u = normal.any_orthonormal_vector() * radius;
+                let v = normal.cross(u);
+                let point = |k: usize| {
+                    let angle = k as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                    center + u * angle.cos() + v * angle.sin()
+                };
+                for k in 0..RING_SEGMENTS {
+                    queue.line(point(k), point(k + 1), color);
+                }
+            }
+            Source::Shell { center, radius, .. } => {
+                queue.sphere_wires(center, radius, color);
+            }
+            Source::Capacitor {
+                center,
+                normal,
+                separation,
+                area,
+                voltage,
+            } => {
+                // The field runs from the positive plate to the negative one
+                let (u, v) = sources::plate_axes(normal);
+                let half = area.sqrt() / 2.;
+                let (positive, negative) = if voltage < 0. {
+                    (theme.electron, theme.proton)
+                } else {
+                    (theme.proton, theme.electron)
+                };
+                for (side, plate) in [(-1., positive), (1., negative)] {
+                    let middle = center + normal * (side * separation / 2.);
+                    let color = Color { a: 0.6, ..plate };
+                    for k in 0..=PLANE_LINES {
+                        let t = (k as f32 / PLANE_LINES as f32 * 2. - 1.) * half;
+                        queue.line(middle + u * t - v * half, middle + u * t + v * half, color);
+                        queue.line(middle + v * t - u * half, middle + v * t + u * half, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Field lines traced from around each magnet, and how far they may run
+const FIELD_LINE_SEEDS: usize = 8;
+const FIELD_LINE_STEPS: usize = 400;
+
+// Magnets as wireframes with field lines through them. The lines follow the
+// field of all magnets together, so nearby ones bend each other's lines.
+fn draw_magnets(world: &World, theme: &Theme, queue: &mut Translucent) {
+    let body = Color {
+        a: 0.7,
+        ..theme.selection
+    };
+    let line = Color {
+        a: 0.35,
+        ..theme.selection
+    };
+    let magnets = &world.magnets;
+    for magnet in magnets {
+        let mut lines = Vec::new();
+        match *magnet {
+            Magnet::Dipole {
+                center,
+                axis,
+                radius,
+                ..
+            } => {
+                queue.sphere_wires(center, radius, body);
+                queue.line(center - axis * radius, center + axis * radius * 1.5, body);
+                // Out of the north pole, ending back at the magnet
+                let (u, v) = sources::plate_axes(axis);
+                for k in 0..FIELD_LINE_SEEDS {
+                    let angle = k as f32 / FIELD_LINE_SEEDS as f32 * std::f32::consts::TAU;
+                    let across = u * angle.cos() + v * angle.sin();
+                    for tilt in [0.3f32, 0.7] {
+                        let dir = axis * tilt.cos() + across * tilt.sin();
+                        lines.push(magnets::field_line(
+                            magnets,
+                            center + dir * radius * 1.01,
+                            radius * 0.1,
+                            FIELD_LINE_STEPS,
+                            |pos| {
+                                let distance = pos.distance(center);
+                                distance < radius || distance > radius * 15.
+                            },
+                        ));
+                    }
+                }
+            }
+            Magnet::Solenoid {
+                center,
+                axis,
+                radius,
+                length,
+                ..
+            } => {
+                let (u, v) = sources::plate_axes(axis);
+                let point = |k: usize, z: f32| {
+                    let angle = k as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                    center + axis * z + (u * angle.cos() + v * angle.sin()) * radius
+                };
+                for k in 0..RING_SEGMENTS {
+                    for z in [-length / 2., 0., length / 2.] {
+                        queue.line(point(k, z), point(k + 1, z), body);
+                    }
+                    if k % (RING_SEGMENTS / 4) == 0 {
+                        queue.line(point(k, -length / 2.), point(k, length / 2.), body);
+                    }
+                }
+                // Into the bore at one end and out of the other
+                for k in 0..FIELD_LINE_SEEDS {
+                    let angle = k as f32 / FIELD_LINE_SEEDS as f32 * std::f32::consts::TAU;
+                    let across = u * angle.cos() + v * angle.sin();
+                    for offset in [0.3, 0.7] {
+                        let start = center - axis * (length * 0.75) + across * (radius * offset);
+                        lines.push(magnets::field_line(
+                            magnets,
+                            start,
+                            length / 100.,
+                            FIELD_LINE_STEPS,
+                            |pos| (pos - center).dot(axis).abs() > length,
+                        ));
+                    }
+                }
+            }
+        }
+        for points in lines {
+            for pair in points.windows(2) {
+                queue.line(pair[0], pair[1], line);
+            }
+        }
+    }
+}
+
+// Time bubbles as faint wire spheres, bluer the slower time runs inside.
+fn draw_bubbles(world: &World, theme: &Theme, queue: &mut Translucent) {
+    for bubble in &world.bubbles {
+        let color = Color {
+            a: 0.3,
+            ..theme.selection
+        };
+        let frozen = Color { a: 0.3, ..SKYBLUE };
+        queue.sphere_wires(
+            bubble.center,
+            bubble.radius,
+            Color::from_vec(frozen.to_vec().lerp(color.to_vec(), bubble.rate)),
+        );
+    }
+}
+
+// Trail segments are culled in runs this long, each skipped whole when the
+// box around its points is out of view
+const TRAIL_CHUNK: usize = 32;
+
+fn draw_trails(world: &World, ctx: &RenderContext, frustum: &Frustum, queue: &mut Translucent) {
+    let (theme, quality, trails) = (ctx.theme, &ctx.settings.quality, &ctx.settings.trails);
+    for (entity, trail) in world.trails.iter() {
+        let Some(slot) = world.particles.slot(entity) else {
+            continue;
+        };
+        let particle = world.particles.particle[slot];
+        if !trails.shows(particle) || !ctx.visibility.shows(particle) {
+            continue;
+        }
+        let base = world
+            .group_color(entity)
+            .unwrap_or_else(|| theme.particle(particle));
+
+        let points = &trail.points;
+        for start in (1..points.len()).step_by(TRAIL_CHUNK) {
+            let end = (start + TRAIL_CHUNK).min(points.len());
+            let around = start.saturating_sub(2)..(end + 1).min(points.len());
+            if !frustum.sees_points(points.range(around).copied()) {
+                continue;
+            }
+            for i in start..end {
+                let color = if trails.fade {
+                    Color {
+                        a: base.a * i as f32 / points.len() as f32,
+                        ..base
+                    }
+                } else {
+                    base
+                };
+                let p0 = points[i.saturating_sub(2)];
+                let (p1, p2) = (points[i - 1], points[i]);
+                if p1 == p2 || !ctx.clip.keeps(p1) || !ctx.clip.keeps(p2) {
+                    continue;
+                }
+                let p3 = points[(i + 1).min(points.len() - 1)];
+
+                let steps = quality.trail_smoothness + 1;
+                let mut last = p1;
+                for step in 1..=steps {
+                    let next = catmull_rom(p0, p1, p2, p3, step as f32 / steps as f32);
+                    queue.line(last, next, ctx.shade(color, next));
+                    last = next;
+                }
+            }
+        }
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * (2. * p1
+        + (p2 - p0) * t
+        + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+        + (3. * p1 - p0 - 3. * p2 + p3) * t3)
+}
+
+fn draw_problems(world: &World, sanity: &SanityChecker, theme: &Theme) {
+    for problem in &sanity.problems {
+        let Some(slot) = world.particles.slot(problem.entity) else {
+            continue;
+        };
+        let pos = world.particles.pos[slot];
+        if pos.is_finite() {
+            draw_sphere_wires(pos, 4., None, theme.error);
+        }
+    }
+}
+
+pub fn draw_sanity_prompt(sanity: &SanityChecker, theme: &Theme) {
+    let Some(first) = sanity.problems.first() else {
+        return;
+    };
+
+    let fault = match first.fault {
+        Fault::NonFinite => tr("sanity.fault.non_finite"),
+        Fault::Exploded => tr("sanity.fault.exploded"),
+        Fault::Escaped => tr("sanity.fault.escaped"),
+    };
+    let mut text = trf("sanity.halted", &[&sanity.problems.len(), &fault]);
+    if sanity.can_rewind() {
+        text.push_str(tr("sanity.rewind"));
+    }
+    let font_size = theme.text_size(30.);
+    let size = measure_text(text.as_str(), None, font_size as u16, 1.);
+    draw_text(
+        text.as_str(),
+        (screen_width() - size.width) / 2.,
+        60.,
+        font_size,
+        theme.error,
+    );
+}
@@ -0,0 +1,145 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+use crate::toast;
+
+// Cells along each axis of a lattice
+const MAX_CELLS: f32 = 10.;
+
+// What a tool is applied to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    Selected,
+    // Every particle in the selected particle's group
+    Group,
+    All,
+}
+
+impl Target {
+    fn next(&self) -> Target {
+        match self {
+            Target::Selected => Target::Group,
+            Target::Group => Target::All,
+            Target::All => Target::Selected,
+        }
+    }
+
+    fn name_key(&self) -> &'static str {
+        match self {
+            Target::Selected => "replicate.selected",
+            Target::Group => "replicate.group",
+            Target::All => "replicate.all",
+        }
+    }
+
+    fn entities(&self, world: &World, selected: Option<Entity>) -> Vec<Entity> {
+        let selected = selected.filter(|entity| world.particles.slot(*entity).is_some());
+        match self {
+            Target::Selected => selected.into_iter().collect(),
+            Target::Group => {
+                let Some(entity) = selected else {
+                    return Vec::new();
+                };
+                match world.membership.get(entity) {
+                    Some(id) => world.members(*id),
+                    None => vec![entity],
+                }
+            }
+            Target::All => world.particles.entities().to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Replicate {
+    // Across the plane through the origin normal to this axis
+    Mirror(Vec3),
+    Lattice([u32; 3], Vec3),
+}
+
+// Tools for building symmetric scenes out of what is already there: mirror
+// images across the coordinate planes, and copies on a lattice for foils and
+// crystals.
+pub struct ReplicatePanel {
+    pub visible: bool,
+    target: Target,
+    counts: [f32; 3],
+    spacing: f32,
+}
+
+impl ReplicatePanel {
+    pub fn new() -> ReplicatePanel {
+        ReplicatePanel {
+            visible: false,
+            target: Target::Selected,
+            counts: [2., 2., 1.],
+            spacing: 40.,
+        }
+    }
+
+    // Returns a tool to apply when one of its buttons is clicked.
+    pub fn draw(&mut self, theme: &Theme) -> Option<Replicate> {
+        if !self.visible {
+            return None;
+        }
+
+        let mut request = None;
+        dock::window(
+            "replicate.title",
+            hash!(),
+            vec2(theme.scaled(320.), theme.scaled(200.)),
+            vec2(theme.scaled(320.), theme.scaled(330.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let target = trf("replicate.target", &[&tr(self.target.name_key())]);
+            if ui.button(None, target.as_str()) {
+                self.target = self.target.next();
+            }
+            ui.separator();
+            for (axis, normal) in [("x", Vec3::X), ("y", Vec3::Y), ("z", Vec3::Z)] {
+                if ui.button(None, trf("replicate.mirror", &[&axis]).as_str()) {
+                    request = Some(Replicate::Mirror(normal));
+                }
+            }
+            ui.separator();
+            let [nx, ny, nz] = &mut self.counts;
+            ui.slider(hash!(), tr("replicate.nx"), 1.0..MAX_CELLS, nx);
+            ui.slider(hash!(), tr("replicate.ny"), 1.0..MAX_CELLS, ny);
+            ui.slider(hash!(), tr("replicate.nz"), 1.0..MAX_CELLS, nz);
+            ui.slider(
+                hash!(),
+                tr("replicate.spacing"),
+                5.0..200.0,
+                &mut self.spacing,
+            );
+            let counts = self.counts.map(|n| n.round().max(1.) as u32);
+            let label = trf("replicate.lattice", &[&counts[0], &counts[1], &counts[2]]);
+            if ui.button(None, label.as_str()) {
+                request = Some(Replicate::Lattice(counts, Vec3::splat(self.spacing)));
+            }
+        });
+        request
+    }
+
+    // Applies `request` to the particles the panel targets.
+    pub fn apply(&self, request: Replicate, world: &mut World, selected: Option<Entity>) {
+        let entities = self.target.entities(world, selected);
+        if entities.is_empty() {
+            toast::warning(tr("replicate.nothing").to_string());
+            return;
+        }
+        let copies = match request {
+            Replicate::Mirror(normal) => world.mirror(&entities, Vec3::ZERO, normal),
+            Replicate::Lattice(counts, spacing) => {
+                world.lattice(&entities, counts, world.constrain(spacing))
+            }
+        };
+        toast::info(trf("replicate.added", &[&copies.len()]));
+    }
+}
@@ -0,0 +1,414 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rutherford_atomic_model::detector::{self, Detector, Fit};
+use rutherford_atomic_model::histogram::Histogram;
+use rutherford_atomic_model::particle::PARTICLES;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::world::World;
+
+use crate::distributions;
+use crate::i18n::{tr, trf};
+use crate::settings::Settings;
+use crate::toast;
+
+const CHART_WIDTH: f32 = 480.;
+const CHART_HEIGHT: f32 = 240.;
+const MARGIN: f32 = 24.;
+
+// One chart of a report: its title, file name stem and SVG source.
+struct Chart {
+    title: String,
+    name: &'static str,
+    svg: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Bars for `values`, with `line` drawn over them when given, labelled with
+// the range the bins span.
+fn svg_chart(values: &[f32], line: Option<&[f32]>, range: (String, String)) -> String {
+    let peak = values
+        .iter()
+        .chain(line.unwrap_or_default())
+        .copied()
+        .filter(|v| v.is_finite())
+        .fold(1., f32::max);
+    let (w, h) = (CHART_WIDTH - 2. * MARGIN, CHART_HEIGHT - 2. * MARGIN);
+    let bar = w / values.len().max(1) as f32;
+    let y = |value: f32| MARGIN + h - h * (value / peak).min(1.);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         viewBox=\"0 0 {} {}\" font-family=\"sans-serif\" font-size=\"12\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        CHART_WIDTH, CHART_HEIGHT, CHART_WIDTH, CHART_HEIGHT
+    );
+    for (i, value) in values.iter().enumerate() {
+        let top = y(*value);
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4a7bd0\"/>\n",
+            MARGIN + i as f32 * bar,
+            top,
+            (bar - 1.).max(1.),
+            MARGIN + h - top
+        ));
+    }
+    if let Some(line) = line {
+        let points: Vec<String> = line
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_finite())
+            .map(|(i, value)| format!("{:.1},{:.1}", MARGIN + (i as f32 + 0.5) * bar, y(*value)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"#d0702a\" stroke-width=\"2\"/>\n",
+            points.join(" ")
+        ));
+    }
+    svg.push_str(&format!(
+        "<line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"black\"/>\n\
+         <text x=\"{m}\" y=\"{t}\">{}</text>\n\
+         <text x=\"{r}\" y=\"{t}\" text-anchor=\"end\">{}</text>\n\
+         <text x=\"{m}\" y=\"{top}\">{}</text>\n</svg>\n",
+        escape(&range.0),
+        escape(&range.1),
+        peak.round(),
+        m = MARGIN,
+        r = MARGIN + w,
+        b = MARGIN + h,
+        t = MARGIN + h + 16.,
+        top = MARGIN - 8.,
+    ));
+    svg
+}
+
+fn histogram_chart(key: &'static str, name: &'static str, histogram: &Histogram) -> Chart {
+    let values: Vec<f32> = histogram.counts.iter().map(|c| *c as f32).collect();
+    Chart {
+        title: tr(key).to_string(),
+        name,
+        svg: svg_chart(
+            &values,
+            None,
+            (
+                format!("{:.3e}", histogram.min),
+                format!("{:.3e}", histogram.max),
+            ),
+        ),
+    }
+}
+
+// Label and value rows making up the report's tables.
+type Rows = Vec<(String, String)>;
+
+struct Report {
+    scene: String,
+    configuration: Rows,
+    fit: Rows,
+    summary: Rows,
+    // Angle range, hits, predicted hits and residual per detector bin
+    bins: Vec<(String, u32, f32, Option<f32>)>,
+    charts: Vec<Chart>,
+}
+
+fn detector_rows(detector: &Detector, fit: &Fit) -> (Rows, Rows, Rows) {
+    let configuration = vec![
+        (
+            tr("report.detector_shape").to_string(),
+            detector.shape.code().to_string(),
+        ),
+        (
+            tr("detector.distance").to_string(),
+            detector.distance.to_string(),
+        ),
+        (
+            tr("detector.extent").to_string(),
+            detector.extent.to_string(),
+        ),
+        (tr("detector.bins").to_string(), detector.bins.to_string()),
+    ];
+    let fit_rows = vec![
+        (tr("report.scale").to_string(), format!("{:.4e}", fit.scale)),
+        (
+            tr("report.exponent").to_string(),
+            fit.exponent
+                .map_or_else(|| "-".to_string(), |n| format!("{:.3}", n)),
+        ),
+        (
+            tr("report.chi_square").to_string(),
+            format!("{:.2} / {}", fit.chi_square, fit.degrees_of_freedom),
+        ),
+    ];
+    let counts = detector.angular_counts();
+    let total: u32 = counts.iter().sum();
+    let half = counts.len() / 2;
+    let backward: u32 = counts.iter().skip(half).sum();
+    let summary = vec![
+        (tr("report.hits").to_string(), total.to_string()),
+        (
+            tr("report.backscattered").to_string(),
+            if total > 0 {
+                format!(
+                    "{} ({:.2}%)",
+                    backward,
+                    100. * backward as f32 / total as f32
+                )
+            } else {
+                "0".to_string()
+            },
+        ),
+        (tr("report.ticks").to_string(), detector.tick.to_string()),
+    ];
+    (configuration, fit_rows, summary)
+}
+
+fn gather(world: &World) -> Report {
+    let mut configuration = Vec::new();
+    for particle in PARTICLES {
+        let count = world
+            .particles
+            .particle
+            .iter()
+            .filter(|p| **p == particle)
+            .count();
+        if count > 0 {
+            configuration.push((particle.code().to_string(), count.to_string()));
+        }
+    }
+    let custom = world
+        .particles
+        .particle
+        .iter()
+        .filter(|p| p.is_custom())
+        .count();
+    if custom > 0 {
+        configuration.push((tr("report.custom").to_string(), custom.to_string()));
+    }
+    for foil in &world.foils {
+        configuration.push((
+            tr("report.foil").to_string(),
+            format!(
+                "{} ({})",
+                foil.element.code(),
+                trf("report.nuclei", &[&foil.nuclei.len()])
+            ),
+        ));
+    }
+
+    let mut fit = Vec::new();
+    let mut summary = vec![
+        (
+            tr("report.particles").to_string(),
+            world.particle_count().to_string(),
+        ),
+        (
+            tr("report.annihilations").to_string(),
+            world.annihilations.to_string(),
+        ),
+    ];
+    let mut bins = Vec::new();
+    let mut charts = Vec::new();
+    if let Some(detector) = &world.detector {
+        let angular_fit = detector::fit(detector);
+        let (detector_config, fit_rows, detector_summary) = detector_rows(detector, &angular_fit);
+        configuration.extend(detector_config);
+        fit = fit_rows;
+        summary.extend(detector_summary);
+
+        let counts = detector.angular_counts();
+        let width = detector.bin_width().to_degrees();
+        // The forward bin's prediction diverges, so it is left blank
+        let predicted: Vec<f32> = angular_fit
+            .expected
+            .iter()
+            .enumerate()
+            .map(|(bin, e)| {
+                if bin == 0 {
+                    f32::NAN
+                } else {
+                    e * angular_fit.scale
+                }
+            })
+            .collect();
+        for (bin, count) in counts.iter().enumerate() {
+            bins.push((
+                format!("{:.0}–{:.0}°", bin as f32 * width, (bin + 1) as f32 * width),
+                *count,
+                predicted[bin],
+                angular_fit.residuals[bin],
+            ));
+        }
+        let values: Vec<f32> = counts.iter().map(|c| *c as f32).collect();
+        charts.push(Chart {
+            title: tr("angular.title").to_string(),
+            name: "angular",
+            svg: svg_chart(
+                &values,
+                Some(&predicted),
+                ("0°".to_string(), "180°".to_string()),
+            ),
+        });
+    }
+    for (key, name, histogram) in distributions::histograms(world) {
+        charts.push(histogram_chart(key, name, &histogram));
+    }
+
+    Report {
+        scene: Scenario::from_world(world).to_text(),
+        configuration,
+        fit,
+        summary,
+        bins,
+        charts,
+    }
+}
+
+fn predicted_text(predicted: f32) -> String {
+    if predicted.is_finite() {
+        format!("{:.1}", predicted)
+    } else {
+        "-".to_string()
+    }
+}
+
+fn residual_text(residual: Option<f32>) -> String {
+    residual.map_or_else(|| "-".to_string(), |r| format!("{:+.2}σ", r))
+}
+
+fn markdown(report: &Report, stem: &str) -> String {
+    let table = |rows: &Rows| {
+        let mut text = String::from("| | |\n|---|---|\n");
+        for (label, value) in rows {
+            text.push_str(&format!("| {} | {} |\n", label, value));
+        }
+        text
+    };
+    let mut md = format!("# {}\n\n", tr("report.title"));
+    md.push_str(&format!("## {}\n\n", tr("report.configuration")));
+    md.push_str(&table(&report.configuration));
+    md.push_str(&format!("\n```\n{}```\n\n", report.scene));
+    md.push_str(&format!("## {}\n\n", tr("report.summary")));
+    md.push_str(&table(&report.summary));
+    if !report.fit.is_empty() {
+        md.push_str(&format!("\n## {}\n\n", tr("report.fit")));
+        md.push_str(&table(&report.fit));
+        md.push_str(&format!(
+            "\n| {} | {} | {} | {} |\n|---|---|---|---|\n",
+            tr("report.angle"),
+            tr("report.hits"),
+            tr("report.predicted"),
+            tr("report.residual")
+        ));
+        for (range, count, predicted, residual) in &report.bins {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                range,
+                count,
+                predicted_text(*predicted),
+                residual_text(*residual)
+            ));
+        }
+    }
+    md.push_str(&format!("\n## {}\n\n", tr("report.charts")));
+    for chart in &report.charts {
+        md.push_str(&format!(
+            "### {}\n\n![{}]({}-{}.svg)\n\n",
+            chart.title, chart.title, stem, chart.name
+        ));
+    }
+    md
+}
+
+fn html(report: &Report) -> String {
+    let table = |rows: &Rows| {
+        let mut text = String::from("<table>\n");
+        for (label, value) in rows {
+            text.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                escape(label),
+                escape(value)
+            ));
+        }
+        text + "</table>\n"
+    };
+    let title = escape(tr("report.title"));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>body{{font-family:sans-serif;max-width:52em;margin:auto}}\
+         table{{border-collapse:collapse}}th,td{{border:1px solid #ccc;padding:2px 8px;text-align:left}}\
+         </style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, title
+    );
+    html.push_str(&format!(
+        "<h2>{}</h2>\n",
+        escape(tr("report.configuration"))
+    ));
+    html.push_str(&table(&report.configuration));
+    html.push_str(&format!("<pre>{}</pre>\n", escape(&report.scene)));
+    html.push_str(&format!("<h2>{}</h2>\n", escape(tr("report.summary"))));
+    html.push_str(&table(&report.summary));
+    if !report.fit.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>\n", escape(tr("report.fit"))));
+        html.push_str(&table(&report.fit));
+        html.push_str(&format!(
+            "<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>\n",
+            escape(tr("report.angle")),
+            escape(tr("report.hits")),
+            escape(tr("report.predicted")),
+            escape(tr("report.residual"))
+        ));
+        for (range, count, predicted, residual) in &report.bins {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                range,
+                count,
+                predicted_text(*predicted),
+                residual_text(*residual)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str(&format!("<h2>{}</h2>\n", escape(tr("report.charts"))));
+    for chart in &report.charts {
+        html.push_str(&format!("<h3>{}</h3>\n{}", escape(&chart.title), chart.svg));
+    }
+    html + "</body>\n</html>\n"
+}
+
+pub fn dir() -> PathBuf {
+    Settings::path().with_file_name("reports")
+}
+
+// Writes an HTML report with the charts inline, and the same as Markdown with
+// the charts as SVG files beside it.
+pub fn write(world: &World) {
+    let dir = dir();
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let stem = format!("report-{}", stamp);
+    let report = gather(world);
+    let result = fs::create_dir_all(&dir).and_then(|_| {
+        fs::write(dir.join(format!("{}.html", stem)), html(&report))?;
+        fs::write(dir.join(format!("{}.md", stem)), markdown(&report, &stem))?;
+        report.charts.iter().try_for_each(|chart| {
+            fs::write(dir.join(format!("{}-{}.svg", stem, chart.name)), &chart.svg)
+        })
+    });
+    match result {
+        Ok(()) => {
+            let message = trf("report.written", &[&dir.join(&stem).display()]);
+            log::info!("{}", message);
+            toast::info(message);
+        }
+        Err(err) => {
+            log::warn!("could not write report to {}: {}", dir.display(), err);
+            toast::warning(tr("report.not_written").to_string());
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use macroquad::prelude::*;
+
+// Small seeded generator (SplitMix64) for results that must come out the same
+// again from the same seed. macroquad's generator is global, so anything else
+// drawing from it would shift the sequence.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    // Everything needed to carry on the sequence: `Rng::new(rng.state())`
+    // draws the same numbers from here on.
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.next_f32()
+    }
+
+    // Uniform over the unit sphere. Drawn from the unit ball and scaled out
+    // rather than from two angles, so no sine or cosine, whose last bits
+    // vary between platforms, gets between the seed and the result.
+    pub fn unit_vector(&mut self) -> Vec3 {
+        loop {
+            let v = vec3(
+                self.range(-1., 1.),
+                self.range(-1., 1.),
+                self.range(-1., 1.),
+            );
+            let length_squared = v.length_squared();
+            if length_squared > 1e-6 && length_squared <= 1. {
+                return v / length_squared.sqrt();
+            }
+        }
+    }
+}
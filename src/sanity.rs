@@ -0,0 +1,132 @@
+use macroquad::prelude::*;
+
+use crate::ecs::Entity;
+use crate::world::World;
+
+// Per-tick speed above which a particle is considered to have exploded.
+pub const MAX_SPEED: f32 = 1000.;
+// Distance from the origin beyond which a particle is considered lost.
+pub const MAX_DISTANCE: f32 = 1_000_000.;
+// How often a healthy world is copied as the rewind target.
+const SNAPSHOT_INTERVAL: u32 = 60;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Fault {
+    NonFinite,
+    Exploded,
+    Escaped,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Problem {
+    pub entity: Entity,
+    pub fault: Fault,
+}
+
+pub fn check(world: &World) -> Vec<Problem> {
+    let particles = &world.particles;
+    particles
+        .entities()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entity)| {
+            let (pos, vel, acc) = (particles.pos[i], particles.vel[i], particles.acc[i]);
+            let fault = if !(pos.is_finite() && vel.is_finite() && acc.is_finite()) {
+                Fault::NonFinite
+            } else if vel.length() > MAX_SPEED {
+                Fault::Exploded
+            } else if pos.length() > MAX_DISTANCE {
+                Fault::Escaped
+            } else {
+                return None;
+            };
+            Some(Problem {
+                entity: *entity,
+                fault,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct SanityChecker {
+    pub problems: Vec<Problem>,
+    last_good: Option<World>,
+    ticks_since_snapshot: u32,
+}
+
+impl SanityChecker {
+    pub fn new() -> SanityChecker {
+        SanityChecker::default()
+    }
+
+    pub fn has_problems(&self) -> bool {
+        !self.problems.is_empty()
+    }
+
+    pub fn can_rewind(&self) -> bool {
+        self.last_good.is_some()
+    }
+
+    // Returns true when the world is healthy after this tick.
+    pub fn after_tick(&mut self, world: &World) -> bool {
+        self.problems = check(world);
+        if !self.problems.is_empty() {
+            for problem in &self.problems {
+                log::warn!(
+                    "particle {:?} failed sanity check: {:?}",
+                    problem.entity,
+                    problem.fault
+                );
+            }
+            return false;
+        }
+
+        if self.ticks_since_snapshot == 0 || self.last_good.is_none() {
+            self.last_good = Some(world.clone());
+        }
+        self.ticks_since_snapshot = (self.ticks_since_snapshot + 1) % SNAPSHOT_INTERVAL;
+        true
+    }
+
+    pub fn clamp(&mut self, world: &mut World) {
+        for problem in self.problems.drain(..) {
+            let fallback = self
+                .last_good
+                .as_ref()
+                .and_then(|good| Some(good.particles.pos[good.particles.slot(problem.entity)?]))
+                .unwrap_or(Vec3::ZERO);
+
+            let particles = &mut world.particles;
+            let Some(slot) = particles.slot(problem.entity) else {
+                continue;
+            };
+            if !particles.pos[slot].is_finite() {
+                particles.pos[slot] = fallback;
+            }
+            if !particles.vel[slot].is_finite() {
+                particles.vel[slot] = Vec3::ZERO;
+            }
+            if !particles.acc[slot].is_finite() {
+                particles.acc[slot] = Vec3::ZERO;
+            }
+            particles.vel[slot] = particles.vel[slot].clamp_length_max(MAX_SPEED);
+            particles.pos[slot] = particles.pos[slot].clamp_length_max(MAX_DISTANCE);
+        }
+    }
+
+    pub fn remove(&mut self, world: &mut World) {
+        for problem in self.problems.drain(..) {
+            world.despawn(problem.entity);
+        }
+    }
+
+    pub fn rewind(&mut self, world: &mut World) -> bool {
+        let Some(good) = &self.last_good else {
+            return false;
+        };
+        *world = good.clone();
+        self.problems.clear();
+        true
+    }
+}
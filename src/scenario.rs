@@ -0,0 +1,639 @@
+use std::fmt;
+
+use macroquad::prelude::*;
+
+use crate::bubble::TimeBubble;
+use crate::foil::{self, Element};
+use crate::magnets::Magnet;
+use crate::medium::Medium;
+use crate::particle::{get_charge, Particle};
+use crate::portable;
+use crate::rng::Rng;
+use crate::sources::Source;
+use crate::species::{self, Species};
+use crate::systems::ForceLaw;
+use crate::units::{self, SECONDS_PER_TICK};
+use crate::world::World;
+
+// Largest changes `Scenario::randomized` makes: position as a fraction of the
+// distance to the nearest other body, speed as a fraction of itself, and
+// direction in radians
+pub const POSITION_JITTER: f32 = 0.05;
+pub const SPEED_JITTER: f32 = 0.05;
+pub const ANGLE_JITTER: f32 = 0.05;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Body {
+    pub particle: Particle,
+    pub pos: Vec3,
+    pub vel: Vec3,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Initial conditions for a scene, written as `key = value` lines:
+//
+//     name = Hydrogen
+//     particle = electron 200 0 0
+//     particle = proton 100 0 0 0 0.5 0
+//     foil = gold 0 0 0
+//     species = muon -1 0.1126 255 128 0 1.5
+//     particle = muon 50 0 0
+//     seed = 42
+//     screening = 40
+//     nuclear_radius = 2
+//     planar = true
+//     force_law = 3
+//     strength = 0.5
+//     bubble = 0 0 0 30 0
+//     plane = 0 40 0 0 1 0 0.001
+//     ring = 0 0 0 1 0 0 50 20
+//     shell = 0 0 0 80 -10
+//     capacitor = 0 0 0 0 1 0 100 40000 2e6
+//     magnet = 0 0 0 0 0 1 20 1e10
+//     solenoid = 0 0 0 1 0 0 60 400 3e9
+//     gravity = 0 -4e25 0
+//     drag = 6e19
+//
+// A particle line is the species, its position and an optional velocity. A
+// foil line is the element and the center of its patch of nuclei. Species
+// lines define custom particles as in `species::Species::parse`, and must come
+// before the particles that use them. A seed turns on deterministic mode,
+// see `World::rng`. A screening length screens every charge and a nuclear
+// radius spreads it over a sphere; the force law is the exponent of 1 / rⁿ and
+// the strength scales the Coulomb constant, see `systems::ForceModel`. A
+// planar scene keeps everything in the z = 0 plane. A bubble line is the
+// center, radius and rate of a `bubble::TimeBubble`. Plane, ring and shell
+// lines are fixed charge distributions, see `sources::Source`: a point and a
+// normal for the plane followed by its charge density, a center and a normal
+// for the ring followed by its radius and charge, and a center, radius and
+// charge for the shell. Their charges are in elementary charges. A capacitor
+// line is the center and normal, then the plate separation, plate area and
+// voltage in volts. Magnet and solenoid lines are a center and an axis, then
+// the radius, the solenoid's length and the field in tesla, see
+// `magnets::Magnet`. Gravity is an acceleration in metres per second squared
+// and drag a rate per second, see `medium::Medium`.
+// Lines starting with `#` are comments.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub bodies: Vec<Body>,
+    pub foils: Vec<(Element, Vec3)>,
+    pub seed: Option<u64>,
+    pub screening: Option<f32>,
+    pub nuclear_radius: Option<f32>,
+    pub planar: bool,
+    pub force_law: ForceLaw,
+    pub strength: Option<f32>,
+    pub bubbles: Vec<TimeBubble>,
+    pub sources: Vec<Source>,
+    pub magnets: Vec<Magnet>,
+    pub medium: Medium,
+}
+
+impl Scenario {
+    pub fn parse(source: &str) -> Result<Scenario, ParseError> {
+        let mut scenario = Scenario::default();
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| ParseError {
+                line: number + 1,
+                message,
+            };
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected 'key = value', found '{}'", line)));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "name" => scenario.name = value.to_string(),
+                "particle" => scenario.bodies.push(parse_body(value).map_err(error)?),
+                "foil" => scenario.foils.push(parse_foil(value).map_err(error)?),
+                "plane" | "ring" | "shell" | "capacitor" => scenario
+                    .sources
+                    .push(parse_source(key.trim(), value).map_err(error)?),
+                "magnet" | "solenoid" => scenario
+                    .magnets
+                    .push(parse_magnet(key.trim(), value).map_err(error)?),
+                "bubble" => scenario.bubbles.push(parse_bubble(value).map_err(error)?),
+                "gravity" => {
+                    let numbers = value
+                        .split_whitespace()
+                        .map(|field| field.parse::<f32>().ok())
+                        .collect::<Option<Vec<f32>>>();
+                    let Some([x, y, z]) = numbers.as_deref() else {
+                        return Err(error(format!("'{}' is not an acceleration", value)));
+                    };
+                    scenario.medium.gravity = vec3(
+                        units::acceleration_from_si(*x),
+                        units::acceleration_from_si(*y),
+                        units::acceleration_from_si(*z),
+                    );
+                }
+                "drag" => {
+                    let rate = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|rate| *rate >= 0.)
+                        .ok_or_else(|| error(format!("'{}' is not a rate", value)))?;
+                    scenario.medium.drag = rate * SECONDS_PER_TICK;
+                }
+                "seed" => {
+                    let seed = value
+                        .parse::<u64>()
+                        .map_err(|_| error(format!("'{}' is not a whole number", value)))?;
+                    scenario.seed = Some(seed);
+                }
+                "screening" => {
+                    let length = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|length| *length > 0.)
+                        .ok_or_else(|| error(format!("'{}' is not a length", value)))?;
+                    scenario.screening = Some(length);
+                }
+                "nuclear_radius" => {
+                    let radius = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|radius| *radius > 0.)
+                        .ok_or_else(|| error(format!("'{}' is not a length", value)))?;
+                    scenario.nuclear_radius = Some(radius);
+                }
+                "planar" => {
+                    scenario.planar = value
+                        .parse::<bool>()
+                        .map_err(|_| error(format!("'{}' is not true or false", value)))?;
+                }
+                "force_law" => {
+                    scenario.force_law = value
+                        .parse::<u32>()
+                        .ok()
+                        .and_then(ForceLaw::from_exponent)
+                        .ok_or_else(|| error(format!("'{}' is not 1, 2 or 3", value)))?;
+                }
+                "strength" => {
+                    let strength = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|strength| *strength > 0.)
+                        .ok_or_else(|| error(format!("'{}' is not a positive number", value)))?;
+                    scenario.strength = Some(strength);
+                }
+                "species" => {
+                    species::register(Species::parse(value).map_err(error)?);
+                }
+                other => return Err(error(format!("unknown key '{}'", other))),
+            }
+        }
+        Ok(scenario)
+    }
+
+    pub fn from_world(world: &World) -> Scenario {
+        let particles = &world.particles;
+        // Foil nuclei are written as their foil rather than one by one
+        let bodies = (0..particles.len())
+            .filter(|i| foil::foil_of(world, particles.entities()[*i]).is_none())
+            .map(|i| Body {
+                particle: particles.particle[i],
+                pos: particles.pos[i],
+                vel: particles.vel[i],
+            })
+            .collect();
+        Scenario {
+            name: String::new(),
+            bodies,
+            foils: world
+                .foils
+                .iter()
+                .map(|foil| (foil.element, foil.center))
+                .collect(),
+            // The generator's state is all it takes to carry on the sequence
+            seed: world.rng.as_ref().map(Rng::state),
+            screening: world.force_model.screening,
+            nuclear_radius: world.force_model.nuclear_radius,
+            planar: world.planar,
+            force_law: world.force_model.law,
+            strength: Some(world.force_model.strength).filter(|strength| *strength != 1.),
+            bubbles: world.bubbles.clone(),
+            sources: world.sources.clone(),
+            magnets: world.magnets.clone(),
+            medium: world.medium,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if !self.name.is_empty() {
+            text.push_str(&format!("name = {}\n", self.name));
+        }
+        // Custom species travel with the scene so it loads anywhere
+        let mut custom: Vec<u16> = Vec::new();
+        for body in &self.bodies {
+            if let Particle::Custom(index) = body.particle {
+                if !custom.contains(&index) {
+                    custom.push(index);
+                    if let Some(species) = species::get(index) {
+                        text.push_str(&(species.to_line() + "\n"));
+                    }
+                }
+            }
+        }
+        for body in &self.bodies {
+            let (p, v) = (body.pos, body.vel);
+            text.push_str(&format!(
+                "particle = {} {} {} {} {} {} {}\n",
+                body.particle.name(),
+                p.x,
+                p.y,
+                p.z,
+                v.x,
+                v.y,
+                v.z
+            ));
+        }
+        for (element, c) in &self.foils {
+            text.push_str(&format!(
+                "foil = {} {} {} {}\n",
+                element.code(),
+                c.x,
+                c.y,
+                c.z
+            ));
+        }
+        for bubble in &self.bubbles {
+            let c = bubble.center;
+            text.push_str(&format!(
+                "bubble = {} {} {} {} {}\n",
+                c.x, c.y, c.z, bubble.radius, bubble.rate
+            ));
+        }
+        for source in &self.sources {
+            text.push_str(&(source_line(source) + "\n"));
+        }
+        for magnet in &self.magnets {
+            text.push_str(&(magnet_line(magnet) + "\n"));
+        }
+        let gravity = self.medium.gravity;
+        if gravity != Vec3::ZERO {
+            text.push_str(&format!(
+                "gravity = {} {} {}\n",
+                units::acceleration_to_si(gravity.x),
+                units::acceleration_to_si(gravity.y),
+                units::acceleration_to_si(gravity.z)
+            ));
+        }
+        if self.medium.drag > 0. {
+            let rate = self.medium.drag / SECONDS_PER_TICK;
+            text.push_str(&format!("drag = {}\n", rate));
+        }
+        if let Some(seed) = self.seed {
+            text.push_str(&format!("seed = {}\n", seed));
+        }
+        if let Some(length) = self.screening {
+            text.push_str(&format!("screening = {}\n", length));
+        }
+        if let Some(radius) = self.nuclear_radius {
+            text.push_str(&format!("nuclear_radius = {}\n", radius));
+        }
+        if self.planar {
+            text.push_str("planar = true\n");
+        }
+        if self.force_law != ForceLaw::InverseSquare {
+            text.push_str(&format!("force_law = {}\n", self.force_law.exponent()));
+        }
+        if let Some(strength) = self.strength {
+            text.push_str(&format!("strength = {}\n", strength));
+        }
+        text
+    }
+
+    // A copy with every body nudged by an amount drawn from `seed`, so the same
+    // seed always gives the same scene. Scaling the nudge by the distance to
+    // the nearest neighbour keeps atoms bound; for a projectile it shifts the
+    // impact parameter. Foils stay where they are.
+    pub fn randomized(&self, seed: u64) -> Scenario {
+        self.perturbed(seed, 1.)
+    }
+
+    // As `randomized`, with every jitter scaled by `scale`.
+    pub fn perturbed(&self, seed: u64, scale: f32) -> Scenario {
+        let mut rng = Rng::new(seed);
+        let anchors: Vec<Vec3> = self
+            .bodies
+            .iter()
+            .map(|body| body.pos)
+            .chain(self.foils.iter().map(|(_, center)| *center))
+            .collect();
+        let mut scenario = self.clone();
+        for (i, body) in scenario.bodies.iter_mut().enumerate() {
+            let nearest = anchors
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, pos)| pos.distance(body.pos))
+                .fold(f32::INFINITY, f32::min);
+            if nearest.is_finite() {
+                body.pos += rng.unit_vector() * rng.range(0., scale * POSITION_JITTER * nearest);
+            }
+            let axis = body.vel.cross(rng.unit_vector()).normalize_or_zero();
+            let angle = rng.range(-scale * ANGLE_JITTER, scale * ANGLE_JITTER);
+            if axis != Vec3::ZERO {
+                // `Quat::from_axis_angle` with the portable sine and cosine
+                let (sin, cos) = portable::sin_cos(angle / 2.);
+                let rotation = Quat::from_xyzw(axis.x * sin, axis.y * sin, axis.z * sin, cos);
+                body.vel = rotation * body.vel;
+            }
+            body.vel *= 1. + scale * rng.range(-SPEED_JITTER, SPEED_JITTER);
+        }
+        scenario
+    }
+
+    // Moves the bodies of `world` to the state given here, provided it holds
+    // the same species in the same order and the same number of foils, as it
+    // does when both came from the same scene. Returns false and leaves the
+    // world alone otherwise. Unlike `build` this keeps trails and settings.
+    pub fn apply_to(&self, world: &mut World) -> bool {
+        let particles = &world.particles;
+        let slots: Vec<usize> = (0..particles.len())
+            .filter(|i| foil::foil_of(world, particles.entities()[*i]).is_none())
+            .collect();
+        let same = slots.len() == self.bodies.len()
+            && world.foils.len() == self.foils.len()
+            && world.planar == self.planar
+            && slots
+                .iter()
+                .zip(&self.bodies)
+                .all(|(slot, body)| particles.particle[*slot] == body.particle);
+        if !same {
+            return false;
+        }
+        for (slot, body) in slots.into_iter().zip(&self.bodies) {
+            world.particles.pos[slot] = body.pos;
+            world.particles.vel[slot] = body.vel;
+        }
+        world.rng = self.seed.map(Rng::new);
+        true
+    }
+
+    pub fn build(&self) -> World {
+        let mut world = World::new();
+        world.rng = self.seed.map(Rng::new);
+        world.force_model.screening = self.screening;
+        world.force_model.nuclear_radius = self.nuclear_radius;
+        world.force_model.law = self.force_law;
+        world.force_model.strength = self.strength.unwrap_or(1.);
+        // Before anything is spawned, so bodies and foils land in the plane
+        world.planar = self.planar;
+        world.bubbles = self.bubbles.clone();
+        world.sources = self.sources.clone();
+        world.magnets = self.magnets.clone();
+        world.medium = self.medium;
+        for body in &self.bodies {
+            let entity = world.spawn_particle(body.particle, Some(body.pos));
+            if let Some(slot) = world.particles.slot(entity) {
+                world.particles.vel[slot] = world.constrain(body.vel);
+            }
+        }
+        for (element, center) in &self.foils {
+            foil::add_foil(&mut world, *element, *center);
+        }
+        // Loading a scene is not the same as the user spawning particles
+        world.events.clear();
+        world
+    }
+}
+
+fn parse_body(value: &str) -> Result<Body, String> {
+    let mut fields = value.split_whitespace();
+    let code = fields.next().unwrap_or_default();
+    let particle =
+        Particle::from_code(code).ok_or_else(|| format!("unknown particle '{}'", code))?;
+    let numbers = fields
+        .map(|field| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a number", field))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    let (pos, vel) = match numbers.as_slice() {
+        [x, y, z] => (vec3(*x, *y, *z), Vec3::ZERO),
+        [x, y, z, vx, vy, vz] => (vec3(*x, *y, *z), vec3(*vx, *vy, *vz)),
+        _ => return Err("expected a position and an optional velocity".to_string()),
+    };
+    Ok(Body { particle, pos, vel })
+}
+
+fn parse_foil(value: &str) -> Result<(Element, Vec3), String> {
+    let mut fields = value.split_whitespace();
+    let code = fields.next().unwrap_or_default();
+    let element = Element::from_code(code).ok_or_else(|| format!("unknown element '{}'", code))?;
+    let numbers = fields
+        .map(|field| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a number", field))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    match numbers.as_slice() {
+        [x, y, z] => Ok((element, vec3(*x, *y, *z))),
+        _ => Err("expected a foil center".to_string()),
+    }
+}
+
+fn parse_bubble(value: &str) -> Result<TimeBubble, String> {
+    let numbers = value
+        .split_whitespace()
+        .map(|field| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a number", field))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    match numbers.as_slice() {
+        [x, y, z, radius, rate] if *radius > 0. && (0. ..=1.).contains(rate) => Ok(TimeBubble {
+            center: vec3(*x, *y, *z),
+            radius: *radius,
+            rate: *rate,
+        }),
+        _ => Err("expected a center, a radius and a rate from 0 to 1".to_string()),
+    }
+}
+
+fn parse_source(key: &str, value: &str) -> Result<Source, String> {
+    let numbers = value
+        .split_whitespace()
+        .map(|field| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a number", field))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    let e = get_charge(Particle::Proton);
+    let normal = |x: f32, y: f32, z: f32| {
+        vec3(x, y, z)
+            .try_normalize()
+            .ok_or_else(|| "the normal cannot be zero".to_string())
+    };
+    match (key, numbers.as_slice()) {
+        ("plane", [x, y, z, nx, ny, nz, density]) => Ok(Source::Plane {
+            point: vec3(*x, *y, *z),
+            normal: normal(*nx, *ny, *nz)?,
+            density: density * e,
+        }),
+        ("ring", [x, y, z, nx, ny, nz, radius, charge]) if *radius > 0. => Ok(Source::Ring {
+            center: vec3(*x, *y, *z),
+            normal: normal(*nx, *ny, *nz)?,
+            radius: *radius,
+            charge: charge * e,
+        }),
+        ("shell", [x, y, z, radius, charge]) if *radius > 0. => Ok(Source::Shell {
+            center: vec3(*x, *y, *z),
+            radius: *radius,
+            charge: charge * e,
+        }),
+        ("capacitor", [x, y, z, nx, ny, nz, separation, area, voltage])
+            if *separation > 0. && *area > 0. =>
+        {
+            Ok(Source::Capacitor {
+                center: vec3(*x, *y, *z),
+                normal: normal(*nx, *ny, *nz)?,
+                separation: *separation,
+                area: *area,
+                voltage: *voltage,
+            })
+        }
+        ("plane", _) => Err("expected a point, a normal and a charge density".to_string()),
+        ("ring", _) => Err("expected a center, a normal, a radius and a charge".to_string()),
+        ("capacitor", _) => {
+            Err("expected a center, a normal, a separation, an area and a voltage".to_string())
+        }
+        _ => Err("expected a center, a radius and a charge".to_string()),
+    }
+}
+
+fn source_line(source: &Source) -> String {
+    let e = get_charge(Particle::Proton);
+    match *source {
+        Source::Plane {
+            point: p,
+            normal: n,
+            density,
+        } => format!(
+            "plane = {} {} {} {} {} {} {}",
+            p.x,
+            p.y,
+            p.z,
+            n.x,
+            n.y,
+            n.z,
+            density / e
+        ),
+        Source::Ring {
+            center: c,
+            normal: n,
+            radius,
+            charge,
+        } => format!(
+            "ring = {} {} {} {} {} {} {} {}",
+            c.x,
+            c.y,
+            c.z,
+            n.x,
+            n.y,
+            n.z,
+            radius,
+            charge / e
+        ),
+        Source::Shell {
+            center: c,
+            radius,
+            charge,
+        } => format!("shell = {} {} {} {} {}", c.x, c.y, c.z, radius, charge / e),
+        Source::Capacitor {
+            center: c,
+            normal: n,
+            separation,
+            area,
+            voltage,
+        } => format!(
+            "capacitor = {} {} {} {} {} {} {} {} {}",
+            c.x, c.y, c.z, n.x, n.y, n.z, separation, area, voltage
+        ),
+    }
+}
+
+fn parse_magnet(key: &str, value: &str) -> Result<Magnet, String> {
+    let numbers = value
+        .split_whitespace()
+        .map(|field| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a number", field))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    let axis = |x: f32, y: f32, z: f32| {
+        vec3(x, y, z)
+            .try_normalize()
+            .ok_or_else(|| "the axis cannot be zero".to_string())
+    };
+    match (key, numbers.as_slice()) {
+        ("magnet", [x, y, z, ax, ay, az, radius, field]) if *radius > 0. => Ok(Magnet::Dipole {
+            center: vec3(*x, *y, *z),
+            axis: axis(*ax, *ay, *az)?,
+            radius: *radius,
+            field: *field,
+        }),
+        ("solenoid", [x, y, z, ax, ay, az, radius, length, field])
+            if *radius > 0. && *length > 0. =>
+        {
+            Ok(Magnet::Solenoid {
+                center: vec3(*x, *y, *z),
+                axis: axis(*ax, *ay, *az)?,
+                radius: *radius,
+                length: *length,
+                field: *field,
+            })
+        }
+        ("magnet", _) => Err("expected a center, an axis, a radius and a field".to_string()),
+        _ => Err("expected a center, an axis, a radius, a length and a field".to_string()),
+    }
+}
+
+fn magnet_line(magnet: &Magnet) -> String {
+    match *magnet {
+        Magnet::Dipole {
+            center: c,
+            axis: a,
+            radius,
+            field,
+        } => format!(
+            "magnet = {} {} {} {} {} {} {} {}",
+            c.x, c.y, c.z, a.x, a.y, a.z, radius, field
+        ),
+        Magnet::Solenoid {
+            center: c,
+            axis: a,
+            radius,
+            length,
+            field,
+        } => format!(
+            "solenoid = {} {} {} {} {} {} {} {} {}",
+            c.x, c.y, c.z, a.x, a.y, a.z, radius, length, field
+        ),
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::world::World;
+
+use crate::annotate::Annotations;
+use crate::i18n::tr;
+use crate::settings::Settings;
+use crate::toast;
+
+// Saved scenes live next to the settings file.
+pub fn path() -> PathBuf {
+    Settings::path().with_file_name("scene.txt")
+}
+
+// A scene file is a scenario followed by the annotation overlay.
+pub fn save(world: &World, annotations: &Annotations) {
+    let mut contents = Scenario::from_world(world).to_text();
+    for line in annotations.to_lines() {
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    let path = path();
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&path, contents)),
+        None => fs::write(&path, contents),
+    };
+    match result {
+        Ok(()) => {
+            log::info!("saved scene to {}", path.display());
+            toast::info(tr("toast.scene_saved").to_string());
+        }
+        Err(err) => {
+            log::warn!("could not save scene to {}: {}", path.display(), err);
+            toast::warning(tr("toast.scene_not_saved").to_string());
+        }
+    }
+}
+
+// Reads the scenario in `path` and replaces the annotations with its overlay.
+pub fn load(path: &Path, annotations: &mut Annotations) -> Option<Scenario> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            log::warn!("could not read scene {}: {}", path.display(), err);
+            return None;
+        }
+    };
+
+    let is_annotation = |line: &&str| line.trim_start().starts_with("annotation");
+    let overlay = source.lines().filter(is_annotation);
+    // Blank out the overlay rather than dropping it so error line numbers match
+    let scenario: Vec<&str> = source
+        .lines()
+        .map(|line| if is_annotation(&line) { "" } else { line })
+        .collect();
+    match Scenario::parse(&scenario.join("\n")) {
+        Ok(scenario) => {
+            annotations.load_lines(
+                overlay.filter_map(|line| line.split_once('=').map(|(_, value)| value.trim())),
+            );
+            log::info!("loaded scene from {}", path.display());
+            Some(scenario)
+        }
+        Err(err) => {
+            log::warn!("could not load scene {}: {}", path.display(), err);
+            toast::warning(tr("toast.scene_not_loaded").to_string());
+            None
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::particle::PARTICLE_RADIUS;
+use rutherford_atomic_model::species;
+use rutherford_atomic_model::world::World;
+
+use crate::theme::Theme;
+use crate::visibility::SpeciesVisibility;
+
+// Extra slack around each sphere so small particles are still easy to click.
+const PICK_RADIUS: f32 = PARTICLE_RADIUS * 1.5;
+// Radians per second of the hover ring's pulse
+const PULSE_SPEED: f32 = 6.;
+
+// World-space ray from the camera through the mouse cursor.
+pub fn mouse_ray(camera: &Camera3D) -> (Vec3, Vec3) {
+    let (mx, my) = mouse_position();
+    let ndc = vec2(
+        mx / screen_width() * 2. - 1.,
+        1. - my / screen_height() * 2.,
+    );
+    let inverse = camera.matrix().inverse();
+    let near = inverse.project_point3(vec3(ndc.x, ndc.y, -1.));
+    let far = inverse.project_point3(vec3(ndc.x, ndc.y, 1.));
+    (near, (far - near).normalize())
+}
+
+// Screen position of `pos`, or `None` when it is behind the camera.
+pub fn world_to_screen(camera: &Camera3D, pos: Vec3) -> Option<Vec2> {
+    let clip = camera.matrix() * pos.extend(1.);
+    if clip.w <= 0. {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    Some(vec2(
+        (ndc.x + 1.) / 2. * screen_width(),
+        (1. - ndc.y) / 2. * screen_height(),
+    ))
+}
+
+// Camera position that keeps `front` as the view direction and fits the
+// sphere around `center` in the default field of view.
+pub fn framing_position(center: Vec3, radius: f32, front: Vec3) -> Vec3 {
+    let fovy = Camera3D::default().fovy;
+    let radius = radius.max(PARTICLE_RADIUS * 10.);
+    let distance = radius / (fovy / 2.).sin() * 1.1;
+    center - front.normalize() * distance
+}
+
+pub fn pick(world: &World, camera: &Camera3D, visibility: &SpeciesVisibility) -> Option<Entity> {
+    let (origin, dir) = mouse_ray(camera);
+    let particles = &world.particles;
+
+    // A sphere the ray passes through beats one it only grazes within the
+    // slack, however much nearer the camera the grazed one is, so in a
+    // crowded scene the click goes to whatever is under the cursor
+    particles
+        .entities()
+        .iter()
+        .zip(&particles.pos)
+        .zip(&particles.particle)
+        .filter(|(_, particle)| visibility.shows(**particle))
+        .filter_map(|((entity, pos), particle)| {
+            let to_center = *pos - origin;
+            let t = to_center.dot(dir);
+            let miss = (to_center - dir * t).length();
+            let radius = species::radius(*particle);
+            let grazed = miss > radius;
+            (t > 0. && miss < radius.max(PICK_RADIUS)).then_some((*entity, grazed, t, miss))
+        })
+        .min_by(|a, b| {
+            a.1.cmp(&b.1).then(if a.1 {
+                a.3.total_cmp(&b.3)
+            } else {
+                a.2.total_cmp(&b.2)
+            })
+        })
+        .map(|(entity, ..)| entity)
+}
+
+// Centre and radius on screen of `entity`'s sphere, or `None` when it is
+// gone or behind the camera.
+pub fn screen_circle(world: &World, camera: &Camera3D, entity: Entity) -> Option<(Vec2, f32)> {
+    let slot = world.particles.slot(entity)?;
+    let pos = world.particles.pos[slot];
+    let center = world_to_screen(camera, pos)?;
+    let across = (pos - camera.position).cross(camera.up).try_normalize()?;
+    let radius = species::radius(world.particles.particle[slot]);
+    let edge = world_to_screen(camera, pos + across * radius)?;
+    Some((center, center.distance(edge)))
+}
+
+// Rings drawn in screen space over the scene: a pulsing one around the
+// particle under the cursor, so it is clear what a click would pick, and a
+// steady double one around the selection. Both stay a readable size however
+// far off the particle is.
+pub fn draw_outlines(
+    world: &World,
+    camera: &Camera3D,
+    hovered: Option<Entity>,
+    selected: Option<Entity>,
+    theme: &Theme,
+) {
+    let gap = theme.scaled(4.);
+    if let Some((center, radius)) = hovered
+        .filter(|entity| Some(*entity) != selected)
+        .and_then(|entity| screen_circle(world, camera, entity))
+    {
+        let pulse = (get_time() as f32 * PULSE_SPEED).sin() * 0.5 + 0.5;
+        draw_circle_lines(
+            center.x,
+            center.y,
+            radius.max(gap) + gap * (1. + pulse),
+            theme.scaled(1.5),
+            Color {
+                a: 0.5 + 0.5 * pulse,
+                ..theme.warning
+            },
+        );
+    }
+    if let Some((center, radius)) = selected.and_then(|entity| screen_circle(world, camera, entity))
+    {
+        let radius = radius.max(gap) + gap;
+        draw_circle_lines(
+            center.x,
+            center.y,
+            radius,
+            theme.scaled(2.5),
+            theme.selection,
+        );
+        draw_circle_lines(
+            center.x,
+            center.y,
+            radius + gap,
+            theme.scaled(1.),
+            Color {
+                a: 0.5,
+                ..theme.selection
+            },
+        );
+    }
+}
+
+pub fn draw_selection(world: &World, selected: Option<Entity>, theme: &Theme) {
+    let Some(slot) = selected.and_then(|entity| world.particles.slot(entity)) else {
+        return;
+    };
+    draw_sphere_wires(
+        world.particles.pos[slot],
+        PARTICLE_RADIUS * 1.5,
+        None,
+        theme.selection,
+    );
+}
@@ -0,0 +1,369 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::environment::{Fog, Skybox};
+use crate::i18n::Language;
+use crate::theme::ThemeKind;
+
+use rutherford_atomic_model::particle::{Particle, TRAIL_LENGTH};
+
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 3.;
+pub const MAX_TRAIL_LENGTH: usize = 2000;
+pub const MAX_GRID_SLICES: u32 = 4000;
+pub const MIN_BOUNDS: f32 = 20.;
+pub const MAX_BOUNDS: f32 = 10_000.;
+// Closest approach that triggers slow motion, and the ticks per frame it runs at
+pub const MIN_SLOW_MOTION_DISTANCE: f32 = 0.5;
+pub const MAX_SLOW_MOTION_DISTANCE: f32 = 500.;
+pub const MIN_SLOW_MOTION_RATE: f32 = 0.01;
+pub const MAX_SLOW_MOTION_RATE: f32 = 0.5;
+
+pub const RESOLUTIONS: [(i32, i32); 6] = [
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2360, 1368),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    High,
+}
+
+pub const QUALITY_PRESETS: [QualityPreset; 3] = [
+    QualityPreset::Low,
+    QualityPreset::Medium,
+    QualityPreset::High,
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Quality {
+    // Rings and slices used to tessellate particle spheres
+    pub sphere_detail: usize,
+    // Interpolated points inserted between consecutive trail samples
+    pub trail_smoothness: usize,
+}
+
+impl Quality {
+    pub fn preset(preset: QualityPreset) -> Quality {
+        match preset {
+            QualityPreset::Low => Quality {
+                sphere_detail: 6,
+                trail_smoothness: 0,
+            },
+            QualityPreset::Medium => Quality {
+                sphere_detail: 10,
+                trail_smoothness: 2,
+            },
+            QualityPreset::High => Quality {
+                sphere_detail: 16,
+                trail_smoothness: 4,
+            },
+        }
+    }
+}
+
+impl QualityPreset {
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            QualityPreset::Low => "quality.low",
+            QualityPreset::Medium => "quality.medium",
+            QualityPreset::High => "quality.high",
+        }
+    }
+}
+
+// Persisted user preferences, stored as `key = value` lines like the
+// translation catalogs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Settings {
+    pub ui_scale: f32,
+    pub theme: ThemeKind,
+    pub language: Language,
+    pub window_width: i32,
+    pub window_height: i32,
+    pub fullscreen: bool,
+    // Only read when the window is created, so changes apply after a restart
+    pub vsync: bool,
+    pub quality: Quality,
+    pub skybox: Skybox,
+    pub fog: Fog,
+    pub tutorial_done: bool,
+    pub trails: TrailSettings,
+    pub grid: GridSettings,
+    pub bounds: BoundsSettings,
+    pub slow_motion: SlowMotionSettings,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridSettings {
+    // Lines on each side of the origin, and the distance between them
+    pub slices: u32,
+    pub spacing: f32,
+}
+
+// Reflecting walls of a cube centered on the origin, see `World::bounds`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoundsSettings {
+    pub enabled: bool,
+    pub half_size: f32,
+}
+
+impl BoundsSettings {
+    pub fn active(&self) -> Option<f32> {
+        self.enabled.then_some(self.half_size)
+    }
+}
+
+// Drop to `rate` ticks per frame while two particles closing in are nearer
+// than `distance`, see `slowmo::SlowMotion`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SlowMotionSettings {
+    pub enabled: bool,
+    pub distance: f32,
+    pub rate: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TrailSettings {
+    pub length: usize,
+    // Older points fade towards transparent
+    pub fade: bool,
+    // Keep the whole trajectory instead of the last `length` points
+    pub persistent: bool,
+    pub electron: bool,
+    pub proton: bool,
+    pub neutron: bool,
+    pub alpha: bool,
+}
+
+impl TrailSettings {
+    pub fn limit(&self) -> Option<usize> {
+        (!self.persistent).then_some(self.length)
+    }
+
+    pub fn shows(&self, particle: Particle) -> bool {
+        match particle {
+            // Antiparticles follow their partner's setting
+            Particle::Electron | Particle::Positron => self.electron,
+            Particle::Proton | Particle::Antiproton => self.proton,
+            Particle::Neutron => self.neutron,
+            Particle::Alpha => self.alpha,
+            Particle::Custom(_) => true,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            ui_scale: 1.,
+            theme: ThemeKind::Dark,
+            language: Language::English,
+            window_width: 2360,
+            window_height: 1368,
+            fullscreen: false,
+            vsync: true,
+            quality: Quality::preset(QualityPreset::High),
+            skybox: Skybox::Off,
+            fog: Fog {
+                enabled: false,
+                start: 200.,
+                end: 2000.,
+            },
+            tutorial_done: false,
+            trails: TrailSettings {
+                length: TRAIL_LENGTH,
+                fade: true,
+                persistent: false,
+                electron: true,
+                proton: true,
+                neutron: true,
+                alpha: true,
+            },
+            grid: GridSettings {
+                slices: 2000,
+                spacing: 10.,
+            },
+            bounds: BoundsSettings {
+                enabled: false,
+                half_size: 1000.,
+            },
+            slow_motion: SlowMotionSettings {
+                enabled: false,
+                distance: 10.,
+                rate: 0.125,
+            },
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .unwrap_or_else(|| PathBuf::from("."));
+        base.join("rutherford_atomic_model").join("settings.txt")
+    }
+
+    pub fn load() -> Settings {
+        let mut settings = Settings::default();
+        let Ok(source) = fs::read_to_string(Settings::path()) else {
+            return settings;
+        };
+
+        for line in source.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "ui_scale" => {
+                    if let Ok(scale) = value.parse::<f32>() {
+                        settings.ui_scale = scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+                    }
+                }
+                "theme" => {
+                    if let Some(kind) = ThemeKind::from_code(value) {
+                        settings.theme = kind;
+                    }
+                }
+                "language" => {
+                    if let Some(language) = Language::from_code(value) {
+                        settings.language = language;
+                    }
+                }
+                "window_width" => {
+                    if let Ok(width) = value.parse::<i32>() {
+                        settings.window_width = width.max(320);
+                    }
+                }
+                "window_height" => {
+                    if let Ok(height) = value.parse::<i32>() {
+                        settings.window_height = height.max(240);
+                    }
+                }
+                "fullscreen" => settings.fullscreen = value == "true",
+                "vsync" => settings.vsync = value == "true",
+                "skybox" => {
+                    if let Some(skybox) = Skybox::from_code(value) {
+                        settings.skybox = skybox;
+                    }
+                }
+                "fog" => settings.fog.enabled = value == "true",
+                "fog_start" => {
+                    if let Ok(start) = value.parse::<f32>() {
+                        settings.fog.start = start.max(0.);
+                    }
+                }
+                "fog_end" => {
+                    if let Ok(end) = value.parse::<f32>() {
+                        settings.fog.end = end.max(1.);
+                    }
+                }
+                "sphere_detail" => {
+                    if let Ok(detail) = value.parse::<usize>() {
+                        settings.quality.sphere_detail = detail.clamp(4, 32);
+                    }
+                }
+                "trail_smoothness" => {
+                    if let Ok(smoothness) = value.parse::<usize>() {
+                        settings.quality.trail_smoothness = smoothness.min(8);
+                    }
+                }
+                "tutorial_done" => settings.tutorial_done = value == "true",
+                "trail_length" => {
+                    if let Ok(length) = value.parse::<usize>() {
+                        settings.trails.length = length.clamp(2, MAX_TRAIL_LENGTH);
+                    }
+                }
+                "trail_fade" => settings.trails.fade = value == "true",
+                "trail_persistent" => settings.trails.persistent = value == "true",
+                "trails_electron" => settings.trails.electron = value == "true",
+                "trails_proton" => settings.trails.proton = value == "true",
+                "trails_neutron" => settings.trails.neutron = value == "true",
+                "trails_alpha" => settings.trails.alpha = value == "true",
+                "grid_slices" => {
+                    if let Ok(slices) = value.parse::<u32>() {
+                        settings.grid.slices = slices.clamp(1, MAX_GRID_SLICES);
+                    }
+                }
+                "grid_spacing" => {
+                    if let Ok(spacing) = value.parse::<f32>() {
+                        settings.grid.spacing = spacing.clamp(1., 1000.);
+                    }
+                }
+                "bounds" => settings.bounds.enabled = value == "true",
+                "bounds_size" => {
+                    if let Ok(size) = value.parse::<f32>() {
+                        settings.bounds.half_size = size.clamp(MIN_BOUNDS, MAX_BOUNDS);
+                    }
+                }
+                "slow_motion" => settings.slow_motion.enabled = value == "true",
+                "slow_motion_distance" => {
+                    if let Ok(distance) = value.parse::<f32>() {
+                        settings.slow_motion.distance =
+                            distance.clamp(MIN_SLOW_MOTION_DISTANCE, MAX_SLOW_MOTION_DISTANCE);
+                    }
+                }
+                "slow_motion_rate" => {
+                    if let Ok(rate) = value.parse::<f32>() {
+                        settings.slow_motion.rate =
+                            rate.clamp(MIN_SLOW_MOTION_RATE, MAX_SLOW_MOTION_RATE);
+                    }
+                }
+                other => log::warn!("unknown setting '{}'", other),
+            }
+        }
+        settings.fog.end = settings.fog.end.max(settings.fog.start + 1.);
+        settings
+    }
+
+    pub fn save(&self) {
+        let path = Settings::path();
+        let contents = format!(
+            "ui_scale = {}\ntheme = {}\nlanguage = {}\nwindow_width = {}\nwindow_height = {}\nfullscreen = {}\nvsync = {}\nsphere_detail = {}\ntrail_smoothness = {}\nskybox = {}\nfog = {}\nfog_start = {}\nfog_end = {}\ntutorial_done = {}\ntrail_length = {}\ntrail_fade = {}\ntrail_persistent = {}\ntrails_electron = {}\ntrails_proton = {}\ntrails_neutron = {}\ntrails_alpha = {}\ngrid_slices = {}\ngrid_spacing = {}\nbounds = {}\nbounds_size = {}\nslow_motion = {}\nslow_motion_distance = {}\nslow_motion_rate = {}\n",
+            self.ui_scale,
+            self.theme.code(),
+            self.language.code(),
+            self.window_width,
+            self.window_height,
+            self.fullscreen,
+            self.vsync,
+            self.quality.sphere_detail,
+            self.quality.trail_smoothness,
+            self.skybox.code(),
+            self.fog.enabled,
+            self.fog.start,
+            self.fog.end,
+            self.tutorial_done,
+            self.trails.length,
+            self.trails.fade,
+            self.trails.persistent,
+            self.trails.electron,
+            self.trails.proton,
+            self.trails.neutron,
+            self.trails.alpha,
+            self.grid.slices,
+            self.grid.spacing,
+            self.bounds.enabled,
+            self.bounds.half_size,
+            self.slow_motion.enabled,
+            self.slow_motion.distance,
+            self.slow_motion.rate
+        );
+        let result = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&path, contents)),
+            None => fs::write(&path, contents),
+        };
+        if let Err(err) = result {
+            log::warn!("could not save settings to {}: {}", path.display(), err);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::trf;
+use crate::settings::SlowMotionSettings;
+use crate::theme::Theme;
+
+// Frames over which the rate eases to its new target
+const EASE_FRAMES: f32 = 10.;
+// Full speed comes back only once the closest pair is this many times the
+// threshold apart, so a pair hovering at it does not make the rate flicker
+const RELEASE: f32 = 1.5;
+// A pair still close after this many ticks is bound rather than passing, and
+// is ignored until it parts
+const MAX_TICKS: u32 = 200;
+
+// Slows the simulation down to a fraction of a tick per frame while two
+// particles close in on each other, and brings it back to full speed once
+// they part, so a fast fly-by can be watched without pausing and stepping by
+// hand. Atoms and other bound pairs, and the nuclei of a foil, sit close for
+// good and are left out. Only paces the render-thread simulation; the
+// physics thread keeps its own clock.
+pub struct SlowMotion {
+    // The pair being watched and the ticks it has been close for
+    engaged: Option<((Entity, Entity), u32)>,
+    // Bound pairs, ignored until they part
+    ignored: HashSet<(Entity, Entity)>,
+    // Ticks per frame, easing towards 1 or the slow rate
+    rate: f32,
+    // Part of a tick carried over to the next frame
+    owed: f32,
+}
+
+impl SlowMotion {
+    pub fn new() -> SlowMotion {
+        SlowMotion {
+            engaged: None,
+            ignored: HashSet::new(),
+            rate: 1.,
+            owed: 0.,
+        }
+    }
+
+    // Call after every tick.
+    pub fn update(&mut self, settings: &SlowMotionSettings, world: &World) {
+        if !settings.enabled {
+            self.engaged = None;
+            self.ignored.clear();
+            return;
+        }
+        let particles = &world.particles;
+        let position = |entity| particles.slot(entity).map(|slot| particles.pos[slot]);
+        let release = settings.distance * RELEASE;
+        self.ignored
+            .retain(|(a, b)| match (position(*a), position(*b)) {
+                (Some(a), Some(b)) => a.distance(b) <= release,
+                _ => false,
+            });
+
+        if let Some((pair, ticks)) = &mut self.engaged {
+            *ticks += 1;
+            match (position(pair.0), position(pair.1)) {
+                (Some(a), Some(b)) if a.distance(b) <= release => {
+                    if *ticks > MAX_TICKS {
+                        self.ignored.insert(*pair);
+                        self.engaged = None;
+                    }
+                }
+                _ => self.engaged = None,
+            }
+            return;
+        }
+
+        // The closest approaching pair in range engages, leaving out pairs
+        // of foil nuclei, which never move
+        let nuclei: HashSet<Entity> = world
+            .foils
+            .iter()
+            .flat_map(|foil| foil.nuclei.iter().copied())
+            .collect();
+        let entities = particles.entities();
+        let mut best: Option<(usize, usize, f32)> = None;
+        for i in 0..particles.len() {
+            for j in (i + 1)..particles.len() {
+                let pair = (entities[i], entities[j]);
+                if self.ignored.contains(&pair)
+                    || (nuclei.contains(&pair.0) && nuclei.contains(&pair.1))
+                {
+                    continue;
+                }
+                let r = particles.pos[j] - particles.pos[i];
+                let distance = r.length();
+                let approaching = r.dot(particles.vel[j] - particles.vel[i]) < 0.;
+                if distance < settings.distance
+                    && approaching
+                    && best.is_none_or(|(_, _, closest)| distance < closest)
+                {
+                    best = Some((i, j, distance));
+                }
+            }
+        }
+        self.engaged = best.map(|(i, j, _)| ((entities[i], entities[j]), 0));
+    }
+
+    // Whether a tick is due this frame; call once per frame the simulation
+    // could run. Never more than one tick a frame.
+    pub fn due(&mut self, settings: &SlowMotionSettings) -> bool {
+        let target = if self.engaged.is_some() && settings.enabled {
+            settings.rate
+        } else {
+            1.
+        };
+        self.rate += (target - self.rate) / EASE_FRAMES;
+        if (target - self.rate).abs() < 0.01 {
+            self.rate = target;
+        }
+        self.owed = (self.owed + self.rate).min(1.);
+        if self.owed >= 1. {
+            self.owed -= 1.;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        if self.rate >= 1. {
+            return;
+        }
+        let text = trf("slowmo.active", &[&format!("{:.0}", self.rate * 100.)]);
+        let size = theme.text_size(24.);
+        let width = measure_text(&text, None, size as u16, 1.).width;
+        draw_text(
+            &text,
+            (screen_width() - width) / 2.,
+            theme.scaled(60.),
+            size,
+            theme.warning,
+        );
+    }
+}
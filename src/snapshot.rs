@@ -0,0 +1,315 @@
+use macroquad::prelude::*;
+
+use crate::detector::{Detector, DetectorShape, Hit};
+use crate::ecs::Entity;
+use crate::foil;
+use crate::ghost::Ghost;
+use crate::group::{Group, GroupId};
+use crate::particle::{get_charge, get_mass, Particle, Trail};
+use crate::scenario::Scenario;
+use crate::systems::{Integrator, Substepping};
+use crate::world::World;
+
+// A world written out in full, for checkpoints: the scene as a scenario
+// plus everything a scenario leaves out.
+//
+//     annihilations = 3
+//     integrator = verlet
+//     substepping = true 0.01 64
+//     pair_off = electron electron
+//     bounds = 500
+//     group = 1 0.5 0 1 beam
+//     member = 0 0
+//     particle_state = 2 3.2e-27 1.6e-19
+//     nucleus = 0 0.1 -0.2 0 0 0 0
+//     trail = 0 -100 0 0 -99 0 0
+//     ghost = alpha 120 straight -100 0 0 1 0 0
+//     detector = plate 300 150 18 5120
+//     hit = 0 300 12.5 -4 4877
+//
+// Particles are referred to by index: the scenario's bodies first, in
+// order, then the nuclei of each foil. A group is its color (`-` for
+// none) and name, and a member line is a particle and a group index. A
+// particle state is the mass and charge of a body that differs from its
+// species.
+// Nucleus lines give the foil, position and velocity of every foil nucleus
+// left, in order, and replace the nuclei the foil lines spawn; checkpoints
+// written before they existed keep those. A trail lists its points oldest
+// first. The detector line is its shape, distance, extent, bins and tick; a
+// hit is the particle that made it (or `-`), where it landed and when.
+//
+// Left out, as they only matter for the frame they were made in: pending
+// events, the contacts and bound pairs the event tracker remembers, which
+// foil each alpha came closest to, and annihilation flashes.
+
+// Every particle in index order: bodies in slot order, then foil nuclei.
+fn indexed(world: &World) -> Vec<Entity> {
+    world
+        .particles
+        .entities()
+        .iter()
+        .copied()
+        .filter(|entity| foil::foil_of(world, *entity).is_none())
+        .chain(
+            world
+                .foils
+                .iter()
+                .flat_map(|foil| foil.nuclei.iter().copied()),
+        )
+        .collect()
+}
+
+fn vector(v: Vec3) -> String {
+    format!("{} {} {}", v.x, v.y, v.z)
+}
+
+pub fn to_text(world: &World) -> String {
+    let mut text = Scenario::from_world(world).to_text();
+    let particles = &world.particles;
+    let entities = indexed(world);
+    let index = |entity: Entity| entities.iter().position(|e| *e == entity);
+
+    text.push_str(&format!("annihilations = {}\n", world.annihilations));
+    text.push_str(&format!("integrator = {}\n", world.integrator.code()));
+    let substepping = world.substepping;
+    text.push_str(&format!(
+        "substepping = {} {} {}\n",
+        substepping.enabled, substepping.threshold, substepping.max_substeps
+    ));
+    for (a, b) in world.pair_forces.disabled_pairs() {
+        text.push_str(&format!("pair_off = {} {}\n", a.name(), b.name()));
+    }
+    if let Some(half) = world.bounds {
+        text.push_str(&format!("bounds = {}\n", half));
+    }
+    for group in &world.groups {
+        let color = group.color.map_or_else(
+            || "-".to_string(),
+            |c| format!("{} {} {} {}", c.r, c.g, c.b, c.a),
+        );
+        text.push_str(&format!("group = {} {}\n", color, group.name));
+    }
+    for (index, foil) in world.foils.iter().enumerate() {
+        for nucleus in &foil.nuclei {
+            let slot = particles.slot(*nucleus).unwrap();
+            text.push_str(&format!(
+                "nucleus = {} {} {}\n",
+                index,
+                vector(particles.pos[slot]),
+                vector(particles.vel[slot])
+            ));
+        }
+    }
+    for (i, entity) in entities.iter().copied().enumerate() {
+        let Some(slot) = particles.slot(entity) else {
+            continue;
+        };
+        if let Some(group) = world.membership.get(entity) {
+            text.push_str(&format!("member = {} {}\n", i, group.0));
+        }
+        let particle = particles.particle[slot];
+        let (mass, charge) = (particles.mass[slot], particles.charge[slot]);
+        if foil::foil_of(world, entity).is_none()
+            && (mass != get_mass(particle) || charge != get_charge(particle))
+        {
+            text.push_str(&format!("particle_state = {} {} {}\n", i, mass, charge));
+        }
+        if let Some(trail) = world.trails.get(entity) {
+            let points: Vec<String> = trail.points.iter().map(|p| vector(*p)).collect();
+            text.push_str(&format!("trail = {} {}\n", i, points.join(" ")));
+        }
+    }
+    for ghost in &world.ghosts {
+        text.push_str(&format!("ghost = {}\n", ghost.to_text()));
+    }
+    if let Some(detector) = &world.detector {
+        text.push_str(&format!(
+            "detector = {} {} {} {} {}\n",
+            detector.shape.code(),
+            detector.distance,
+            detector.extent,
+            detector.bins,
+            detector.tick
+        ));
+        for hit in &detector.hits {
+            let body = hit
+                .entity
+                .and_then(index)
+                .map_or_else(|| "-".to_string(), |index| index.to_string());
+            text.push_str(&format!(
+                "hit = {} {} {}\n",
+                body,
+                vector(hit.pos),
+                hit.tick
+            ));
+        }
+    }
+    text
+}
+
+pub fn from_text(text: &str) -> Result<World, String> {
+    let extra = |line: &&str| {
+        let key = line.split('=').next().unwrap_or_default().trim();
+        matches!(
+            key,
+            "annihilations"
+                | "integrator"
+                | "substepping"
+                | "pair_off"
+                | "bounds"
+                | "group"
+                | "member"
+                | "particle_state"
+                | "nucleus"
+                | "trail"
+                | "ghost"
+                | "detector"
+                | "hit"
+        )
+    };
+    // Blank out the extra lines rather than dropping them so error line
+    // numbers match
+    let scenario: Vec<&str> = text
+        .lines()
+        .map(|line| if extra(&line) { "" } else { line })
+        .collect();
+    let mut world = Scenario::parse(&scenario.join("\n"))
+        .map_err(|err| err.to_string())?
+        .build();
+    let nuclei: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| key.trim() == "nucleus")
+        .map(|(_, value)| value)
+        .collect();
+    if !nuclei.is_empty() {
+        let spawned: Vec<Entity> = world
+            .foils
+            .iter()
+            .flat_map(|foil| foil.nuclei.iter().copied())
+            .collect();
+        for nucleus in spawned {
+            world.despawn(nucleus);
+        }
+    }
+    for value in nuclei {
+        let numbers = value
+            .split_whitespace()
+            .map(|field| field.parse::<f32>().ok())
+            .collect::<Option<Vec<f32>>>();
+        let Some([index, x, y, z, vx, vy, vz]) = numbers.as_deref() else {
+            return Err(format!("malformed nucleus '{}'", value.trim()));
+        };
+        let index = *index as usize;
+        if index >= world.foils.len() {
+            return Err(format!("no foil {}", index));
+        }
+        let nucleus = foil::add_nucleus(&mut world, index, vec3(*x, *y, *z));
+        let slot = world.particles.slot(nucleus).unwrap();
+        world.particles.vel[slot] = vec3(*vx, *vy, *vz);
+    }
+    // Bodies and then nuclei, in the order `indexed` lists them
+    let entities = indexed(&world);
+    for line in text.lines().filter(extra) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        let number = |field: &str| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a number", field))
+        };
+        let count = |field: &str| {
+            field
+                .parse::<u64>()
+                .map_err(|_| format!("'{}' is not a whole number", field))
+        };
+        let entity = |field: &str| {
+            field
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| entities.get(index).copied())
+                .ok_or_else(|| format!("no particle '{}'", field))
+        };
+        let species = |field: &str| {
+            Particle::from_code(field).ok_or_else(|| format!("unknown particle '{}'", field))
+        };
+        match (key.trim(), fields.as_slice()) {
+            ("annihilations", [n]) => world.annihilations = count(n)?,
+            ("integrator", [code]) => {
+                world.integrator = Integrator::from_code(code)
+                    .ok_or_else(|| format!("unknown integrator '{}'", code))?;
+            }
+            ("substepping", [enabled, threshold, max_substeps]) => {
+                world.substepping = Substepping {
+                    enabled: *enabled == "true",
+                    threshold: number(threshold)?,
+                    max_substeps: count(max_substeps)? as u32,
+                };
+            }
+            ("pair_off", [a, b]) => world.pair_forces.set(species(a)?, species(b)?, false),
+            ("bounds", [half]) => world.bounds = Some(number(half)?),
+            ("group", fields) => {
+                let (color, name) = match fields {
+                    ["-", name @ ..] => (None, name),
+                    [r, g, b, a, name @ ..] => (
+                        Some(Color::new(number(r)?, number(g)?, number(b)?, number(a)?)),
+                        name,
+                    ),
+                    _ => return Err(format!("malformed line '{}'", line)),
+                };
+                world.groups.push(Group {
+                    name: name.join(" "),
+                    color,
+                });
+            }
+            ("member", [body, group]) => {
+                let group = count(group)? as usize;
+                if group >= world.groups.len() {
+                    return Err(format!("no group {}", group));
+                }
+                world.membership.insert(entity(body)?, GroupId(group));
+            }
+            ("particle_state", [body, mass, charge]) => {
+                let slot = world.particles.slot(entity(body)?).unwrap();
+                world.particles.mass[slot] = number(mass)?;
+                world.particles.charge[slot] = number(charge)?;
+            }
+            ("nucleus", _) => {}
+            ("trail", [body, points @ ..]) if points.len() % 3 == 0 => {
+                let points = points
+                    .chunks(3)
+                    .map(|p| Ok(vec3(number(p[0])?, number(p[1])?, number(p[2])?)))
+                    .collect::<Result<_, String>>()?;
+                world.trails.insert(entity(body)?, Trail { points });
+            }
+            ("ghost", _) => world.ghosts.push(Ghost::parse(value)?),
+            ("detector", [shape, distance, extent, bins, tick]) => {
+                let shape = DetectorShape::from_code(shape)
+                    .ok_or_else(|| format!("unknown detector shape '{}'", shape))?;
+                let mut detector = Detector::new(shape);
+                detector.distance = number(distance)?;
+                detector.extent = number(extent)?;
+                detector.bins = count(bins)? as usize;
+                detector.tick = count(tick)?;
+                world.detector = Some(detector);
+            }
+            ("hit", [body, x, y, z, tick]) => {
+                let hit = Hit {
+                    entity: entity(body).ok(),
+                    pos: vec3(number(x)?, number(y)?, number(z)?),
+                    tick: count(tick)?,
+                };
+                match &mut world.detector {
+                    Some(detector) => detector.hits.push(hit),
+                    None => return Err("hit before the detector line".to_string()),
+                }
+            }
+            _ => return Err(format!("malformed line '{}'", line)),
+        }
+    }
+    // Restoring is not the same as the user spawning particles
+    world.events.clear();
+    Ok(world)
+}
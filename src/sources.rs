@@ -0,0 +1,254 @@
+use std::f64::consts::PI;
+
+use macroquad::prelude::*;
+
+use crate::systems::COULOMB_K;
+use crate::units;
+use crate::world::World;
+
+// Charge distributions that aren't particles: fixed in place, with fields
+// known in closed form, so classic electrostatics setups like a pair of
+// deflection plates can be put in the way of a beam. They push particles
+// around but nothing pushes back on them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Source {
+    // Infinite plane through `point` with unit `normal`, carrying `density`
+    // coulombs per square unit
+    Plane {
+        point: Vec3,
+        normal: Vec3,
+        density: f32,
+    },
+    // Thin ring around `center` in the plane with unit `normal`
+    Ring {
+        center: Vec3,
+        normal: Vec3,
+        radius: f32,
+        charge: f32,
+    },
+    // Uniformly charged spherical shell
+    Shell {
+        center: Vec3,
+        radius: f32,
+        charge: f32,
+    },
+    // Ideal parallel-plate capacitor: two square plates of `area` square
+    // units, `separation` apart across `center` along unit `normal`. The
+    // field is uniform between them, pointing along `normal` for a positive
+    // `voltage`, and zero everywhere else; fringing is left out.
+    Capacitor {
+        center: Vec3,
+        normal: Vec3,
+        separation: f32,
+        area: f32,
+        voltage: f32,
+    },
+}
+
+impl Source {
+    pub fn charge_sign(&self) -> f32 {
+        let charge = match self {
+            Source::Plane { density, .. } => *density,
+            Source::Ring { charge, .. } | Source::Shell { charge, .. } => *charge,
+            Source::Capacitor { voltage, .. } => *voltage,
+        };
+        charge.signum()
+    }
+
+    // Force on a unit charge at `pos`.
+    pub fn field(&self, pos: Vec3) -> Vec3 {
+        match *self {
+            Source::Plane {
+                point,
+                normal,
+                density,
+            } => {
+                let side = (pos - point).dot(normal).signum();
+                normal * (side * 2. * std::f32::consts::PI * COULOMB_K * density)
+            }
+            Source::Ring {
+                center,
+                normal,
+                radius,
+                charge,
+            } => {
+                let (radial, rho, z) = cylindrical(pos - center, normal);
+                let (e_rho, e_z) = ring_field(radius as f64, rho as f64, z as f64);
+                (radial * e_rho as f32 + normal * e_z as f32) * (COULOMB_K * charge)
+            }
+            Source::Shell {
+                center,
+                radius,
+                charge,
+            } => {
+                let r = pos - center;
+                let distance = r.length();
+                if distance <= radius {
+                    Vec3::ZERO
+                } else {
+                    r * (COULOMB_K * charge / (distance * distance * distance))
+                }
+            }
+            Source::Capacitor {
+                normal,
+                separation,
+                voltage,
+                ..
+            } => {
+                if self.between_plates(pos) {
+                    normal * units::field_from_volts(voltage, separation)
+                } else {
+                    Vec3::ZERO
+                }
+            }
+        }
+    }
+
+    // Whether `pos` is in the gap of a capacitor, false for anything else.
+    pub fn between_plates(&self, pos: Vec3) -> bool {
+        let Source::Capacitor {
+            center,
+            normal,
+            separation,
+            area,
+            ..
+        } = *self
+        else {
+            return false;
+        };
+        let (u, v) = plate_axes(normal);
+        let r = pos - center;
+        let half = area.sqrt() / 2.;
+        r.dot(normal).abs() < separation / 2. && r.dot(u).abs() < half && r.dot(v).abs() < half
+    }
+
+    // Potential at `pos`. The plane's is taken as zero on the plane itself,
+    // as an infinite one has no zero at infinity.
+    pub fn potential(&self, pos: Vec3) -> f32 {
+        match *self {
+            Source::Plane {
+                point,
+                normal,
+                density,
+            } => {
+                let distance = (pos - point).dot(normal).abs();
+                -2. * std::f32::consts::PI * COULOMB_K * density * distance
+            }
+            Source::Ring {
+                center,
+                normal,
+                radius,
+                charge,
+            } => {
+                let (_, rho, z) = cylindrical(pos - center, normal);
+                COULOMB_K * charge * ring_potential(radius as f64, rho as f64, z as f64) as f32
+            }
+            Source::Shell {
+                center,
+                radius,
+                charge,
+            } => COULOMB_K * charge / pos.distance(center).max(radius),
+            // Zero halfway between the plates and everywhere outside the gap,
+            // so a particle crossing a plate without fringing to ease it in
+            // sees its energy jump
+            Source::Capacitor {
+                center,
+                normal,
+                separation,
+                voltage,
+                ..
+            } => {
+                if !self.between_plates(pos) {
+                    return 0.;
+                }
+                -units::field_from_volts(voltage, separation) * (pos - center).dot(normal)
+            }
+        }
+    }
+}
+
+// Directions along the sides of a capacitor's plates.
+pub fn plate_axes(normal: Vec3) -> (Vec3, Vec3) {
+    let u = normal.any_orthonormal_vector();
+    (u, normal.cross(u))
+}
+
+// Splits `r` into the unit vector pointing away from the axis along
+// `normal`, the distance from that axis, and the height along it.
+fn cylindrical(r: Vec3, normal: Vec3) -> (Vec3, f32, f32) {
+    let z = r.dot(normal);
+    let across = r - normal * z;
+    let rho = across.length();
+    let radial = if rho > 0. { across / rho } else { Vec3::ZERO };
+    (radial, rho, z)
+}
+
+// Complete elliptic integrals of the first and second kind, K(m) and E(m),
+// by the arithmetic-geometric mean.
+fn elliptic(m: f64) -> (f64, f64) {
+    let (mut a, mut b, mut c) = (1., (1. - m).sqrt(), m.sqrt());
+    let mut sum = c * c / 2.;
+    let mut weight = 0.5;
+    while c.abs() > 1e-15 {
+        (a, b, c) = ((a + b) / 2., (a * b).sqrt(), (a - b) / 2.);
+        weight *= 2.;
+        sum += weight * c * c;
+    }
+    let k = PI / (2. * a);
+    (k, k * (1. - sum))
+}
+
+// Field of a ring of unit charge and `radius`, with the Coulomb constant left
+// out, at distance `rho` from its axis and height `z` above its plane.
+fn ring_field(radius: f64, rho: f64, z: f64) -> (f64, f64) {
+    let sum = (radius + rho) * (radius + rho) + z * z;
+    let difference = (radius - rho) * (radius - rho) + z * z;
+    // On the wire itself the field is unbounded
+    if difference < 1e-9 * radius * radius {
+        return (0., 0.);
+    }
+    let (k, e) = elliptic(4. * radius * rho / sum);
+    let e_z = 2. * z * e / (PI * sum.sqrt() * difference);
+    // The radial part vanishes on the axis, where it is 0 / 0 as written
+    let e_rho = if rho < 1e-9 * radius {
+        0.
+    } else {
+        (k - (radius * radius - rho * rho + z * z) / difference * e) / (PI * rho * sum.sqrt())
+    };
+    (e_rho, e_z)
+}
+
+fn ring_potential(radius: f64, rho: f64, z: f64) -> f64 {
+    let sum = (radius + rho) * (radius + rho) + z * z;
+    let (k, _) = elliptic((4. * radius * rho / sum).min(1. - 1e-12));
+    2. * k / (PI * sum.sqrt())
+}
+
+// Acceleration the sources give a particle of `charge` and `mass` at `pos`.
+pub fn acceleration(sources: &[Source], pos: Vec3, charge: f32, mass: f32) -> Vec3 {
+    if sources.is_empty() || charge == 0. {
+        return Vec3::ZERO;
+    }
+    let field = sources
+        .iter()
+        .fold(Vec3::ZERO, |sum, source| sum + source.field(pos));
+    field * (charge / mass)
+}
+
+// Potential energy of every particle in the field of the sources.
+pub fn potential_energy(world: &World) -> f32 {
+    let particles = &world.particles;
+    particles
+        .pos
+        .iter()
+        .zip(&particles.charge)
+        .map(|(pos, charge)| {
+            charge
+                * world
+                    .sources
+                    .iter()
+                    .map(|source| source.potential(*pos))
+                    .sum::<f32>()
+        })
+        .sum()
+}
@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::PathBuf;
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::particle::{Particle, PARTICLES};
+use rutherford_atomic_model::species::{self, Species};
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::settings::Settings;
+use crate::theme::Theme;
+use crate::toast;
+use crate::unit_field::{self, UnitField};
+
+const SPECIES_COLORS: [Color; 8] = [ORANGE, PINK, VIOLET, LIME, SKYBLUE, GOLD, MAGENTA, BEIGE];
+
+// Custom species definitions live next to the settings file.
+pub fn path() -> PathBuf {
+    Settings::path().with_file_name("species.txt")
+}
+
+pub fn load() {
+    let path = path();
+    if let Ok(source) = fs::read_to_string(&path) {
+        let count = species::load(&source);
+        log::info!("loaded {} custom species from {}", count, path.display());
+    }
+}
+
+fn save() {
+    let path = path();
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&path, species::to_text())),
+        None => fs::write(&path, species::to_text()),
+    };
+    match result {
+        Ok(()) => {
+            log::info!("saved custom species to {}", path.display());
+            toast::info(tr("toast.species_saved").to_string());
+        }
+        Err(err) => log::warn!("could not save species to {}: {}", path.display(), err),
+    }
+}
+
+// Spawn buttons for built-in and custom species, and a form for defining new
+// ones.
+pub struct SpawnPanel {
+    pub visible: bool,
+    name: String,
+    // In elementary charges
+    charge: f32,
+    // In proton masses
+    mass: f32,
+    color: usize,
+    radius: f32,
+    charge_field: UnitField,
+    mass_field: UnitField,
+}
+
+impl SpawnPanel {
+    pub fn new() -> SpawnPanel {
+        SpawnPanel {
+            visible: false,
+            name: String::new(),
+            charge: -1.,
+            mass: 0.1,
+            color: 0,
+            radius: 2.,
+            charge_field: UnitField::new(),
+            mass_field: UnitField::new(),
+        }
+    }
+
+    fn draft(&self) -> Result<Species, String> {
+        let name = self.name.trim();
+        if name.contains(char::is_whitespace) {
+            return Err(tr("spawn.name_spaces").to_string());
+        }
+        // Go through the file format so the same rules apply to both
+        let color = SPECIES_COLORS[self.color];
+        let channel = |value: f32| (value * 255.).round() as u8;
+        let (r, g, b) = (channel(color.r), channel(color.g), channel(color.b));
+        Species::parse(&format!(
+            "{} {} {} {} {} {} {}",
+            name, self.charge, self.mass, r, g, b, self.radius
+        ))
+    }
+
+    // Returns the species to spawn when one of its buttons is clicked.
+    pub fn draw(&mut self, theme: &Theme) -> Option<Particle> {
+        if !self.visible {
+            return None;
+        }
+
+        let mut spawn = None;
+        dock::window(
+            "spawn.title",
+            hash!(),
+            vec2(theme.scaled(10.), theme.scaled(200.)),
+            vec2(theme.scaled(360.), theme.scaled(440.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            for particle in PARTICLES {
+                if ui.button(None, trf("spawn.particle", &[&particle.name()]).as_str()) {
+                    spawn = Some(particle);
+                }
+            }
+            for (index, custom) in species::all().iter().enumerate() {
+                if ui.button(None, trf("spawn.particle", &[&custom.name]).as_str()) {
+                    spawn = Some(Particle::Custom(index as u16));
+                }
+            }
+            ui.separator();
+
+            ui.label(None, tr("spawn.new_species"));
+            ui.input_text(hash!(), tr("spawn.name"), &mut self.name);
+            ui.slider(hash!(), tr("spawn.charge"), -3.0..3.0, &mut self.charge);
+            self.charge_field.ui(
+                ui,
+                hash!(),
+                &mut self.charge,
+                unit_field::charge,
+                unit_field::format_charge,
+            );
+            // The slider is logarithmic; the mass is only written back when
+            // it moves, so a typed mass is kept exactly
+            let mut log_mass = self.mass.log10();
+            let before = log_mass;
+            ui.slider(hash!(), tr("spawn.log_mass"), -4.0..3.0, &mut log_mass);
+            if log_mass != before {
+                self.mass = 10f32.powf(log_mass);
+            }
+            self.mass_field.ui(
+                ui,
+                hash!(),
+                &mut self.mass,
+                unit_field::mass,
+                unit_field::format_mass,
+            );
+            ui.label(None, &trf("spawn.mass", &[&format!("{:.4}", self.mass)]));
+            ui.slider(hash!(), tr("spawn.radius"), 0.5..6.0, &mut self.radius);
+            if ui.button(None, tr("spawn.color")) {
+                self.color = (self.color + 1) % SPECIES_COLORS.len();
+            }
+            let size = vec2(theme.scaled(40.), theme.scaled(12.));
+            let corner = ui.canvas().request_space(size);
+            let color = SPECIES_COLORS[self.color];
+            ui.canvas()
+                .rect(Rect::new(corner.x, corner.y, size.x, size.y), color, color);
+            if ui.button(None, tr("spawn.add")) {
+                match self.draft() {
+                    Ok(species) => {
+                        log::info!("defined species {}", species.name);
+                        toast::info(trf("toast.species_defined", &[&species.name]));
+                        species::register(species);
+                        self.name.clear();
+                    }
+                    Err(err) => log::warn!("{}", err),
+                }
+            }
+            ui.same_line(0.);
+            if ui.button(None, tr("spawn.save")) {
+                save();
+            }
+        });
+        spawn
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::{PoisonError, RwLock};
+
+use macroquad::prelude::*;
+
+use crate::particle::{get_charge, get_mass, Particle, PARTICLE_RADIUS};
+
+// User-defined particle species, such as muons, looked up by
+// `Particle::Custom(index)`. The registry is shared by every world so that
+// loading a scene or switching to the physics thread keeps the definitions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Species {
+    pub name: String,
+    pub charge: f32,
+    pub mass: f32,
+    pub color: Color,
+    pub radius: f32,
+}
+
+static REGISTRY: RwLock<Vec<Species>> = RwLock::new(Vec::new());
+
+impl Species {
+    // One definition per line in the species file:
+    //
+    //     species = muon -1 0.1126 255 128 0 1.5
+    //
+    // Name, charge in elementary charges, mass in proton masses, red, green
+    // and blue from 0 to 255, and radius.
+    pub fn parse(value: &str) -> Result<Species, String> {
+        let mut fields = value.split_whitespace();
+        let name = fields.next().unwrap_or_default();
+        if name.is_empty() || Particle::from_code(name).is_some_and(|p| !p.is_custom()) {
+            return Err(format!("'{}' is not a usable species name", name));
+        }
+        let numbers = fields
+            .map(|field| {
+                field
+                    .parse::<f32>()
+                    .map_err(|_| format!("'{}' is not a number", field))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        let [charge, mass, r, g, b, radius] = numbers.as_slice() else {
+            return Err("expected charge, mass, color and radius".to_string());
+        };
+        if *mass <= 0. || *radius <= 0. {
+            return Err("mass and radius must be positive".to_string());
+        }
+        Ok(Species {
+            name: name.to_string(),
+            charge: charge * get_charge(Particle::Proton),
+            mass: mass * get_mass(Particle::Proton),
+            color: Color::from_rgba(*r as u8, *g as u8, *b as u8, 255),
+            radius: *radius,
+        })
+    }
+
+    pub fn to_line(&self) -> String {
+        let channel = |value: f32| (value * 255.).round() as u8;
+        let (r, g, b) = (
+            channel(self.color.r),
+            channel(self.color.g),
+            channel(self.color.b),
+        );
+        format!(
+            "species = {} {} {} {} {} {} {}",
+            self.name,
+            self.charge / get_charge(Particle::Proton),
+            self.mass / get_mass(Particle::Proton),
+            r,
+            g,
+            b,
+            self.radius
+        )
+    }
+}
+
+// Adds `species`, or updates the definition with the same name, and returns
+// the particle that refers to it.
+pub fn register(species: Species) -> Particle {
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    let index = match registry.iter().position(|s| s.name == species.name) {
+        Some(index) => {
+            registry[index] = species;
+            index
+        }
+        None => {
+            registry.push(species);
+            registry.len() - 1
+        }
+    };
+    Particle::Custom(index as u16)
+}
+
+pub fn get(index: u16) -> Option<Species> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(index as usize)
+        .cloned()
+}
+
+pub fn find(name: &str) -> Option<Particle> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .position(|s| s.name == name)
+        .map(|index| Particle::Custom(index as u16))
+}
+
+pub fn all() -> Vec<Species> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+// Drawing radius of any particle, built-in or custom.
+pub fn radius(particle: Particle) -> f32 {
+    match particle {
+        Particle::Custom(index) => get(index).map_or(PARTICLE_RADIUS, |s| s.radius),
+        _ => PARTICLE_RADIUS,
+    }
+}
+
+// Registers every definition in a species file, skipping bad lines.
+pub fn load(source: &str) -> usize {
+    let mut count = 0;
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parsed = match line.split_once('=') {
+            Some((key, value)) if key.trim() == "species" => Species::parse(value.trim()),
+            _ => Err(format!("expected 'species = ...', found '{}'", line)),
+        };
+        match parsed {
+            Ok(species) => {
+                register(species);
+                count += 1;
+            }
+            Err(err) => log::warn!("species line {}: {}", number + 1, err),
+        }
+    }
+    count
+}
+
+pub fn to_text() -> String {
+    all().iter().map(|s| s.to_line() + "\n").collect()
+}
@@ -0,0 +1,96 @@
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::magnets;
+use rutherford_atomic_model::sources;
+use rutherford_atomic_model::units::{METRES_PER_UNIT, SECONDS_PER_TICK};
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::trf;
+use crate::labels::{self, Layout};
+use crate::selection;
+use crate::theme::Theme;
+
+// Fewest trail points inside the field worth fitting a circle to
+const MIN_ORBIT_POINTS: usize = 8;
+
+// Center and radius of the circle that best fits `points` once flattened onto
+// the plane normal to `normal`, by the algebraic least-squares fit, which
+// handles anything from a short arc to many laps.
+fn fit_orbit(points: &[Vec3], normal: Vec3) -> Option<(Vec3, f32)> {
+    let (u, v) = sources::plate_axes(normal);
+    let n = points.len() as f64;
+    let mean = points.iter().fold(Vec3::ZERO, |sum, p| sum + *p) / points.len() as f32;
+    let flat: Vec<(f64, f64)> = points
+        .iter()
+        .map(|p| ((*p - mean).dot(u) as f64, (*p - mean).dot(v) as f64))
+        .collect();
+    let sum = |f: &dyn Fn(f64, f64) -> f64| flat.iter().map(|(x, y)| f(*x, *y)).sum::<f64>();
+    let (xx, yy, xy) = (sum(&|x, _| x * x), sum(&|_, y| y * y), sum(&|x, y| x * y));
+    let rx = sum(&|x, y| x * (x * x + y * y)) / 2.;
+    let ry = sum(&|x, y| y * (x * x + y * y)) / 2.;
+    let det = xx * yy - xy * xy;
+    // A straight track leaves the center undetermined
+    if det <= 1e-9 * (xx + yy).powi(2) {
+        return None;
+    }
+    let a = (rx * yy - ry * xy) / det;
+    let b = (ry * xx - rx * xy) / det;
+    let radius = (a * a + b * b + (xx + yy) / n).sqrt();
+    Some((mean + u * a as f32 + v * b as f32, radius as f32))
+}
+
+// Labels every charged particle curling in a magnetic field with the radius
+// of its track, fitted to its trail, and the mass-to-charge ratio that radius
+// gives, m/q = rB / v, which is how a mass spectrometer tells ions apart.
+pub fn draw_orbits(world: &World, camera: &Camera3D, layout: &mut Layout, theme: &Theme) {
+    if world.magnets.is_empty() {
+        return;
+    }
+    let particles = &world.particles;
+    for (slot, entity) in particles.entities().iter().enumerate() {
+        let pos = particles.pos[slot];
+        let field = magnets::field_at(&world.magnets, pos);
+        let Some(normal) = field.try_normalize() else {
+            continue;
+        };
+        if particles.charge[slot] == 0. {
+            continue;
+        }
+        let Some(trail) = world.trails.get(*entity) else {
+            continue;
+        };
+        // Only the part of the track inside the field is an arc
+        let points: Vec<Vec3> = trail
+            .points
+            .iter()
+            .copied()
+            .filter(|p| magnets::field_at(&world.magnets, *p) != Vec3::ZERO)
+            .collect();
+        if points.len() < MIN_ORBIT_POINTS {
+            continue;
+        }
+        let Some((center, radius)) = fit_orbit(&points, normal) else {
+            continue;
+        };
+        let vel = particles.vel[slot];
+        let across = (vel - normal * vel.dot(normal)).length() * METRES_PER_UNIT / SECONDS_PER_TICK;
+        if across <= 0. {
+            continue;
+        }
+        let mass_to_charge = radius * METRES_PER_UNIT * field.length() / across;
+        let Some(at) = selection::world_to_screen(camera, center) else {
+            continue;
+        };
+        let particle = particles.particle[slot];
+        let text = trf(
+            "spectrometer.label",
+            &[
+                &particle.name(),
+                &format!("{:.1}", radius),
+                &format!("{:.3e}", mass_to_charge.abs()),
+            ],
+        );
+        let color = theme.particle(particle);
+        labels::draw(layout, &text, at, 0., theme.text_size(20.), color);
+    }
+}
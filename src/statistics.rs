@@ -0,0 +1,103 @@
+use macroquad::prelude::*;
+
+use crate::diagnostics::pair_potential;
+use crate::ecs::Entity;
+use crate::group::kinetic_energy;
+use crate::medium;
+use crate::particle::Particle;
+use crate::sources;
+use crate::world::World;
+
+// Aggregates over the whole scene at one tick, gathered in a single pass over
+// the particles and a single one over their pairs, for every consumer that
+// wants them: the panels, the telemetry stream, the HTTP API and scripts.
+// Matches what the separate functions in `diagnostics` give, without each
+// caller going over the particles again.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Statistics {
+    pub particles: usize,
+    // How many of each species, in the order first met
+    pub counts: Vec<(Particle, usize)>,
+    pub kinetic: f32,
+    // Of every pair, each counted once, and of the sources and gravity
+    pub potential: f32,
+    pub charge: f32,
+    pub mass: f32,
+    // Total linear momentum
+    pub momentum: Vec3,
+    // Position and velocity of the center of mass, when there is any mass
+    pub center_of_mass: Option<(Vec3, Vec3)>,
+    // About the center of mass, in its rest frame
+    pub angular_momentum: Vec3,
+    // Closest pair of particles and how far apart they are
+    pub closest: Option<(Entity, Entity, f32)>,
+}
+
+impl Statistics {
+    pub fn gather(world: &World) -> Statistics {
+        let particles = &world.particles;
+        let entities = particles.entities();
+        let mut stats = Statistics {
+            particles: particles.len(),
+            potential: sources::potential_energy(world) + medium::potential_energy(world),
+            ..Statistics::default()
+        };
+
+        // Sums of m r, m v and m r × v, from which the center of mass and the
+        // angular momentum about it follow without a second pass
+        let (mut moment, mut spin) = (Vec3::ZERO, Vec3::ZERO);
+        for i in 0..particles.len() {
+            let (m, r, v) = (particles.mass[i], particles.pos[i], particles.vel[i]);
+            let species = particles.particle[i];
+            match stats.counts.iter_mut().find(|(p, _)| *p == species) {
+                Some((_, n)) => *n += 1,
+                None => stats.counts.push((species, 1)),
+            }
+            stats.kinetic += kinetic_energy(m, v);
+            stats.charge += particles.charge[i];
+            stats.mass += m;
+            stats.momentum += v * m;
+            moment += r * m;
+            spin += r.cross(v) * m;
+        }
+        if stats.mass > 0. {
+            let (center, drift) = (moment / stats.mass, stats.momentum / stats.mass);
+            stats.center_of_mass = Some((center, drift));
+            // Σ m (r - R) × (v - V) = Σ m r × v - M R × V
+            stats.angular_momentum = spin - center.cross(drift) * stats.mass;
+        }
+
+        for i in 0..particles.len() {
+            for j in (i + 1)..particles.len() {
+                let distance = particles.pos[i].distance(particles.pos[j]);
+                if stats.closest.is_none_or(|(_, _, d)| distance < d) {
+                    stats.closest = Some((entities[i], entities[j], distance));
+                }
+                if !world
+                    .pair_forces
+                    .allows(particles.particle[i], particles.particle[j])
+                {
+                    continue;
+                }
+                stats.potential += pair_potential(
+                    &world.force_model,
+                    particles.charge[i],
+                    particles.charge[j],
+                    distance,
+                );
+            }
+        }
+        stats
+    }
+
+    pub fn total_energy(&self) -> f32 {
+        self.kinetic + self.potential
+    }
+
+    pub fn count(&self, particle: Particle) -> usize {
+        self.counts
+            .iter()
+            .find(|(p, _)| *p == particle)
+            .map_or(0, |(_, n)| *n)
+    }
+}
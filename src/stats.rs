@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::i18n::trf;
+use crate::theme::Theme;
+
+const HISTORY: usize = 300;
+const GRAPH_WIDTH: f32 = 300.;
+const GRAPH_HEIGHT: f32 = 80.;
+// Frame time mapped to the top of the graph, in milliseconds.
+const GRAPH_MAX_MS: f32 = 50.;
+
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    pub visible: bool,
+    frame_millis: VecDeque<f32>,
+}
+
+impl FrameStats {
+    pub fn new() -> FrameStats {
+        FrameStats {
+            visible: false,
+            frame_millis: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    pub fn push(&mut self, frame_time: f32) {
+        if self.frame_millis.len() == HISTORY {
+            self.frame_millis.pop_front();
+        }
+        self.frame_millis.push_back(frame_time * 1000.);
+    }
+
+    pub fn average_fps(&self) -> f32 {
+        if self.frame_millis.is_empty() {
+            return 0.;
+        }
+        let avg = self.frame_millis.iter().sum::<f32>() / self.frame_millis.len() as f32;
+        1000. / avg
+    }
+
+    // Average FPS over the slowest 1% of recorded frames.
+    pub fn one_percent_low(&self) -> f32 {
+        if self.frame_millis.is_empty() {
+            return 0.;
+        }
+        let mut sorted: Vec<f32> = self.frame_millis.iter().copied().collect();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let worst = (sorted.len() / 100).max(1);
+        let avg = sorted[..worst].iter().sum::<f32>() / worst as f32;
+        1000. / avg
+    }
+
+    pub fn draw(
+        &self,
+        physics_millis: f32,
+        particle_count: usize,
+        annihilations: u64,
+        theme: &Theme,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let (width, height) = (theme.scaled(GRAPH_WIDTH), theme.scaled(GRAPH_HEIGHT));
+        let x = screen_width() - width - 10.;
+        let y = 10.;
+        draw_rectangle(x, y, width, height, theme.panel);
+        draw_rectangle_lines(x, y, width, height, theme.line_scale, theme.grid_lines);
+
+        // Reference lines at 60 and 30 FPS
+        for (millis, color) in [(1000. / 60., DARKGREEN), (1000. / 30., MAROON)] {
+            let line_y = y + height - millis / GRAPH_MAX_MS * height;
+            draw_line(x, line_y, x + width, line_y, theme.line_scale, color);
+        }
+
+        let step = width / HISTORY as f32;
+        let offset = HISTORY - self.frame_millis.len();
+        for (i, millis) in self.frame_millis.iter().enumerate() {
+            let bar = (millis / GRAPH_MAX_MS).min(1.) * height;
+            let bar_x = x + (offset + i) as f32 * step;
+            draw_line(bar_x, y + height, bar_x, y + height - bar, step, theme.text);
+        }
+
+        let lines = [
+            trf("stats.avg_fps", &[&format!("{:.0}", self.average_fps())]),
+            trf(
+                "stats.low_fps",
+                &[&format!("{:.0}", self.one_percent_low())],
+            ),
+            trf("stats.physics", &[&format!("{:.2}", physics_millis)]),
+            trf("stats.particles", &[&particle_count]),
+            trf("stats.annihilations", &[&annihilations]),
+        ];
+        let font_size = theme.text_size(20.);
+        let mut text_y = y + height + font_size;
+        for line in lines {
+            draw_text(line.as_str(), x, text_y, font_size, theme.text);
+            text_y += font_size;
+        }
+    }
+}
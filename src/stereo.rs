@@ -0,0 +1,165 @@
+use macroquad::prelude::*;
+
+use crate::render;
+
+const ANAGLYPH_VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+
+varying lowp vec2 uv;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+}
+"#;
+
+// Red channel from the left eye, green and blue from the right eye.
+const ANAGLYPH_FRAGMENT: &str = r#"#version 100
+precision lowp float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform sampler2D right_eye;
+
+void main() {
+    vec3 left = texture2D(Texture, uv).rgb;
+    vec3 right = texture2D(right_eye, uv).rgb;
+    gl_FragColor = vec4(left.r, right.g, right.b, 1.0);
+}
+"#;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StereoMode {
+    Off,
+    Anaglyph,
+    SideBySide,
+}
+
+impl StereoMode {
+    pub fn next(&self) -> StereoMode {
+        match self {
+            StereoMode::Off => StereoMode::Anaglyph,
+            StereoMode::Anaglyph => StereoMode::SideBySide,
+            StereoMode::SideBySide => StereoMode::Off,
+        }
+    }
+
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            StereoMode::Off => "stereo.off",
+            StereoMode::Anaglyph => "stereo.anaglyph",
+            StereoMode::SideBySide => "stereo.side_by_side",
+        }
+    }
+}
+
+pub struct Stereo {
+    pub mode: StereoMode,
+    // Distance between the two eye cameras, in world units
+    pub eye_separation: f32,
+    material: Option<Material>,
+    targets: Option<(RenderTarget, RenderTarget, (u32, u32))>,
+}
+
+impl Stereo {
+    pub fn new() -> Stereo {
+        let material = load_material(
+            ANAGLYPH_VERTEX,
+            ANAGLYPH_FRAGMENT,
+            MaterialParams {
+                textures: vec!["right_eye".to_string()],
+                ..Default::default()
+            },
+        );
+        if let Err(err) = &material {
+            log::warn!("anaglyph shader unavailable: {:?}", err);
+        }
+
+        Stereo {
+            mode: StereoMode::Off,
+            eye_separation: 2.,
+            material: material.ok(),
+            targets: None,
+        }
+    }
+
+    pub fn change_separation(&mut self, amount: f32) {
+        self.eye_separation = (self.eye_separation + amount).clamp(0., 50.);
+        log::info!("eye separation {:.1}", self.eye_separation);
+    }
+
+    fn eyes(&self, camera: &Camera3D) -> (Camera3D, Camera3D) {
+        let right = (camera.target - camera.position)
+            .cross(camera.up)
+            .normalize_or_zero()
+            * self.eye_separation
+            / 2.;
+        let eye = |offset: Vec3| Camera3D {
+            position: camera.position + offset,
+            target: camera.target + offset,
+            ..*camera
+        };
+        (eye(-right), eye(right))
+    }
+
+    fn targets(&mut self) -> (RenderTarget, RenderTarget) {
+        let size = (screen_width() as u32, screen_height() as u32);
+        match self.targets {
+            Some((left, right, current)) if current == size => (left, right),
+            _ => {
+                if let Some((left, right, _)) = self.targets.take() {
+                    left.delete();
+                    right.delete();
+                }
+                let left = render_target(size.0, size.1);
+                let right = render_target(size.0, size.1);
+                left.texture.set_filter(FilterMode::Linear);
+                right.texture.set_filter(FilterMode::Linear);
+                self.targets = Some((left, right, size));
+                (left, right)
+            }
+        }
+    }
+
+    // Runs `draw` once per view required by the current mode and composites
+    // the results onto the screen.
+    pub fn render(&mut self, camera: &Camera3D, draw: impl Fn(&Camera3D)) {
+        match (self.mode, self.material) {
+            (StereoMode::Off, _) | (StereoMode::Anaglyph, None) => draw(camera),
+            (StereoMode::SideBySide, _) => {
+                let (left, right) = self.eyes(camera);
+                draw(&render::half_screen(&left, false));
+                draw(&render::half_screen(&right, true));
+            }
+            (StereoMode::Anaglyph, Some(material)) => {
+                let (mut left, mut right) = self.eyes(camera);
+                let (left_target, right_target) = self.targets();
+                left.render_target = Some(left_target);
+                right.render_target = Some(right_target);
+                draw(&left);
+                draw(&right);
+
+                set_default_camera();
+                material.set_texture("right_eye", right_target.texture);
+                gl_use_material(material);
+                draw_texture_ex(
+                    left_target.texture,
+                    0.,
+                    0.,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(screen_width(), screen_height())),
+                        flip_y: true,
+                        ..Default::default()
+                    },
+                );
+                gl_use_default_material();
+            }
+        }
+    }
+}
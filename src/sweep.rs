@@ -0,0 +1,291 @@
+use macroquad::color::hsl_to_rgb;
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use rutherford_atomic_model::particle::{get_charge, get_mass, Particle, PARTICLES};
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::units;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+use crate::unit_field::{self, UnitField};
+
+// How far upstream of the nucleus each projectile starts
+const START_DISTANCE: f32 = 300.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SweepParameter {
+    BeamEnergy,
+    NuclearCharge,
+    ImpactParameter,
+}
+
+impl SweepParameter {
+    fn next(&self) -> SweepParameter {
+        match self {
+            SweepParameter::BeamEnergy => SweepParameter::NuclearCharge,
+            SweepParameter::NuclearCharge => SweepParameter::ImpactParameter,
+            SweepParameter::ImpactParameter => SweepParameter::BeamEnergy,
+        }
+    }
+
+    fn name_key(&self) -> &'static str {
+        match self {
+            SweepParameter::BeamEnergy => "sweep.beam_energy",
+            SweepParameter::NuclearCharge => "sweep.nuclear_charge",
+            SweepParameter::ImpactParameter => "sweep.impact_parameter",
+        }
+    }
+
+    // Reads and writes the parameter's values with their units
+    fn parse(&self) -> fn(&str) -> Option<f32> {
+        match self {
+            SweepParameter::BeamEnergy => unit_field::energy,
+            SweepParameter::NuclearCharge => unit_field::charge,
+            SweepParameter::ImpactParameter => unit_field::length,
+        }
+    }
+
+    fn format(&self) -> fn(f32) -> String {
+        match self {
+            SweepParameter::BeamEnergy => unit_field::format_energy,
+            SweepParameter::NuclearCharge => unit_field::format_charge,
+            SweepParameter::ImpactParameter => unit_field::format_length,
+        }
+    }
+
+    fn range(&self) -> (f32, f32) {
+        match self {
+            SweepParameter::BeamEnergy => (0.5, 20.),
+            SweepParameter::NuclearCharge => (1., 100.),
+            SweepParameter::ImpactParameter => (0., 100.),
+        }
+    }
+}
+
+// Fires a projectile at a fixed nucleus once per value of the swept parameter
+// and overlays every resulting trajectory, colored from blue (lowest) to red.
+pub struct Sweep {
+    pub visible: bool,
+    parameter: SweepParameter,
+    projectile: Particle,
+    min: f32,
+    max: f32,
+    count: f32,
+    ticks: f32,
+    // Fixed values for the parameters not being swept: the beam's kinetic
+    // energy in MeV, the nucleus's charge in elementary charges and the
+    // impact parameter in units
+    energy: f32,
+    charge: f32,
+    impact: f32,
+    min_field: UnitField,
+    max_field: UnitField,
+    energy_field: UnitField,
+    charge_field: UnitField,
+    impact_field: UnitField,
+    paths: Vec<(f32, Vec<Vec3>)>,
+}
+
+impl Sweep {
+    pub fn new() -> Sweep {
+        Sweep {
+            visible: false,
+            parameter: SweepParameter::ImpactParameter,
+            projectile: Particle::Alpha,
+            min: 0.,
+            max: 60.,
+            count: 8.,
+            ticks: 1500.,
+            energy: 5.3,
+            charge: 79.,
+            impact: 20.,
+            min_field: UnitField::new(),
+            max_field: UnitField::new(),
+            energy_field: UnitField::new(),
+            charge_field: UnitField::new(),
+            impact_field: UnitField::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    fn color(&self, i: usize) -> Color {
+        let t = i as f32 / (self.paths.len().max(2) - 1) as f32;
+        hsl_to_rgb(0.66 * (1. - t), 0.9, 0.55)
+    }
+
+    fn run(&mut self) {
+        let count = self.count.round().max(1.) as usize;
+        self.paths = (0..count)
+            .map(|i| {
+                let t = if count > 1 {
+                    i as f32 / (count - 1) as f32
+                } else {
+                    0.
+                };
+                let value = self.min + (self.max - self.min) * t;
+                (value, self.trajectory(value))
+            })
+            .collect();
+        log::info!(
+            "swept {} over {} values",
+            tr(self.parameter.name_key()),
+            count
+        );
+    }
+
+    fn trajectory(&self, value: f32) -> Vec<Vec3> {
+        let (mut energy, mut charge, mut impact) = (self.energy, self.charge, self.impact);
+        match self.parameter {
+            SweepParameter::BeamEnergy => energy = value,
+            SweepParameter::NuclearCharge => charge = value,
+            SweepParameter::ImpactParameter => impact = value,
+        }
+
+        let mut world = World::new();
+        let nucleus = world.spawn_particle(Particle::Proton, Some(Vec3::ZERO));
+        let projectile =
+            world.spawn_particle(self.projectile, Some(vec3(-START_DISTANCE, impact, 0.)));
+        let (Some(n), Some(p)) = (
+            world.particles.slot(nucleus),
+            world.particles.slot(projectile),
+        ) else {
+            return Vec::new();
+        };
+        // A bare nucleus of charge Z, pinned in place so only the projectile
+        // moves
+        world.particles.charge[n] = charge * get_charge(Particle::Proton);
+        world.particles.mass[n] = f32::INFINITY;
+        // Relativistic, so light projectiles like electrons stay below c
+        let speed = units::speed_from_mev(get_mass(self.projectile), energy, true);
+        world.particles.vel[p] = vec3(speed, 0., 0.);
+
+        let mut path = vec![world.particles.pos[p]];
+        for _ in 0..self.ticks as usize {
+            systems::step(&mut world);
+            let Some(slot) = world.particles.slot(projectile) else {
+                break;
+            };
+            let pos = world.particles.pos[slot];
+            if !pos.is_finite() {
+                break;
+            }
+            path.push(pos);
+        }
+        path
+    }
+
+    pub fn draw_paths(&self) {
+        for (i, (_, path)) in self.paths.iter().enumerate() {
+            let color = self.color(i);
+            for pair in path.windows(2) {
+                draw_line_3d(pair[0], pair[1], color);
+            }
+        }
+    }
+
+    pub fn draw_panel(&mut self, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        dock::window(
+            "sweep.title",
+            hash!(),
+            vec2(screen_width() - theme.scaled(370.), theme.scaled(490.)),
+            vec2(theme.scaled(360.), theme.scaled(420.)),
+        )
+        .ui(&mut root_ui(), |ui| {
+            let label = format!(
+                "{}: {}",
+                tr("sweep.parameter"),
+                tr(self.parameter.name_key())
+            );
+            if ui.button(None, label.as_str()) {
+                self.parameter = self.parameter.next();
+                (self.min, self.max) = self.parameter.range();
+            }
+            let projectile = trf("sweep.projectile", &[&self.projectile.name()]);
+            if ui.button(None, projectile.as_str()) {
+                let next = PARTICLES
+                    .iter()
+                    .position(|particle| *particle == self.projectile)
+                    .map_or(0, |i| (i + 1) % PARTICLES.len());
+                self.projectile = PARTICLES[next];
+            }
+            let (low, high) = self.parameter.range();
+            let (parse, format) = (self.parameter.parse(), self.parameter.format());
+            ui.slider(hash!(), tr("sweep.min"), low..high, &mut self.min);
+            self.min_field.ui(ui, hash!(), &mut self.min, parse, format);
+            ui.slider(hash!(), tr("sweep.max"), low..high, &mut self.max);
+            self.max_field.ui(ui, hash!(), &mut self.max, parse, format);
+            ui.slider(hash!(), tr("sweep.count"), 1f32..16f32, &mut self.count);
+            ui.slider(hash!(), tr("sweep.ticks"), 100f32..3000f32, &mut self.ticks);
+            ui.separator();
+            ui.label(None, tr("sweep.fixed"));
+            if self.parameter != SweepParameter::BeamEnergy {
+                ui.slider(
+                    hash!(),
+                    tr("sweep.beam_energy"),
+                    0.5f32..20f32,
+                    &mut self.energy,
+                );
+                self.energy_field.ui(
+                    ui,
+                    hash!(),
+                    &mut self.energy,
+                    unit_field::energy,
+                    unit_field::format_energy,
+                );
+            }
+            if self.parameter != SweepParameter::NuclearCharge {
+                ui.slider(
+                    hash!(),
+                    tr("sweep.nuclear_charge"),
+                    1f32..100f32,
+                    &mut self.charge,
+                );
+                self.charge_field.ui(
+                    ui,
+                    hash!(),
+                    &mut self.charge,
+                    |text| unit_field::charge(text).filter(|z| *z > 0.),
+                    unit_field::format_charge,
+                );
+            }
+            if self.parameter != SweepParameter::ImpactParameter {
+                ui.slider(
+                    hash!(),
+                    tr("sweep.impact_parameter"),
+                    0f32..100f32,
+                    &mut self.impact,
+                );
+                self.impact_field.ui(
+                    ui,
+                    hash!(),
+                    &mut self.impact,
+                    unit_field::length,
+                    unit_field::format_length,
+                );
+            }
+            ui.separator();
+            if ui.button(None, tr("sweep.run")) {
+                self.run();
+            }
+            ui.same_line(0.);
+            if ui.button(None, tr("sweep.clear")) {
+                self.paths.clear();
+            }
+            for (i, (value, _)) in self.paths.iter().enumerate() {
+                let color = self.color(i);
+                let size = vec2(theme.scaled(40.), theme.scaled(10.));
+                let mut canvas = ui.canvas();
+                let at = canvas.request_space(size);
+                canvas.rect(Rect::new(at.x, at.y, size.x, size.y), color, color);
+                ui.same_line(0.);
+                ui.label(None, &format!("{:.2}", value));
+            }
+        });
+    }
+}
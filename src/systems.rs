@@ -0,0 +1,611 @@
+use std::collections::HashSet;
+
+use macroquad::prelude::*;
+
+use crate::annihilation;
+use crate::bubble;
+use crate::detector;
+use crate::events;
+use crate::foil;
+use crate::ghost;
+use crate::magnets;
+use crate::medium;
+use crate::particle::{Particle, Particles};
+use crate::portable;
+use crate::sanity;
+use crate::sources;
+use crate::world::World;
+
+// Coulomb constant in simulation units
+pub const COULOMB_K: f32 = 9e9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Integrator {
+    // Moves with the velocity from before the kick; gains energy every orbit
+    ExplicitEuler,
+    #[default]
+    SemiImplicitEuler,
+    // Velocity Verlet; costs a second force evaluation per tick
+    Verlet,
+    // Classic fourth-order Runge-Kutta; four force evaluations per tick, and
+    // not symplectic, so energy slowly drifts over many orbits
+    RungeKutta4,
+}
+
+pub const INTEGRATORS: [Integrator; 4] = [
+    Integrator::ExplicitEuler,
+    Integrator::SemiImplicitEuler,
+    Integrator::Verlet,
+    Integrator::RungeKutta4,
+];
+
+impl Integrator {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Integrator::ExplicitEuler => "explicit_euler",
+            Integrator::SemiImplicitEuler => "semi_implicit_euler",
+            Integrator::Verlet => "verlet",
+            Integrator::RungeKutta4 => "rk4",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Integrator> {
+        INTEGRATORS
+            .into_iter()
+            .find(|integrator| integrator.code() == code)
+    }
+}
+
+// Splits a tick into smaller steps for particles whose acceleration is above
+// `threshold`, so a fast projectile cannot jump through a nucleus.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Substepping {
+    pub enabled: bool,
+    // Acceleration in units per tick squared that each sub-step may cover
+    pub threshold: f32,
+    pub max_substeps: u32,
+}
+
+impl Default for Substepping {
+    fn default() -> Self {
+        Substepping {
+            enabled: true,
+            threshold: 0.01,
+            max_substeps: 64,
+        }
+    }
+}
+
+// Species pairs whose mutual force is switched off, e.g. electron-electron
+// repulsion, so its effect on an atom can be isolated.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct InteractionMatrix {
+    disabled: HashSet<(Particle, Particle)>,
+}
+
+impl InteractionMatrix {
+    pub fn allows(&self, a: Particle, b: Particle) -> bool {
+        !self.disabled.contains(&(a, b))
+    }
+
+    pub fn set(&mut self, a: Particle, b: Particle, enabled: bool) {
+        if enabled {
+            self.disabled.remove(&(a, b));
+            self.disabled.remove(&(b, a));
+        } else {
+            self.disabled.insert((a, b));
+            self.disabled.insert((b, a));
+        }
+    }
+
+    pub fn all_enabled(&self) -> bool {
+        self.disabled.is_empty()
+    }
+
+    // Each switched-off pair once, in a stable order.
+    pub fn disabled_pairs(&self) -> Vec<(Particle, Particle)> {
+        let mut pairs: Vec<(Particle, Particle)> = self
+            .disabled
+            .iter()
+            .copied()
+            .filter(|(a, b)| a.name() <= b.name())
+            .collect();
+        pairs.sort_by_key(|(a, b)| (a.name(), b.name()));
+        pairs
+    }
+}
+
+// How the force between two charges falls off with distance. Only the
+// inverse square gives closed orbits; the others make them precess.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ForceLaw {
+    InverseLinear,
+    #[default]
+    InverseSquare,
+    InverseCube,
+}
+
+pub const FORCE_LAWS: [ForceLaw; 3] = [
+    ForceLaw::InverseLinear,
+    ForceLaw::InverseSquare,
+    ForceLaw::InverseCube,
+];
+
+impl ForceLaw {
+    pub fn exponent(&self) -> u32 {
+        match self {
+            ForceLaw::InverseLinear => 1,
+            ForceLaw::InverseSquare => 2,
+            ForceLaw::InverseCube => 3,
+        }
+    }
+
+    pub fn from_exponent(exponent: u32) -> Option<ForceLaw> {
+        FORCE_LAWS
+            .iter()
+            .copied()
+            .find(|law| law.exponent() == exponent)
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ForceLaw::InverseLinear => "1/r",
+            ForceLaw::InverseSquare => "1/r²",
+            ForceLaw::InverseCube => "1/r³",
+        }
+    }
+
+    pub fn next(&self) -> ForceLaw {
+        let index = FORCE_LAWS.iter().position(|law| law == self).unwrap_or(0);
+        FORCE_LAWS[(index + 1) % FORCE_LAWS.len()]
+    }
+
+    // Force relative to the inverse square at `distance`
+    fn force_ratio(&self, distance: f32) -> f32 {
+        match self {
+            ForceLaw::InverseLinear => distance,
+            ForceLaw::InverseSquare => 1.,
+            ForceLaw::InverseCube => 1. / distance,
+        }
+    }
+
+    // Potential relative to 1 / r at `distance`: -ln r for 1/r, whose
+    // potential has no natural zero, and 1 / 2r² for 1/r³
+    fn potential_ratio(&self, distance: f32) -> f32 {
+        match self {
+            ForceLaw::InverseLinear => -distance * portable::ln(distance),
+            ForceLaw::InverseSquare => 1.,
+            ForceLaw::InverseCube => 1. / (2. * distance),
+        }
+    }
+}
+
+// Departures from the bare point-charge Coulomb law, applied to every pair.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ForceModel {
+    // Yukawa screening length λ: bound electrons hide a charge from anything
+    // farther than about λ away, so the potential falls off as e^(-r/λ) / r.
+    // None for bare charges
+    pub screening: Option<f32>,
+    // Radius R of a uniformly charged sphere standing in for each charge.
+    // Closer than R the force grows linearly with distance instead of as
+    // 1 / r², which is what a fast alpha reaching into a nucleus sees. None
+    // for point charges
+    pub nuclear_radius: Option<f32>,
+    pub law: ForceLaw,
+    // Multiplies the Coulomb constant
+    pub strength: f32,
+}
+
+impl Default for ForceModel {
+    fn default() -> Self {
+        ForceModel {
+            screening: None,
+            nuclear_radius: None,
+            law: ForceLaw::InverseSquare,
+            strength: 1.,
+        }
+    }
+}
+
+impl ForceModel {
+    // The plain Coulomb law, the only one the analytic orbits in `ghost`
+    // and the Rutherford formula hold for.
+    pub fn is_coulomb(&self) -> bool {
+        *self == ForceModel::default()
+    }
+
+    // Factor on the Coulomb potential at `distance`.
+    pub fn potential_factor(&self, distance: f32) -> f32 {
+        let screened = match self.screening {
+            Some(length) if length > 0. => portable::exp(-distance / length),
+            _ => 1.,
+        };
+        let sphere = match self.nuclear_radius {
+            // k q1 q2 (3R² - r²) / 2R³ inside, matching 1 / r at the surface
+            Some(radius) if distance < radius => {
+                distance * (3. * radius * radius - distance * distance)
+                    / (2. * radius * radius * radius)
+            }
+            _ => 1.,
+        };
+        screened * sphere * self.law.potential_ratio(distance) * self.strength
+    }
+
+    // Factor on the inverse-square force at `distance`, the derivative of
+    // the potential above. Screening, finite size and the force law simply
+    // multiply, which is close enough while the screening length is much
+    // larger than R and exact for the plain Coulomb law.
+    pub fn force_factor(&self, distance: f32) -> f32 {
+        let screened = match self.screening {
+            Some(length) if length > 0. => {
+                portable::exp(-distance / length) * (1. + distance / length)
+            }
+            _ => 1.,
+        };
+        let sphere = match self.nuclear_radius {
+            Some(radius) if distance < radius => {
+                let ratio = distance / radius;
+                ratio * ratio * ratio
+            }
+            _ => 1.,
+        };
+        screened * sphere * self.law.force_ratio(distance) * self.strength
+    }
+}
+
+// Acceleration of a particle with `charge` and `mass` at `pos` caused by one
+// with `other_charge` at `other_pos`.
+fn pair_acceleration(
+    model: &ForceModel,
+    pos: Vec3,
+    charge: f32,
+    mass: f32,
+    other_pos: Vec3,
+    other_charge: f32,
+) -> Vec3 {
+    // Inverse-square along the separation, reshaped by the force model, so
+    // the force derives from the potential in `diagnostics::pair_potential`
+    let r = pos - other_pos;
+    let distance = r.length();
+    if distance != 0. {
+        // Spelled out rather than `powi`, which may call into a runtime
+        // library that differs between platforms
+        let cube = distance * distance * distance;
+        let factor = model.force_factor(distance);
+        r * (COULOMB_K * charge * other_charge * factor / (cube * mass))
+    } else {
+        Vec3::ZERO
+    }
+}
+
+pub fn apply_forces(world: &mut World) {
+    let matrix = &world.pair_forces;
+    let model = &world.force_model;
+    let field_sources = &world.sources;
+    let gravity = world.medium.gravity;
+    let Particles {
+        particle,
+        pos,
+        charge,
+        mass,
+        acc,
+        ..
+    } = &mut world.particles;
+
+    // Accelerations are written to their own column while positions and
+    // charges are only read, so no copy of the world is needed.
+    for (i, acc) in acc.iter_mut().enumerate() {
+        let (pos_i, charge_i, mass_i) = (pos[i], charge[i], mass[i]);
+
+        *acc = pos
+            .iter()
+            .zip(charge.iter())
+            .enumerate()
+            .filter(|(j, _)| *j != i && matrix.allows(particle[i], particle[*j]))
+            .map(|(_, (other_pos, other_charge))| {
+                pair_acceleration(model, pos_i, charge_i, mass_i, *other_pos, *other_charge)
+            })
+            .fold(Vec3::ZERO, |sum, a| sum + a)
+            + sources::acceleration(field_sources, pos_i, charge_i, mass_i)
+            + gravity;
+    }
+}
+
+// Expects `apply_forces` to have filled in the accelerations for this tick.
+pub fn integrate(world: &mut World) {
+    match world.integrator {
+        Integrator::ExplicitEuler => {
+            let Particles { pos, vel, acc, .. } = &mut world.particles;
+            for ((pos, vel), acc) in pos.iter_mut().zip(vel.iter_mut()).zip(acc.iter()) {
+                *pos += *vel;
+                *vel += *acc;
+            }
+        }
+        Integrator::SemiImplicitEuler => {
+            let Particles { pos, vel, acc, .. } = &mut world.particles;
+            for ((pos, vel), acc) in pos.iter_mut().zip(vel.iter_mut()).zip(acc.iter()) {
+                *vel += *acc;
+                *pos += *vel;
+            }
+        }
+        Integrator::Verlet => {
+            kick(&mut world.particles, 0.5);
+            let Particles { pos, vel, .. } = &mut world.particles;
+            for (pos, vel) in pos.iter_mut().zip(vel.iter()) {
+                *pos += *vel;
+            }
+            apply_forces(world);
+            kick(&mut world.particles, 0.5);
+        }
+        Integrator::RungeKutta4 => runge_kutta(world),
+    }
+}
+
+// One tick of RK4 for x'' = a(x). Each stage moves the particles to a trial
+// position, from the start of the tick, and evaluates the forces there; the
+// tick is then a weighted average of the stages' velocities and
+// accelerations.
+fn runge_kutta(world: &mut World) {
+    let (pos0, vel0) = (world.particles.pos.clone(), world.particles.vel.clone());
+    let mut vel_sum = vel0.clone();
+    let mut acc_sum = world.particles.acc.clone();
+    // Velocity of the previous stage and the fraction of the tick it is
+    // followed for; the acceleration of the previous stage is left in `acc`
+    let mut stage_vel = vel0.clone();
+    for (fraction, weight) in [(0.5, 2.), (0.5, 2.), (1., 1.)] {
+        let particles = &mut world.particles;
+        let next_vel: Vec<Vec3> = vel0
+            .iter()
+            .zip(&particles.acc)
+            .map(|(vel, acc)| *vel + *acc * fraction)
+            .collect();
+        for ((pos, start), vel) in particles.pos.iter_mut().zip(&pos0).zip(&stage_vel) {
+            *pos = *start + *vel * fraction;
+        }
+        apply_forces(world);
+        for (sum, vel) in vel_sum.iter_mut().zip(&next_vel) {
+            *sum += *vel * weight;
+        }
+        for (sum, acc) in acc_sum.iter_mut().zip(&world.particles.acc) {
+            *sum += *acc * weight;
+        }
+        stage_vel = next_vel;
+    }
+    let Particles { pos, vel, .. } = &mut world.particles;
+    for (i, (pos, vel)) in pos.iter_mut().zip(vel.iter_mut()).enumerate() {
+        *pos = pos0[i] + vel_sum[i] / 6.;
+        *vel = vel0[i] + acc_sum[i] / 6.;
+    }
+}
+
+fn kick(particles: &mut Particles, fraction: f32) {
+    for (vel, acc) in particles.vel.iter_mut().zip(particles.acc.iter()) {
+        *vel += *acc * fraction;
+    }
+}
+
+pub fn record_trails(world: &mut World, limit: Option<usize>) {
+    let World {
+        particles, trails, ..
+    } = world;
+    for (entity, trail) in trails.iter_mut() {
+        if let Some(slot) = particles.slot(entity) {
+            trail.push(particles.pos[slot], limit);
+        }
+    }
+}
+
+// Acceleration of the particle in slot `i` with every particle at
+// `positions`.
+fn substep_acceleration(world: &World, positions: &[Vec3], i: usize) -> Vec3 {
+    let particles = &world.particles;
+    (0..positions.len())
+        .filter(|j| {
+            *j != i
+                && world
+                    .pair_forces
+                    .allows(particles.particle[i], particles.particle[*j])
+        })
+        .map(|j| {
+            pair_acceleration(
+                &world.force_model,
+                positions[i],
+                particles.charge[i],
+                particles.mass[i],
+                positions[j],
+                particles.charge[j],
+            )
+        })
+        .fold(Vec3::ZERO, |sum, a| sum + a)
+        + sources::acceleration(
+            &world.sources,
+            positions[i],
+            particles.charge[i],
+            particles.mass[i],
+        )
+        + world.medium.gravity
+}
+
+// Re-runs the tick for the stiff particles in `slots` as `count` steps of the
+// world's integrator, starting from every particle's position in `start` and
+// the stiff ones' velocities in `start_vel`. The other particles have already
+// been integrated and move in a straight line through the sub-steps.
+fn substep(world: &mut World, slots: &[usize], count: u32, start: &[Vec3], start_vel: &[Vec3]) {
+    let dt = 1. / count as f32;
+    let end = world.particles.pos.clone();
+    // Every particle a fraction `t` through the tick, with the stiff ones at
+    // `stiff`
+    let at = |t: f32, stiff: &[Vec3]| -> Vec<Vec3> {
+        let mut positions: Vec<Vec3> = start
+            .iter()
+            .zip(&end)
+            .map(|(from, to)| from.lerp(*to, t))
+            .collect();
+        for (k, &i) in slots.iter().enumerate() {
+            positions[i] = stiff[k];
+        }
+        positions
+    };
+    let accelerations = |positions: &[Vec3]| -> Vec<Vec3> {
+        slots
+            .iter()
+            .map(|&i| substep_acceleration(world, positions, i))
+            .collect()
+    };
+    let mut pos: Vec<Vec3> = slots.iter().map(|&i| start[i]).collect();
+    let mut vel = start_vel.to_vec();
+    let mut acc = Vec::new();
+    for step in 0..count {
+        let t = step as f32 * dt;
+        acc = accelerations(&at(t, &pos));
+        match world.integrator {
+            Integrator::ExplicitEuler => {
+                for k in 0..slots.len() {
+                    pos[k] += vel[k] * dt;
+                    vel[k] += acc[k] * dt;
+                }
+            }
+            Integrator::SemiImplicitEuler => {
+                for k in 0..slots.len() {
+                    vel[k] += acc[k] * dt;
+                    pos[k] += vel[k] * dt;
+                }
+            }
+            Integrator::Verlet => {
+                for k in 0..slots.len() {
+                    vel[k] += acc[k] * dt / 2.;
+                    pos[k] += vel[k] * dt;
+                }
+                acc = accelerations(&at(t + dt, &pos));
+                for k in 0..slots.len() {
+                    vel[k] += acc[k] * dt / 2.;
+                }
+            }
+            // The same stages as `runge_kutta`, over a sub-step
+            Integrator::RungeKutta4 => {
+                let (pos0, vel0) = (pos.clone(), vel.clone());
+                let (mut vel_sum, mut acc_sum) = (vel0.clone(), acc.clone());
+                let (mut stage_vel, mut stage_acc) = (vel0.clone(), acc.clone());
+                for (fraction, weight) in [(0.5, 2.), (0.5, 2.), (1., 1.)] {
+                    let h = fraction * dt;
+                    let next_vel: Vec<Vec3> = (0..slots.len())
+                        .map(|k| vel0[k] + stage_acc[k] * h)
+                        .collect();
+                    let trial: Vec<Vec3> = (0..slots.len())
+                        .map(|k| pos0[k] + stage_vel[k] * h)
+                        .collect();
+                    stage_acc = accelerations(&at(t + h, &trial));
+                    for k in 0..slots.len() {
+                        vel_sum[k] += next_vel[k] * weight;
+                        acc_sum[k] += stage_acc[k] * weight;
+                    }
+                    stage_vel = next_vel;
+                }
+                for k in 0..slots.len() {
+                    pos[k] = pos0[k] + vel_sum[k] * dt / 6.;
+                    vel[k] = vel0[k] + acc_sum[k] * dt / 6.;
+                }
+            }
+        }
+    }
+    let particles = &mut world.particles;
+    for (k, &i) in slots.iter().enumerate() {
+        particles.pos[i] = pos[k];
+        particles.vel[i] = vel[k];
+        particles.acc[i] = acc[k];
+    }
+}
+
+// Slots whose acceleration needs sub-stepping, and how many sub-steps the
+// stiffest of them asks for.
+fn stiff_particles(world: &World) -> (Vec<usize>, u32) {
+    let settings = world.substepping;
+    if !settings.enabled || settings.threshold <= 0. {
+        return (Vec::new(), 1);
+    }
+    let mut count = 1;
+    let slots = (0..world.particles.len())
+        .filter(|i| {
+            let needed = (world.particles.acc[*i].length() / settings.threshold).ceil();
+            if needed > 1. {
+                count = count.max((needed as u32).min(settings.max_substeps.max(1)));
+                true
+            } else {
+                false
+            }
+        })
+        .collect();
+    (slots, count)
+}
+
+// Reflects particles that crossed a wall of `World::bounds` back inside and
+// turns their velocity around, and holds them to the plane in 2D mode.
+pub fn confine(world: &mut World) {
+    if world.planar {
+        let Particles { pos, vel, acc, .. } = &mut world.particles;
+        for ((pos, vel), acc) in pos.iter_mut().zip(vel.iter_mut()).zip(acc.iter_mut()) {
+            // Rounding can leave a force with a sliver of z
+            pos.z = 0.;
+            vel.z = 0.;
+            acc.z = 0.;
+        }
+    }
+    let Some(half) = world.bounds else {
+        return;
+    };
+    let Particles { pos, vel, .. } = &mut world.particles;
+    for (pos, vel) in pos.iter_mut().zip(vel.iter_mut()) {
+        for axis in 0..3 {
+            if pos[axis] > half {
+                pos[axis] = (2. * half - pos[axis]).max(-half);
+                vel[axis] = -vel[axis].abs();
+            } else if pos[axis] < -half {
+                pos[axis] = (-2. * half - pos[axis]).min(half);
+                vel[axis] = vel[axis].abs();
+            }
+        }
+    }
+}
+
+// One full simulation tick.
+pub fn step(world: &mut World) {
+    apply_forces(world);
+    let (stiff, count) = stiff_particles(world);
+    // Only what the sub-steps and bubbles go back to is kept from before
+    // the tick: every position, and the stiff particles' velocities
+    let start = (!stiff.is_empty()).then(|| {
+        let particles = &world.particles;
+        let vel: Vec<Vec3> = stiff.iter().map(|&i| particles.vel[i]).collect();
+        (particles.pos.clone(), vel)
+    });
+    let held = bubble::held(world);
+    integrate(world);
+    if let Some((pos, vel)) = &start {
+        substep(world, &stiff, count, pos, vel);
+    }
+    magnets::rotate(world);
+    medium::damp(world);
+    bubble::dilate(world, &held);
+    world.last_substeps = count;
+    confine(world);
+    ghost::advance(world);
+    annihilation::age_flashes(world);
+    annihilation::annihilate(world);
+    events::detect_events(world);
+    foil::tag_interactions(world);
+    detector::detect_hits(world);
+}
+
+// Runs up to `ticks` ticks back to back, stopping early once the sanity check
+// fails. Returns how many ticks ran.
+pub fn fast_forward(world: &mut World, ticks: u64, trail_limit: Option<usize>) -> u64 {
+    for tick in 0..ticks {
+        step(world);
+        record_trails(world, trail_limit);
+        if !sanity::check(world).is_empty() {
+            return tick + 1;
+        }
+    }
+    ticks
+}
@@ -0,0 +1,155 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::statistics::Statistics;
+use rutherford_atomic_model::websocket;
+use rutherford_atomic_model::world::World;
+
+use crate::json::{self, scientific};
+
+// A client that takes longer than this to accept a frame is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+// Summaries waiting to be sent; when the sender falls behind, ticks are skipped
+const QUEUE: usize = 64;
+
+// Streams a JSON summary of every tick to WebSocket clients, for live plots in
+// a dashboard or notebook. Sending happens on a thread of its own so a slow
+// client never holds up a frame; clients that stop reading are dropped.
+pub struct Telemetry {
+    ticks: u64,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    count: Arc<AtomicUsize>,
+    messages: SyncSender<String>,
+}
+
+impl Telemetry {
+    pub fn start(port: u16) -> Option<Telemetry> {
+        let listener = match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::warn!("could not start telemetry on port {}: {}", port, err);
+                return None;
+            }
+        };
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let (accepted, accept_count) = (Arc::clone(&clients), Arc::clone(&count));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                match handshake(&stream) {
+                    Ok(()) => {
+                        stream.set_write_timeout(Some(WRITE_TIMEOUT)).ok();
+                        let mut clients = accepted.lock().unwrap_or_else(PoisonError::into_inner);
+                        clients.push(stream);
+                        accept_count.store(clients.len(), Ordering::Relaxed);
+                        log::info!("telemetry client connected; {} listening", clients.len());
+                    }
+                    Err(err) => log::debug!("telemetry handshake failed: {}", err),
+                }
+            }
+        });
+
+        let (messages, receiver) = sync_channel::<String>(QUEUE);
+        let (sending, send_count) = (Arc::clone(&clients), Arc::clone(&count));
+        thread::spawn(move || {
+            for message in receiver {
+                let frame = websocket::text_frame(&message);
+                let mut clients = sending.lock().unwrap_or_else(PoisonError::into_inner);
+                clients.retain_mut(|client| client.write_all(&frame).is_ok());
+                send_count.store(clients.len(), Ordering::Relaxed);
+            }
+        });
+
+        log::info!("telemetry streaming on ws://127.0.0.1:{}", port);
+        Some(Telemetry {
+            ticks: 0,
+            clients,
+            count,
+            messages,
+        })
+    }
+
+    pub fn has_clients(&self) -> bool {
+        self.count.load(Ordering::Relaxed) > 0
+    }
+
+    // Call once per simulation tick. The summary is only built while someone
+    // is listening.
+    pub fn tick(&mut self, world: &World, statistics: &Statistics, selected: Option<Entity>) {
+        self.ticks += 1;
+        if self.has_clients() {
+            self.messages
+                .try_send(summary(world, statistics, selected, self.ticks))
+                .ok();
+        }
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        let clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        for client in clients.iter() {
+            client.shutdown(std::net::Shutdown::Both).ok();
+        }
+    }
+}
+
+fn summary(world: &World, statistics: &Statistics, selected: Option<Entity>, tick: u64) -> String {
+    let selected = selected
+        .and_then(|entity| {
+            let slot = world.particles.slot(entity)?;
+            let kinetic = diagnostics::particle_kinetic_energy(world, entity)?;
+            Some(format!(
+                "{{\"particle\":{},\"kinetic_energy\":{}}}",
+                json::particle(world, slot),
+                scientific(kinetic)
+            ))
+        })
+        .unwrap_or_else(|| "null".to_string());
+    format!(
+        "{{\"tick\":{},\"particles\":{},\"kinetic_energy\":{},\"potential_energy\":{},\"total_energy\":{},\"annihilations\":{},\"substeps\":{},\"selected\":{}}}",
+        tick,
+        statistics.particles,
+        scientific(statistics.kinetic),
+        scientific(statistics.potential),
+        scientific(statistics.total_energy()),
+        world.annihilations,
+        world.last_substeps,
+        selected
+    )
+}
+
+fn handshake(mut stream: &TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut key = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n")?;
+        return Err(std::io::Error::other("not a WebSocket request"));
+    };
+    let accept = websocket::accept_key(&key);
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )
+}
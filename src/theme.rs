@@ -0,0 +1,212 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, Skin};
+
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::species;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ThemeKind {
+    Dark,
+    ColorBlind,
+    HighContrast,
+}
+
+pub const THEMES: [ThemeKind; 3] = [
+    ThemeKind::Dark,
+    ThemeKind::ColorBlind,
+    ThemeKind::HighContrast,
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    pub kind: ThemeKind,
+    pub background: Color,
+    pub grid_axes: Color,
+    pub grid_lines: Color,
+    pub text: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub selection: Color,
+    pub electron: Color,
+    pub proton: Color,
+    pub neutron: Color,
+    pub alpha: Color,
+    pub positron: Color,
+    pub antiproton: Color,
+    pub panel: Color,
+    pub text_scale: f32,
+    pub line_scale: f32,
+    // User-chosen UI scale, carried over when switching themes
+    pub ui_scale: f32,
+}
+
+impl ThemeKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ThemeKind::Dark => "dark",
+            ThemeKind::ColorBlind => "color_blind",
+            ThemeKind::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ThemeKind> {
+        THEMES.iter().copied().find(|k| k.code() == code)
+    }
+}
+
+impl Theme {
+    pub fn new(kind: ThemeKind, ui_scale: f32) -> Theme {
+        match kind {
+            ThemeKind::Dark => Theme {
+                kind,
+                background: BLACK,
+                grid_axes: BLACK,
+                grid_lines: GRAY,
+                text: GREEN,
+                warning: ORANGE,
+                error: RED,
+                selection: WHITE,
+                electron: YELLOW,
+                proton: RED,
+                neutron: LIGHTGRAY,
+                alpha: SKYBLUE,
+                positron: PINK,
+                antiproton: PURPLE,
+                panel: Color::new(0., 0., 0., 0.6),
+                text_scale: 1.,
+                line_scale: 1.,
+                ui_scale,
+            },
+            // Okabe-Ito palette, distinguishable under the common forms of
+            // colour vision deficiency
+            ThemeKind::ColorBlind => Theme {
+                kind,
+                background: BLACK,
+                grid_axes: BLACK,
+                grid_lines: GRAY,
+                text: Color::from_rgba(240, 228, 66, 255),
+                warning: Color::from_rgba(230, 159, 0, 255),
+                error: Color::from_rgba(213, 94, 0, 255),
+                selection: WHITE,
+                electron: Color::from_rgba(86, 180, 233, 255),
+                proton: Color::from_rgba(213, 94, 0, 255),
+                neutron: Color::from_rgba(240, 228, 66, 255),
+                alpha: Color::from_rgba(0, 158, 115, 255),
+                positron: Color::from_rgba(204, 121, 167, 255),
+                antiproton: Color::from_rgba(0, 114, 178, 255),
+                panel: Color::new(0., 0., 0., 0.6),
+                text_scale: 1.,
+                line_scale: 1.,
+                ui_scale,
+            },
+            // Light background for projectors in bright lecture halls
+            ThemeKind::HighContrast => Theme {
+                kind,
+                background: WHITE,
+                grid_axes: BLACK,
+                grid_lines: DARKGRAY,
+                text: BLACK,
+                warning: Color::from_rgba(160, 80, 0, 255),
+                error: Color::from_rgba(180, 0, 0, 255),
+                selection: BLACK,
+                electron: Color::from_rgba(0, 60, 200, 255),
+                proton: Color::from_rgba(200, 0, 0, 255),
+                neutron: DARKGRAY,
+                alpha: Color::from_rgba(0, 120, 0, 255),
+                positron: Color::from_rgba(200, 0, 160, 255),
+                antiproton: Color::from_rgba(90, 0, 160, 255),
+                panel: Color::new(1., 1., 1., 0.85),
+                text_scale: 1.5,
+                line_scale: 2.,
+                ui_scale,
+            },
+        }
+    }
+
+    pub fn next(&self) -> Theme {
+        let index = THEMES.iter().position(|k| *k == self.kind).unwrap_or(0);
+        Theme::new(THEMES[(index + 1) % THEMES.len()], self.ui_scale)
+    }
+
+    pub fn name_key(&self) -> &'static str {
+        match self.kind {
+            ThemeKind::Dark => "theme.dark",
+            ThemeKind::ColorBlind => "theme.color_blind",
+            ThemeKind::HighContrast => "theme.high_contrast",
+        }
+    }
+
+    pub fn particle(&self, particle: Particle) -> Color {
+        match particle {
+            Particle::Electron => self.electron,
+            Particle::Proton => self.proton,
+            Particle::Neutron => self.neutron,
+            Particle::Alpha => self.alpha,
+            Particle::Positron => self.positron,
+            Particle::Antiproton => self.antiproton,
+            Particle::Custom(index) => species::get(index).map_or(self.text, |s| s.color),
+        }
+    }
+
+    pub fn text_size(&self, size: f32) -> f32 {
+        size * self.text_scale * self.ui_scale
+    }
+
+    // Sizes of panels and gizmos follow the UI scale only
+    pub fn scaled(&self, size: f32) -> f32 {
+        size * self.ui_scale
+    }
+
+    // Skin for macroquad UI windows with fonts and margins scaled to match
+    pub fn skin(&self) -> Skin {
+        let ui = root_ui();
+        let font_size = self.text_size(16.) as u16;
+        let margin = RectOffset::new(2., 2., 2., 2.);
+        let black = Color::from_rgba(0, 0, 0, 255);
+
+        Skin {
+            label_style: ui
+                .style_builder()
+                .margin(margin)
+                .text_color(black)
+                .color_inactive(Color::from_rgba(0, 0, 0, 128))
+                .font_size(font_size)
+                .build(),
+            button_style: ui
+                .style_builder()
+                .margin(margin)
+                .color(Color::from_rgba(204, 204, 204, 235))
+                .color_clicked(Color::from_rgba(187, 187, 187, 255))
+                .color_hovered(Color::from_rgba(170, 170, 170, 235))
+                .text_color(black)
+                .font_size(font_size)
+                .build(),
+            editbox_style: ui
+                .style_builder()
+                .text_color(black)
+                .color_selected(Color::from_rgba(200, 200, 200, 255))
+                .font_size(font_size)
+                .build(),
+            window_titlebar_style: ui
+                .style_builder()
+                .color(Color::from_rgba(68, 68, 68, 255))
+                .color_inactive(Color::from_rgba(102, 102, 102, 127))
+                .text_color(black)
+                .font_size(font_size)
+                .build(),
+            checkbox_style: ui
+                .style_builder()
+                .text_color(black)
+                .color(Color::from_rgba(200, 200, 200, 255))
+                .color_hovered(Color::from_rgba(210, 210, 210, 255))
+                .color_clicked(Color::from_rgba(150, 150, 150, 255))
+                .color_selected(Color::from_rgba(128, 128, 128, 255))
+                .color_selected_hovered(Color::from_rgba(140, 140, 140, 255))
+                .font_size(font_size)
+                .build(),
+            title_height: self.scaled(14.),
+            margin: self.scaled(2.),
+            ..ui.default_skin()
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::statistics::Statistics;
+
+use crate::i18n::trf;
+use crate::theme::Theme;
+
+const MAX_TOASTS: usize = 5;
+// Seconds a toast stays up, the last of which it spends fading out
+const LIFETIME: f32 = 4.;
+const FADE: f32 = 1.;
+// Relative change in total energy that counts as drift
+const DRIFT_LIMIT: f32 = 0.05;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Info,
+    Warning,
+}
+
+struct Toast {
+    kind: Kind,
+    text: String,
+    age: f32,
+}
+
+// Shared like the log so any module can post without a handle being passed
+// down to it.
+static TOASTS: Mutex<VecDeque<Toast>> = Mutex::new(VecDeque::new());
+
+pub fn push(kind: Kind, text: String) {
+    let Ok(mut toasts) = TOASTS.lock() else {
+        return;
+    };
+    if toasts.len() == MAX_TOASTS {
+        toasts.pop_front();
+    }
+    toasts.push_back(Toast {
+        kind,
+        text,
+        age: 0.,
+    });
+}
+
+pub fn info(text: String) {
+    push(Kind::Info, text);
+}
+
+pub fn warning(text: String) {
+    push(Kind::Warning, text);
+}
+
+// Ages the queue by `delta` seconds and draws it in the top right corner,
+// newest at the bottom.
+pub fn draw(delta: f32, theme: &Theme) {
+    let Ok(mut toasts) = TOASTS.lock() else {
+        return;
+    };
+    for toast in toasts.iter_mut() {
+        toast.age += delta;
+    }
+    toasts.retain(|toast| toast.age < LIFETIME);
+
+    let font_size = theme.text_size(22.);
+    let padding = theme.scaled(8.);
+    let mut y = theme.scaled(60.);
+    for toast in toasts.iter() {
+        let fade = ((LIFETIME - toast.age) / FADE).min(1.);
+        let size = measure_text(&toast.text, None, font_size as u16, 1.);
+        let (width, height) = (size.width + padding * 2., font_size + padding);
+        let x = screen_width() - width - theme.scaled(10.);
+        let mut panel = theme.panel;
+        panel.a *= fade;
+        let mut text = match toast.kind {
+            Kind::Info => theme.text,
+            Kind::Warning => theme.warning,
+        };
+        text.a *= fade;
+        draw_rectangle(x, y, width, height, panel);
+        draw_text(
+            &toast.text,
+            x + padding,
+            y + font_size * 0.8,
+            font_size,
+            text,
+        );
+        y += height + padding / 2.;
+    }
+}
+
+// Warns once when the total energy has drifted more than `DRIFT_LIMIT` from
+// where it stood when the particle count last changed.
+pub struct DriftWatch {
+    reference: Option<(usize, f32)>,
+    warned: bool,
+}
+
+impl DriftWatch {
+    pub fn new() -> DriftWatch {
+        DriftWatch {
+            reference: None,
+            warned: false,
+        }
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, statistics: &Statistics) {
+        let count = statistics.particles;
+        let energy = statistics.total_energy();
+        let start = match self.reference {
+            Some((reference_count, start)) if reference_count == count => start,
+            _ => {
+                self.reference = Some((count, energy));
+                self.warned = false;
+                return;
+            }
+        };
+        if start == 0. || !energy.is_finite() {
+            return;
+        }
+        let drift = ((energy - start) / start).abs();
+        if drift > DRIFT_LIMIT && !self.warned {
+            warning(trf("toast.energy_drift", &[&(drift * 100.).round()]));
+        }
+        self.warned = drift > DRIFT_LIMIT;
+    }
+}
@@ -0,0 +1,94 @@
+use macroquad::prelude::*;
+
+enum Primitive {
+    Line(Vec3, Vec3),
+    Sphere(Vec3, f32),
+    SphereWires(Vec3, f32),
+    Cube(Vec3, Vec3),
+    // Colored per vertex, sorted as a whole by the given point
+    Mesh(Mesh, Vec3),
+}
+
+impl Primitive {
+    // Point the primitive is sorted by
+    fn center(&self) -> Vec3 {
+        match self {
+            Primitive::Line(a, b) => (*a + *b) / 2.,
+            Primitive::Sphere(center, _)
+            | Primitive::SphereWires(center, _)
+            | Primitive::Cube(center, _)
+            | Primitive::Mesh(_, center) => *center,
+        }
+    }
+
+    fn draw(self, color: Color) {
+        match self {
+            Primitive::Line(a, b) => draw_line_3d(a, b, color),
+            Primitive::Sphere(center, radius) => draw_sphere(center, radius, None, color),
+            Primitive::SphereWires(center, radius) => {
+                draw_sphere_wires(center, radius, None, color)
+            }
+            Primitive::Cube(center, size) => draw_cube(center, size, None, color),
+            Primitive::Mesh(mesh, _) => draw_mesh(&mesh),
+        }
+    }
+}
+
+// Primitives for one pass of the 3D scene. A see-through one blends over
+// whatever was drawn before it, and hides whatever comes after it behind
+// it, so overlapping ones only come out right drawn after everything opaque,
+// furthest from the eye first. Opaque primitives are drawn straight away;
+// the rest wait for `flush`, which sorts them.
+pub struct Translucent {
+    eye: Vec3,
+    queued: Vec<(f32, Primitive, Color)>,
+}
+
+impl Translucent {
+    pub fn new(eye: Vec3) -> Translucent {
+        Translucent {
+            eye,
+            queued: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, primitive: Primitive, color: Color) {
+        if color.a >= 1. {
+            primitive.draw(color);
+        } else if color.a > 0. {
+            let depth = primitive.center().distance_squared(self.eye);
+            self.queued.push((depth, primitive, color));
+        }
+    }
+
+    pub fn line(&mut self, a: Vec3, b: Vec3, color: Color) {
+        self.push(Primitive::Line(a, b), color);
+    }
+
+    pub fn sphere(&mut self, center: Vec3, radius: f32, color: Color) {
+        self.push(Primitive::Sphere(center, radius), color);
+    }
+
+    pub fn sphere_wires(&mut self, center: Vec3, radius: f32, color: Color) {
+        self.push(Primitive::SphereWires(center, radius), color);
+    }
+
+    pub fn cube(&mut self, center: Vec3, size: Vec3, color: Color) {
+        self.push(Primitive::Cube(center, size), color);
+    }
+
+    // A mesh with see-through vertices, sorted by `center`.
+    pub fn mesh(&mut self, mesh: Mesh, center: Vec3) {
+        let depth = center.distance_squared(self.eye);
+        self.queued
+            .push((depth, Primitive::Mesh(mesh, center), WHITE));
+    }
+
+    // Draws everything queued, back to front.
+    pub fn flush(&mut self) {
+        self.queued.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, primitive, color) in self.queued.drain(..) {
+            primitive.draw(color);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use macroquad::prelude::*;
+
+use crate::i18n::tr;
+use crate::theme::Theme;
+
+// What the user has to do to finish a step.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Goal {
+    Continue,
+    Look,
+    Move,
+    Spawn,
+    Select,
+    Focus,
+    Minimap,
+    Histograms,
+}
+
+const STEPS: [(&str, Goal); 9] = [
+    ("tutorial.welcome", Goal::Continue),
+    ("tutorial.look", Goal::Look),
+    ("tutorial.move", Goal::Move),
+    ("tutorial.spawn", Goal::Spawn),
+    ("tutorial.select", Goal::Select),
+    ("tutorial.focus", Goal::Focus),
+    ("tutorial.minimap", Goal::Minimap),
+    ("tutorial.histograms", Goal::Histograms),
+    ("tutorial.done", Goal::Continue),
+];
+
+// Snapshot of the app state the tutorial reacts to, filled in by main.
+pub struct Observation {
+    // The key that moves past a step with nothing to do was pressed
+    pub continued: bool,
+    pub looked: bool,
+    pub moved: bool,
+    pub particles: usize,
+    pub selected: bool,
+    pub focused: bool,
+    pub minimap: bool,
+    pub histograms: bool,
+    // Screen area of the minimap, highlighted while it is being introduced
+    pub minimap_rect: Rect,
+}
+
+// Guided tour shown on first launch: one instruction at a time, each advancing
+// once the user has actually done it.
+pub struct Tutorial {
+    step: Option<usize>,
+    // Particle count when the spawn step began
+    baseline: usize,
+}
+
+impl Tutorial {
+    pub fn new(done: bool) -> Tutorial {
+        Tutorial {
+            step: (!done).then_some(0),
+            baseline: 0,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.step.is_some()
+    }
+
+    // Whether the current step waits for the continue key, which then
+    // belongs to the tutorial alone.
+    pub fn waiting(&self) -> bool {
+        self.step
+            .is_some_and(|step| STEPS[step].1 == Goal::Continue)
+    }
+
+    pub fn restart(&mut self) {
+        self.step = Some(0);
+    }
+
+    pub fn skip(&mut self) {
+        self.step = None;
+    }
+
+    // Returns true when the last step has just been completed.
+    pub fn update(&mut self, observed: &Observation) -> bool {
+        let Some(step) = self.step else {
+            return false;
+        };
+        let done = match STEPS[step].1 {
+            Goal::Continue => observed.continued,
+            Goal::Look => observed.looked,
+            Goal::Move => observed.moved,
+            Goal::Spawn => observed.particles > self.baseline,
+            Goal::Select => observed.selected,
+            Goal::Focus => observed.focused,
+            Goal::Minimap => observed.minimap,
+            Goal::Histograms => observed.histograms,
+        };
+        if !done {
+            return false;
+        }
+        self.baseline = observed.particles;
+        if step + 1 < STEPS.len() {
+            self.step = Some(step + 1);
+            false
+        } else {
+            self.step = None;
+            true
+        }
+    }
+
+    pub fn draw(&self, observed: &Observation, theme: &Theme) {
+        let Some(step) = self.step else {
+            return;
+        };
+        let (key, goal) = STEPS[step];
+
+        if goal == Goal::Minimap {
+            let r = observed.minimap_rect;
+            let pad = theme.scaled(6.);
+            draw_rectangle_lines(
+                r.x - pad,
+                r.y - pad,
+                r.w + pad * 2.,
+                r.h + pad * 2.,
+                theme.scaled(3.),
+                theme.warning,
+            );
+        }
+
+        let width = theme.scaled(520.);
+        let height = theme.scaled(90.);
+        let x = (screen_width() - width) / 2.;
+        let y = screen_height() - height - theme.scaled(40.);
+        draw_rectangle(x, y, width, height, theme.panel);
+        draw_rectangle_lines(x, y, width, height, theme.scaled(2.), theme.warning);
+
+        let size = theme.text_size(22.);
+        let progress = format!("{}/{}", step + 1, STEPS.len());
+        draw_text(
+            &progress,
+            x + theme.scaled(10.),
+            y + theme.scaled(24.),
+            size,
+            theme.warning,
+        );
+        draw_text(
+            tr(key),
+            x + theme.scaled(10.),
+            y + theme.scaled(56.),
+            size,
+            theme.text,
+        );
+        draw_text(
+            tr("tutorial.skip"),
+            x + width - theme.scaled(170.),
+            y + theme.scaled(24.),
+            theme.text_size(18.),
+            theme.text,
+        );
+    }
+}
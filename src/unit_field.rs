@@ -0,0 +1,166 @@
+use egui_macroquad::egui;
+use macroquad::ui::{Id, Ui};
+
+use rutherford_atomic_model::particle::{get_charge, get_mass, Particle};
+use rutherford_atomic_model::units::{self, Quantity};
+
+use crate::i18n::{tr, trf};
+
+// A text box for a physical value that takes it with its unit, like "5 MeV",
+// "0.05c" or "3 fm", and writes it back converted to simulation units. It
+// follows the value when something else changes it, such as a slider next
+// to it. Drawn with macroquad's UI by `ui` and with egui by `egui`.
+pub struct UnitField {
+    text: String,
+    // Value the text was last written from or read into
+    synced: Option<f32>,
+    // Whether the last edit was understood
+    valid: bool,
+}
+
+impl UnitField {
+    pub fn new() -> UnitField {
+        UnitField {
+            text: String::new(),
+            synced: None,
+            valid: true,
+        }
+    }
+
+    // `parse` reads what was typed into the value, or None if it can't be
+    // used; `format` writes the value back out with its unit.
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        id: Id,
+        value: &mut f32,
+        parse: impl Fn(&str) -> Option<f32>,
+        format: impl Fn(f32) -> String,
+    ) {
+        self.follow(*value, format);
+        let before = self.text.clone();
+        ui.input_text(id, tr("units.input"), &mut self.text);
+        if self.text != before {
+            self.read(value, parse);
+        }
+        if !self.valid {
+            ui.label(None, &trf("units.invalid", &[&self.text.trim()]));
+        }
+    }
+
+    // Same as `ui`, in an egui panel. Returns the text box's response.
+    pub fn egui(
+        &mut self,
+        ui: &mut egui::Ui,
+        value: &mut f32,
+        parse: impl Fn(&str) -> Option<f32>,
+        format: impl Fn(f32) -> String,
+    ) -> egui::Response {
+        self.follow(*value, format);
+        let response =
+            ui.add(egui::TextEdit::singleline(&mut self.text).hint_text(tr("units.input")));
+        if response.changed() {
+            self.read(value, parse);
+        }
+        if !self.valid {
+            ui.label(trf("units.invalid", &[&self.text.trim()]));
+        }
+        response
+    }
+
+    // Rewrites the text when the value was changed from elsewhere.
+    fn follow(&mut self, value: f32, format: impl Fn(f32) -> String) {
+        if self.synced != Some(value) {
+            self.text = format(value);
+            self.synced = Some(value);
+            self.valid = true;
+        }
+    }
+
+    fn read(&mut self, value: &mut f32, parse: impl Fn(&str) -> Option<f32>) {
+        match parse(&self.text) {
+            Some(parsed) => {
+                *value = parsed;
+                self.synced = Some(parsed);
+                self.valid = true;
+            }
+            None => self.valid = false,
+        }
+    }
+}
+
+// Reads a length for a field, in units.
+pub fn length(text: &str) -> Option<f32> {
+    match units::parse_quantity(text, Quantity::Length)? {
+        Quantity::Length(length) if length > 0. => Some(length),
+        _ => None,
+    }
+}
+
+pub fn format_length(length: f32) -> String {
+    format!("{:.1} fm", length)
+}
+
+// Reads a kinetic energy for a field, in MeV.
+pub fn energy(text: &str) -> Option<f32> {
+    match units::parse_quantity(text, Quantity::Energy)? {
+        Quantity::Energy(energy) if energy > 0. => Some(energy),
+        _ => None,
+    }
+}
+
+pub fn format_energy(energy: f32) -> String {
+    format!("{:.3} MeV", energy)
+}
+
+// Reads an area for a field, in square units.
+pub fn area(text: &str) -> Option<f32> {
+    match units::parse_quantity(text, Quantity::Area)? {
+        Quantity::Area(area) if area > 0. => Some(area),
+        _ => None,
+    }
+}
+
+pub fn format_area(area: f32) -> String {
+    format!("{:.0} fm²", area)
+}
+
+// Reads a charge for a field, in elementary charges, which a bare number is
+// taken to be in.
+pub fn charge(text: &str) -> Option<f32> {
+    let e = get_charge(Particle::Proton);
+    match units::parse_quantity(text, |n| Quantity::Charge(n * get_charge(Particle::Proton)))? {
+        Quantity::Charge(charge) => Some(charge / e),
+        _ => None,
+    }
+}
+
+pub fn format_charge(charge: f32) -> String {
+    format!("{:.2} e", charge)
+}
+
+// Reads a mass for a field, in proton masses, which a bare number is taken
+// to be in.
+pub fn mass(text: &str) -> Option<f32> {
+    let proton = get_mass(Particle::Proton);
+    match units::parse_quantity(text, |n| Quantity::Mass(n * get_mass(Particle::Proton)))? {
+        Quantity::Mass(mass) if mass > 0. => Some(mass / proton),
+        _ => None,
+    }
+}
+
+pub fn format_mass(mass: f32) -> String {
+    format!("{:.3e} kg", mass * get_mass(Particle::Proton))
+}
+
+// Reads a magnetic field for a field, in tesla.
+pub fn magnetic_field(text: &str) -> Option<f32> {
+    match units::parse_quantity(text, Quantity::MagneticField)? {
+        Quantity::MagneticField(field) if field > 0. => Some(field),
+        _ => None,
+    }
+}
+
+pub fn format_magnetic_field(field: f32) -> String {
+    format!("{:.3e} T", field)
+}
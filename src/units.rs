@@ -0,0 +1,217 @@
+// Conversions between simulation units and physical ones.
+//
+// Masses are in kilograms and charges in coulombs, and the Coulomb constant
+// has its SI value. Reading one length unit as a femtometre then keeps the
+// force law exact as long as one tick is sqrt(1e-45) s, which puts alpha
+// kinematics in the range of the scene: a 5 MeV alpha moves about half a
+// unit per tick and gets within tens of units of a gold nucleus.
+
+use std::mem::discriminant;
+
+use crate::particle::Particle;
+use crate::systems::COULOMB_K;
+
+pub const METRES_PER_UNIT: f32 = 1e-15;
+pub const SECONDS_PER_TICK: f32 = 3.162_278e-23;
+pub const JOULES_PER_MEV: f32 = 1.602_177e-13;
+pub const LIGHT_METRES_PER_SECOND: f32 = 299_792_458.;
+pub const SPEED_OF_LIGHT: f32 = LIGHT_METRES_PER_SECOND * SECONDS_PER_TICK / METRES_PER_UNIT;
+
+// Rest energy in MeV of a particle of `mass` kilograms.
+pub fn rest_energy_mev(mass: f32) -> f32 {
+    mass * LIGHT_METRES_PER_SECOND.powi(2) / JOULES_PER_MEV
+}
+
+// Speed in units per tick for a kinetic energy in MeV.
+pub fn speed_from_mev(mass: f32, energy: f32, relativistic: bool) -> f32 {
+    let energy = energy.max(0.);
+    if relativistic {
+        let gamma = 1. + energy / rest_energy_mev(mass);
+        SPEED_OF_LIGHT * (1. - 1. / (gamma * gamma)).sqrt()
+    } else {
+        let joules = energy * JOULES_PER_MEV;
+        (2. * joules / mass).sqrt() * SECONDS_PER_TICK / METRES_PER_UNIT
+    }
+}
+
+// Field in simulation units, the force on a unit charge, between plates
+// `separation` units apart held `volts` apart. The time and length scales
+// are squared as a ratio, as SECONDS_PER_TICK² alone is too small for f32.
+pub fn field_from_volts(volts: f32, separation: f32) -> f32 {
+    volts / separation * (SECONDS_PER_TICK / METRES_PER_UNIT).powi(2)
+}
+
+// Acceleration in units per tick² from metres per second squared, and back.
+pub fn acceleration_from_si(acceleration: f32) -> f32 {
+    acceleration * (SECONDS_PER_TICK / METRES_PER_UNIT).powi(2) * METRES_PER_UNIT
+}
+
+pub fn acceleration_to_si(acceleration: f32) -> f32 {
+    acceleration / METRES_PER_UNIT / (SECONDS_PER_TICK / METRES_PER_UNIT).powi(2)
+}
+
+// Kinetic energy in MeV for a speed in units per tick.
+pub fn kinetic_mev(mass: f32, speed: f32, relativistic: bool) -> f32 {
+    if relativistic {
+        let beta = (speed / SPEED_OF_LIGHT).min(0.999_999);
+        (1. / (1. - beta * beta).sqrt() - 1.) * rest_energy_mev(mass)
+    } else {
+        let metres_per_second = speed * METRES_PER_UNIT / SECONDS_PER_TICK;
+        0.5 * mass * metres_per_second.powi(2) / JOULES_PER_MEV
+    }
+}
+
+// Head-on distance of closest approach in units, where all of the kinetic
+// energy in the centre-of-mass frame has turned into Coulomb potential.
+pub fn closest_approach(energy: f32, projectile: (f32, f32), target: (f32, f32)) -> Option<f32> {
+    let ((mass, charge), (target_mass, target_charge)) = (projectile, target);
+    let available = energy * JOULES_PER_MEV * target_mass / (mass + target_mass);
+    if available <= 0. || charge * target_charge <= 0. {
+        return None;
+    }
+    Some(COULOMB_K * charge * target_charge / available / METRES_PER_UNIT)
+}
+
+// A value read from text, converted to simulation units.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Quantity {
+    // In units
+    Length(f32),
+    // In MeV
+    Energy(f32),
+    // In units per tick
+    Speed(f32),
+    // In volts
+    Voltage(f32),
+    // In coulombs
+    Charge(f32),
+    // In kilograms
+    Mass(f32),
+    // In tesla
+    MagneticField(f32),
+    // In square units
+    Area(f32),
+}
+
+// Units accepted after a number, as a prefix and a base, with what one of
+// them is in simulation units. Prefixes are matched exactly, since m is
+// milli and M mega; the base is matched without regard to case.
+type Unit = (&'static str, &'static str, f32);
+
+const LENGTH_UNITS: [Unit; 7] = [
+    ("f", "m", 1.),
+    ("p", "m", 1e3),
+    ("", "a", 1e5),
+    ("", "å", 1e5),
+    ("n", "m", 1e6),
+    ("", "m", 1. / METRES_PER_UNIT),
+    ("", "units", 1.),
+];
+const ENERGY_UNITS: [Unit; 6] = [
+    ("", "eV", 1e-6),
+    ("k", "eV", 1e-3),
+    ("M", "eV", 1.),
+    ("G", "eV", 1e3),
+    ("m", "eV", 1e-9),
+    ("", "J", 1. / JOULES_PER_MEV),
+];
+const VOLTAGE_UNITS: [Unit; 5] = [
+    ("m", "V", 1e-3),
+    ("", "V", 1.),
+    ("k", "V", 1e3),
+    ("M", "V", 1e6),
+    ("G", "V", 1e9),
+];
+const SPEED_UNITS: [Unit; 2] = [
+    ("", "c", SPEED_OF_LIGHT),
+    ("", "m/s", SECONDS_PER_TICK / METRES_PER_UNIT),
+];
+// e is the simulation's, see `particle::get_charge`
+const CHARGE_UNITS: [Unit; 2] = [("", "e", 1.6e-19), ("", "C", 1.)];
+const MASS_UNITS: [Unit; 5] = [
+    ("k", "g", 1.),
+    ("", "u", 1.660_539e-27),
+    ("", "Da", 1.660_539e-27),
+    (
+        "M",
+        "eV/c2",
+        JOULES_PER_MEV / (LIGHT_METRES_PER_SECOND * LIGHT_METRES_PER_SECOND),
+    ),
+    (
+        "M",
+        "eV/c²",
+        JOULES_PER_MEV / (LIGHT_METRES_PER_SECOND * LIGHT_METRES_PER_SECOND),
+    ),
+];
+const FIELD_UNITS: [Unit; 3] = [("", "T", 1.), ("m", "T", 1e-3), ("", "gauss", 1e-4)];
+
+// Unit names with their scales, and the kind of quantity they make
+type Units = (&'static [Unit], fn(f32) -> Quantity);
+const KINDS: [Units; 7] = [
+    (&LENGTH_UNITS, Quantity::Length),
+    (&ENERGY_UNITS, Quantity::Energy),
+    (&SPEED_UNITS, Quantity::Speed),
+    (&VOLTAGE_UNITS, Quantity::Voltage),
+    (&CHARGE_UNITS, Quantity::Charge),
+    (&MASS_UNITS, Quantity::Mass),
+    (&FIELD_UNITS, Quantity::MagneticField),
+];
+
+// Scale of `unit` in `units`, if it is one of them.
+fn scale(units: &[Unit], unit: &str) -> Option<f32> {
+    units.iter().find_map(|(prefix, base, scale)| {
+        let rest = unit.strip_prefix(prefix)?;
+        (rest.to_lowercase() == base.to_lowercase()).then_some(*scale)
+    })
+}
+
+// Reads a number with an optional unit, like "5 MeV", "2.5fm", "0.1 nm",
+// "0.05c", "2e" or "3 nm²". A bare number is taken as already in simulation
+// units and handed to `bare`, e.g. `Quantity::Length`. Where a unit means
+// two things, as "c" does for light speed and coulombs, the kind `bare`
+// makes wins.
+pub fn parse_quantity(text: &str, bare: fn(f32) -> Quantity) -> Option<Quantity> {
+    let text = text.trim();
+    // The longest prefix that reads as a number, so "5eV" isn't "5e" and "V"
+    let (number, unit) = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([text.len()])
+        .rev()
+        .find_map(|end| {
+            let number = text[..end].trim().parse::<f32>().ok()?;
+            Some((number, text[end..].trim()))
+        })?;
+    if !number.is_finite() {
+        return None;
+    }
+    if unit.is_empty() {
+        return Some(bare(number));
+    }
+    let expected = discriminant(&bare(number));
+    KINDS
+        .iter()
+        .filter(|(_, kind)| discriminant(&kind(number)) == expected)
+        .chain(&KINDS)
+        .find_map(|(units, kind)| scale(units, unit).map(|scale| kind(number * scale)))
+        .or_else(|| {
+            // A length unit squared, as "nm²" or "fm2"
+            let length = unit.strip_suffix('²').or_else(|| unit.strip_suffix('2'))?;
+            let scale = scale(&LENGTH_UNITS, length)?;
+            Some(Quantity::Area(number * scale * scale))
+        })
+}
+
+// Reads a quantity that may be followed by the name of a particle, like
+// "5 MeV alpha" or "0.1c proton".
+pub fn parse_beam(text: &str, bare: fn(f32) -> Quantity) -> Option<(Quantity, Option<Particle>)> {
+    let text = text.trim();
+    if let Some((rest, name)) = text.rsplit_once(char::is_whitespace) {
+        let particle =
+            Particle::from_code(name).or_else(|| Particle::from_code(&name.to_lowercase()));
+        if let Some(particle) = particle {
+            return Some((parse_quantity(rest, bare)?, Some(particle)));
+        }
+    }
+    Some((parse_quantity(text, bare)?, None))
+}
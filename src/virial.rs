@@ -0,0 +1,102 @@
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::statistics::Statistics;
+
+use crate::dock;
+use crate::gui;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Averages over fewer ticks than this are too noisy to judge
+const MIN_SAMPLES: u32 = 600;
+const TOLERANCE: f32 = 0.05;
+
+// Running time averages of total kinetic and potential energy. For a bound
+// inverse-square system in equilibrium 2<T> = -<U>, so the ratio tends to 1.
+pub struct VirialMonitor {
+    pub visible: bool,
+    kinetic: f64,
+    potential: f64,
+    samples: u32,
+    // Averages restart whenever particles are added or removed
+    particles: usize,
+}
+
+impl VirialMonitor {
+    pub fn new() -> VirialMonitor {
+        VirialMonitor {
+            visible: false,
+            kinetic: 0.,
+            potential: 0.,
+            samples: 0,
+            particles: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.kinetic = 0.;
+        self.potential = 0.;
+        self.samples = 0;
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, statistics: &Statistics) {
+        if !self.visible {
+            return;
+        }
+        if statistics.particles != self.particles {
+            self.particles = statistics.particles;
+            self.reset();
+        }
+        self.kinetic += statistics.kinetic as f64;
+        self.potential += statistics.potential as f64;
+        self.samples += 1;
+    }
+
+    fn ratio(&self) -> Option<f32> {
+        let potential = self.potential / self.samples as f64;
+        if self.samples == 0 || potential == 0. {
+            return None;
+        }
+        let kinetic = self.kinetic / self.samples as f64;
+        Some((2. * kinetic / potential.abs()) as f32)
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (width, height) = (theme.scaled(340.), theme.scaled(110.));
+        let x = theme.scaled(690.);
+        let y = screen_height() - height - theme.scaled(220.);
+        let n = self.samples.max(1) as f64;
+        let averages = trf(
+            "virial.averages",
+            &[
+                &format!("{:.3e}", self.kinetic / n),
+                &format!("{:.3e}", self.potential / n),
+            ],
+        );
+        let ratio = self.ratio();
+        let samples = self.samples;
+        dock::egui_window("virial.title", Rect::new(x, y, width, height))
+            .open(&mut self.visible)
+            .show(ctx, |ui| {
+                ui.label(averages);
+                let Some(ratio) = ratio else {
+                    ui.label(tr("virial.undefined"));
+                    return;
+                };
+                ui.label(trf("virial.ratio", &[&format!("{:.3}", ratio), &samples]));
+                let (verdict, color) = if samples < MIN_SAMPLES {
+                    (tr("virial.collecting"), theme.text)
+                } else if (ratio - 1.).abs() <= TOLERANCE {
+                    (tr("virial.virialized"), theme.selection)
+                } else {
+                    (tr("virial.not_virialized"), theme.warning)
+                };
+                ui.colored_label(gui::color(color), verdict);
+            });
+    }
+}
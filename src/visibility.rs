@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::particle::{Particle, PARTICLES};
+use rutherford_atomic_model::species;
+use rutherford_atomic_model::world::World;
+
+use crate::dock;
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+// Species left out of the picture: their particles and trails are not drawn
+// and cannot be picked, but they stay in the world and keep pushing and
+// pulling everything else. Hiding the nuclei of a foil leaves just the
+// alpha tracks.
+pub struct SpeciesVisibility {
+    pub visible: bool,
+    hidden: HashSet<Particle>,
+}
+
+impl SpeciesVisibility {
+    pub fn new() -> SpeciesVisibility {
+        SpeciesVisibility {
+            visible: false,
+            hidden: HashSet::new(),
+        }
+    }
+
+    pub fn shows(&self, particle: Particle) -> bool {
+        !self.hidden.contains(&particle)
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, world: &World, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        // Built-in species first, then custom ones in registry order
+        let custom = (0..species::all().len() as u16).map(Particle::Custom);
+        let rows: Vec<(Particle, usize)> = PARTICLES
+            .into_iter()
+            .chain(custom)
+            .map(|particle| {
+                let count = world
+                    .particles
+                    .particle
+                    .iter()
+                    .filter(|p| **p == particle)
+                    .count();
+                (particle, count)
+            })
+            .collect();
+        let hidden = &mut self.hidden;
+        let (width, height) = (theme.scaled(260.), theme.scaled(300.));
+        let default = Rect::new(theme.scaled(380.), theme.scaled(120.), width, height);
+        dock::egui_window("visibility.title", default)
+            .open(&mut self.visible)
+            .show(ctx, |ui| {
+                for (particle, count) in rows {
+                    let mut shown = !hidden.contains(&particle);
+                    let label = trf("visibility.species", &[&particle.name(), &count]);
+                    if ui.checkbox(&mut shown, label).changed() {
+                        if shown {
+                            hidden.remove(&particle);
+                        } else {
+                            hidden.insert(particle);
+                        }
+                    }
+                }
+                ui.separator();
+                ui.label(tr("visibility.note"));
+                if !hidden.is_empty() && ui.button(tr("visibility.show_all")).clicked() {
+                    hidden.clear();
+                }
+            });
+    }
+}
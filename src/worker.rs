@@ -0,0 +1,140 @@
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rutherford_atomic_model::sanity;
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::world::World;
+
+// Matches the foreground rate of one tick per frame at 60 FPS
+const TICK_INTERVAL: Duration = Duration::from_micros(16_667);
+
+enum Command {
+    // Take over the app's copy after the user edited it
+    Replace(Box<World>, Option<usize>),
+    Pause(bool),
+    Stop,
+}
+
+// Runs the simulation on its own thread. The newest state is published into
+// a single slot that the render loop empties, so the thread only pays for a
+// copy once the previous one has been picked up, and a slow frame never
+// holds up the physics.
+pub struct Worker {
+    pub unthrottled: bool,
+    commands: Sender<Command>,
+    latest: Arc<Mutex<Option<World>>>,
+    handle: JoinHandle<World>,
+}
+
+impl Worker {
+    pub fn start(world: World, trail_limit: Option<usize>, unthrottled: bool) -> Worker {
+        let (commands, receiver) = channel();
+        let latest = Arc::new(Mutex::new(None));
+        let slot = Arc::clone(&latest);
+        let handle = thread::spawn(move || run(world, trail_limit, unthrottled, receiver, slot));
+        log::info!(
+            "physics moved to background thread{}",
+            if unthrottled { " (unthrottled)" } else { "" }
+        );
+        Worker {
+            unthrottled,
+            commands,
+            latest,
+            handle,
+        }
+    }
+
+    // Newest published state, if one arrived since the last call.
+    pub fn latest(&self) -> Option<World> {
+        self.latest.lock().ok()?.take()
+    }
+
+    pub fn replace(&self, world: World, trail_limit: Option<usize>) {
+        self.latest.lock().map(|mut slot| slot.take()).ok();
+        self.commands
+            .send(Command::Replace(Box::new(world), trail_limit))
+            .ok();
+    }
+
+    // The thread only returns when told to stop, which consumes the worker,
+    // so one that has finished while still held must have panicked.
+    pub fn crashed(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.commands.send(Command::Pause(paused)).ok();
+    }
+
+    // Stops the thread and hands back its world.
+    pub fn stop(self) -> Option<World> {
+        self.commands.send(Command::Stop).ok();
+        let world = self.handle.join().ok();
+        log::info!("physics back on the render thread");
+        world
+    }
+}
+
+fn run(
+    mut world: World,
+    mut trail_limit: Option<usize>,
+    unthrottled: bool,
+    commands: Receiver<Command>,
+    latest: Arc<Mutex<Option<World>>>,
+) -> World {
+    let mut next_tick = Instant::now();
+    // Set when the sanity check fails; the thread then waits for the app to
+    // resolve the problem and send back a repaired world
+    let mut halted = false;
+    let mut paused = false;
+    loop {
+        let command = if halted || paused {
+            commands.recv().map_err(|_| TryRecvError::Disconnected)
+        } else {
+            commands.try_recv()
+        };
+        match command {
+            Ok(Command::Replace(replacement, limit)) => {
+                world = *replacement;
+                trail_limit = limit;
+                halted = false;
+            }
+            Ok(Command::Pause(pause)) => {
+                paused = pause;
+                next_tick = Instant::now();
+            }
+            Ok(Command::Stop) | Err(TryRecvError::Disconnected) => return world,
+            Err(TryRecvError::Empty) => {}
+        }
+        if halted || paused {
+            continue;
+        }
+
+        systems::step(&mut world);
+        systems::record_trails(&mut world, trail_limit);
+        halted = !sanity::check(&world).is_empty();
+
+        if let Ok(mut slot) = latest.lock() {
+            if slot.is_none() || halted {
+                // Events travel with the snapshot so none are reported twice;
+                // a halt overwrites an unread one, so carry its events over
+                let mut snapshot = world.clone();
+                if let Some(unread) = slot.take() {
+                    snapshot.events.splice(0..0, unread.events);
+                }
+                *slot = Some(snapshot);
+                world.events.clear();
+            }
+        }
+
+        if !unthrottled {
+            next_tick += TICK_INTERVAL;
+            match next_tick.checked_duration_since(Instant::now()) {
+                Some(wait) => thread::sleep(wait),
+                None => next_tick = Instant::now(),
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
+
+use macroquad::prelude::*;
+
+use crate::annihilation::Flash;
+use crate::bubble::TimeBubble;
+use crate::detector::Detector;
+use crate::ecs::{Components, Entities, Entity};
+use crate::events::{Event, EventTracker};
+use crate::foil::{Foil, Interaction};
+use crate::ghost::Ghost;
+use crate::group::{kinetic_energy, Group, GroupId, GroupStats};
+use crate::magnets::Magnet;
+use crate::medium::Medium;
+use crate::particle::{gen_random_vector, Particle, Particles, Trail};
+use crate::rng::Rng;
+use crate::sources::Source;
+use crate::systems::{ForceModel, Integrator, InteractionMatrix, Substepping};
+
+#[derive(Clone, Default, Debug)]
+pub struct World {
+    entities: Entities,
+    pub particles: Particles,
+    pub trails: Components<Trail>,
+    pub groups: Vec<Group>,
+    pub membership: Components<GroupId>,
+    // Filled by the simulation and drained by the app once per frame
+    pub events: Vec<Event>,
+    pub tracker: EventTracker,
+    pub integrator: Integrator,
+    pub substepping: Substepping,
+    pub pair_forces: InteractionMatrix,
+    pub force_model: ForceModel,
+    // Sub-steps the stiffest particle took in the last tick
+    pub last_substeps: u32,
+    pub detector: Option<Detector>,
+    pub foils: Vec<Foil>,
+    // Foil each alpha interacted with, filled by `foil::tag_interactions`
+    pub interactions: Components<Interaction>,
+    pub flashes: Vec<Flash>,
+    // Pairs annihilated since the world was created
+    pub annihilations: u64,
+    // Deterministic mode: randomness inside the simulation is drawn from this
+    // instead of macroquad's global generator, so the same scene and seed
+    // give the same trajectories on every machine
+    pub rng: Option<Rng>,
+    // Half the side of a cube around the origin whose walls reflect particles,
+    // or None for open space
+    pub bounds: Option<f32>,
+    // 2D mode: every particle stays in the z = 0 plane
+    pub planar: bool,
+    pub ghosts: Vec<Ghost>,
+    pub bubbles: Vec<TimeBubble>,
+    // Fixed charge distributions acting on every particle
+    pub sources: Vec<Source>,
+    pub magnets: Vec<Magnet>,
+    pub medium: Medium,
+}
+
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        self.entities.spawn()
+    }
+
+    pub fn spawn_particle(&mut self, particle: Particle, pos: Option<Vec3>) -> Entity {
+        let pos = match pos {
+            Some(vec) => vec,
+            None => self.random_vector(-10., 10.),
+        };
+        let pos = self.constrain(pos);
+
+        let entity = self.spawn();
+        self.particles.insert(entity, particle, pos);
+        self.trails.insert(entity, Trail::new(pos));
+        self.events.push(Event::Spawned(entity));
+        log::debug!(
+            "spawned {:?} at {}; {} particles",
+            particle,
+            pos,
+            self.particle_count()
+        );
+        entity
+    }
+
+    // Each component uniform in `start..end`, from `rng` when it is set.
+    pub fn random_vector(&mut self, start: f32, end: f32) -> Vec3 {
+        match &mut self.rng {
+            Some(rng) => vec3(
+                rng.range(start, end),
+                rng.range(start, end),
+                rng.range(start, end),
+            ),
+            None => gen_random_vector(start, end),
+        }
+    }
+
+    // `v` projected into the plane particles are held to, if any.
+    pub fn constrain(&self, v: Vec3) -> Vec3 {
+        if self.planar {
+            vec3(v.x, v.y, 0.)
+        } else {
+            v
+        }
+    }
+
+    // Turns 2D mode on or off, flattening everything into the plane when it
+    // goes on.
+    pub fn set_planar(&mut self, planar: bool) {
+        self.planar = planar;
+        if planar {
+            let particles = &mut self.particles;
+            for ((pos, vel), acc) in particles
+                .pos
+                .iter_mut()
+                .zip(particles.vel.iter_mut())
+                .zip(particles.acc.iter_mut())
+            {
+                pos.z = 0.;
+                vel.z = 0.;
+                acc.z = 0.;
+            }
+        }
+    }
+
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.entities.despawn(entity) {
+            return false;
+        }
+        self.particles.remove(entity);
+        self.trails.remove(entity);
+        self.membership.remove(entity);
+        self.interactions.remove(entity);
+        for foil in &mut self.foils {
+            foil.nuclei.retain(|nucleus| *nucleus != entity);
+        }
+        true
+    }
+
+    // Removes every particle, and with them the foils they made up and any
+    // ghosts. Groups, the detector and the simulation settings stay.
+    pub fn clear_particles(&mut self) -> usize {
+        let entities = self.particles.entities().to_vec();
+        for entity in &entities {
+            self.despawn(*entity);
+        }
+        self.foils.clear();
+        self.flashes.clear();
+        self.ghosts.clear();
+        entities.len()
+    }
+
+    // Copies of `entities` moved by `offset`, keeping their velocities and
+    // groups. Returns the copies in the same order; entities that no longer
+    // exist are skipped.
+    pub fn duplicate(&mut self, entities: &[Entity], offset: Vec3) -> Vec<Entity> {
+        self.copy(entities, |pos, vel| (pos + offset, vel))
+    }
+
+    // Mirror images of `entities` across the plane through `point` with unit
+    // `normal`, their velocities reflected with them.
+    pub fn mirror(&mut self, entities: &[Entity], point: Vec3, normal: Vec3) -> Vec<Entity> {
+        let reflect = |v: Vec3| v - 2. * v.dot(normal) * normal;
+        self.copy(entities, |pos, vel| {
+            (point + reflect(pos - point), reflect(vel))
+        })
+    }
+
+    // Copies of `entities` filling a lattice of `counts` cells `spacing`
+    // apart along each axis, with the originals in the corner cell.
+    pub fn lattice(&mut self, entities: &[Entity], counts: [u32; 3], spacing: Vec3) -> Vec<Entity> {
+        let mut copies = Vec::new();
+        for i in 0..counts[0] {
+            for j in 0..counts[1] {
+                for k in 0..counts[2] {
+                    if (i, j, k) != (0, 0, 0) {
+                        let offset = vec3(i as f32, j as f32, k as f32) * spacing;
+                        copies.extend(self.duplicate(entities, offset));
+                    }
+                }
+            }
+        }
+        copies
+    }
+
+    // Copies of `entities` with positions and velocities mapped by `place`,
+    // each the same species, charge and mass and in the same group.
+    fn copy(
+        &mut self,
+        entities: &[Entity],
+        place: impl Fn(Vec3, Vec3) -> (Vec3, Vec3),
+    ) -> Vec<Entity> {
+        let mut copies = Vec::with_capacity(entities.len());
+        for entity in entities {
+            let Some(slot) = self.particles.slot(*entity) else {
+                continue;
+            };
+            let particles = &self.particles;
+            let particle = particles.particle[slot];
+            let (pos, vel) = place(particles.pos[slot], particles.vel[slot]);
+            let (mass, charge) = (particles.mass[slot], particles.charge[slot]);
+            let group = self.membership.get(*entity).copied();
+            let copy = self.spawn_particle(particle, Some(pos));
+            if let Some(copy_slot) = self.particles.slot(copy) {
+                self.particles.vel[copy_slot] = self.constrain(vel);
+                self.particles.mass[copy_slot] = mass;
+                self.particles.charge[copy_slot] = charge;
+            }
+            self.set_group(copy, group);
+            copies.push(copy);
+        }
+        copies
+    }
+
+    // Every particle in group `id`.
+    pub fn members(&self, id: GroupId) -> Vec<Entity> {
+        self.membership
+            .iter()
+            .filter(|(_, group)| **group == id)
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    // A hash of everything the user can edit: the particles and their state,
+    // groups and membership, and every run setting and scene object. Two
+    // worlds with different fingerprints differ; one taken before and after
+    // a frame's UI tells whether the UI changed the world. Trails, events
+    // and the rest of what only the simulation writes are left out.
+    pub fn fingerprint(&self) -> u64 {
+        // Feeds formatted text straight into the hasher
+        struct Feed<'a>(&'a mut DefaultHasher);
+        impl Write for Feed<'_> {
+            fn write_str(&mut self, text: &str) -> fmt::Result {
+                self.0.write(text.as_bytes());
+                Ok(())
+            }
+        }
+
+        let mut hasher = DefaultHasher::new();
+        let particles = &self.particles;
+        particles.entities().hash(&mut hasher);
+        particles.particle.hash(&mut hasher);
+        let columns = [&particles.pos, &particles.vel];
+        for v in columns.into_iter().flatten() {
+            v.to_array().map(f32::to_bits).hash(&mut hasher);
+        }
+        for x in particles.mass.iter().chain(&particles.charge) {
+            x.to_bits().hash(&mut hasher);
+        }
+        for (entity, group) in self.membership.iter() {
+            (entity, group).hash(&mut hasher);
+        }
+        write!(
+            Feed(&mut hasher),
+            "{:?} {:?}",
+            (
+                &self.groups,
+                &self.integrator,
+                &self.substepping,
+                &self.pair_forces,
+                &self.force_model,
+                &self.detector,
+                &self.foils,
+            ),
+            (
+                &self.rng,
+                &self.bounds,
+                &self.planar,
+                &self.ghosts,
+                &self.bubbles,
+                &self.sources,
+                &self.magnets,
+                &self.medium,
+            )
+        )
+        .ok();
+        hasher.finish()
+    }
+
+    // Centroid of all particles and the distance to the farthest one.
+    pub fn bounding_sphere(&self) -> Option<(Vec3, f32)> {
+        let pos = &self.particles.pos;
+        if pos.is_empty() {
+            return None;
+        }
+        let center = pos.iter().sum::<Vec3>() / pos.len() as f32;
+        let radius = pos.iter().map(|p| p.distance(center)).fold(0., f32::max);
+        Some((center, radius))
+    }
+
+    pub fn add_group(&mut self, name: &str) -> GroupId {
+        if let Some(id) = self.find_group(name) {
+            return id;
+        }
+        self.groups.push(Group {
+            name: name.to_string(),
+            color: None,
+        });
+        GroupId(self.groups.len() - 1)
+    }
+
+    pub fn find_group(&self, name: &str) -> Option<GroupId> {
+        self.groups
+            .iter()
+            .position(|group| group.name == name)
+            .map(GroupId)
+    }
+
+    pub fn set_group(&mut self, entity: Entity, group: Option<GroupId>) {
+        match group {
+            Some(id) => self.membership.insert(entity, id),
+            None => {
+                self.membership.remove(entity);
+            }
+        }
+    }
+
+    pub fn group_color(&self, entity: Entity) -> Option<Color> {
+        let id = self.membership.get(entity)?;
+        self.groups[id.0].color
+    }
+
+    pub fn group_stats(&self) -> Vec<GroupStats> {
+        let mut stats: Vec<GroupStats> = (0..self.groups.len())
+            .map(|i| GroupStats {
+                group: GroupId(i),
+                count: 0,
+                mean_kinetic_energy: 0.,
+            })
+            .collect();
+
+        for (entity, id) in self.membership.iter() {
+            let Some(slot) = self.particles.slot(entity) else {
+                continue;
+            };
+            let entry = &mut stats[id.0];
+            entry.count += 1;
+            entry.mean_kinetic_energy +=
+                kinetic_energy(self.particles.mass[slot], self.particles.vel[slot]);
+        }
+
+        for entry in &mut stats {
+            if entry.count > 0 {
+                entry.mean_kinetic_energy /= entry.count as f32;
+            }
+        }
+        stats
+    }
+}
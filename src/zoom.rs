@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::foil::INTERACTION_RADIUS;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::species;
+use rutherford_atomic_model::world::World;
+
+use crate::i18n::{tr, trf};
+use crate::theme::Theme;
+
+const ZOOM_SIZE: u32 = 256;
+const MARGIN: f32 = 10.;
+// A pair closer than this is recorded, and an encounter ends when it parts
+// beyond it again
+const CAPTURE_RADIUS: f32 = 3. * INTERACTION_RADIUS;
+// Only encounters reaching this close are replayed
+const CLOSE_DISTANCE: f32 = INTERACTION_RADIUS;
+// A pair still together after this many ticks is bound rather than passing,
+// and is dropped until it parts
+const MAX_FRAMES: usize = 600;
+// Replay speed is one tick every this many frames
+const SLOWDOWN: f32 = 8.;
+// Frames the last position stays up after the replay
+const HOLD: f32 = 90.;
+
+struct Pair {
+    entities: (Entity, Entity),
+    species: (Particle, Particle),
+    masses: (f32, f32),
+    // Positions of both, one entry per tick
+    frames: Vec<(Vec3, Vec3)>,
+    closest: f32,
+}
+
+struct Replay {
+    pair: Pair,
+    // Fractional index into the frames
+    time: f32,
+    // Normal of the encounter plane, which the camera looks down
+    normal: Vec3,
+    extent: f32,
+}
+
+// Watches for close two-particle encounters and replays each one in slow
+// motion in a zoomed view that follows the pair's center of mass. With a
+// particle selected, only its own encounters are picked up.
+pub struct EncounterZoom {
+    pub enabled: bool,
+    tracking: Option<Pair>,
+    replay: Option<Replay>,
+    // Bound pairs, ignored until they part
+    ignored: HashSet<(Entity, Entity)>,
+    target: RenderTarget,
+}
+
+impl EncounterZoom {
+    pub fn new() -> EncounterZoom {
+        let target = render_target(ZOOM_SIZE, ZOOM_SIZE);
+        target.texture.set_filter(FilterMode::Linear);
+        EncounterZoom {
+            enabled: false,
+            tracking: None,
+            replay: None,
+            ignored: HashSet::new(),
+            target,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.tracking = None;
+        self.replay = None;
+        self.ignored.clear();
+        let key = if self.enabled { "zoom.on" } else { "zoom.off" };
+        log::info!("{}", tr(key));
+    }
+
+    // Call once per simulation tick.
+    pub fn update(&mut self, world: &World, selected: Option<Entity>) {
+        if !self.enabled {
+            return;
+        }
+        let particles = &world.particles;
+        let position = |entity| particles.slot(entity).map(|slot| particles.pos[slot]);
+        self.ignored
+            .retain(|(a, b)| match (position(*a), position(*b)) {
+                (Some(a), Some(b)) => a.distance(b) <= CAPTURE_RADIUS,
+                _ => false,
+            });
+
+        if let Some(pair) = &mut self.tracking {
+            let (a, b) = pair.entities;
+            let (Some(pos_a), Some(pos_b)) = (position(a), position(b)) else {
+                self.tracking = None;
+                return;
+            };
+            let distance = pos_a.distance(pos_b);
+            pair.frames.push((pos_a, pos_b));
+            pair.closest = pair.closest.min(distance);
+            if pair.frames.len() > MAX_FRAMES {
+                self.ignored.insert(pair.entities);
+                self.tracking = None;
+            } else if distance > CAPTURE_RADIUS {
+                let pair = self.tracking.take().expect("tracking");
+                // Only a replay already running keeps a new one from starting
+                if pair.closest <= CLOSE_DISTANCE && self.replay.is_none() {
+                    self.replay = Some(Replay::new(pair));
+                }
+            }
+            return;
+        }
+
+        // The closest approaching pair in range starts the next encounter
+        let entities = particles.entities();
+        let mut best: Option<(usize, usize, f32)> = None;
+        for i in 0..particles.len() {
+            for j in (i + 1)..particles.len() {
+                if selected.is_some_and(|s| s != entities[i] && s != entities[j])
+                    || self.ignored.contains(&(entities[i], entities[j]))
+                {
+                    continue;
+                }
+                let r = particles.pos[j] - particles.pos[i];
+                let distance = r.length();
+                let approaching = r.dot(particles.vel[j] - particles.vel[i]) < 0.;
+                if distance <= CAPTURE_RADIUS
+                    && approaching
+                    && best.is_none_or(|(_, _, closest)| distance < closest)
+                {
+                    best = Some((i, j, distance));
+                }
+            }
+        }
+        if let Some((i, j, distance)) = best {
+            self.tracking = Some(Pair {
+                entities: (entities[i], entities[j]),
+                species: (particles.particle[i], particles.particle[j]),
+                masses: (particles.mass[i], particles.mass[j]),
+                frames: vec![(particles.pos[i], particles.pos[j])],
+                closest: distance,
+            });
+        }
+    }
+
+    // Call once per frame, whether or not the simulation ticked, so the
+    // replay runs at the same pace while paused.
+    pub fn advance(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        replay.time += 1. / SLOWDOWN;
+        if replay.time > (replay.pair.frames.len() - 1) as f32 + HOLD / SLOWDOWN {
+            self.replay = None;
+        }
+    }
+
+    pub fn render(&self, theme: &Theme) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        let (a, b) = replay.at(replay.time);
+        let (mass_a, mass_b) = replay.pair.masses;
+        let center = (a * mass_a + b * mass_b) / (mass_a + mass_b);
+        let (first_a, first_b) = replay.pair.frames[0];
+        let along = first_b - first_a;
+        let up = (along - replay.normal * along.dot(replay.normal))
+            .try_normalize()
+            .unwrap_or(Vec3::Y);
+        set_camera(&Camera3D {
+            position: center + replay.normal * replay.extent * 2.,
+            target: center,
+            up,
+            fovy: replay.extent,
+            projection: Projection::Orthographics,
+            aspect: Some(1.),
+            render_target: Some(self.target),
+            ..Default::default()
+        });
+        clear_background(theme.background);
+        let shown = (replay.time.floor() as usize + 1).min(replay.pair.frames.len());
+        for pair in replay.pair.frames[..shown].windows(2) {
+            draw_line_3d(pair[0].0, pair[1].0, theme.particle(replay.pair.species.0));
+            draw_line_3d(pair[0].1, pair[1].1, theme.particle(replay.pair.species.1));
+        }
+        for (pos, particle) in [(a, replay.pair.species.0), (b, replay.pair.species.1)] {
+            draw_sphere(
+                pos,
+                species::radius(particle),
+                None,
+                theme.particle(particle),
+            );
+        }
+        // Tick marking the center of mass the view follows
+        draw_line_3d(center - up * 2., center + up * 2., theme.text);
+        set_default_camera();
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        let size = theme.scaled(ZOOM_SIZE as f32);
+        let x = screen_width() - size - MARGIN;
+        let y = size + MARGIN * 2.;
+        draw_texture_ex(
+            self.target.texture,
+            x,
+            y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(size, size)),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        draw_rectangle_lines(x, y, size, size, theme.scaled(2.), theme.selection);
+        let text = theme.text_size(18.);
+        let (a, b) = replay.pair.species;
+        draw_text(
+            &trf("zoom.title", &[&a.name(), &b.name(), &SLOWDOWN]),
+            x + theme.scaled(4.),
+            y + text,
+            text,
+            theme.text,
+        );
+        draw_text(
+            &trf("zoom.closest", &[&format!("{:.2}", replay.pair.closest)]),
+            x + theme.scaled(4.),
+            y + size - theme.scaled(6.),
+            text,
+            theme.text,
+        );
+    }
+}
+
+impl Replay {
+    fn new(pair: Pair) -> Replay {
+        // The plane holds the approach and the departure, unless the pair
+        // went straight through
+        let (first_a, first_b) = pair.frames[0];
+        let (last_a, last_b) = pair.frames[pair.frames.len() - 1];
+        let normal = (first_b - first_a)
+            .cross(last_b - last_a)
+            .try_normalize()
+            .unwrap_or_else(|| (first_b - first_a).any_orthonormal_vector());
+        let extent =
+            (pair.closest * 4.).clamp(species::radius(pair.species.0) * 8., CAPTURE_RADIUS);
+        Replay {
+            pair,
+            time: 0.,
+            normal,
+            extent,
+        }
+    }
+
+    // Positions at fractional frame `time`, held at the last frame
+    fn at(&self, time: f32) -> (Vec3, Vec3) {
+        let frames = &self.pair.frames;
+        let index = (time.floor() as usize).min(frames.len() - 1);
+        let next = (index + 1).min(frames.len() - 1);
+        let t = (time - index as f32).clamp(0., 1.);
+        (
+            frames[index].0.lerp(frames[next].0, t),
+            frames[index].1.lerp(frames[next].1, t),
+        )
+    }
+}
@@ -0,0 +1,54 @@
+// The pairwise force is Coulomb's law: inverse-square in the distance and
+// directed along the line between the two charges, whichever way that line
+// points relative to the axes.
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::particle::{get_charge, get_mass, Particle};
+use rutherford_atomic_model::systems::{self, COULOMB_K};
+use rutherford_atomic_model::world::World;
+
+// Acceleration of a proton at `offset` from another at the origin
+fn acceleration(offset: Vec3) -> Vec3 {
+    let mut world = World::new();
+    world.spawn_particle(Particle::Proton, Some(Vec3::ZERO));
+    let entity = world.spawn_particle(Particle::Proton, Some(offset));
+    systems::apply_forces(&mut world);
+    world.particles.acc[world.particles.slot(entity).unwrap()]
+}
+
+#[test]
+fn force_follows_coulombs_law() {
+    let (charge, mass) = (get_charge(Particle::Proton), get_mass(Particle::Proton));
+    for offset in [
+        vec3(10., 0., 0.),
+        vec3(3., 4., 0.),
+        vec3(-2., 5., 7.),
+        vec3(0.5, -0.5, 40.),
+    ] {
+        let acc = acceleration(offset);
+        let distance = offset.length();
+        let expected = COULOMB_K * charge * charge / (distance * distance * mass);
+        assert!(
+            (acc.length() - expected).abs() < 1e-4 * expected,
+            "{} at {}, expected a magnitude of {}",
+            acc,
+            offset,
+            expected
+        );
+        assert!(
+            acc.normalize().distance(offset.normalize()) < 1e-5,
+            "{} is not along {}",
+            acc,
+            offset
+        );
+    }
+}
+
+#[test]
+fn force_quarters_when_the_distance_doubles() {
+    let offset = vec3(1., 2., -3.);
+    let near = acceleration(offset).length();
+    let far = acceleration(offset * 2.).length();
+    assert!((near / far - 4.).abs() < 1e-4, "ratio {}", near / far);
+}
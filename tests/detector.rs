@@ -0,0 +1,74 @@
+// The angular fit against hits laid out exactly as Rutherford's formula
+// predicts: it has to find the 1/sin^4(θ/2) law through the solid angle the
+// detector bins cover.
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::detector::{self, Detector, DetectorShape, Hit};
+
+// Steps each bin is cut into when laying out hits
+const STEPS: usize = 200;
+
+// A sphere detector with hits spread over the directions around the target
+// in proportion to Rutherford's cross-section. The forward bin, where it
+// diverges and which the fit leaves out, gets none.
+fn rutherford_hits(hits_per_steradian: f32) -> Detector {
+    let mut detector = Detector::new(DetectorShape::Sphere);
+    let step = detector.bin_width() / STEPS as f32;
+    let mut owed = 0.;
+    for i in STEPS..detector.bins * STEPS {
+        let angle = (i as f32 + 0.5) * step;
+        // Solid angle of the ring at `angle`, times the cross-section there
+        owed += hits_per_steradian
+            * std::f32::consts::TAU
+            * angle.sin()
+            * step
+            * detector::rutherford(angle);
+        while owed >= 1. {
+            owed -= 1.;
+            detector.hits.push(Hit {
+                entity: None,
+                pos: vec3(angle.cos(), angle.sin(), 0.) * detector.distance,
+                tick: 0,
+            });
+        }
+    }
+    detector
+}
+
+#[test]
+fn fitted_exponent_is_four_for_rutherford_hits() {
+    let detector = rutherford_hits(20.);
+    let counts = detector.angular_counts();
+    assert!(
+        counts.iter().skip(1).all(|count| *count > 0),
+        "every bin should see hits: {:?}",
+        counts
+    );
+    let fit = detector::fit(&detector);
+    let exponent = fit.exponent.unwrap();
+    assert!(
+        (exponent - 4.).abs() < 0.1,
+        "fitted exponent {} for counts {:?}",
+        exponent,
+        counts
+    );
+}
+
+#[test]
+fn fitted_curve_follows_rutherford_hits() {
+    let detector = rutherford_hits(20.);
+    let fit = detector::fit(&detector);
+    let counts = detector.angular_counts();
+    for (bin, count) in counts.iter().enumerate().skip(1) {
+        let predicted = fit.expected[bin] * fit.scale;
+        let count = *count as f32;
+        assert!(
+            (count - predicted).abs() < 0.05 * count + 2.,
+            "bin {}: {} hits, fitted curve gives {}",
+            bin,
+            count,
+            predicted
+        );
+    }
+}
@@ -0,0 +1,103 @@
+// The C interface has to turn every bad argument a caller can pass into its
+// documented error value instead of crashing the host program. Run with
+// `cargo test --features ffi`.
+#![cfg(feature = "ffi")]
+
+use std::ffi::CString;
+use std::ptr;
+
+use rutherford_atomic_model::ffi::*;
+
+fn spawn(sim: *mut Simulation, species: &str, x: f32) -> i64 {
+    let species = CString::new(species).unwrap();
+    unsafe { rutherford_sim_spawn(sim, species.as_ptr(), x, 0., 0.) }
+}
+
+#[test]
+fn null_handles_are_refused() {
+    let species = CString::new("alpha").unwrap();
+    let mut out = [0.; 3];
+    unsafe {
+        assert_eq!(
+            rutherford_sim_spawn(ptr::null_mut(), species.as_ptr(), 0., 0., 0.),
+            -1
+        );
+        assert_eq!(rutherford_sim_step(ptr::null_mut(), 10), 0);
+        assert_eq!(rutherford_sim_particle_count(ptr::null()), 0);
+        assert_eq!(
+            rutherford_sim_get_positions(ptr::null(), out.as_mut_ptr(), 1),
+            0
+        );
+        assert_eq!(
+            rutherford_sim_get_velocities(ptr::null(), out.as_mut_ptr(), 1),
+            0
+        );
+        assert_eq!(rutherford_sim_clear(ptr::null_mut()), 0);
+        rutherford_sim_free(ptr::null_mut());
+        assert!(rutherford_sim_from_scenario(ptr::null()).is_null());
+    }
+}
+
+#[test]
+fn null_species_and_outputs_are_refused() {
+    let sim = rutherford_sim_new();
+    assert_eq!(spawn(sim, "alpha", 1.), 0);
+    unsafe {
+        assert_eq!(rutherford_sim_spawn(sim, ptr::null(), 0., 0., 0.), -1);
+        assert_eq!(rutherford_sim_particle_count(sim), 1);
+        assert_eq!(rutherford_sim_get_positions(sim, ptr::null_mut(), 1), 0);
+        assert_eq!(rutherford_sim_get_velocities(sim, ptr::null_mut(), 1), 0);
+        rutherford_sim_free(sim);
+    }
+}
+
+#[test]
+fn unknown_species_are_refused() {
+    let sim = rutherford_sim_new();
+    assert_eq!(spawn(sim, "unobtainium", 0.), -1);
+    assert_eq!(spawn(sim, "", 0.), -1);
+    unsafe {
+        assert_eq!(rutherford_sim_particle_count(sim), 0);
+        rutherford_sim_free(sim);
+    }
+}
+
+#[test]
+fn copies_stop_at_the_capacity() {
+    let sim = rutherford_sim_new();
+    for i in 0..3 {
+        assert_eq!(spawn(sim, "electron", i as f32 + 1.), i);
+    }
+    // A sentinel past the room given must be left alone
+    let mut out = [f32::NAN; 7];
+    unsafe {
+        assert_eq!(rutherford_sim_get_positions(sim, out.as_mut_ptr(), 2), 2);
+        assert_eq!(out[..6], [1., 0., 0., 2., 0., 0.]);
+        assert!(out[6].is_nan());
+        assert_eq!(rutherford_sim_get_positions(sim, out.as_mut_ptr(), 0), 0);
+        assert_eq!(rutherford_sim_clear(sim), 3);
+        assert_eq!(rutherford_sim_particle_count(sim), 0);
+        rutherford_sim_free(sim);
+    }
+}
+
+#[test]
+fn bad_scenarios_give_null() {
+    for text in [
+        "not a scene",
+        "particle = alpha 1 2",
+        "particle = photino 0 0 0 0 0 0",
+    ] {
+        let text = CString::new(text).unwrap();
+        assert!(unsafe { rutherford_sim_from_scenario(text.as_ptr()) }.is_null());
+    }
+    let text =
+        CString::new("name = Two\nparticle = alpha 0 0 0 0 0 0\nparticle = proton 5 0 0 0 0 0")
+            .unwrap();
+    unsafe {
+        let sim = rutherford_sim_from_scenario(text.as_ptr());
+        assert!(!sim.is_null());
+        assert_eq!(rutherford_sim_particle_count(sim), 2);
+        rutherford_sim_free(sim);
+    }
+}
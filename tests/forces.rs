@@ -0,0 +1,178 @@
+// Properties of the pairwise force, checked through `systems::apply_forces`
+// on random configurations: equal and opposite between any two charges,
+// along the line joining them, and scaling with the charges and distance as
+// the force law says.
+
+use macroquad::prelude::*;
+use proptest::prelude::*;
+
+use rutherford_atomic_model::particle::{get_charge, get_mass, Particle};
+use rutherford_atomic_model::systems::{self, ForceModel, FORCE_LAWS};
+use rutherford_atomic_model::world::World;
+
+// Relative error allowed between quantities computed along different paths
+const TOLERANCE: f32 = 1e-4;
+
+fn position() -> impl Strategy<Value = Vec3> {
+    prop::array::uniform3(-100f32..100.).prop_map(Vec3::from)
+}
+
+// A few elementary charges of either sign, never zero
+fn charge() -> impl Strategy<Value = f32> {
+    (1..=4, any::<bool>()).prop_map(|(n, negative)| {
+        let q = n as f32 * get_charge(Particle::Proton);
+        if negative {
+            -q
+        } else {
+            q
+        }
+    })
+}
+
+fn mass() -> impl Strategy<Value = f32> {
+    (0.5f32..10.).prop_map(|m| m * get_mass(Particle::Proton))
+}
+
+fn force_model() -> impl Strategy<Value = ForceModel> {
+    (
+        prop::sample::select(FORCE_LAWS.to_vec()),
+        prop::option::of(20f32..200.),
+        prop::option::of(0.5f32..5.),
+        0.1f32..3.,
+    )
+        .prop_map(|(law, screening, nuclear_radius, strength)| ForceModel {
+            screening,
+            nuclear_radius,
+            law,
+            strength,
+        })
+}
+
+// Forces on each of the given charges, as mass times acceleration
+fn forces(model: ForceModel, bodies: &[(Vec3, f32, f32)]) -> Vec<Vec3> {
+    let mut world = World::new();
+    world.force_model = model;
+    for (pos, charge, mass) in bodies {
+        let entity = world.spawn_particle(Particle::Proton, Some(*pos));
+        let slot = world.particles.slot(entity).unwrap();
+        world.particles.charge[slot] = *charge;
+        world.particles.mass[slot] = *mass;
+    }
+    systems::apply_forces(&mut world);
+    let particles = &world.particles;
+    particles
+        .acc
+        .iter()
+        .zip(&particles.mass)
+        .map(|(acc, mass)| *acc * *mass)
+        .collect()
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() <= TOLERANCE * a.abs().max(b.abs())
+}
+
+proptest! {
+    #[test]
+    fn pair_forces_are_equal_and_opposite(
+        model in force_model(),
+        a in (position(), charge(), mass()),
+        b in (position(), charge(), mass()),
+    ) {
+        prop_assume!(a.0.distance(b.0) > 1.);
+        let f = forces(model, &[a, b]);
+        let scale = f[0].length().max(f[1].length());
+        prop_assert!(
+            (f[0] + f[1]).length() <= TOLERANCE * scale,
+            "{} on a, {} on b",
+            f[0],
+            f[1]
+        );
+    }
+
+    #[test]
+    fn forces_cancel_over_many_bodies(
+        model in force_model(),
+        bodies in prop::collection::vec((position(), charge(), mass()), 3..8),
+    ) {
+        for (i, a) in bodies.iter().enumerate() {
+            for b in &bodies[i + 1..] {
+                prop_assume!(a.0.distance(b.0) > 1.);
+            }
+        }
+        let f = forces(model, &bodies);
+        let scale: f32 = f.iter().map(|f| f.length()).sum();
+        let total = f.iter().fold(Vec3::ZERO, |sum, f| sum + *f);
+        prop_assert!(total.length() <= TOLERANCE * scale, "net force {}", total);
+    }
+
+    #[test]
+    fn pair_force_lies_along_the_separation(
+        model in force_model(),
+        a in (position(), charge(), mass()),
+        b in (position(), charge(), mass()),
+    ) {
+        let separation = a.0 - b.0;
+        prop_assume!(separation.length() > 1.);
+        let f = forces(model, &[a, b])[0];
+        let along = f.dot(separation.normalize());
+        prop_assert!(
+            f.reject_from(separation).length() <= TOLERANCE * f.length(),
+            "{} off the separation {}",
+            f,
+            separation
+        );
+        // Like charges push apart, unlike ones pull together
+        prop_assert_eq!(along > 0., a.1 * b.1 > 0.);
+    }
+
+    #[test]
+    fn pair_force_scales_with_charge_product(
+        model in force_model(),
+        a in (position(), charge(), mass()),
+        b in (position(), charge(), mass()),
+        factor in -4f32..4.,
+    ) {
+        prop_assume!(a.0.distance(b.0) > 1. && factor.abs() > 0.1);
+        let before = forces(model, &[a, b])[1];
+        let scaled = (a.0, a.1 * factor, a.2);
+        let after = forces(model, &[scaled, b])[1];
+        let expected = before * factor;
+        prop_assert!(
+            (after - expected).length() <= TOLERANCE * expected.length(),
+            "{} with the charge times {}, expected {}",
+            after,
+            factor,
+            expected
+        );
+    }
+
+    #[test]
+    fn pair_force_falls_off_with_the_law(
+        law in prop::sample::select(FORCE_LAWS.to_vec()),
+        a in (position(), charge(), mass()),
+        b in (position(), charge(), mass()),
+        stretch in 0.25f32..4.,
+    ) {
+        let separation = a.0 - b.0;
+        prop_assume!(separation.length() > 1.);
+        // Screening and a finite nucleus change the shape on purpose, so
+        // only the bare law is checked
+        let model = ForceModel {
+            law,
+            ..ForceModel::default()
+        };
+        let near = forces(model, &[a, b])[0].length();
+        let moved = (b.0 + separation * stretch, a.1, a.2);
+        let far = forces(model, &[moved, b])[0].length();
+        let expected = near / stretch.powi(law.exponent() as i32);
+        prop_assert!(
+            close(far, expected),
+            "{:?}: {} at {} times the distance, expected {}",
+            law,
+            far,
+            stretch,
+            expected
+        );
+    }
+}
@@ -0,0 +1,128 @@
+// Golden trajectories: every preset scene run for a fixed number of ticks
+// with a fixed seed, its particles sampled at intervals and compared with the
+// states stored under `tests/golden`. A change to the force or integrator
+// code that moves any of them fails here rather than passing unnoticed.
+//
+// After a change that is meant to alter the physics, rewrite the files with
+//
+//     UPDATE_GOLDEN=1 cargo test --test golden
+//
+// and review the diff.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rutherford_atomic_model::particle::TRAIL_LENGTH;
+use rutherford_atomic_model::scenario::Scenario;
+use rutherford_atomic_model::systems;
+use rutherford_atomic_model::world::World;
+
+// Seed for scenes that do not give their own
+const SEED: u64 = 1;
+const TICKS: u64 = 1500;
+// Ticks between the samples written to the golden file
+const INTERVAL: u64 = 100;
+// Largest difference allowed in any component, relative to its size or to
+// one unit, whichever is bigger. Room for a compiler or platform rounding
+// differently, far short of any change to the physics.
+const TOLERANCE: f32 = 1e-4;
+
+// One line per particle per sample: the tick, the species, then position and
+// velocity
+fn sample(world: &World, tick: u64, out: &mut String) {
+    let particles = &world.particles;
+    for i in 0..particles.len() {
+        let (pos, vel) = (particles.pos[i], particles.vel[i]);
+        out.push_str(&format!(
+            "{} {} {} {} {} {} {} {}\n",
+            tick,
+            particles.particle[i].name(),
+            pos.x,
+            pos.y,
+            pos.z,
+            vel.x,
+            vel.y,
+            vel.z
+        ));
+    }
+}
+
+fn trajectory(scene: &Path) -> String {
+    let source = fs::read_to_string(scene).unwrap();
+    let mut scenario = Scenario::parse(&source).unwrap();
+    scenario.seed.get_or_insert(SEED);
+    let mut world = scenario.build();
+
+    let mut out = format!("# {} after every {} ticks\n", scenario.name, INTERVAL);
+    sample(&world, 0, &mut out);
+    for tick in (INTERVAL..=TICKS).step_by(INTERVAL as usize) {
+        let ran = systems::fast_forward(&mut world, INTERVAL, Some(TRAIL_LENGTH));
+        assert_eq!(ran, INTERVAL, "{} stopped being sane", scene.display());
+        sample(&world, tick, &mut out);
+    }
+    out
+}
+
+// Where the first difference between two trajectories is, if any
+fn compare(actual: &str, expected: &str) -> Option<String> {
+    let (actual, expected): (Vec<&str>, Vec<&str>) = (
+        actual.lines().filter(|l| !l.starts_with('#')).collect(),
+        expected.lines().filter(|l| !l.starts_with('#')).collect(),
+    );
+    if actual.len() != expected.len() {
+        return Some(format!(
+            "{} sampled states, expected {}",
+            actual.len(),
+            expected.len()
+        ));
+    }
+    for (a, e) in actual.iter().zip(&expected) {
+        let (a_fields, e_fields): (Vec<&str>, Vec<&str>) =
+            (a.split(' ').collect(), e.split(' ').collect());
+        if a_fields.len() != e_fields.len() || a_fields[..2] != e_fields[..2] {
+            return Some(format!("got `{}`, expected `{}`", a, e));
+        }
+        for (x, y) in a_fields[2..].iter().zip(&e_fields[2..]) {
+            let (x, y): (f32, f32) = (x.parse().unwrap(), y.parse().unwrap());
+            if (x - y).abs() > TOLERANCE * y.abs().max(1.) {
+                return Some(format!("got `{}`, expected `{}`", a, e));
+            }
+        }
+    }
+    None
+}
+
+fn golden(scene: &Path) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(scene.file_name().unwrap())
+}
+
+#[test]
+fn presets_follow_their_golden_trajectories() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let presets = Path::new(env!("CARGO_MANIFEST_DIR")).join("presets");
+    let mut scenes: Vec<PathBuf> = fs::read_dir(presets)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    scenes.sort();
+
+    let mut failures = Vec::new();
+    for scene in scenes {
+        let actual = trajectory(&scene);
+        let path = golden(&scene);
+        if update {
+            fs::write(&path, actual).unwrap();
+            continue;
+        }
+        let Ok(expected) = fs::read_to_string(&path) else {
+            failures.push(format!("{}: no golden file", path.display()));
+            continue;
+        };
+        if let Some(difference) = compare(&actual, &expected) {
+            failures.push(format!("{}: {}", scene.display(), difference));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,259 @@
+// Physics invariants of the simulation core, checked headless: what the
+// force and integrator code has to keep true whatever else changes.
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::particle::{get_charge, get_mass, Particle, TRAIL_LENGTH};
+use rutherford_atomic_model::rng::Rng;
+use rutherford_atomic_model::statistics::Statistics;
+use rutherford_atomic_model::systems::{self, Integrator, Substepping, COULOMB_K, INTEGRATORS};
+use rutherford_atomic_model::world::World;
+
+fn launch(world: &mut World, particle: Particle, pos: Vec3, vel: Vec3) -> Entity {
+    let entity = world.spawn_particle(particle, Some(pos));
+    let slot = world.particles.slot(entity).unwrap();
+    world.particles.vel[slot] = vel;
+    entity
+}
+
+fn run(world: &mut World, ticks: u64) {
+    assert_eq!(
+        systems::fast_forward(world, ticks, Some(TRAIL_LENGTH)),
+        ticks,
+        "the simulation stopped being sane"
+    );
+}
+
+// Sum of the magnitudes of the particles' momenta, which the change in the
+// total is measured against so the bound does not depend on the units
+fn momentum_scale(world: &World) -> f32 {
+    let particles = &world.particles;
+    particles
+        .vel
+        .iter()
+        .zip(&particles.mass)
+        .map(|(vel, mass)| vel.length() * mass)
+        .sum()
+}
+
+#[test]
+fn momentum_is_conserved_in_isolated_systems() {
+    for integrator in INTEGRATORS {
+        let mut world = World::new();
+        world.integrator = integrator;
+        world.rng = Some(Rng::new(7));
+        for i in 0..8 {
+            let particle = if i % 2 == 0 {
+                Particle::Proton
+            } else {
+                Particle::Alpha
+            };
+            let pos = world.random_vector(-60., 60.);
+            let vel = world.random_vector(-0.05, 0.05);
+            launch(&mut world, particle, pos, vel);
+        }
+        let scale = momentum_scale(&world);
+        let before = Statistics::gather(&world).momentum / scale;
+        run(&mut world, 2000);
+        let after = Statistics::gather(&world).momentum / scale;
+        assert!(
+            before.distance(after) < 1e-4,
+            "{:?}: momentum went from {} to {}",
+            integrator,
+            before,
+            after
+        );
+    }
+}
+
+#[test]
+fn mirrored_pairs_stay_mirrored() {
+    let pairs = [
+        // Repelling, passing each other
+        (Particle::Proton, vec3(20., 3., 0.), vec3(-0.06, 0., 0.)),
+        // Head on, turning back
+        (Particle::Alpha, vec3(40., 0., 0.), vec3(-0.08, 0., 0.)),
+        // Out of the plane
+        (Particle::Proton, vec3(15., -5., 8.), vec3(0., 0.04, -0.03)),
+    ];
+    for integrator in INTEGRATORS {
+        for (particle, pos, vel) in pairs {
+            let mut world = World::new();
+            world.integrator = integrator;
+            let a = launch(&mut world, particle, pos, vel);
+            let b = launch(&mut world, particle, -pos, -vel);
+            for tick in 0..1500 {
+                run(&mut world, 1);
+                let particles = &world.particles;
+                let (a, b) = (particles.slot(a).unwrap(), particles.slot(b).unwrap());
+                let scale = particles.pos[a].length().max(1.);
+                assert!(
+                    (particles.pos[a] + particles.pos[b]).length() < 1e-5 * scale,
+                    "{:?}: {:?} pair out of mirror at tick {}: {} and {}",
+                    integrator,
+                    particle,
+                    tick,
+                    particles.pos[a],
+                    particles.pos[b]
+                );
+                assert!((particles.vel[a] + particles.vel[b]).length() < 1e-6);
+            }
+        }
+    }
+}
+
+// Largest relative change in total energy over ten turns of a proton and an
+// antiproton on a circular orbit about their center of mass
+fn orbit_drift(integrator: Integrator, substepping: Substepping) -> f32 {
+    let mut world = World::new();
+    world.integrator = integrator;
+    world.substepping = substepping;
+    let (proton, antiproton) = (Particle::Proton, Particle::Antiproton);
+    let radius = 10.;
+    let charge = get_charge(proton);
+    let mass = get_mass(proton);
+    // Each body at `radius` from the center, pulled by its partner at twice
+    // that: m v² / r = k q² / (2r)²
+    let speed = (COULOMB_K * charge * charge / (4. * radius * mass)).sqrt();
+    launch(
+        &mut world,
+        proton,
+        vec3(radius, 0., 0.),
+        vec3(0., speed, 0.),
+    );
+    launch(
+        &mut world,
+        antiproton,
+        vec3(-radius, 0., 0.),
+        vec3(0., -speed, 0.),
+    );
+    let period = (std::f32::consts::TAU * radius / speed) as u64;
+
+    let start = Statistics::gather(&world).total_energy();
+    let mut drift: f32 = 0.;
+    for _ in 0..period * 10 {
+        run(&mut world, 1);
+        assert_eq!(
+            world.particle_count(),
+            2,
+            "{:?}: the pair annihilated",
+            integrator
+        );
+        let energy = Statistics::gather(&world).total_energy();
+        drift = drift.max(((energy - start) / start).abs());
+    }
+    drift
+}
+
+#[test]
+fn energy_drift_is_bounded_per_integrator() {
+    let substepping = Substepping::default();
+    let euler = orbit_drift(Integrator::SemiImplicitEuler, substepping);
+    let verlet = orbit_drift(Integrator::Verlet, substepping);
+    let runge_kutta = orbit_drift(Integrator::RungeKutta4, substepping);
+    let explicit = orbit_drift(Integrator::ExplicitEuler, substepping);
+    assert!(euler < 2e-4, "semi-implicit Euler drifted by {}", euler);
+    assert!(verlet < 5e-5, "Verlet drifted by {}", verlet);
+    assert!(runge_kutta < 5e-5, "RK4 drifted by {}", runge_kutta);
+    // Explicit Euler is there to show an unstable scheme, and should
+    assert!(
+        explicit > 100. * euler,
+        "explicit Euler drifted by only {}",
+        explicit
+    );
+}
+
+#[test]
+fn substeps_follow_the_integrator() {
+    // Every particle split into four sub-steps every tick
+    let substepping = Substepping {
+        enabled: true,
+        threshold: f32::MIN_POSITIVE,
+        max_substeps: 4,
+    };
+    let euler = orbit_drift(Integrator::SemiImplicitEuler, substepping);
+    let verlet = orbit_drift(Integrator::Verlet, substepping);
+    let runge_kutta = orbit_drift(Integrator::RungeKutta4, substepping);
+    let explicit = orbit_drift(Integrator::ExplicitEuler, substepping);
+    assert!(
+        euler < 2e-4,
+        "sub-stepped semi-implicit Euler drifted by {}",
+        euler
+    );
+    assert!(verlet < 5e-5, "sub-stepped Verlet drifted by {}", verlet);
+    assert!(
+        runge_kutta < 5e-5,
+        "sub-stepped RK4 drifted by {}",
+        runge_kutta
+    );
+    // Were the sub-steps semi-implicit whatever the integrator, explicit
+    // Euler would be as stable as the rest
+    assert!(
+        explicit > 10. * euler,
+        "sub-stepped explicit Euler drifted by only {}",
+        explicit
+    );
+}
+
+// Distance between the alpha and the proton when the alpha is launched
+const LAUNCH_DISTANCE: f32 = 2000.;
+
+// Angle the relative velocity of an alpha and a proton turns through, in
+// their center of mass frame, when the alpha is launched at `speed` from
+// `LAUNCH_DISTANCE` along the x axis and `impact` off it, at the proton.
+fn deflection(integrator: Integrator, impact: f32, speed: f32) -> f32 {
+    let mut world = World::new();
+    world.integrator = integrator;
+    let alpha = launch(
+        &mut world,
+        Particle::Alpha,
+        vec3(-LAUNCH_DISTANCE, impact, 0.),
+        vec3(speed, 0., 0.),
+    );
+    let proton = launch(&mut world, Particle::Proton, Vec3::ZERO, Vec3::ZERO);
+    let relative = |world: &World| {
+        let particles = &world.particles;
+        particles.vel[particles.slot(alpha).unwrap()]
+            - particles.vel[particles.slot(proton).unwrap()]
+    };
+    let incoming = relative(&world);
+    run(&mut world, (2. * LAUNCH_DISTANCE / speed) as u64);
+    incoming.angle_between(relative(&world))
+}
+
+#[test]
+fn deflection_follows_rutherford() {
+    let (alpha, proton) = (Particle::Alpha, Particle::Proton);
+    let (m1, m2) = (get_mass(alpha), get_mass(proton));
+    // Written so the product of two masses, too small for an f32, never
+    // comes up
+    let reduced = m1 / (1. + m1 / m2);
+    // k q₁ q₂ / μ, fixing the relative orbit
+    let coupling = COULOMB_K * get_charge(alpha) * get_charge(proton) / reduced;
+    let speed = 0.1;
+    // Impact parameter for a right-angle deflection
+    let right_angle = coupling / (speed * speed);
+    for integrator in INTEGRATORS {
+        for factor in [0.3, 0.6, 1., 2., 5.] {
+            let impact = right_angle * factor;
+            // The launch is not at infinity: the speed and impact parameter
+            // there follow from the energy and angular momentum at launch,
+            // and tan(θ/2) = k q₁ q₂ / (μ v² b) holds for those
+            let launch = (LAUNCH_DISTANCE * LAUNCH_DISTANCE + impact * impact).sqrt();
+            let asymptotic_speed = (speed * speed + 2. * coupling / launch).sqrt();
+            let asymptotic_impact = impact * speed / asymptotic_speed;
+            let expected =
+                2. * (coupling / (asymptotic_speed * asymptotic_speed * asymptotic_impact)).atan();
+            let angle = deflection(integrator, impact, speed);
+            assert!(
+                (angle - expected).abs() < 0.005,
+                "{:?}: b = {}: deflected {} rad, Rutherford gives {}",
+                integrator,
+                impact,
+                angle,
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Whatever `lz::compress` writes, `lz::decompress` has to give back byte for
+// byte, and anything it could not have written has to be turned down rather
+// than read out of bounds or allowed to grow without limit.
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::lz;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::rng::Rng;
+use rutherford_atomic_model::snapshot;
+use rutherford_atomic_model::world::World;
+
+fn scene_text() -> Vec<u8> {
+    let mut world = World::new();
+    for i in 0..200 {
+        let x = i as f32 * 0.25;
+        world.spawn_particle(Particle::Alpha, Some(vec3(x, -x, 0.5)));
+    }
+    snapshot::to_text(&world).into_bytes()
+}
+
+fn noise(length: usize) -> Vec<u8> {
+    let mut rng = Rng::new(7);
+    (0..length).map(|_| rng.next_u64() as u8).collect()
+}
+
+#[test]
+fn scenes_round_trip_smaller() {
+    let text = scene_text();
+    let packed = lz::compress(&text);
+    assert!(
+        packed.len() < text.len() / 2,
+        "{} of {} bytes",
+        packed.len(),
+        text.len()
+    );
+    assert_eq!(lz::decompress(&packed), Some(text));
+}
+
+#[test]
+fn long_runs_round_trip() {
+    // Matches overlapping the bytes they produce, and ones longer than the
+    // longest a single match can copy
+    let data = vec![b'0'; 10_000];
+    assert_eq!(lz::decompress(&lz::compress(&data)), Some(data));
+    let data = b"abc".repeat(5_000);
+    assert_eq!(lz::decompress(&lz::compress(&data)), Some(data));
+}
+
+#[test]
+fn empty_input_round_trips() {
+    assert_eq!(lz::decompress(&lz::compress(&[])), Some(Vec::new()));
+}
+
+#[test]
+fn incompressible_input_is_stored_as_it_is() {
+    let data = noise(100_000);
+    let packed = lz::compress(&data);
+    assert!(
+        packed.len() <= data.len() + 8,
+        "{} of {} bytes",
+        packed.len(),
+        data.len()
+    );
+    assert_eq!(lz::decompress(&packed), Some(data));
+}
+
+#[test]
+fn truncated_streams_are_refused() {
+    let packed = lz::compress(&scene_text());
+    for end in [0, 3, 4, 8, 9, packed.len() / 2, packed.len() - 1] {
+        assert_eq!(lz::decompress(&packed[..end]), None, "cut at {}", end);
+    }
+}
+
+#[test]
+fn garbage_is_refused() {
+    assert_eq!(lz::decompress(b"not a checkpoint"), None);
+    // A match reaching back before the start of the output
+    assert_eq!(
+        lz::decompress(b"RCK1\x08\x00\x00\x00\x01\x05\x00\x00"),
+        None
+    );
+    // A match running past the length the header claims
+    assert_eq!(
+        lz::decompress(b"RCK1\x06\x00\x00\x00\x02a\x01\x00\x20"),
+        None
+    );
+    // A length too large to be a checkpoint
+    assert_eq!(lz::decompress(b"RCK1\xff\xff\xff\xff"), None);
+    // Noise behind a valid header reads as something or nothing, but never panics
+    for length in [1, 10, 100, 1_000] {
+        let mut data = b"RCK1\x00\x10\x00\x00".to_vec();
+        data.extend(noise(length));
+        let _ = lz::decompress(&data);
+    }
+}
@@ -0,0 +1,84 @@
+// The portable `exp`, `ln` and `sin_cos` have to agree with the standard library's to
+// within a few ulps, and handle the edges of their domains the same way.
+
+use rutherford_atomic_model::portable;
+
+// Relative difference, in units of f32 precision
+fn ulps(actual: f32, expected: f32) -> f32 {
+    ((actual - expected) / expected).abs() / f32::EPSILON
+}
+
+#[test]
+fn exp_matches_the_standard_library() {
+    for i in -10_000..=10_000 {
+        let x = i as f32 * 0.0087;
+        let (actual, expected) = (portable::exp(x), x.exp());
+        assert!(
+            ulps(actual, expected) < 4.,
+            "exp({}) = {}, expected {}",
+            x,
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn ln_matches_the_standard_library() {
+    let mut x = 1e-30_f32;
+    while x < 1e30 {
+        let (actual, expected) = (portable::ln(x), x.ln());
+        // Near 1, where ln is near zero, only the absolute error is small
+        assert!(
+            (actual - expected).abs() < 4. * f32::EPSILON * expected.abs().max(1.),
+            "ln({}) = {}, expected {}",
+            x,
+            actual,
+            expected
+        );
+        x *= 1.0013;
+    }
+}
+
+#[test]
+fn sin_cos_matches_the_standard_library() {
+    for i in -20_000..=20_000 {
+        let x = i as f32 * 0.0031;
+        let (sin, cos) = portable::sin_cos(x);
+        // Both are near zero somewhere in every turn, so the error is
+        // measured against the size of a full swing
+        assert!(
+            (sin - x.sin()).abs() < 4. * f32::EPSILON,
+            "sin({}) = {}, expected {}",
+            x,
+            sin,
+            x.sin()
+        );
+        assert!(
+            (cos - x.cos()).abs() < 4. * f32::EPSILON,
+            "cos({}) = {}, expected {}",
+            x,
+            cos,
+            x.cos()
+        );
+    }
+    assert_eq!(portable::sin_cos(0.), (0., 1.));
+    assert!(portable::sin_cos(f32::INFINITY).0.is_nan());
+}
+
+#[test]
+fn edges_match_the_standard_library() {
+    assert_eq!(portable::exp(0.), 1.);
+    assert_eq!(portable::exp(100.), f32::INFINITY);
+    assert_eq!(portable::exp(-200.), 0.);
+    assert_eq!(portable::exp(f32::NEG_INFINITY), 0.);
+    assert!(portable::exp(f32::NAN).is_nan());
+    assert_eq!(portable::ln(1.), 0.);
+    assert_eq!(portable::ln(0.), f32::NEG_INFINITY);
+    assert_eq!(portable::ln(f32::INFINITY), f32::INFINITY);
+    assert!(portable::ln(-1.).is_nan());
+    assert!(portable::ln(f32::NAN).is_nan());
+    // Subnormal
+    let tiny = f32::MIN_POSITIVE / 1024.;
+    assert!(ulps(portable::ln(tiny), tiny.ln()) < 4.);
+}
@@ -0,0 +1,200 @@
+// A world written out with `snapshot::to_text` and read back has to be the
+// same world: the same particles with the same state, the same run settings,
+// and the same future. A world's fingerprint changes with any edit.
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::detector::{Detector, DetectorShape, Hit};
+use rutherford_atomic_model::ecs::Entity;
+use rutherford_atomic_model::foil::{self, Element};
+use rutherford_atomic_model::ghost;
+use rutherford_atomic_model::particle::{Particle, TRAIL_LENGTH};
+use rutherford_atomic_model::rng::Rng;
+use rutherford_atomic_model::snapshot;
+use rutherford_atomic_model::systems::{self, Integrator, Substepping};
+use rutherford_atomic_model::world::World;
+
+fn launch(world: &mut World, particle: Particle, pos: Vec3, vel: Vec3) -> Entity {
+    let entity = world.spawn_particle(particle, Some(pos));
+    let slot = world.particles.slot(entity).unwrap();
+    world.particles.vel[slot] = vel;
+    entity
+}
+
+// A world using everything a checkpoint has to carry, some ticks into its run
+fn scene() -> World {
+    let mut world = World::new();
+    world.rng = Some(Rng::new(3));
+    world.integrator = Integrator::Verlet;
+    world.substepping = Substepping {
+        enabled: true,
+        threshold: 0.02,
+        max_substeps: 16,
+    };
+    world
+        .pair_forces
+        .set(Particle::Electron, Particle::Electron, false);
+    world.bounds = Some(800.);
+
+    let electron = launch(
+        &mut world,
+        Particle::Electron,
+        vec3(0., 12., 0.),
+        vec3(0.01, 0., 0.),
+    );
+    launch(&mut world, Particle::Proton, Vec3::ZERO, Vec3::ZERO);
+    let alpha = launch(
+        &mut world,
+        Particle::Alpha,
+        vec3(-300., 8., 2.),
+        vec3(1., 0., 0.),
+    );
+    // After the bodies, where a restored world has its nuclei; in another
+    // order the forces would be summed in another order and the runs would
+    // part in the last bits
+    foil::add_foil(&mut world, Element::Gold, vec3(150., 0., 0.));
+    // A heavier, more highly charged alpha
+    let slot = world.particles.slot(alpha).unwrap();
+    world.particles.mass[slot] *= 2.;
+    world.particles.charge[slot] *= 1.5;
+
+    let beam = world.add_group("alpha beam");
+    world.groups[beam.0].color = Some(Color::new(1., 0.5, 0., 1.));
+    world.set_group(alpha, Some(beam));
+    let atom = world.add_group("atom");
+    world.set_group(electron, Some(atom));
+
+    systems::fast_forward(&mut world, 40, Some(TRAIL_LENGTH));
+    world.ghosts.push(ghost::straight(&world, alpha).unwrap());
+    world.ghosts.push(ghost::rutherford(&world, alpha).unwrap());
+    let mut detector = Detector::new(DetectorShape::Sphere);
+    detector.tick = 40;
+    detector.hits.push(Hit {
+        entity: Some(alpha),
+        pos: vec3(300., 2., -1.),
+        tick: 17,
+    });
+    detector.hits.push(Hit {
+        entity: None,
+        pos: vec3(0., 300., 0.),
+        tick: 30,
+    });
+    world.detector = Some(detector);
+    world.annihilations = 2;
+    world
+}
+
+// Bodies in slot order, then foil nuclei, the order checkpoints number
+// particles in
+fn indexed(world: &World) -> Vec<Entity> {
+    world
+        .particles
+        .entities()
+        .iter()
+        .copied()
+        .filter(|entity| foil::foil_of(world, *entity).is_none())
+        .chain(world.foils.iter().flat_map(|foil| foil.nuclei.clone()))
+        .collect()
+}
+
+fn assert_same(expected: &World, actual: &World) {
+    let (a, b) = (indexed(expected), indexed(actual));
+    assert_eq!(a.len(), b.len(), "particle count");
+    for (i, (a, b)) in a.into_iter().zip(b).enumerate() {
+        let (pa, pb) = (&expected.particles, &actual.particles);
+        let (sa, sb) = (pa.slot(a).unwrap(), pb.slot(b).unwrap());
+        assert_eq!(pa.particle[sa], pb.particle[sb], "particle {}", i);
+        assert_eq!(pa.pos[sa], pb.pos[sb], "position of {}", i);
+        assert_eq!(pa.vel[sa], pb.vel[sb], "velocity of {}", i);
+        assert_eq!(pa.mass[sa], pb.mass[sb], "mass of {}", i);
+        assert_eq!(pa.charge[sa], pb.charge[sb], "charge of {}", i);
+        assert_eq!(
+            expected.membership.get(a),
+            actual.membership.get(b),
+            "group of {}",
+            i
+        );
+        assert_eq!(
+            expected.trails.get(a),
+            actual.trails.get(b),
+            "trail of {}",
+            i
+        );
+        assert_eq!(
+            foil::foil_of(expected, a),
+            foil::foil_of(actual, b),
+            "foil of {}",
+            i
+        );
+    }
+    assert_eq!(expected.integrator, actual.integrator);
+    assert_eq!(expected.substepping, actual.substepping);
+    assert_eq!(expected.pair_forces, actual.pair_forces);
+    assert_eq!(expected.force_model, actual.force_model);
+    assert_eq!(expected.bounds, actual.bounds);
+    assert_eq!(expected.planar, actual.planar);
+    assert_eq!(expected.groups, actual.groups);
+    assert_eq!(expected.ghosts, actual.ghosts);
+    assert_eq!(expected.annihilations, actual.annihilations);
+    assert_eq!(
+        expected.rng.as_ref().map(Rng::state),
+        actual.rng.as_ref().map(Rng::state)
+    );
+    let (da, db) = (
+        expected.detector.as_ref().unwrap(),
+        actual.detector.as_ref().unwrap(),
+    );
+    assert_eq!(da.shape, db.shape);
+    assert_eq!(da.tick, db.tick);
+    assert_eq!(da.hits.len(), db.hits.len());
+    for (ha, hb) in da.hits.iter().zip(&db.hits) {
+        assert_eq!((ha.pos, ha.tick), (hb.pos, hb.tick));
+        let index = |world: &World, entity: Option<Entity>| {
+            entity.and_then(|entity| indexed(world).iter().position(|e| *e == entity))
+        };
+        assert_eq!(index(expected, ha.entity), index(actual, hb.entity));
+    }
+}
+
+#[test]
+fn checkpoints_restore_the_whole_world() {
+    let world = scene();
+    let restored = snapshot::from_text(&snapshot::to_text(&world)).unwrap();
+    assert_same(&world, &restored);
+}
+
+#[test]
+fn restored_worlds_run_on_identically() {
+    let mut world = scene();
+    let mut restored = snapshot::from_text(&snapshot::to_text(&world)).unwrap();
+    systems::fast_forward(&mut world, 200, Some(TRAIL_LENGTH));
+    systems::fast_forward(&mut restored, 200, Some(TRAIL_LENGTH));
+    assert_same(&world, &restored);
+}
+
+#[test]
+fn lost_foil_nuclei_stay_lost() {
+    let mut world = scene();
+    let nucleus = world.foils[0].nuclei[4];
+    world.despawn(nucleus);
+    let restored = snapshot::from_text(&snapshot::to_text(&world)).unwrap();
+    assert_eq!(restored.foils[0].nuclei.len(), world.foils[0].nuclei.len());
+    assert_same(&world, &restored);
+}
+
+#[test]
+fn fingerprints_follow_edits() {
+    let world = scene();
+    assert_eq!(world.fingerprint(), world.clone().fingerprint());
+    let edits: [fn(&mut World); 4] = [
+        |world| world.particles.pos[0].x += 1e-3,
+        |world| world.particles.charge[1] *= 2.,
+        |world| world.bounds = None,
+        |world| world.groups[0].name.push('!'),
+    ];
+    for (i, edit) in edits.into_iter().enumerate() {
+        let mut edited = world.clone();
+        edit(&mut edited);
+        assert_ne!(world.fingerprint(), edited.fingerprint(), "edit {}", i);
+    }
+}
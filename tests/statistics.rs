@@ -0,0 +1,102 @@
+// `Statistics::gather` sums everything in one pass for the panels, telemetry
+// and scripts, and has to give what the single-purpose functions in
+// `diagnostics` give, and what summing pair by pair gives.
+
+use macroquad::prelude::*;
+
+use rutherford_atomic_model::diagnostics;
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::rng::Rng;
+use rutherford_atomic_model::statistics::Statistics;
+use rutherford_atomic_model::world::World;
+
+// Particles of several species scattered about, with one kind of pair left
+// out of the forces and gravity on
+fn scene() -> World {
+    let mut world = World::new();
+    world.clear_particles();
+    let mut rng = Rng::new(11);
+    let species = [
+        Particle::Electron,
+        Particle::Proton,
+        Particle::Alpha,
+        Particle::Neutron,
+    ];
+    for i in 0..24 {
+        let pos = rng.unit_vector() * rng.range(5., 80.);
+        let entity = world.spawn_particle(species[i % species.len()], Some(pos));
+        let slot = world.particles.slot(entity).unwrap();
+        world.particles.vel[slot] = rng.unit_vector() * rng.range(0., 0.5);
+    }
+    world
+        .pair_forces
+        .set(Particle::Electron, Particle::Alpha, false);
+    world.medium.gravity = vec3(0., -1e-4, 0.);
+    world
+}
+
+fn close(actual: f32, expected: f32) -> bool {
+    (actual - expected).abs() <= 1e-4 * expected.abs().max(1.)
+}
+
+#[test]
+fn gather_agrees_with_diagnostics() {
+    let world = scene();
+    let statistics = Statistics::gather(&world);
+    assert_eq!(statistics.particles, world.particle_count());
+
+    let kinetic = diagnostics::total_kinetic_energy(&world);
+    assert!(
+        close(statistics.kinetic, kinetic),
+        "{} != {}",
+        statistics.kinetic,
+        kinetic
+    );
+    let potential = diagnostics::total_potential_energy(&world);
+    assert!(
+        close(statistics.potential, potential),
+        "{} != {}",
+        statistics.potential,
+        potential
+    );
+
+    let (center, drift) = diagnostics::center_of_mass(&world).unwrap();
+    let (gathered_center, gathered_drift) = statistics.center_of_mass.unwrap();
+    assert!(gathered_center.abs_diff_eq(center, 1e-3));
+    assert!(gathered_drift.abs_diff_eq(drift, 1e-5));
+    let angular = diagnostics::total_angular_momentum(&world);
+    assert!(
+        statistics
+            .angular_momentum
+            .abs_diff_eq(angular, 1e-4 * angular.length().max(1.)),
+        "{} != {}",
+        statistics.angular_momentum,
+        angular
+    );
+}
+
+#[test]
+fn potential_counts_each_allowed_pair_once() {
+    let mut world = scene();
+    // Gravity aside, the potential is the sum over pairs the forces allow
+    world.medium.gravity = Vec3::ZERO;
+    let particles = &world.particles;
+    let mut expected = 0.;
+    for i in 0..particles.len() {
+        for j in (i + 1)..particles.len() {
+            if world
+                .pair_forces
+                .allows(particles.particle[i], particles.particle[j])
+            {
+                expected += diagnostics::pair_potential(
+                    &world.force_model,
+                    particles.charge[i],
+                    particles.charge[j],
+                    particles.pos[i].distance(particles.pos[j]),
+                );
+            }
+        }
+    }
+    let potential = Statistics::gather(&world).potential;
+    assert!(close(potential, expected), "{} != {}", potential, expected);
+}
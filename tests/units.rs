@@ -0,0 +1,143 @@
+// Reading values typed with their units: every prefix lands on the right
+// scale, and anything that isn't a number with a known unit is refused.
+
+use rutherford_atomic_model::particle::Particle;
+use rutherford_atomic_model::units::{self, Quantity, SPEED_OF_LIGHT};
+
+fn length(text: &str) -> Option<Quantity> {
+    units::parse_quantity(text, Quantity::Length)
+}
+
+fn energy(text: &str) -> Option<Quantity> {
+    units::parse_quantity(text, Quantity::Energy)
+}
+
+// Same kind of quantity and the same value to f32 precision
+fn close(actual: Option<Quantity>, expected: Quantity) -> bool {
+    let value = |quantity: Quantity| match quantity {
+        Quantity::Length(v)
+        | Quantity::Energy(v)
+        | Quantity::Speed(v)
+        | Quantity::Voltage(v)
+        | Quantity::Charge(v)
+        | Quantity::Mass(v)
+        | Quantity::MagneticField(v)
+        | Quantity::Area(v) => v,
+    };
+    actual.is_some_and(|actual| {
+        std::mem::discriminant(&actual) == std::mem::discriminant(&expected)
+            && (value(actual) - value(expected)).abs() <= 1e-6 * value(expected).abs()
+    })
+}
+
+#[test]
+fn prefixes_scale_lengths_energies_and_voltages() {
+    let cases = [
+        (length("5 fm"), Quantity::Length(5.)),
+        (length("2 pm"), Quantity::Length(2e3)),
+        (length("0.1 nm"), Quantity::Length(1e5)),
+        (length("1 Å"), Quantity::Length(1e5)),
+        (length("3e-15 m"), Quantity::Length(3.)),
+        (energy("500 keV"), Quantity::Energy(0.5)),
+        (energy("1e6 eV"), Quantity::Energy(1.)),
+        (energy("3 MeV"), Quantity::Energy(3.)),
+        (energy("2 GeV"), Quantity::Energy(2e3)),
+        (energy("2 kV"), Quantity::Voltage(2e3)),
+        (energy("1.5 MV"), Quantity::Voltage(1.5e6)),
+        (energy("0.05c"), Quantity::Speed(0.05 * SPEED_OF_LIGHT)),
+    ];
+    for (i, (actual, expected)) in cases.into_iter().enumerate() {
+        assert!(close(actual, expected), "case {}: {:?}", i, actual);
+    }
+}
+
+#[test]
+fn units_ignore_spacing_and_the_case_of_their_base() {
+    for text in ["5 MeV", "5MeV", "5 MEV", "5 Mev", "  5   MeV  "] {
+        assert!(close(energy(text), Quantity::Energy(5.)), "{}", text);
+    }
+    // The longest number is taken, so this is electronvolts, not 5e and V
+    assert!(close(energy("5eV"), Quantity::Energy(5e-6)));
+}
+
+#[test]
+fn prefixes_keep_their_case() {
+    // Milli, not mega
+    assert!(close(energy("5 meV"), Quantity::Energy(5e-9)));
+    assert!(close(energy("5 mV"), Quantity::Voltage(5e-3)));
+    assert!(close(energy("5 MV"), Quantity::Voltage(5e6)));
+    let field = |text| units::parse_quantity(text, Quantity::MagneticField);
+    assert!(close(field("5 mT"), Quantity::MagneticField(5e-3)));
+    assert_eq!(field("5 MT"), None);
+    assert!(close(energy("5 mev"), Quantity::Energy(5e-9)));
+}
+
+#[test]
+fn bare_numbers_take_the_fields_kind() {
+    assert!(close(length("7"), Quantity::Length(7.)));
+    assert!(close(energy("7"), Quantity::Energy(7.)));
+    assert!(close(energy("-2.5"), Quantity::Energy(-2.5)));
+}
+
+#[test]
+fn the_other_quantities_read_their_units() {
+    let e = 1.6e-19;
+    let charge = |text| units::parse_quantity(text, Quantity::Charge);
+    assert!(close(charge("3e"), Quantity::Charge(3. * e)));
+    assert!(close(charge("-2 e"), Quantity::Charge(-2. * e)));
+    let mass = |text| units::parse_quantity(text, Quantity::Mass);
+    assert!(close(mass("4 u"), Quantity::Mass(4. * 1.660_539e-27)));
+    assert!(close(mass("2e-27 kg"), Quantity::Mass(2e-27)));
+    let electron = 0.511 * 1.602_177e-13 / 8.987_552e16;
+    assert!(close(mass("0.511 MeV/c²"), Quantity::Mass(electron)));
+    let field = |text| units::parse_quantity(text, Quantity::MagneticField);
+    assert!(close(field("2 T"), Quantity::MagneticField(2.)));
+    assert!(close(field("5 gauss"), Quantity::MagneticField(5e-4)));
+    let area = |text| units::parse_quantity(text, Quantity::Area);
+    assert!(close(area("3 fm2"), Quantity::Area(3.)));
+    assert!(close(area("2 nm²"), Quantity::Area(2e12)));
+}
+
+#[test]
+fn a_shared_unit_means_what_the_field_expects() {
+    // "c" is light speed, except where a charge is wanted
+    let charge = units::parse_quantity("2 C", Quantity::Charge);
+    assert!(close(charge, Quantity::Charge(2.)));
+    assert!(close(
+        length("0.1 c"),
+        Quantity::Speed(0.1 * SPEED_OF_LIGHT)
+    ));
+}
+
+#[test]
+fn unknown_units_are_refused() {
+    for text in ["5 furlongs", "5 ly", "3 nm³", "2 fm2x", "5 MeV unicorn"] {
+        assert_eq!(length(text), None, "{}", text);
+    }
+}
+
+#[test]
+fn bad_input_is_refused() {
+    for text in [
+        "", "   ", "fm", "MeV 5", "abc", "5 5 fm", "1..2 fm", "inf fm", "NaN",
+    ] {
+        assert_eq!(length(text), None, "{:?}", text);
+    }
+}
+
+#[test]
+fn beams_name_their_particle() {
+    let beam = |text| units::parse_beam(text, Quantity::Energy);
+    let (quantity, particle) = beam("5 MeV alpha").unwrap();
+    assert!(close(Some(quantity), Quantity::Energy(5.)));
+    assert_eq!(particle, Some(Particle::Alpha));
+    let (quantity, particle) = beam("0.1c Proton").unwrap();
+    assert!(close(Some(quantity), Quantity::Speed(0.1 * SPEED_OF_LIGHT)));
+    assert_eq!(particle, Some(Particle::Proton));
+    let (quantity, particle) = beam("5 MeV").unwrap();
+    assert!(close(Some(quantity), Quantity::Energy(5.)));
+    assert_eq!(particle, None);
+    assert_eq!(beam("5 MeV unicorn"), None);
+    assert_eq!(beam("alpha"), None);
+    assert_eq!(beam(""), None);
+}
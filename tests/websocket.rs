@@ -0,0 +1,73 @@
+// The handshake and framing telemetry hand-rolls have to match what browsers
+// and WebSocket libraries expect byte for byte.
+
+use rutherford_atomic_model::websocket;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn accept_key_matches_the_rfc_example() {
+    // RFC 6455, section 1.3
+    assert_eq!(
+        websocket::accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+        "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+}
+
+#[test]
+fn sha1_matches_known_digests() {
+    // FIPS 180-2 examples, plus the empty message and one ending exactly
+    // where its padding no longer fits in the last block
+    let cases: [(&[u8], &str); 4] = [
+        (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+        ),
+        (
+            &[b'a'; 1_000_000],
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f",
+        ),
+    ];
+    for (data, digest) in cases {
+        assert_eq!(hex(&websocket::sha1(data)), digest, "{} bytes", data.len());
+    }
+}
+
+#[test]
+fn base64_pads_every_remainder() {
+    // RFC 4648, section 10
+    let cases = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+    for (data, encoded) in cases {
+        assert_eq!(websocket::base64(data.as_bytes()), encoded);
+    }
+}
+
+#[test]
+fn text_frames_pick_the_length_encoding_by_size() {
+    // Payloads up to 125 bytes give their length in the second byte, up to
+    // 65535 in the two after a 126, and beyond that in eight after a 127
+    let cases: [(usize, &[u8]); 4] = [
+        (125, &[0x81, 125]),
+        (126, &[0x81, 126, 0x00, 0x7e]),
+        (65_535, &[0x81, 126, 0xff, 0xff]),
+        (65_536, &[0x81, 127, 0, 0, 0, 0, 0, 0x01, 0x00, 0x00]),
+    ];
+    for (length, header) in cases {
+        let text = "x".repeat(length);
+        let frame = websocket::text_frame(&text);
+        assert_eq!(&frame[..header.len()], header, "{} bytes", length);
+        assert_eq!(&frame[header.len()..], text.as_bytes(), "{} bytes", length);
+    }
+}